@@ -1,15 +1,31 @@
 use std::io;
+use std::thread;
+use std::time::Duration;
 
 use crossterm::event::{Event, KeyEventKind};
+use ratatui::backend::Backend;
+#[cfg(test)]
+use ratatui::buffer::Buffer;
 
 use crate::input::keymap::KeyBinding;
+use crate::input::source::EventSource;
+#[cfg(test)]
+use crate::input::source::ScriptedEventSource;
 use crate::state::{Environment, State};
 use crate::state::action::ActionResult;
 use crate::state::event::EventResult;
 use crate::state::init::StateInitializer;
 use crate::state::view::View;
+#[cfg(test)]
+use crate::state::view::HeadlessView;
 
-pub fn run(state_initializer: &StateInitializer, view: &mut View) -> io::Result<()> {
+/// How often to check for results of background work while waiting for terminal events.
+const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often to check for results of background work while an event source waits for it to finish.
+const BACKGROUND_WORK_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+pub fn run<B: Backend>(state_initializer: &StateInitializer, view: &mut View<B>, event_source: &mut dyn EventSource) -> io::Result<()> {
 	let environment = Environment::try_from(&*view)?;
 	let mut state = State::new(state_initializer, environment);
 	
@@ -28,32 +44,39 @@ pub fn run(state_initializer: &StateInitializer, view: &mut View) -> io::Result<
 		
 		view.render(|frame| state.render(frame))?;
 		
-		match handle_terminal_event(&mut state, crossterm::event::read()?) {
-			ActionResult::Nothing => {
-				continue;
-			}
+		if event_source.waits_for_background_work() && state.has_background_work() {
+			thread::sleep(BACKGROUND_WORK_POLL_INTERVAL);
+			continue;
+		}
+		
+		if event_source.is_exhausted() {
+			break;
+		}
+		
+		let Some(event) = event_source.next_event(EVENT_POLL_INTERVAL)? else {
+			continue;
+		};
+		
+		match handle_terminal_event(&mut state, event) {
+			ActionResult::Nothing => {}
 			
 			ActionResult::Draw => {
 				view.set_dirty(false);
-				continue;
 			}
 			
 			ActionResult::Redraw => {
 				view.set_dirty(true);
-				continue;
 			}
 			
 			ActionResult::PushLayer(layer) => {
 				state.push_layer(layer);
 				view.set_dirty(false);
-				continue;
 			}
 			
 			ActionResult::ReplaceLayer(layer) => {
 				state.pop_layer();
 				state.push_layer(layer);
 				view.set_dirty(false);
-				continue;
 			}
 			
 			ActionResult::PopLayer => {
@@ -61,7 +84,6 @@ pub fn run(state_initializer: &StateInitializer, view: &mut View) -> io::Result<
 					break;
 				} else {
 					view.set_dirty(false);
-					continue;
 				}
 			}
 		}
@@ -70,6 +92,17 @@ pub fn run(state_initializer: &StateInitializer, view: &mut View) -> io::Result<
 	Ok(())
 }
 
+/// Runs the application without a terminal, presses the keys in the key sequence, and returns the screen after the last key was handled. Each key is pressed only once all jobs started by earlier keys have finished.
+#[cfg(test)]
+pub fn run_headless(state_initializer: &StateInitializer, width: u16, height: u16, key_sequence: &str) -> io::Result<Buffer> {
+	let mut event_source = ScriptedEventSource::from_keys(key_sequence).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+	let mut view = HeadlessView::headless(width, height)?;
+	
+	run(state_initializer, &mut view, &mut event_source)?;
+	
+	Ok(view.buffer().clone())
+}
+
 #[allow(clippy::needless_pass_by_value)]
 fn handle_terminal_event(state: &mut State, event: Event) -> ActionResult {
 	if let Event::Key(key) = event {
@@ -78,6 +111,8 @@ fn handle_terminal_event(state: &mut State, event: Event) -> ActionResult {
 		} else {
 			state.handle_input(KeyBinding::from(key))
 		}
+	} else if let Event::Mouse(mouse) = event {
+		state.handle_mouse(mouse)
 	} else if let Event::Resize(w, h) = event {
 		state.handle_resize(w, h);
 		ActionResult::Draw
@@ -85,3 +120,88 @@ fn handle_terminal_event(state: &mut State, event: Event) -> ActionResult {
 		ActionResult::Nothing
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::path::Path;
+	use std::sync::Arc;
+	
+	use ratatui::buffer::Buffer;
+	
+	use crate::app::run_headless;
+	use crate::component::filesystem::defaults::get_action_map;
+	use crate::file::{FileSystem, MemoryFileSystem};
+	use crate::state::init::StateInitializer;
+	
+	fn screen_lines(buffer: &Buffer) -> Vec<String> {
+		let width = usize::from(buffer.area.width);
+		buffer.content.chunks(width).map(|row| row.iter().map(|cell| cell.symbol.as_str()).collect::<String>().trim_end().to_owned()).collect()
+	}
+	
+	fn create_state_initializer(file_system: &Arc<MemoryFileSystem>) -> StateInitializer<'static> {
+		let Ok(action_map) = get_action_map() else {
+			panic!("Default key bindings could not be inserted");
+		};
+		
+		StateInitializer {
+			filesystem_start_path: Path::new("/root"),
+			filesystem_backend: Arc::clone(file_system) as Arc<dyn FileSystem>,
+			filesystem_action_map: action_map,
+			filesystem_action_map_errors: &[],
+		}
+	}
+	
+	fn run_keys(file_system: &Arc<MemoryFileSystem>, key_sequence: &str) -> Vec<String> {
+		let result = run_headless(&create_state_initializer(file_system), 60, 10, key_sequence);
+		let Ok(buffer) = result else {
+			panic!("Application failed: {result:?}");
+		};
+		
+		screen_lines(&buffer)
+	}
+	
+	#[test]
+	fn renames_selected_file() {
+		let file_system = Arc::new(MemoryFileSystem::new()
+			.with_file("/root/a.txt", "a")
+			.with_file("/root/b.txt", "b"));
+		
+		let lines = run_keys(&file_system, "jj<F2><Ctrl-U>c.txt<Enter>");
+		let names = lines.iter().filter_map(|line| line.split_whitespace().next()).collect::<Vec<_>>();
+		
+		assert_eq!(names.get(..3), Some(["root", "a.txt", "c.txt"].as_slice()));
+		assert!(lines.last().is_some_and(|line| line.contains("Renamed b.txt → c.txt")), "Status message is not shown: {lines:#?}");
+		assert!(file_system.exists(Path::new("/root/c.txt")), "Renamed file does not exist");
+		assert!(!file_system.exists(Path::new("/root/b.txt")), "Old file still exists");
+		assert!(file_system.exists(Path::new("/root/a.txt")), "Other file was renamed");
+	}
+	
+	fn create_directory_to_delete() -> Arc<MemoryFileSystem> {
+		Arc::new(MemoryFileSystem::new()
+			.with_file("/root/dir/a.txt", "a")
+			.with_file("/root/dir/sub/b.txt", "b")
+			.with_file("/root/other.txt", "other"))
+	}
+	
+	#[test]
+	fn confirms_deletion_with_counted_entries() {
+		let file_system = create_directory_to_delete();
+		let lines = run_keys(&file_system, "jd");
+		
+		assert!(lines.iter().any(|line| line.contains("This will affect 2 files and 2 directories.")), "Counted entries are not shown: {lines:#?}");
+		assert!(file_system.exists(Path::new("/root/dir")), "Directory was deleted without confirmation");
+	}
+	
+	#[test]
+	fn deletes_directory_in_job() {
+		let file_system = create_directory_to_delete();
+		let lines = run_keys(&file_system, "jdy");
+		
+		assert!(lines.last().is_some_and(|line| line.contains("Deleted /root/dir")), "Status message is not shown: {lines:#?}");
+		assert!(!file_system.exists(Path::new("/root/dir")), "Directory still exists");
+		assert!(file_system.exists(Path::new("/root/other.txt")), "Other file was deleted");
+		
+		let names = lines.iter().filter_map(|line| line.split_whitespace().next()).collect::<Vec<_>>();
+		assert_eq!(names.get(..2), Some(["root", "other.txt"].as_slice()));
+	}
+}
@@ -1,8 +1,9 @@
-use ratatui::style::Color;
 use ratatui::text::{Line, Text};
 
-use crate::component::dialog::input::InputFieldDialogLayer;
+use crate::component::dialog::input::{InputFieldCompletion, InputFieldDialogLayer, InputFieldPreview};
+use crate::component::input::InputField;
 use crate::state::action::ActionResult;
+use crate::theme::DialogStyle;
 
 pub struct InputFieldDialogBuilder;
 
@@ -17,8 +18,7 @@ pub struct InputFieldDialogBuilder2 {
 
 pub struct InputFieldDialogBuilder3 {
 	step2: InputFieldDialogBuilder2,
-	default_color: Color,
-	darker_color: Color,
+	style: DialogStyle,
 }
 
 pub struct InputFieldDialogBuilder4<'a> {
@@ -30,6 +30,8 @@ pub struct InputFieldDialogBuilder5<'a> {
 	step4: InputFieldDialogBuilder4<'a>,
 	message: Text<'a>,
 	initial_value: Option<String>,
+	completion: Option<Box<InputFieldCompletion>>,
+	preview: Option<Box<InputFieldPreview>>,
 }
 
 impl InputFieldDialogBuilder {
@@ -46,8 +48,8 @@ impl InputFieldDialogBuilder1 {
 }
 
 impl InputFieldDialogBuilder2 {
-	pub const fn color(self, default_color: Color, darker_color: Color) -> InputFieldDialogBuilder3 {
-		InputFieldDialogBuilder3 { step2: self, default_color, darker_color }
+	pub const fn style(self, style: DialogStyle) -> InputFieldDialogBuilder3 {
+		InputFieldDialogBuilder3 { step2: self, style }
 	}
 }
 
@@ -59,7 +61,7 @@ impl InputFieldDialogBuilder3 {
 
 impl<'a> InputFieldDialogBuilder4<'a> {
 	pub fn message(self, message: impl Into<Text<'a>>) -> InputFieldDialogBuilder5<'a> {
-		InputFieldDialogBuilder5 { step4: self, message: message.into(), initial_value: None }
+		InputFieldDialogBuilder5 { step4: self, message: message.into(), initial_value: None, completion: None, preview: None }
 	}
 }
 
@@ -69,11 +71,39 @@ impl<'a> InputFieldDialogBuilder5<'a> {
 		self
 	}
 	
+	/// Enables completion with the `Tab` key. The function receives the current text, and returns every text it can be completed to.
+	pub fn completion<F>(mut self, completion: F) -> Self where F: Fn(&str) -> Vec<String> + 'static {
+		self.completion = Some(Box::new(completion));
+		self
+	}
+	
+	/// Shows text below the input field, which is updated every time the text in the input field changes.
+	pub fn preview<F>(mut self, preview: F) -> Self where F: Fn(&str) -> Text<'static> + 'static {
+		self.preview = Some(Box::new(preview));
+		self
+	}
+	
 	pub fn on_confirm<F>(self, confirm_action: F) -> InputFieldDialogLayer<'a> where F: Fn(String) -> ActionResult + 'static {
 		let step4 = self.step4;
 		let step3 = step4.step3;
 		let step2 = step3.step2;
 		let step1 = step2.step1;
-		InputFieldDialogLayer::new(step1.y, step2.min_width, step3.default_color, step3.darker_color, step4.title, self.message, self.initial_value, confirm_action)
+		
+		let field = self.initial_value.map_or_else(InputField::new, InputField::with_text);
+		let preview_text = self.preview.as_ref().map(|preview| preview(field.text())).unwrap_or_default();
+		
+		InputFieldDialogLayer {
+			y: step1.y,
+			min_width: step2.min_width,
+			style: step3.style,
+			title: step4.title,
+			message: self.message,
+			field,
+			completion: self.completion,
+			completion_candidates: None,
+			preview: self.preview,
+			preview_text,
+			confirm_action: Box::new(confirm_action),
+		}
 	}
 }
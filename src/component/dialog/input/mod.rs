@@ -1,8 +1,7 @@
 use std::cmp::max;
 
-use crossterm::event::{KeyCode, KeyModifiers};
-use ratatui::layout::Alignment;
-use ratatui::style::Color;
+use crossterm::event::{KeyCode, KeyModifiers, MouseEvent};
+use ratatui::layout::{Alignment, Rect};
 use ratatui::text::{Line, Text};
 use ratatui::widgets::Paragraph;
 
@@ -15,33 +14,86 @@ use crate::state::Environment;
 use crate::state::event::EventResult;
 use crate::state::layer::Layer;
 use crate::state::view::Frame;
+use crate::theme::{DialogStyle, theme};
 
 mod builder;
 
+type InputFieldCompletion = dyn Fn(&str) -> Vec<String>;
+type InputFieldPreview = dyn Fn(&str) -> Text<'static>;
+
 pub struct InputFieldDialogLayer<'a> {
 	y: u16,
 	min_width: u16,
-	default_color: Color,
-	darker_color: Color,
+	style: DialogStyle,
 	title: Line<'a>,
 	message: Text<'a>,
 	field: InputField,
+	completion: Option<Box<InputFieldCompletion>>,
+	completion_candidates: Option<String>,
+	preview: Option<Box<InputFieldPreview>>,
+	preview_text: Text<'static>,
 	confirm_action: Box<dyn Fn(String) -> ActionResult>,
 }
 
-impl<'a> InputFieldDialogLayer<'a> {
-	fn new<F>(y: u16, min_width: u16, default_color: Color, darker_color: Color, title: Line<'a>, message: Text<'a>, initial_value: Option<String>, confirm_action: F) -> Self where F: Fn(String) -> ActionResult + 'static {
-		let field = initial_value.map_or_else(InputField::new, InputField::with_text);
-		let confirm_action = Box::new(confirm_action);
-		Self { y, min_width, default_color, darker_color, title, message, field, confirm_action }
-	}
-	
+impl InputFieldDialogLayer<'_> {
 	pub const fn build() -> InputFieldDialogBuilder {
 		InputFieldDialogBuilder
 	}
+	
+	fn complete(&mut self) -> bool {
+		let Some(completion) = &self.completion else {
+			return false;
+		};
+		
+		let mut candidates = completion(self.field.text());
+		candidates.sort();
+		candidates.dedup();
+		
+		if candidates.is_empty() {
+			return self.completion_candidates.take().is_some();
+		}
+		
+		let completed_text = get_common_prefix(&candidates).to_owned();
+		let changed_text = self.field.set_text(completed_text);
+		
+		let new_candidates = (candidates.len() > 1).then(|| candidates.join("  "));
+		let changed_candidates = self.completion_candidates != new_candidates;
+		self.completion_candidates = new_candidates;
+		
+		if changed_text {
+			self.update_preview();
+		}
+		
+		changed_text || changed_candidates
+	}
+	
+	fn update_preview(&mut self) {
+		if let Some(preview) = &self.preview {
+			self.preview_text = preview(self.field.text());
+		}
+	}
 }
 
-impl<'a> Layer for InputFieldDialogLayer<'a> {
+fn get_common_prefix(candidates: &[String]) -> &str {
+	let Some((first, rest)) = candidates.split_first() else {
+		return "";
+	};
+	
+	let mut prefix_len = first.len();
+	
+	for candidate in rest {
+		prefix_len = first.char_indices()
+			.zip(candidate.chars())
+			.take_while(|((_, a), b)| a == b)
+			.last()
+			.map_or(0, |((i, c), _)| i.saturating_add(c.len_utf8()))
+			.min(prefix_len);
+	}
+	
+	first.get(..prefix_len).unwrap_or("")
+}
+
+impl Layer for InputFieldDialogLayer<'_> {
 	#[allow(clippy::wildcard_enum_match_arm)]
 	fn handle_input(&mut self, _environment: &Environment, key_binding: KeyBinding) -> ActionResult {
 		match (key_binding.code(), key_binding.modifiers()) {
@@ -54,12 +106,27 @@ impl<'a> Layer for InputFieldDialogLayer<'a> {
 				(self.confirm_action)(self.field.text().to_owned())
 			}
 			
+			(KeyCode::Tab, KeyModifiers::NONE) => {
+				ActionResult::draw_if(self.complete())
+			}
+			
 			_ => {
-				ActionResult::draw_if(self.field.handle_input(key_binding))
+				let changed_candidates = self.completion_candidates.take().is_some();
+				let changed_text = self.field.handle_input(key_binding);
+				
+				if changed_text {
+					self.update_preview();
+				}
+				
+				ActionResult::draw_if(changed_text || changed_candidates)
 			}
 		}
 	}
 	
+	fn handle_mouse(&mut self, _environment: &Environment, _event: MouseEvent) -> ActionResult {
+		ActionResult::Nothing
+	}
+	
 	fn handle_events(&mut self, _environment: &Environment) -> EventResult {
 		EventResult::Nothing
 	}
@@ -67,14 +134,33 @@ impl<'a> Layer for InputFieldDialogLayer<'a> {
 	fn render(&mut self, frame: &mut Frame) {
 		let message_width = u16::try_from(self.message.width()).unwrap_or(u16::MAX);
 		let message_height = u16::try_from(self.message.height()).unwrap_or(u16::MAX);
+		let candidates_height = if self.completion_candidates.is_some() { 2 } else { 0 };
+		let preview_width = u16::try_from(self.preview_text.width()).unwrap_or(u16::MAX);
+		let preview_height = match u16::try_from(self.preview_text.height()).unwrap_or(u16::MAX) {
+			0 => 0,
+			height => height.saturating_add(1),
+		};
 		
-		let content_width = max(message_width, self.min_width);
-		let content_height = message_height.saturating_add(2);
+		let content_width = max(max(message_width, preview_width), self.min_width);
+		let content_height = message_height.saturating_add(2).saturating_add(candidates_height).saturating_add(preview_height);
 		
 		let paragraph = Paragraph::new(self.message.clone()).alignment(Alignment::Left);
-		let content_area = render_dialog_border(frame, self.y, content_width, content_height, self.title.clone(), self.default_color);
+		let content_area = render_dialog_border(frame, self.y, content_width, content_height, self.title.clone(), self.style);
+		let field_y = content_area.bottom().saturating_sub(1).saturating_sub(candidates_height).saturating_sub(preview_height);
 		
 		frame.render_widget(paragraph, content_area);
-		self.field.render(frame, content_area.x, content_area.bottom().saturating_sub(1), content_area.width, self.default_color, self.darker_color);
+		
+		if let Some(candidates) = &self.completion_candidates {
+			let candidates_area = Rect { y: field_y.saturating_add(2), height: 1, ..content_area };
+			frame.render_widget(Paragraph::new(candidates.as_str()).style(theme().secondary), candidates_area);
+		}
+		
+		if preview_height > 0 {
+			let preview_y = field_y.saturating_add(candidates_height).saturating_add(2);
+			let preview_area = Rect { y: preview_y, height: content_area.bottom().saturating_sub(preview_y), ..content_area };
+			frame.render_widget(Paragraph::new(self.preview_text.clone()).alignment(Alignment::Left), preview_area);
+		}
+		
+		self.field.render(frame, content_area.x, field_y, content_area.width, self.style);
 	}
 }
@@ -1,40 +1,110 @@
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::ops::Range;
+use std::rc::Rc;
 
-use ratatui::style::{Color, Style};
+use ratatui::style::Style;
 use ratatui::text::Span;
 
 use crate::input::keymap::KeyBinding;
 use crate::state::action::ActionResult;
+use crate::theme::theme;
 
 type ActionHashMap = HashMap<KeyBinding, Box<dyn Fn() -> ActionResult>>;
 
 pub trait MessageDialogActions<'a> {
 	fn handle_input(&mut self, key_binding: KeyBinding) -> ActionResult;
 	fn describe(&self) -> &Vec<Span<'a>>;
+	
+	/// Returns the key of the button at the character offset in the description.
+	fn get_button_at(&self, offset: usize) -> Option<KeyBinding>;
 }
 
 pub struct MessageDialogActionMap<'a> {
 	map: ActionHashMap,
+	labels: Vec<&'a str>,
+	toggle: Option<MessageDialogToggle<'a>>,
 	description: Vec<Span<'a>>,
+	buttons: Vec<(Range<usize>, KeyBinding)>,
+}
+
+/// Checkbox shown after the buttons, which changes what the buttons do instead of closing the dialog.
+struct MessageDialogToggle<'a> {
+	label: &'a str,
+	checked: Rc<Cell<bool>>,
+}
+
+impl MessageDialogToggle<'_> {
+	fn key(&self) -> Option<KeyBinding> {
+		self.label.chars().next().map(KeyBinding::char)
+	}
 }
 
 impl<'a> MessageDialogActionMap<'a> {
-	fn new(map: ActionHashMap, description: Vec<Span<'a>>) -> Self {
-		Self { map, description }
+	fn new(map: ActionHashMap, labels: &[&'a str]) -> Self {
+		Self::with_toggle(map, labels, None)
+	}
+	
+	fn with_toggle(map: ActionHashMap, labels: &[&'a str], toggle: Option<MessageDialogToggle<'a>>) -> Self {
+		let mut actions = Self { map, labels: labels.to_vec(), toggle, description: Vec::new(), buttons: Vec::new() };
+		actions.update_description();
+		actions
+	}
+	
+	/// Describes buttons by their labels separated by slashes, followed by the toggle. The first character of each label is highlighted, and it is the key that activates the button.
+	fn update_description(&mut self) {
+		let mut description = Vec::new();
+		let mut buttons = Vec::new();
+		let mut offset: usize = 0;
+		
+		for label in &self.labels {
+			let Some(key) = label.chars().next() else {
+				continue;
+			};
+			
+			if !description.is_empty() {
+				description.push(Span::raw("/"));
+				offset = offset.saturating_add(1);
+			}
+			
+			let (key_text, rest_text) = label.split_at(key.len_utf8());
+			description.push(Span::styled(key_text, Self::highlight()));
+			description.push(Span::raw(rest_text));
+			
+			let width = label.chars().count();
+			buttons.push((offset..offset.saturating_add(width), KeyBinding::char(key)));
+			offset = offset.saturating_add(width);
+		}
+		
+		if let Some(toggle) = &self.toggle {
+			if let Some(key) = toggle.label.chars().next() {
+				let checkbox = if toggle.checked.get() { "  [x] " } else { "  [ ] " };
+				let (key_text, rest_text) = toggle.label.split_at(key.len_utf8());
+				
+				description.push(Span::raw(checkbox));
+				description.push(Span::styled(key_text, Self::highlight()));
+				description.push(Span::raw(rest_text));
+				
+				// The checkbox is part of the button, but the spaces before it are not.
+				let start = offset.saturating_add(2);
+				let end = offset.saturating_add(checkbox.len()).saturating_add(toggle.label.chars().count());
+				buttons.push((start..end, KeyBinding::char(key)));
+			}
+		}
+		
+		self.description = description;
+		self.buttons = buttons;
 	}
 	
 	fn highlight() -> Style {
-		Style::default().fg(Color::LightCyan)
+		theme().dialog_key
 	}
 	
 	pub fn ok() -> Self {
 		let mut map = ActionHashMap::new();
 		map.insert(KeyBinding::char('o'), Box::new(|| ActionResult::PopLayer));
 		
-		Self::new(map, vec![
-			Span::styled("o", Self::highlight()),
-			Span::raw("k"),
-		])
+		Self::new(map, &["ok"])
 	}
 	
 	pub fn yes_no<F>(yes_action: F) -> Self where F: Fn() -> ActionResult + 'static {
@@ -42,21 +112,43 @@ impl<'a> MessageDialogActionMap<'a> {
 		map.insert(KeyBinding::char('y'), Box::new(yes_action));
 		map.insert(KeyBinding::char('n'), Box::new(|| ActionResult::PopLayer));
 		
-		Self::new(map, vec![
-			Span::styled("y", Self::highlight()),
-			Span::raw("es/"),
-			Span::styled("n", Self::highlight()),
-			Span::raw("o"),
-		])
+		Self::new(map, &["yes", "no"])
+	}
+	
+	/// Creates buttons for several choices, and a toggle that is passed to the action along with the index of the chosen label. The toggle key flips the toggle without closing the dialog.
+	pub fn multi_choice<F>(labels: &[&'a str], toggle_label: &'a str, on_choice: F) -> Self where F: Fn(usize, bool) -> ActionResult + 'static {
+		let on_choice = Rc::new(on_choice);
+		let checked = Rc::new(Cell::new(false));
+		let mut map = ActionHashMap::new();
+		
+		for (index, label) in labels.iter().enumerate() {
+			if let Some(key) = label.chars().next() {
+				let on_choice = Rc::clone(&on_choice);
+				let checked = Rc::clone(&checked);
+				map.insert(KeyBinding::char(key), Box::new(move || on_choice(index, checked.get())));
+			}
+		}
+		
+		Self::with_toggle(map, labels, Some(MessageDialogToggle { label: toggle_label, checked }))
 	}
 }
 
 impl<'a> MessageDialogActions<'a> for MessageDialogActionMap<'a> {
 	fn handle_input(&mut self, key_binding: KeyBinding) -> ActionResult {
+		if let Some(toggle) = self.toggle.as_ref().filter(|toggle| toggle.key() == Some(key_binding)) {
+			toggle.checked.set(!toggle.checked.get());
+			self.update_description();
+			return ActionResult::Draw;
+		}
+		
 		self.map.get(&key_binding).map(|f| f()).unwrap_or(ActionResult::Nothing)
 	}
 	
 	fn describe(&self) -> &Vec<Span<'a>> {
 		&self.description
 	}
+	
+	fn get_button_at(&self, offset: usize) -> Option<KeyBinding> {
+		self.buttons.iter().find(|(range, _)| range.contains(&offset)).map(|(_, key)| *key)
+	}
 }
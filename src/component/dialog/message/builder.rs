@@ -1,9 +1,9 @@
-use ratatui::style::Color;
 use ratatui::text::{Line, Text};
 
 use crate::component::dialog::message::{MessageDialogActionMap, MessageDialogLayer};
 use crate::component::dialog::message::actions::MessageDialogActions;
 use crate::state::action::ActionResult;
+use crate::theme::DialogStyle;
 
 pub struct MessageDialogBuilder;
 
@@ -13,7 +13,7 @@ pub struct MessageDialogBuilder1 {
 
 pub struct MessageDialogBuilder2 {
 	step1: MessageDialogBuilder1,
-	color: Color,
+	style: DialogStyle,
 }
 
 pub struct MessageDialogBuilder3<'a> {
@@ -34,8 +34,8 @@ impl MessageDialogBuilder {
 }
 
 impl MessageDialogBuilder1 {
-	pub const fn color(self, color: Color) -> MessageDialogBuilder2 {
-		MessageDialogBuilder2 { step1: self, color }
+	pub const fn style(self, style: DialogStyle) -> MessageDialogBuilder2 {
+		MessageDialogBuilder2 { step1: self, style }
 	}
 }
 
@@ -56,7 +56,7 @@ impl<'a> MessageDialogBuilder4<'a> {
 		let step3 = self.step3;
 		let step2 = step3.step2;
 		let step1 = step2.step1;
-		MessageDialogLayer::new(step1.y, step2.color, step3.title, self.message, actions)
+		MessageDialogLayer::new(step1.y, step2.style, step3.title, self.message, actions)
 	}
 	
 	pub fn ok(self) -> MessageDialogLayer<'a> {
@@ -66,4 +66,8 @@ impl<'a> MessageDialogBuilder4<'a> {
 	pub fn yes_no<F>(self, yes_action: F) -> MessageDialogLayer<'a> where F: Fn() -> ActionResult + 'static {
 		self.actions(MessageDialogActionMap::yes_no(yes_action))
 	}
+	
+	pub fn multi_choice<F>(self, labels: &[&'a str], toggle_label: &'a str, on_choice: F) -> MessageDialogLayer<'a> where F: Fn(usize, bool) -> ActionResult + 'static {
+		self.actions(MessageDialogActionMap::multi_choice(labels, toggle_label, on_choice))
+	}
 }
@@ -1,5 +1,7 @@
-use ratatui::layout::Alignment;
-use ratatui::style::Color;
+use std::cmp::{max, min};
+
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Alignment, Rect};
 use ratatui::text::{Line, Text};
 use ratatui::widgets::Paragraph;
 
@@ -13,6 +15,7 @@ use crate::state::Environment;
 use crate::state::event::EventResult;
 use crate::state::layer::Layer;
 use crate::state::view::Frame;
+use crate::theme::{DialogStyle, theme};
 
 pub use self::actions::MessageDialogActionMap;
 
@@ -21,30 +24,26 @@ mod builder;
 
 pub struct MessageDialogLayer<'a> {
 	y: u16,
-	color: Color,
+	style: DialogStyle,
 	title: Line<'a>,
 	message: Text<'a>,
 	actions: Box<dyn MessageDialogActions<'a> + 'a>,
+	action_line_area: Rect,
 }
 
 impl<'a> MessageDialogLayer<'a> {
-	fn new<A>(y: u16, color: Color, title: Line<'a>, mut message: Text<'a>, actions: A) -> Self where A: MessageDialogActions<'a> + 'a {
-		let action_line = Line::from(actions.describe().clone()).alignment(Alignment::Right);
-		let actions = Box::new(actions);
-		
-		message.lines.push(action_line);
-		
-		Self { y, color, title, message, actions }
+	fn new<A>(y: u16, style: DialogStyle, title: Line<'a>, message: Text<'a>, actions: A) -> Self where A: MessageDialogActions<'a> + 'a {
+		Self { y, style, title, message, actions: Box::new(actions), action_line_area: Rect::default() }
 	}
 	
 	pub const fn build() -> MessageDialogBuilder {
 		MessageDialogBuilder
 	}
 	
-	pub fn error(y: u16, message: impl Into<Text<'a>>) -> MessageDialogLayer<'a> {
+	pub fn error(y: u16, message: impl Into<Text<'a>>) -> Self {
 		Self::build()
 			.y(y)
-			.color(Color::LightRed)
+			.style(theme().dialog_error)
 			.title("Error")
 			.message(message)
 			.ok()
@@ -56,17 +55,44 @@ impl Layer for MessageDialogLayer<'_> {
 		self.actions.handle_input(key_binding)
 	}
 	
+	fn handle_mouse(&mut self, _environment: &Environment, event: MouseEvent) -> ActionResult {
+		let area = self.action_line_area;
+		
+		if event.kind != MouseEventKind::Down(MouseButton::Left) || event.row != area.y || event.column < area.x || event.column >= area.right() {
+			return ActionResult::Nothing;
+		}
+		
+		let offset = usize::from(event.column.saturating_sub(area.x));
+		self.actions.get_button_at(offset).map_or(ActionResult::Nothing, |key_binding| self.actions.handle_input(key_binding))
+	}
+	
 	fn handle_events(&mut self, _environment: &Environment) -> EventResult {
 		EventResult::Nothing
 	}
 	
 	fn render(&mut self, frame: &mut Frame) {
-		let content_width = u16::try_from(self.message.width()).unwrap_or(u16::MAX);
-		let content_height = u16::try_from(self.message.height()).unwrap_or(u16::MAX);
+		// Actions can change their description, so the action line is added when rendering.
+		let action_line = Line::from(self.actions.describe().clone());
+		let action_line_width = action_line.width();
+		
+		let mut message = self.message.clone();
+		message.lines.push(action_line.alignment(Alignment::Right));
+		
+		let content_width = u16::try_from(max(message.width(), self.title.width())).unwrap_or(u16::MAX);
+		let content_height = u16::try_from(message.height()).unwrap_or(u16::MAX);
 		
-		let paragraph = Paragraph::new(self.message.clone()).alignment(Alignment::Left);
-		let content_area = render_dialog_border(frame, self.y, content_width, content_height, self.title.clone(), self.color);
+		let paragraph = Paragraph::new(message).alignment(Alignment::Left);
+		let content_area = render_dialog_border(frame, self.y, content_width, content_height, self.title.clone(), self.style);
 		
 		frame.render_widget(paragraph, content_area);
+		
+		let action_line_width = min(u16::try_from(action_line_width).unwrap_or(u16::MAX), content_area.width);
+		let action_line_y = content_area.y.saturating_add(content_height).saturating_sub(1);
+		
+		self.action_line_area = if action_line_y < content_area.bottom() {
+			Rect { x: content_area.right().saturating_sub(action_line_width), y: action_line_y, width: action_line_width, height: 1 }
+		} else {
+			Rect::default()
+		};
 	}
 }
@@ -1,11 +1,11 @@
 use std::cmp::min;
 
 use ratatui::layout::{Alignment, Margin, Rect};
-use ratatui::style::{Color, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, BorderType, Clear, Padding};
 
 use crate::state::view::Frame;
+use crate::theme::DialogStyle;
 
 pub mod input;
 pub mod message;
@@ -28,7 +28,7 @@ fn calculate_margin_area(frame: &mut Frame, top_y: u16, content_width: u16, cont
 	Rect { x, y, width, height }
 }
 
-fn render_dialog_border<'a>(frame: &mut Frame, top_y: u16, content_width: u16, content_height: u16, title: impl Into<Line<'a>>, color: Color) -> Rect {
+fn render_dialog_border<'a>(frame: &mut Frame, top_y: u16, content_width: u16, content_height: u16, title: impl Into<Line<'a>>, style: DialogStyle) -> Rect {
 	let margin_area = calculate_margin_area(frame, top_y, content_width, content_height);
 	let border_area = margin_area.inner(&Margin { horizontal: MARGIN_HORIZONTAL, vertical: MARGIN_VERTICAL });
 	
@@ -37,7 +37,7 @@ fn render_dialog_border<'a>(frame: &mut Frame, top_y: u16, content_width: u16, c
 		.title_alignment(Alignment::Center)
 		.borders(Borders::ALL)
 		.border_type(BorderType::Plain)
-		.border_style(Style::default().fg(color))
+		.border_style(style.border)
 		.padding(Padding::new(PADDING_HORIZONTAL, PADDING_HORIZONTAL, PADDING_VERTICAL, PADDING_VERTICAL));
 	
 	let content_area = border_widget.inner(border_area);
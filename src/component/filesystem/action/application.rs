@@ -1,4 +1,12 @@
+use crate::component::filesystem::command::{self, CommandContext};
+use crate::component::filesystem::compare::CompareLayer;
+use crate::component::filesystem::defaults;
 use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::finder::FinderLayer;
+use crate::component::filesystem::status::StatusMessage;
+use crate::component::help::HelpLayer;
+use crate::component::input::InputFieldOverlayLayer;
+use crate::file::{FileEntry, FileKind};
 use crate::state::action::{Action, ActionResult};
 use crate::state::Environment;
 
@@ -17,3 +25,64 @@ impl Action<FsLayer> for RedrawScreen {
 		ActionResult::Redraw
 	}
 }
+
+pub struct EnterCommandMode;
+
+impl Action<FsLayer> for EnterCommandMode {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		let context = CommandContext::new(layer);
+		ActionResult::push_layer(InputFieldOverlayLayer::new(":", move |command| command::run(&context, &command)))
+	}
+}
+
+pub struct ShowHelp;
+
+impl Action<FsLayer> for ShowHelp {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		ActionResult::push_layer(HelpLayer::new(defaults::describe_key_bindings(layer.action_map)))
+	}
+}
+
+pub struct OpenFinder;
+
+impl Action<FsLayer> for OpenFinder {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		if let Some(root_path) = layer.tree.view_root_path() {
+			ActionResult::push_layer(FinderLayer::new(root_path, layer.tree.filter(), layer.tree.file_system(), layer.events()))
+		} else {
+			ActionResult::Nothing
+		}
+	}
+}
+
+pub struct CompareMarkedWithSelected;
+
+impl Action<FsLayer> for CompareMarkedWithSelected {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		let tree = &layer.tree;
+		let is_directory = |entry: &&FileEntry| matches!(entry.kind(), FileKind::Directory);
+		
+		let marked_paths = tree.marked_model_node_ids().into_iter()
+			.filter_map(|model_node_id| tree.get_model_entry(model_node_id))
+			.filter(is_directory)
+			.filter_map(FileEntry::path)
+			.collect::<Vec<_>>();
+		
+		let selected_path = tree.selected_node()
+			.and_then(|node| tree.get_entry(&node))
+			.filter(is_directory)
+			.and_then(FileEntry::path);
+		
+		let (&[marked_path], Some(selected_path)) = (marked_paths.as_slice(), selected_path) else {
+			layer.show_message(StatusMessage::error("Mark one directory, and select another directory to compare it with."));
+			return ActionResult::Draw;
+		};
+		
+		if marked_path == selected_path {
+			layer.show_message(StatusMessage::error("Select a directory other than the marked one."));
+			return ActionResult::Draw;
+		}
+		
+		ActionResult::push_layer(CompareLayer::new(marked_path, selected_path, tree.filter(), tree.file_system(), layer.events()))
+	}
+}
@@ -1,5 +1,5 @@
-use crate::component::dialog::message::MessageDialogLayer;
 use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::status::StatusMessage;
 use crate::state::action::{Action, ActionResult};
 use crate::state::Environment;
 
@@ -17,10 +17,11 @@ impl Action<FsLayer> for PushCountDigit {
 		if new_count > MAX_COUNT {
 			layer.registers.count = None;
 			
-			ActionResult::push_layer(MessageDialogLayer::error(layer.dialog_y(), format!("Count is too large (> {MAX_COUNT}), it will be reset.")))
+			layer.show_message(StatusMessage::error(format!("Count is too large (> {MAX_COUNT}), it was reset.")));
 		} else {
 			layer.registers.count = Some(new_count);
-			ActionResult::Nothing
 		}
+		
+		ActionResult::Draw
 	}
 }
@@ -0,0 +1,36 @@
+use crate::component::filesystem::FsLayer;
+use crate::state::action::{Action, ActionResult};
+use crate::state::Environment;
+
+/// Cycles between decimal units, binary units, and exact byte counts in the size column.
+pub struct CycleSizeFormat;
+
+impl Action<FsLayer> for CycleSizeFormat {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		layer.set_size_format(layer.size_format.next(), layer.size_kind);
+		ActionResult::Draw
+	}
+}
+
+/// Switches the size column between apparent file sizes and space allocated on disk.
+pub struct ToggleAllocatedSize;
+
+impl Action<FsLayer> for ToggleAllocatedSize {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		layer.set_size_format(layer.size_format, layer.size_kind.toggle());
+		ActionResult::Draw
+	}
+}
+
+/// Scans disk usage of the view root directory and sorts entries by their total size, or turns disk usage mode off and restores the previous sort order.
+pub struct ToggleDiskUsage;
+
+impl Action<FsLayer> for ToggleDiskUsage {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		if !layer.stop_disk_usage() {
+			layer.start_disk_usage(false);
+		}
+		
+		ActionResult::Draw
+	}
+}
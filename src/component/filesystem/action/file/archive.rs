@@ -0,0 +1,176 @@
+use std::path::{Path, PathBuf};
+
+use crate::component::dialog::input::InputFieldDialogLayer;
+use crate::component::dialog::message::MessageDialogLayer;
+use crate::component::filesystem::action::file::{get_entry_kind_name, get_marked_or_selected_paths};
+use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::jobs::{Job, JobKind, JobProgress, run_archive_work};
+use crate::file::{ArchiveKind, ArchiveLocation, create_archive, extract_archive_entry, FileEntry, strip_archive_extension};
+use crate::state::action::{Action, ActionResult};
+use crate::state::Environment;
+use crate::state::event::EventResult;
+use crate::theme::theme;
+
+const DEFAULT_ARCHIVE_EXTENSION: &str = ".tar.gz";
+
+/// Extracts the selected archive, or the selected entry inside an archive, into a directory in the background.
+pub struct ExtractSelectedArchiveEntry;
+
+impl Action<FsLayer> for ExtractSelectedArchiveEntry {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		let Some(entry) = layer.tree.selected_node().and_then(|node| layer.tree.get_entry(&node)) else {
+			return ActionResult::Nothing;
+		};
+		
+		let Some(location) = get_archive_location(entry) else {
+			return ActionResult::Nothing;
+		};
+		
+		let y = layer.dialog_y();
+		let events = layer.events();
+		let file_system = layer.tree.file_system();
+		let name = entry.name().str().to_owned();
+		let archive_directory = location.archive_path().parent().unwrap_or_else(|| Path::new("/")).to_path_buf();
+		
+		// Whole archives are extracted into a new directory named after the archive, so that their contents do not mix with other entries.
+		let (title, initial_target) = if location.is_root() {
+			(String::from("Extract Archive"), archive_directory.join(strip_archive_extension(&name).filter(|stem| !stem.is_empty()).unwrap_or(&name)))
+		} else {
+			(format!("Extract {}", get_entry_kind_name(entry)), archive_directory.clone())
+		};
+		
+		ActionResult::push_layer(InputFieldDialogLayer::build()
+			.y(y)
+			.min_width(60)
+			.style(theme().dialog_info)
+			.title(title)
+			.message(format!("Extracting {} into directory:", location.display_path().to_string_lossy()))
+			.initial_value(Some(initial_target.to_string_lossy().into_owned()))
+			.on_confirm(move |target_directory| {
+				if target_directory.is_empty() {
+					return ActionResult::Nothing;
+				}
+				
+				// Relative paths are relative to the directory that contains the archive.
+				let target_directory = archive_directory.join(target_directory);
+				
+				if !location.is_root() && file_system.exists(&location.extraction_target(&target_directory)) {
+					return ActionResult::push_layer(MessageDialogLayer::error(y.saturating_add(1), "Something with this name already exists."));
+				}
+				
+				let location = location.clone();
+				let name = name.clone();
+				
+				events.enqueue_fn(move |layer, _| {
+					let location = location.clone();
+					let target_directory = target_directory.clone();
+					let target_path = location.extraction_target(&target_directory);
+					
+					Job::build(JobKind::Extract, format!("Extracting {name} into {}", target_directory.to_string_lossy()))
+						.refresh_paths(vec![target_directory.clone()])
+						.success_message(format!("Extracted {name} into {}", target_directory.to_string_lossy()))
+						.on_success(refresh_and_select(target_path))
+						.start(&layer.jobs, move |context| run_archive_work(context, |on_progress| extract_archive_entry(&location, &target_directory, on_progress).map(|_| ())));
+					
+					EventResult::Draw
+				});
+				
+				ActionResult::PopLayer
+			}))
+	}
+}
+
+/// Returns the location of the selected entry inside an archive, or the root of the selected archive file.
+fn get_archive_location(entry: &FileEntry) -> Option<ArchiveLocation> {
+	if let Some(location) = entry.archive_location() {
+		Some(location.clone())
+	} else {
+		entry.path().zip(entry.archive_kind()).map(|(path, kind)| ArchiveLocation::root(path, kind))
+	}
+}
+
+/// Compresses the marked entries, or the selected entry, into an archive in the directory that contains them. The kind of archive is chosen by the extension of its name.
+pub struct CompressSelectedEntries;
+
+impl Action<FsLayer> for CompressSelectedEntries {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		let entry_paths = get_marked_or_selected_paths(layer);
+		
+		let Some(first_path) = entry_paths.first() else {
+			return ActionResult::Nothing;
+		};
+		
+		let Some(directory) = first_path.parent().filter(|directory| entry_paths.iter().all(|path| path.parent() == Some(*directory))).map(Path::to_path_buf) else {
+			return ActionResult::push_layer(MessageDialogLayer::error(layer.dialog_y(), "Marked entries must be in the same directory."));
+		};
+		
+		let y = layer.dialog_y();
+		let events = layer.events();
+		let file_system = layer.tree.file_system();
+		
+		let (description, base_name) = if let [path] = entry_paths.as_slice() {
+			let name = get_file_name(path);
+			(name.clone(), name)
+		} else {
+			(format!("{} entries", entry_paths.len()), get_file_name(&directory))
+		};
+		
+		ActionResult::push_layer(InputFieldDialogLayer::build()
+			.y(y)
+			.min_width(60)
+			.style(theme().dialog_info)
+			.title("Compress")
+			.message(format!("Compressing {description} into an archive in {}:", directory.to_string_lossy()))
+			.initial_value(Some(format!("{base_name}{DEFAULT_ARCHIVE_EXTENSION}")))
+			.on_confirm(move |archive_name| {
+				if archive_name.is_empty() {
+					return ActionResult::Nothing;
+				}
+				
+				let Some(kind) = ArchiveKind::from_name(&archive_name) else {
+					return ActionResult::push_layer(MessageDialogLayer::error(y.saturating_add(1), "Archive name must end with .zip, .tar, .tar.gz, .tar.xz, or .tar.zst."));
+				};
+				
+				let archive_path = directory.join(&archive_name);
+				
+				if file_system.exists(&archive_path) {
+					return ActionResult::push_layer(MessageDialogLayer::error(y.saturating_add(1), "Something with this name already exists."));
+				}
+				
+				let entry_paths = entry_paths.clone();
+				let directory = directory.clone();
+				
+				events.enqueue_fn(move |layer, _| {
+					let archive_path = archive_path.clone();
+					let entry_paths = entry_paths.clone();
+					let archive_name = get_file_name(&archive_path);
+					
+					Job::build(JobKind::Compress, format!("Compressing {archive_name}"))
+						.refresh_paths(vec![directory.clone()])
+						.success_message(format!("Created {archive_name}"))
+						.on_success(refresh_and_select(archive_path.clone()))
+						.start(&layer.jobs, move |context| run_archive_work(context, |on_progress| create_archive(&archive_path, kind, &entry_paths, on_progress)));
+					
+					EventResult::Draw
+				});
+				
+				ActionResult::PopLayer
+			}))
+	}
+}
+
+fn get_file_name(path: &Path) -> String {
+	path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+/// Refreshes the directory that contains the new entry, and selects the entry. Jobs refresh their directories after this runs, which would be too late to select it.
+fn refresh_and_select(path: PathBuf) -> impl Fn(&mut FsLayer, JobProgress) -> EventResult {
+	move |layer, _| {
+		if let Some(directory) = path.parent() {
+			layer.tree.refresh_model_directory(directory);
+		}
+		
+		layer.tree.select_path(&path);
+		EventResult::Draw
+	}
+}
@@ -0,0 +1,166 @@
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+use std::process::Command;
+
+use slab_tree::NodeId;
+
+use crate::component::dialog::message::MessageDialogLayer;
+use crate::component::filesystem::action::file::bulk_rename::{apply_renames_and_refresh, collect_rename_targets, create_conflict_dialog, format_rename_preview, MAX_PREVIEW_LINES, plan_renames, PlannedRename, RenameTarget};
+use crate::component::filesystem::action::file::edit::{get_editor, run_editor};
+use crate::component::filesystem::action::file::format_io_error;
+use crate::component::filesystem::FsLayer;
+use crate::file::{create_temporary_file, FileKind};
+use crate::state::action::{Action, ActionResult};
+use crate::state::Environment;
+use crate::state::event::EventResult;
+use crate::theme::theme;
+
+/// Opens the default editor with names of all marked entries, or of all children of the selected directory if nothing is marked, and renames every entry whose name was changed.
+pub struct BulkRenameInEditor;
+
+impl Action<FsLayer> for BulkRenameInEditor {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		let targets = collect_targets(layer);
+		if targets.is_empty() {
+			return ActionResult::Nothing;
+		}
+		
+		let y = layer.dialog_y();
+		
+		if targets.iter().any(|target| target.name.contains('\n')) {
+			return ActionResult::push_layer(MessageDialogLayer::error(y, "Names with line breaks cannot be renamed in an editor."));
+		}
+		
+		let edited_text = match edit_names_in_editor(&targets) {
+			Ok(edited_text) => edited_text,
+			Err(e) => return ActionResult::push_layer(MessageDialogLayer::error(y, e)),
+		};
+		
+		// The editor took over the terminal, so the screen must be fully redrawn.
+		layer.events().enqueue_fn(|_, _| EventResult::Redraw);
+		
+		let renames = match parse_edited_names(&edited_text, &targets).and_then(|new_names| plan_renames(layer.tree.file_system().as_ref(), &targets, new_names)) {
+			Ok(renames) => renames,
+			Err(errors) => return ActionResult::push_layer(create_conflict_dialog(y, errors)),
+		};
+		
+		if renames.is_empty() {
+			ActionResult::Redraw
+		} else {
+			ActionResult::push_layer(create_confirmation_dialog(layer, renames))
+		}
+	}
+}
+
+fn collect_targets(layer: &mut FsLayer) -> Vec<RenameTarget> {
+	let mut model_node_ids = layer.tree.marked_model_node_ids();
+	
+	if model_node_ids.is_empty() {
+		model_node_ids = collect_children_of_selected_directory(layer);
+	}
+	
+	collect_rename_targets(&layer.tree, model_node_ids)
+}
+
+/// Collects children of the selected directory, or siblings of the selected file.
+fn collect_children_of_selected_directory(layer: &mut FsLayer) -> Vec<NodeId> {
+	let tree = &mut layer.tree;
+	
+	let Some(selected_node) = tree.selected_node() else {
+		return Vec::new();
+	};
+	
+	let directory_node_id = if tree.get_entry(&selected_node).is_some_and(|entry| matches!(entry.kind(), FileKind::Directory)) {
+		Some(selected_node.node_id())
+	} else {
+		selected_node.parent_id()
+	};
+	
+	let Some(directory_node_id) = directory_node_id else {
+		return Vec::new();
+	};
+	
+	tree.expand(directory_node_id);
+	
+	tree.get_view_node(directory_node_id)
+	    .map(|node| node.children().map(|child| child.data().model_node_id()).collect())
+	    .unwrap_or_default()
+}
+
+fn edit_names_in_editor(targets: &[RenameTarget]) -> Result<String, String> {
+	let (file_path, file) = create_temporary_file("bark-rename", "txt").map_err(|e| format!("Could not create temporary file: {}", format_io_error(&e)))?;
+	let result = write_and_edit_file(&file_path, file, &format_names(targets));
+	let _ = fs::remove_file(&file_path);
+	result
+}
+
+fn write_and_edit_file(file_path: &Path, mut file: File, text: &str) -> Result<String, String> {
+	file.write_all(text.as_bytes()).map_err(|e| format!("Could not write temporary file: {}", format_io_error(&e)))?;
+	drop(file);
+	
+	let editor = get_editor();
+	let status = run_editor(Command::new(&editor).arg(file_path));
+	
+	match status {
+		Ok(status) if status.success() => {}
+		Ok(_) => return Err(String::from("Editor exited with an error, no entries were renamed.")),
+		Err(e) if e.kind() == ErrorKind::NotFound => return Err(format!("Default editor '{}' not found.", editor.to_string_lossy())),
+		Err(e) => return Err(format!("Could not open editor: {}", format_io_error(&e))),
+	}
+	
+	fs::read_to_string(file_path).map_err(|e| format!("Could not read temporary file: {}", format_io_error(&e)))
+}
+
+fn format_names(targets: &[RenameTarget]) -> String {
+	let number_width = targets.len().to_string().len();
+	let mut text = String::new();
+	
+	for (index, target) in targets.iter().enumerate() {
+		let _ = writeln!(text, "{:0number_width$}\t{}", index.saturating_add(1), target.name);
+	}
+	
+	text
+}
+
+/// Returns the new name of every target, or `None` if its line was removed.
+fn parse_edited_names(text: &str, targets: &[RenameTarget]) -> Result<Vec<Option<String>>, Vec<String>> {
+	let mut new_names = targets.iter().map(|_| None).collect::<Vec<_>>();
+	let mut errors = Vec::new();
+	
+	for (line_index, line) in text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+		let line_number = line_index.saturating_add(1);
+		
+		let Some((number, name)) = line.split_once('\t') else {
+			errors.push(format!("Line {line_number} is missing a tab after the entry number."));
+			continue;
+		};
+		
+		match number.trim().parse::<usize>().ok().and_then(|number| number.checked_sub(1)).and_then(|index| new_names.get_mut(index)) {
+			Some(new_name @ None) => *new_name = Some(name.to_owned()),
+			Some(Some(_)) => errors.push(format!("Line {line_number} repeats entry number {}.", number.trim())),
+			None => errors.push(format!("Line {line_number} has an invalid entry number.")),
+		}
+	}
+	
+	if errors.is_empty() {
+		Ok(new_names)
+	} else {
+		Err(errors)
+	}
+}
+
+fn create_confirmation_dialog<'a>(layer: &FsLayer, renames: Vec<PlannedRename>) -> MessageDialogLayer<'a> {
+	let y = layer.dialog_y();
+	let events = layer.events();
+	let file_system = layer.tree.file_system();
+	let pluralized_entries = if renames.len() == 1 { "Entry" } else { "Entries" };
+	
+	MessageDialogLayer::build()
+		.y(y)
+		.style(theme().dialog_info)
+		.title(format!("Rename {} {pluralized_entries}", renames.len()))
+		.message(format_rename_preview(&renames, MAX_PREVIEW_LINES))
+		.yes_no(move || apply_renames_and_refresh(file_system.as_ref(), y.saturating_add(1), &events, &renames))
+}
@@ -0,0 +1,333 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{self, ErrorKind};
+use std::process;
+use std::path::{Component, Path, PathBuf};
+
+use ratatui::text::Line;
+use slab_tree::NodeId;
+
+use crate::component::dialog::message::MessageDialogLayer;
+use crate::component::filesystem::action::file::create_path_errors_dialog;
+use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::status::StatusMessage;
+use crate::component::filesystem::tree::FsTree;
+use crate::file::FileSystem;
+use crate::state::action::ActionResult;
+use crate::state::event::{EventQueue, EventResult};
+use crate::theme::theme;
+
+pub use self::editor::*;
+pub use self::substitute::*;
+
+mod editor;
+mod substitute;
+
+struct RenameTarget {
+	parent_model_node_id: NodeId,
+	path: PathBuf,
+	name: String,
+}
+
+struct PlannedRename {
+	parent_model_node_id: NodeId,
+	old_path: PathBuf,
+	new_path: PathBuf,
+}
+
+/// Collects entries that can be renamed, sorted in the order they appear in the tree.
+fn collect_rename_targets(tree: &FsTree, model_node_ids: Vec<NodeId>) -> Vec<RenameTarget> {
+	let mut targets = model_node_ids.into_iter().filter_map(|model_node_id| {
+		let parent_model_node_id = tree.get_model_parent_id(model_node_id)?;
+		let entry = tree.get_model_entry(model_node_id)?;
+		let path = entry.path()?.to_path_buf();
+		let name = entry.name().str().to_owned();
+		Some(RenameTarget { parent_model_node_id, path, name })
+	}).collect::<Vec<_>>();
+	
+	targets.sort_by(|a, b| a.path.cmp(&b.path));
+	targets
+}
+
+fn plan_renames(file_system: &dyn FileSystem, targets: &[RenameTarget], new_names: Vec<Option<String>>) -> Result<Vec<PlannedRename>, Vec<String>> {
+	let mut renames = Vec::new();
+	let mut errors = Vec::new();
+	
+	for (target, new_name) in targets.iter().zip(new_names) {
+		let Some(new_name) = new_name.filter(|new_name| *new_name != target.name) else {
+			continue;
+		};
+		
+		if is_valid_name(&new_name) {
+			renames.push(PlannedRename {
+				parent_model_node_id: target.parent_model_node_id,
+				old_path: target.path.clone(),
+				new_path: target.path.with_file_name(&new_name),
+			});
+		} else {
+			errors.push(format!("{} → {}: Invalid name.", target.name, new_name));
+		}
+	}
+	
+	let old_paths = renames.iter().map(|rename| rename.old_path.as_path()).collect::<HashSet<_>>();
+	let mut new_path_counts = HashMap::<&Path, usize>::new();
+	
+	for rename in &renames {
+		let count = new_path_counts.entry(rename.new_path.as_path()).or_default();
+		*count = count.saturating_add(1);
+	}
+	
+	for rename in &renames {
+		let new_path = rename.new_path.as_path();
+		
+		if new_path_counts.get(new_path).is_some_and(|count| *count > 1) {
+			let error = format!("{}: Multiple entries would be renamed to this name.", new_path.to_string_lossy());
+			if !errors.contains(&error) {
+				errors.push(error);
+			}
+		} else if !old_paths.contains(new_path) && file_system.exists(new_path) {
+			errors.push(format!("{}: Something with this name already exists.", new_path.to_string_lossy()));
+		}
+	}
+	
+	if errors.is_empty() {
+		Ok(renames)
+	} else {
+		Err(errors)
+	}
+}
+
+fn is_valid_name(name: &str) -> bool {
+	let mut components = Path::new(name).components();
+	matches!((components.next(), components.next()), (Some(Component::Normal(_)), None)) && !name.contains('/')
+}
+
+/// Renames all entries. Entries that would be renamed to the current name of another renamed entry, such as when swapping names or renaming in a cycle, are first moved to a temporary name. If any step fails, the steps before it are undone, so that no entry is left under a temporary name.
+fn apply_renames(file_system: &dyn FileSystem, renames: &[PlannedRename]) -> Vec<(PathBuf, io::Error)> {
+	let old_paths = renames.iter().map(|rename| rename.old_path.as_path()).collect::<HashSet<_>>();
+	
+	let mut taken_paths = renames.iter().map(|rename| rename.new_path.clone()).collect::<HashSet<_>>();
+	
+	let mut temporary_steps = Vec::new();
+	let mut direct_steps = Vec::new();
+	let mut final_steps = Vec::new();
+	
+	for rename in renames {
+		if old_paths.contains(rename.new_path.as_path()) {
+			let temporary_path = match find_temporary_path(file_system, &rename.old_path, &taken_paths) {
+				Ok(temporary_path) => temporary_path,
+				Err(e) => return vec![(rename.old_path.clone(), e)],
+			};
+			
+			taken_paths.insert(temporary_path.clone());
+			temporary_steps.push((rename.old_path.clone(), temporary_path.clone(), &rename.old_path));
+			final_steps.push((temporary_path, rename.new_path.clone(), &rename.old_path));
+		} else {
+			direct_steps.push((rename.old_path.clone(), rename.new_path.clone(), &rename.old_path));
+		}
+	}
+	
+	let mut completed_steps = Vec::<(PathBuf, PathBuf)>::new();
+	
+	for (from, to, original_path) in temporary_steps.into_iter().chain(direct_steps).chain(final_steps) {
+		if let Err(e) = file_system.rename(&from, &to) {
+			let mut errors = vec![(original_path.clone(), e)];
+			
+			for (from, to) in completed_steps.into_iter().rev() {
+				if let Err(e) = file_system.rename(&to, &from) {
+					errors.push((to, e));
+				}
+			}
+			
+			return errors;
+		}
+		
+		completed_steps.push((from, to));
+	}
+	
+	Vec::new()
+}
+
+/// Finds a temporary name next to the path that does not exist, and that no other rename uses.
+fn find_temporary_path(file_system: &dyn FileSystem, path: &Path, taken_paths: &HashSet<PathBuf>) -> io::Result<PathBuf> {
+	for number in 0_u32.. {
+		let candidate = path.with_file_name(format!(".bark-rename-{}-{number}", process::id()));
+		
+		if !taken_paths.contains(&candidate) && !file_system.exists(&candidate) {
+			return Ok(candidate);
+		}
+	}
+	
+	Err(io::Error::new(ErrorKind::AlreadyExists, "Could not find a free temporary name"))
+}
+
+/// Applies all renames, refreshes affected directories, and reports every entry that could not be renamed in an error dialog.
+fn apply_renames_and_refresh(file_system: &dyn FileSystem, y: u16, events: &EventQueue<FsLayer>, renames: &[PlannedRename]) -> ActionResult {
+	let errors = apply_renames(file_system, renames);
+	
+	let parent_model_node_ids = renames.iter().map(|rename| rename.parent_model_node_id).collect::<HashSet<_>>();
+	
+	events.enqueue_fn(move |layer, _| {
+		layer.tree.clear_marks();
+		
+		for parent_model_node_id in &parent_model_node_ids {
+			layer.tree.refresh_model_children(*parent_model_node_id);
+		}
+		
+		EventResult::Draw
+	});
+	
+	if errors.is_empty() {
+		let pluralized_entries = if renames.len() == 1 { "entry" } else { "entries" };
+		events.enqueue(StatusMessage::info(format!("Renamed {} {pluralized_entries}", renames.len())));
+		ActionResult::PopLayer
+	} else {
+		ActionResult::replace_layer(create_path_errors_dialog(y, &errors))
+	}
+}
+
+const MAX_PREVIEW_LINES: usize = 20;
+
+fn format_rename_preview<'a>(renames: &[PlannedRename], max_lines: usize) -> Vec<Line<'a>> {
+	let mut lines = renames.iter()
+		.take(max_lines)
+		.map(|rename| Line::from(format!("{} → {}", get_file_name(&rename.old_path), get_file_name(&rename.new_path))))
+		.collect::<Vec<_>>();
+	
+	if let Some(remaining) = renames.len().checked_sub(max_lines).filter(|remaining| *remaining > 0) {
+		lines.push(Line::from(format!("...and {remaining} more.")));
+	}
+	
+	lines
+}
+
+fn format_rename_errors<'a>(errors: Vec<String>, max_lines: usize) -> Vec<Line<'a>> {
+	let error_count = errors.len();
+	let mut lines = errors.into_iter().take(max_lines).map(Line::from).collect::<Vec<_>>();
+	
+	if let Some(remaining) = error_count.checked_sub(max_lines).filter(|remaining| *remaining > 0) {
+		lines.push(Line::from(format!("...and {remaining} more.")));
+	}
+	
+	lines
+}
+
+fn create_conflict_dialog<'a>(y: u16, errors: Vec<String>) -> MessageDialogLayer<'a> {
+	let mut lines = format_rename_errors(errors, MAX_PREVIEW_LINES);
+	lines.push(Line::from("No entries were renamed."));
+	
+	MessageDialogLayer::build()
+		.y(y)
+		.style(theme().dialog_error)
+		.title("Rename Conflicts")
+		.message(lines)
+		.ok()
+}
+
+fn get_file_name(path: &Path) -> String {
+	path.file_name().map_or_else(|| path.to_string_lossy(), |name| name.to_string_lossy()).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::{Path, PathBuf};
+	use std::process;
+	
+	use slab_tree::{NodeId, Tree};
+	
+	use crate::component::filesystem::action::file::bulk_rename::{apply_renames, PlannedRename};
+	use crate::file::{FileSystem, MemoryFileSystem};
+	
+	fn create_renames(pairs: &[(&str, &str)]) -> Vec<PlannedRename> {
+		let parent_model_node_id: NodeId = Tree::new().set_root(());
+		
+		pairs.iter().map(|(old_path, new_path)| PlannedRename {
+			parent_model_node_id,
+			old_path: PathBuf::from(old_path),
+			new_path: PathBuf::from(new_path),
+		}).collect()
+	}
+	
+	fn contents(file_system: &MemoryFileSystem, path: &str) -> Option<String> {
+		file_system.read(Path::new(path)).ok().map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+	}
+	
+	fn names(file_system: &MemoryFileSystem, path: &str) -> Vec<String> {
+		let mut names = file_system.list(Path::new(path)).unwrap_or_default().iter().map(|entry| entry.name().str().to_owned()).collect::<Vec<_>>();
+		names.sort();
+		names
+	}
+	
+	#[test]
+	fn renames_entries() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/dir/a", "a");
+		
+		let errors = apply_renames(&file_system, &create_renames(&[("/dir/a", "/dir/b")]));
+		
+		assert!(errors.is_empty(), "rename failed");
+		assert_eq!(names(&file_system, "/dir"), vec!["b"]);
+		assert_eq!(contents(&file_system, "/dir/b").as_deref(), Some("a"));
+	}
+	
+	#[test]
+	fn swaps_names() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/dir/a", "a")
+			.with_file("/dir/b", "b");
+		
+		let errors = apply_renames(&file_system, &create_renames(&[("/dir/a", "/dir/b"), ("/dir/b", "/dir/a")]));
+		
+		assert!(errors.is_empty(), "swap failed");
+		assert_eq!(names(&file_system, "/dir"), vec!["a", "b"]);
+		assert_eq!(contents(&file_system, "/dir/a").as_deref(), Some("b"));
+		assert_eq!(contents(&file_system, "/dir/b").as_deref(), Some("a"));
+	}
+	
+	#[test]
+	fn renames_in_cycle() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/dir/a", "a")
+			.with_file("/dir/b", "b")
+			.with_file("/dir/c", "c");
+		
+		let errors = apply_renames(&file_system, &create_renames(&[("/dir/a", "/dir/b"), ("/dir/b", "/dir/c"), ("/dir/c", "/dir/a")]));
+		
+		assert!(errors.is_empty(), "cyclic rename failed");
+		assert_eq!(names(&file_system, "/dir"), vec!["a", "b", "c"]);
+		assert_eq!(contents(&file_system, "/dir/a").as_deref(), Some("c"));
+		assert_eq!(contents(&file_system, "/dir/b").as_deref(), Some("a"));
+		assert_eq!(contents(&file_system, "/dir/c").as_deref(), Some("b"));
+	}
+	
+	#[test]
+	fn skips_taken_temporary_names() {
+		let taken_name = format!("/dir/.bark-rename-{}-0", process::id());
+		
+		let file_system = MemoryFileSystem::new()
+			.with_file("/dir/a", "a")
+			.with_file("/dir/b", "b")
+			.with_file(&taken_name, "unrelated");
+		
+		let errors = apply_renames(&file_system, &create_renames(&[("/dir/a", "/dir/b"), ("/dir/b", "/dir/a")]));
+		
+		assert!(errors.is_empty(), "swap failed");
+		assert_eq!(contents(&file_system, "/dir/a").as_deref(), Some("b"));
+		assert_eq!(contents(&file_system, "/dir/b").as_deref(), Some("a"));
+		assert_eq!(contents(&file_system, &taken_name).as_deref(), Some("unrelated"));
+		assert_eq!(names(&file_system, "/dir").len(), 3);
+	}
+	
+	#[test]
+	fn undoes_completed_renames_when_one_fails() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/dir/a", "a")
+			.with_file("/dir/b", "b");
+		
+		let errors = apply_renames(&file_system, &create_renames(&[("/dir/a", "/dir/b"), ("/dir/b", "/dir/a"), ("/dir/missing", "/dir/c")]));
+		
+		assert_eq!(errors.iter().map(|(path, _)| path.as_path()).collect::<Vec<_>>(), vec![Path::new("/dir/missing")]);
+		assert_eq!(names(&file_system, "/dir"), vec!["a", "b"]);
+		assert_eq!(contents(&file_system, "/dir/a").as_deref(), Some("a"));
+		assert_eq!(contents(&file_system, "/dir/b").as_deref(), Some("b"));
+	}
+}
@@ -0,0 +1,566 @@
+use std::iter::Peekable;
+use std::rc::Rc;
+use std::slice;
+use std::str::Chars;
+use std::sync::Arc;
+
+use ratatui::text::{Line, Text};
+use regex::{Captures, Regex, RegexBuilder};
+
+use crate::component::dialog::input::InputFieldDialogLayer;
+use crate::component::filesystem::action::file::bulk_rename::{apply_renames_and_refresh, collect_rename_targets, create_conflict_dialog, format_rename_errors, format_rename_preview, plan_renames, PlannedRename, RenameTarget};
+use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::tree::FsTree;
+use crate::file::FileSystem;
+use crate::state::action::ActionResult;
+use crate::state::event::EventQueue;
+use crate::theme::theme;
+
+/// Entries a substitution can rename, captured when entering command mode.
+pub struct SubstitutionTargets {
+	marked: Vec<RenameTarget>,
+	selected: Option<RenameTarget>,
+	siblings: Vec<RenameTarget>,
+	file_system: Arc<dyn FileSystem>,
+}
+
+impl SubstitutionTargets {
+	pub fn collect(tree: &FsTree) -> Self {
+		let marked = collect_rename_targets(tree, tree.marked_model_node_ids());
+		let file_system = tree.file_system();
+		
+		let Some(selected_node) = tree.selected_node() else {
+			return Self { marked, selected: None, siblings: Vec::new(), file_system };
+		};
+		
+		let selected = collect_rename_targets(tree, vec![selected_node.data().model_node_id()]).pop();
+		
+		let siblings = selected_node.parent()
+			.map(|parent| parent.children().map(|child| child.data().model_node_id()).collect())
+			.map(|model_node_ids| collect_rename_targets(tree, model_node_ids))
+			.unwrap_or_default();
+		
+		Self { marked, selected, siblings, file_system }
+	}
+	
+	fn get(&self, scope: SubstitutionScope) -> &[RenameTarget] {
+		match scope {
+			SubstitutionScope::Selection if !self.marked.is_empty() => &self.marked,
+			SubstitutionScope::Selection => self.selected.as_ref().map(slice::from_ref).unwrap_or_default(),
+			SubstitutionScope::Siblings => &self.siblings,
+		}
+	}
+}
+
+#[derive(Copy, Clone)]
+enum SubstitutionScope {
+	/// Marked entries, or the selected entry if nothing is marked.
+	Selection,
+	/// The selected entry and all its siblings.
+	Siblings,
+}
+
+const MAX_LIVE_PREVIEW_LINES: usize = 10;
+
+/// Creates a dialog for editing a substitution expression, which shows a live preview of all renames before they are applied.
+#[allow(clippy::literal_string_with_formatting_args)]
+pub fn create_substitution_dialog<'a>(y: u16, events: EventQueue<FsLayer>, targets: Rc<SubstitutionTargets>, expression: &str) -> InputFieldDialogLayer<'a> {
+	let preview_targets = Rc::clone(&targets);
+	
+	InputFieldDialogLayer::build()
+		.y(y)
+		.min_width(60)
+		.style(theme().dialog_info)
+		.title("Rename by Pattern")
+		.message(vec![
+			Line::from("Syntax: [%]s/pattern/replacement/[flags]"),
+			Line::from("Replacement: $1 ${name} \\U \\L \\E \\u \\l {n} {n:03}"),
+			Line::from("Flags: g (all matches), i (ignore case)"),
+		])
+		.initial_value(Some(expression))
+		.preview(move |expression| create_preview(&preview_targets, expression))
+		.on_confirm(move |expression| {
+			match plan_substitution(&targets, &expression) {
+				Ok(renames) if renames.is_empty() => ActionResult::PopLayer,
+				Ok(renames) => apply_renames_and_refresh(targets.file_system.as_ref(), y.saturating_add(1), &events, &renames),
+				Err(errors) => ActionResult::push_layer(create_conflict_dialog(y.saturating_add(1), errors)),
+			}
+		})
+}
+
+fn create_preview(targets: &SubstitutionTargets, expression: &str) -> Text<'static> {
+	match plan_substitution(targets, expression) {
+		Ok(renames) if renames.is_empty() => Text::styled("No names would change.", theme().secondary),
+		Ok(renames) => Text::from(format_rename_preview(&renames, MAX_LIVE_PREVIEW_LINES)),
+		Err(errors) => {
+			let mut text = Text::from(format_rename_errors(errors, MAX_LIVE_PREVIEW_LINES));
+			text.patch_style(theme().error_text);
+			text
+		}
+	}
+}
+
+fn plan_substitution(targets: &SubstitutionTargets, expression: &str) -> Result<Vec<PlannedRename>, Vec<String>> {
+	let (scope, substitution) = parse_expression(expression).map_err(|e| vec![e])?;
+	let file_system = targets.file_system.as_ref();
+	let targets = targets.get(scope);
+	
+	let new_names = substitution.apply_all(targets.iter().map(|target| target.name.as_str()));
+	plan_renames(file_system, targets, new_names)
+}
+
+struct Substitution {
+	regex: Regex,
+	replacement: Vec<ReplacementPart>,
+	replace_all: bool,
+}
+
+enum ReplacementPart {
+	Text(String),
+	Group(GroupReference),
+	Counter { width: usize, zero_padded: bool },
+	Case(CaseChange),
+}
+
+enum GroupReference {
+	Index(usize),
+	Name(String),
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum CaseChange {
+	Upper,
+	Lower,
+	End,
+	UpperNext,
+	LowerNext,
+}
+
+/// Parses `s/pattern/replacement/flags`, where `/` can be any character that is not alphanumeric, whitespace, or a backslash.
+fn parse_expression(expression: &str) -> Result<(SubstitutionScope, Substitution), String> {
+	let (scope, expression) = match expression.trim_start().strip_prefix('%') {
+		Some(expression) => (SubstitutionScope::Siblings, expression),
+		None => (SubstitutionScope::Selection, expression.trim_start()),
+	};
+	
+	let Some(expression) = expression.strip_prefix('s') else {
+		return Err(String::from("Expected s/pattern/replacement/flags."));
+	};
+	
+	let mut chars = expression.chars();
+	
+	let Some(delimiter) = chars.next() else {
+		return Err(String::from("Missing pattern."));
+	};
+	
+	if delimiter.is_alphanumeric() || delimiter.is_whitespace() || delimiter == '\\' {
+		return Err(format!("Invalid delimiter: {delimiter}"));
+	}
+	
+	let parts = split_by_delimiter(chars.as_str(), delimiter);
+	let mut parts = parts.iter();
+	
+	let pattern = parts.next().map(String::as_str).unwrap_or_default();
+	let replacement = parts.next().map(String::as_str).unwrap_or_default();
+	let flags = parts.next().map(String::as_str).unwrap_or_default();
+	
+	if parts.next().is_some() {
+		return Err(format!("Too many '{delimiter}' delimiters."));
+	}
+	
+	if pattern.is_empty() {
+		return Err(String::from("Missing pattern."));
+	}
+	
+	let mut replace_all = false;
+	let mut ignore_case = false;
+	
+	for flag in flags.chars() {
+		match flag {
+			'g' => replace_all = true,
+			'i' => ignore_case = true,
+			_ => return Err(format!("Unknown flag: {flag}")),
+		}
+	}
+	
+	let regex = RegexBuilder::new(pattern)
+		.case_insensitive(ignore_case)
+		.build()
+		.map_err(|e| format!("Invalid pattern: {}", e.to_string().lines().last().unwrap_or_default().trim_start_matches("error: ")))?;
+	
+	let replacement = parse_replacement(replacement)?;
+	validate_group_references(&regex, &replacement)?;
+	
+	Ok((scope, Substitution { regex, replacement, replace_all }))
+}
+
+/// Splits text by every delimiter that is not escaped by a backslash. Escaped delimiters lose their backslash, other escape sequences are kept as they are.
+fn split_by_delimiter(text: &str, delimiter: char) -> Vec<String> {
+	let mut parts = vec![String::new()];
+	let mut chars = text.chars();
+	
+	while let Some(c) = chars.next() {
+		let Some(part) = parts.last_mut() else {
+			break;
+		};
+		
+		if c == delimiter {
+			parts.push(String::new());
+		} else if c == '\\' {
+			match chars.next() {
+				Some(next) if next == delimiter => part.push(next),
+				Some(next) => {
+					part.push(c);
+					part.push(next);
+				}
+				None => part.push(c),
+			}
+		} else {
+			part.push(c);
+		}
+	}
+	
+	parts
+}
+
+fn parse_replacement(text: &str) -> Result<Vec<ReplacementPart>, String> {
+	let mut parts = Vec::new();
+	let mut literal = String::new();
+	let mut chars = text.chars().peekable();
+	
+	while let Some(c) = chars.next() {
+		let part = match c {
+			'\\' => parse_escape_sequence(&mut chars),
+			'$' => parse_group_reference(&mut chars)?,
+			'{' => parse_counter(&mut chars)?,
+			_ => None,
+		};
+		
+		if let Some(part) = part {
+			if !literal.is_empty() {
+				parts.push(ReplacementPart::Text(std::mem::take(&mut literal)));
+			}
+			parts.push(part);
+		} else if c == '\\' {
+			literal.push(chars.next().unwrap_or('\\'));
+		} else if c == '$' && chars.peek() == Some(&'$') {
+			literal.push(c);
+			chars.next();
+		} else {
+			literal.push(c);
+		}
+	}
+	
+	if !literal.is_empty() {
+		parts.push(ReplacementPart::Text(literal));
+	}
+	
+	Ok(parts)
+}
+
+/// Parses `\0` to `\9`, and case conversions `\U`, `\L`, `\E`, `\u`, `\l`. Other escaped characters are literals.
+fn parse_escape_sequence(chars: &mut Peekable<Chars>) -> Option<ReplacementPart> {
+	let part = match chars.peek()? {
+		'U' => ReplacementPart::Case(CaseChange::Upper),
+		'L' => ReplacementPart::Case(CaseChange::Lower),
+		'E' => ReplacementPart::Case(CaseChange::End),
+		'u' => ReplacementPart::Case(CaseChange::UpperNext),
+		'l' => ReplacementPart::Case(CaseChange::LowerNext),
+		c => ReplacementPart::Group(GroupReference::Index(usize::try_from(c.to_digit(10)?).ok()?)),
+	};
+	
+	chars.next();
+	Some(part)
+}
+
+/// Parses `$1` and `${name}`. A `$` that is not followed by either is a literal.
+fn parse_group_reference(chars: &mut Peekable<Chars>) -> Result<Option<ReplacementPart>, String> {
+	if chars.next_if_eq(&'{').is_some() {
+		let mut name = String::new();
+		
+		loop {
+			match chars.next() {
+				Some('}') => break,
+				Some(c) => name.push(c),
+				None => return Err(String::from("Missing '}' after capture group name.")),
+			}
+		}
+		
+		let reference = match name.parse::<usize>() {
+			Ok(index) => GroupReference::Index(index),
+			Err(_) => GroupReference::Name(name),
+		};
+		
+		return Ok(Some(ReplacementPart::Group(reference)));
+	}
+	
+	let mut digits = String::new();
+	
+	while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+		digits.push(digit);
+	}
+	
+	if digits.is_empty() {
+		Ok(None)
+	} else {
+		digits.parse::<usize>()
+		      .map(|index| Some(ReplacementPart::Group(GroupReference::Index(index))))
+		      .map_err(|_| format!("Invalid capture group: {digits}"))
+	}
+}
+
+/// Parses `{n}`, `{n:W}`, and `{n:0W}`, where `W` is the minimum width of the counter. A `{` that is not followed by `n}` or `n:` is a literal.
+fn parse_counter(chars: &mut Peekable<Chars>) -> Result<Option<ReplacementPart>, String> {
+	let mut lookahead = chars.clone();
+	
+	if lookahead.next() != Some('n') {
+		return Ok(None);
+	}
+	
+	let part = match lookahead.next() {
+		Some('}') => ReplacementPart::Counter { width: 0, zero_padded: false },
+		
+		Some(':') => {
+			let mut format = String::new();
+			
+			loop {
+				match lookahead.next() {
+					Some('}') => break,
+					Some(c) => format.push(c),
+					None => return Err(String::from("Missing '}' after counter format.")),
+				}
+			}
+			
+			let zero_padded = format.starts_with('0') && format.len() > 1;
+			let width = format.parse::<usize>().map_err(|_| format!("Invalid counter format: {format}"))?;
+			ReplacementPart::Counter { width, zero_padded }
+		}
+		
+		_ => return Ok(None),
+	};
+	
+	*chars = lookahead;
+	Ok(Some(part))
+}
+
+#[allow(clippy::wildcard_enum_match_arm)]
+fn validate_group_references(regex: &Regex, replacement: &[ReplacementPart]) -> Result<(), String> {
+	for part in replacement {
+		match part {
+			ReplacementPart::Group(GroupReference::Index(index)) if *index >= regex.captures_len() => {
+				return Err(format!("Unknown capture group: {index}"));
+			}
+			
+			ReplacementPart::Group(GroupReference::Name(name)) if !regex.capture_names().flatten().any(|capture_name| capture_name == name) => {
+				return Err(format!("Unknown capture group: {name}"));
+			}
+			
+			_ => {}
+		}
+	}
+	
+	Ok(())
+}
+
+impl Substitution {
+	/// Returns the new name of every name that matches the pattern. The counter starts at 1 and only counts matching names.
+	fn apply_all<'a>(&self, names: impl Iterator<Item = &'a str>) -> Vec<Option<String>> {
+		let mut counter = 0_usize;
+		
+		names.map(|name| {
+			self.regex.is_match(name).then(|| {
+				counter = counter.saturating_add(1);
+				self.apply(name, counter)
+			})
+		}).collect()
+	}
+	
+	fn apply(&self, name: &str, counter: usize) -> String {
+		let limit = if self.replace_all { usize::MAX } else { 1 };
+		
+		let mut result = String::new();
+		let mut last_end = 0;
+		
+		for captures in self.regex.captures_iter(name).take(limit) {
+			let Some(whole_match) = captures.get(0) else {
+				continue;
+			};
+			
+			result.push_str(name.get(last_end..whole_match.start()).unwrap_or_default());
+			self.expand(&captures, counter, &mut result);
+			last_end = whole_match.end();
+		}
+		
+		result.push_str(name.get(last_end..).unwrap_or_default());
+		result
+	}
+	
+	fn expand(&self, captures: &Captures, counter: usize, output: &mut String) {
+		let mut case = CaseConverter::new();
+		
+		for part in &self.replacement {
+			match part {
+				ReplacementPart::Text(text) => case.push_str(output, text),
+				ReplacementPart::Group(GroupReference::Index(index)) => case.push_str(output, captures.get(*index).map_or("", |group| group.as_str())),
+				ReplacementPart::Group(GroupReference::Name(name)) => case.push_str(output, captures.name(name).map_or("", |group| group.as_str())),
+				ReplacementPart::Counter { width, zero_padded: true } => case.push_str(output, &format!("{counter:0width$}")),
+				ReplacementPart::Counter { width, zero_padded: false } => case.push_str(output, &format!("{counter:width$}")),
+				ReplacementPart::Case(change) => case.change(*change),
+			}
+		}
+	}
+}
+
+/// Converts case of replaced text, following `\U`, `\L`, `\E`, `\u`, and `\l` from `vim`.
+struct CaseConverter {
+	mode: CaseChange,
+	next_char: Option<CaseChange>,
+}
+
+impl CaseConverter {
+	const fn new() -> Self {
+		Self { mode: CaseChange::End, next_char: None }
+	}
+	
+	fn change(&mut self, change: CaseChange) {
+		match change {
+			CaseChange::Upper | CaseChange::Lower | CaseChange::End => self.mode = change,
+			CaseChange::UpperNext | CaseChange::LowerNext => self.next_char = Some(change),
+		}
+	}
+	
+	fn push_str(&mut self, output: &mut String, text: &str) {
+		for c in text.chars() {
+			match self.next_char.take().unwrap_or(self.mode) {
+				CaseChange::Upper | CaseChange::UpperNext => output.extend(c.to_uppercase()),
+				CaseChange::Lower | CaseChange::LowerNext => output.extend(c.to_lowercase()),
+				CaseChange::End => output.push(c),
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::component::filesystem::action::file::bulk_rename::substitute::{parse_expression, split_by_delimiter, CaseChange, CaseConverter, SubstitutionScope};
+	
+	fn substitute(expression: &str, names: &[&str]) -> Vec<Option<String>> {
+		match parse_expression(expression) {
+			Ok((_, substitution)) => substitution.apply_all(names.iter().copied()),
+			Err(e) => panic!("{expression} failed to parse: {e}"),
+		}
+	}
+	
+	fn substitute_one(expression: &str, name: &str) -> String {
+		substitute(expression, &[name]).pop().flatten().unwrap_or_default()
+	}
+	
+	fn parse_error(expression: &str) -> String {
+		parse_expression(expression).err().unwrap_or_default()
+	}
+	
+	#[test]
+	fn splits_by_unescaped_delimiters() {
+		assert_eq!(split_by_delimiter("a/b/c", '/'), vec!["a", "b", "c"]);
+		assert_eq!(split_by_delimiter("a/b/", '/'), vec!["a", "b", ""]);
+		assert_eq!(split_by_delimiter("a\\/b/c", '/'), vec!["a/b", "c"]);
+		assert_eq!(split_by_delimiter("a\\.b#c", '#'), vec!["a\\.b", "c"]);
+		assert_eq!(split_by_delimiter("a\\", '/'), vec!["a\\"]);
+	}
+	
+	#[test]
+	fn parses_scope() {
+		assert!(matches!(parse_expression("s/a/b/"), Ok((SubstitutionScope::Selection, _))));
+		assert!(matches!(parse_expression("  %s/a/b/"), Ok((SubstitutionScope::Siblings, _))));
+	}
+	
+	#[test]
+	fn accepts_any_delimiter() {
+		assert_eq!(substitute_one("s#a#b#", "aa"), "ba");
+		assert_eq!(substitute_one("s|a|b|", "aa"), "ba");
+		assert_eq!(substitute_one("s/a/b", "aa"), "ba");
+	}
+	
+	#[test]
+	fn unescapes_delimiters() {
+		assert_eq!(substitute_one("s/-/\\//", "a-b"), "a/b");
+		assert_eq!(substitute_one("s#\\##-#", "a#b"), "a-b");
+		assert_eq!(substitute_one("s/\\./_/g", "a.b.c"), "a_b_c");
+	}
+	
+	#[test]
+	fn replaces_first_match_without_g_flag() {
+		assert_eq!(substitute_one("s/a/x/", "banana"), "bxnana");
+		assert_eq!(substitute_one("s/a/x/g", "banana"), "bxnxnx");
+	}
+	
+	#[test]
+	fn ignores_case_with_i_flag() {
+		assert_eq!(substitute("s/a/x/", &["ABC"]), vec![None]);
+		assert_eq!(substitute_one("s/a/x/i", "ABA"), "xBA");
+		assert_eq!(substitute_one("s/a/x/gi", "ABA"), "xBx");
+	}
+	
+	#[test]
+	fn replaces_capture_groups() {
+		assert_eq!(substitute_one("s/(\\w+)-(\\w+)/$2-$1/", "ab-cd"), "cd-ab");
+		assert_eq!(substitute_one("s/(\\w+)-(\\w+)/\\2_\\1/", "ab-cd"), "cd_ab");
+		assert_eq!(substitute_one("s/(?<year>\\d{4})/${year}!/", "log 2023"), "log 2023!");
+		assert_eq!(substitute_one("s/a/$$1/", "a"), "$1");
+		assert_eq!(substitute_one("s/a/$/", "a"), "$");
+	}
+	
+	#[test]
+	#[allow(clippy::literal_string_with_formatting_args)]
+	fn numbers_matches_with_counter() {
+		assert_eq!(substitute("s/^/{n}-/", &["a", "b"]), vec![Some(String::from("1-a")), Some(String::from("2-b"))]);
+		assert_eq!(substitute("s/x/{n}/", &["x", "y", "x"]), vec![Some(String::from("1")), None, Some(String::from("2"))]);
+		assert_eq!(substitute_one("s/.*/{n:03}/", "a"), "001");
+		assert_eq!(substitute_one("s/.*/{n:3}/", "a"), "  1");
+		assert_eq!(substitute_one("s/.*/{n:0}/", "a"), "1");
+		assert_eq!(substitute_one("s/.*/{x}/", "a"), "{x}");
+		assert_eq!(substitute_one("s/.*/{n/", "a"), "{n");
+	}
+	
+	#[test]
+	fn converts_case() {
+		assert_eq!(substitute_one("s/(.*)/\\U$1/", "abc"), "ABC");
+		assert_eq!(substitute_one("s/(.*)/\\L$1/", "ABC"), "abc");
+		assert_eq!(substitute_one("s/(\\w+) (\\w+)/\\U$1\\E $2/", "ab cd"), "AB cd");
+		assert_eq!(substitute_one("s/(\\w+)/\\u$1/g", "ab cd"), "Ab Cd");
+		assert_eq!(substitute_one("s/(.*)/\\U\\l$1/", "abc"), "aBC");
+	}
+	
+	#[test]
+	fn case_converter_applies_next_char_change_once() {
+		let mut case = CaseConverter::new();
+		let mut output = String::new();
+		
+		case.change(CaseChange::Lower);
+		case.change(CaseChange::UpperNext);
+		case.push_str(&mut output, "ABC");
+		case.change(CaseChange::End);
+		case.push_str(&mut output, "DEF");
+		
+		assert_eq!(output, "AbcDEF");
+	}
+	
+	#[test]
+	#[allow(clippy::literal_string_with_formatting_args)]
+	fn rejects_malformed_expressions() {
+		assert_eq!(parse_error(""), "Expected s/pattern/replacement/flags.");
+		assert_eq!(parse_error("x/a/b/"), "Expected s/pattern/replacement/flags.");
+		assert_eq!(parse_error("s"), "Missing pattern.");
+		assert_eq!(parse_error("s//b/"), "Missing pattern.");
+		assert_eq!(parse_error("sxaxbx"), "Invalid delimiter: x");
+		assert_eq!(parse_error("s\\a\\b\\"), "Invalid delimiter: \\");
+		assert_eq!(parse_error("s/a/b/c/d"), "Too many '/' delimiters.");
+		assert_eq!(parse_error("s/a/b/q"), "Unknown flag: q");
+		assert!(parse_error("s/(/b/").starts_with("Invalid pattern: "));
+		assert_eq!(parse_error("s/a/$1/"), "Unknown capture group: 1");
+		assert_eq!(parse_error("s/(a)/${name}/"), "Unknown capture group: name");
+		assert_eq!(parse_error("s/a/${name/"), "Missing '}' after capture group name.");
+		assert_eq!(parse_error("s/a/{n:3/"), "Missing '}' after counter format.");
+		assert_eq!(parse_error("s/a/{n:x}/"), "Invalid counter format: x");
+	}
+}
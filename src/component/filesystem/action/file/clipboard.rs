@@ -0,0 +1,103 @@
+use std::path::{Path, PathBuf};
+
+use crate::component::dialog::message::MessageDialogLayer;
+use crate::component::filesystem::action::file::{describe_entries, get_marked_or_selected_paths, get_selected_file};
+use crate::component::filesystem::action::file::conflict::{find_conflicts, resolve_conflicts};
+use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::jobs::{ConflictResolutions, copy_entries, Job, JobKind, move_entries};
+use crate::component::filesystem::registers::{Clipboard, ClipboardMode};
+use crate::component::filesystem::status::StatusMessage;
+use crate::file::FileKind;
+use crate::state::action::{Action, ActionResult};
+use crate::state::Environment;
+use crate::state::event::EventResult;
+
+/// Puts the marked entries, or the selected entry, into the clipboard, so that they are copied once pasted.
+pub struct CopySelectedEntries;
+
+impl Action<FsLayer> for CopySelectedEntries {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		put_into_clipboard(layer, ClipboardMode::Copy)
+	}
+}
+
+/// Puts the marked entries, or the selected entry, into the clipboard, so that they are moved once pasted.
+pub struct CutSelectedEntries;
+
+impl Action<FsLayer> for CutSelectedEntries {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		put_into_clipboard(layer, ClipboardMode::Cut)
+	}
+}
+
+fn put_into_clipboard(layer: &mut FsLayer, mode: ClipboardMode) -> ActionResult {
+	let paths = get_marked_or_selected_paths(layer);
+	if paths.is_empty() {
+		return ActionResult::Nothing;
+	}
+	
+	let verb = if mode == ClipboardMode::Copy { "Copied" } else { "Cut" };
+	layer.show_message(StatusMessage::info(format!("{verb} {} into the clipboard", describe_entries(&paths))));
+	layer.registers.clipboard = Some(Clipboard { paths, mode });
+	ActionResult::Draw
+}
+
+/// Copies or moves entries from the clipboard into the selected directory, or into the directory that contains the selected file, in the background. If names of pasted entries are already taken, asks how to resolve each conflict first.
+pub struct PasteIntoSelectedDirectory;
+
+impl Action<FsLayer> for PasteIntoSelectedDirectory {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		let Some(clipboard) = &layer.registers.clipboard else {
+			return ActionResult::Nothing;
+		};
+		
+		let Some(target_directory) = get_selected_file(layer).and_then(|file| if matches!(file.entry.kind(), FileKind::Directory) { Some(file.path) } else { file.path.parent() }).map(Path::to_path_buf) else {
+			return ActionResult::Nothing;
+		};
+		
+		let paths = clipboard.paths.clone();
+		let mode = clipboard.mode;
+		
+		if let Some(path) = paths.iter().find(|path| target_directory.starts_with(path)) {
+			return ActionResult::push_layer(MessageDialogLayer::error(layer.dialog_y(), format!("Cannot paste {} into itself.", path.to_string_lossy())));
+		}
+		
+		let conflicts = find_conflicts(layer.tree.file_system().as_ref(), &paths, &target_directory);
+		
+		if conflicts.is_empty() {
+			start_paste_job(layer, paths, mode, target_directory, ConflictResolutions::default());
+			ActionResult::Draw
+		} else {
+			resolve_conflicts(layer, conflicts, move |layer, resolutions| {
+				start_paste_job(layer, paths.clone(), mode, target_directory.clone(), resolutions);
+				EventResult::Draw
+			})
+		}
+	}
+}
+
+fn start_paste_job(layer: &mut FsLayer, paths: Vec<PathBuf>, mode: ClipboardMode, target_directory: PathBuf, resolutions: ConflictResolutions) {
+	let file_system = layer.tree.file_system();
+	let description = describe_entries(&paths);
+	let target_name = target_directory.to_string_lossy().into_owned();
+	
+	if mode == ClipboardMode::Copy {
+		Job::build(JobKind::Copy, format!("Copying {description} to {target_name}"))
+			.refresh_paths(vec![target_directory.clone()])
+			.success_message(format!("Copied {description} to {target_name}"))
+			.start(&layer.jobs, move |context| copy_entries(file_system.as_ref(), context, &paths, &target_directory, &resolutions));
+	} else {
+		let mut refresh_paths = paths.iter().filter_map(|path| path.parent()).map(Path::to_path_buf).collect::<Vec<_>>();
+		refresh_paths.push(target_directory.clone());
+		refresh_paths.sort();
+		refresh_paths.dedup();
+		
+		Job::build(JobKind::Move, format!("Moving {description} to {target_name}"))
+			.refresh_paths(refresh_paths)
+			.success_message(format!("Moved {description} to {target_name}"))
+			.start(&layer.jobs, move |context| move_entries(file_system.as_ref(), context, &paths, &target_directory, &resolutions));
+		
+		// Moved entries no longer exist where they were cut from.
+		layer.registers.clipboard = None;
+	}
+}
@@ -0,0 +1,318 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use chrono::{DateTime, Local};
+use ratatui::text::Line;
+
+use crate::component::dialog::message::MessageDialogLayer;
+use crate::component::filesystem::action::file::get_entry_kind_name;
+use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::jobs::{ConflictResolution, ConflictResolutions};
+use crate::component::filesystem::render::SizeFormat;
+use crate::file::{FileEntry, FileKind, FileSystem};
+use crate::state::action::ActionResult;
+use crate::state::event::{EventQueue, EventResult};
+use crate::theme::theme;
+
+/// Returns the entries that would be pasted over something with the same name in the target directory.
+pub(super) fn find_conflicts(file_system: &dyn FileSystem, paths: &[PathBuf], target_directory: &Path) -> Vec<Conflict> {
+	paths.iter()
+	     .filter_map(|path| path.file_name().map(|name| Conflict { source: path.clone(), target: target_directory.join(name) }))
+	     .filter(|conflict| file_system.exists(&conflict.target))
+	     .collect()
+}
+
+/// Asks how to resolve each conflict in a dialog, and calls the function with the chosen resolutions once there is nothing left to ask. Nothing is called if any of the dialogs is cancelled.
+pub(super) fn resolve_conflicts<F>(layer: &FsLayer, conflicts: Vec<Conflict>, on_resolved: F) -> ActionResult where F: Fn(&mut FsLayer, ConflictResolutions) -> EventResult + 'static {
+	let context = Rc::new(ConflictContext {
+		file_system: layer.tree.file_system(),
+		events: layer.events(),
+		dialog_y: layer.dialog_y(),
+		size_format: layer.size_format,
+		on_resolved: Box::new(on_resolved),
+	});
+	
+	let state = ConflictState {
+		context,
+		pending: VecDeque::from(conflicts),
+		resolutions: ConflictResolutions::default(),
+		resolution_for_all: None,
+	};
+	
+	state.next_dialog().map_or(ActionResult::Draw, ActionResult::push_layer)
+}
+
+/// An entry whose name is already taken by the target entry.
+#[derive(Clone)]
+pub(super) struct Conflict {
+	source: PathBuf,
+	target: PathBuf,
+}
+
+impl Conflict {
+	/// Entries cannot replace themselves or the directories they are in, so only renaming and skipping are offered.
+	fn is_target_or_inside_target(&self) -> bool {
+		self.source.starts_with(&self.target)
+	}
+}
+
+type ResolvedCallback = Box<dyn Fn(&mut FsLayer, ConflictResolutions) -> EventResult>;
+
+struct ConflictContext {
+	file_system: Arc<dyn FileSystem>,
+	events: EventQueue<FsLayer>,
+	dialog_y: u16,
+	size_format: SizeFormat,
+	on_resolved: ResolvedCallback,
+}
+
+#[derive(Clone)]
+struct ConflictState {
+	context: Rc<ConflictContext>,
+	pending: VecDeque<Conflict>,
+	resolutions: ConflictResolutions,
+	resolution_for_all: Option<ConflictResolution>,
+}
+
+impl ConflictState {
+	/// Resolves conflicts with the resolution chosen for all of them, until a conflict it does not apply to, and returns a dialog that asks about that conflict. If there are no conflicts left, passes the resolutions on instead.
+	fn next_dialog(mut self) -> Option<MessageDialogLayer<'static>> {
+		while let Some(conflict) = self.pending.pop_front() {
+			let choices = self.get_choices(&conflict);
+			
+			if let Some(resolution) = self.resolution_for_all.filter(|resolution| choices.contains(&Some(*resolution))) {
+				self.resolve(conflict, resolution);
+			} else {
+				return Some(self.create_dialog(conflict, choices));
+			}
+		}
+		
+		let context = Rc::clone(&self.context);
+		let resolutions = self.resolutions;
+		self.context.events.enqueue_fn(move |layer, _| (context.on_resolved)(layer, resolutions.clone()));
+		None
+	}
+	
+	fn resolve(&mut self, conflict: Conflict, resolution: ConflictResolution) {
+		// Conflicts inside merged directories are asked about before the remaining ones.
+		if resolution == ConflictResolution::Merge {
+			if let Ok(children) = self.context.file_system.list(&conflict.source) {
+				let children = children.iter().filter_map(FileEntry::path).map(Path::to_path_buf).collect::<Vec<_>>();
+				
+				for nested_conflict in find_conflicts(self.context.file_system.as_ref(), &children, &conflict.target).into_iter().rev() {
+					self.pending.push_front(nested_conflict);
+				}
+			}
+		}
+		
+		self.resolutions.insert(conflict.target, resolution);
+	}
+	
+	/// Returns resolutions that can be chosen for the conflict, where `None` cancels the paste.
+	fn get_choices(&self, conflict: &Conflict) -> Vec<Option<ConflictResolution>> {
+		if conflict.is_target_or_inside_target() {
+			return vec![Some(ConflictResolution::Rename), Some(ConflictResolution::Skip), None];
+		}
+		
+		let file_system = self.context.file_system.as_ref();
+		let is_directory = |path: &Path| matches!(file_system.stat(path).kind(), FileKind::Directory);
+		
+		let mut choices = vec![Some(ConflictResolution::Overwrite), Some(ConflictResolution::Skip), Some(ConflictResolution::Rename)];
+		
+		if is_directory(&conflict.source) && is_directory(&conflict.target) {
+			choices.push(Some(ConflictResolution::Merge));
+		}
+		
+		choices.push(Some(ConflictResolution::OverwriteIfNewer));
+		choices.push(None);
+		choices
+	}
+	
+	fn create_dialog(&self, conflict: Conflict, choices: Vec<Option<ConflictResolution>>) -> MessageDialogLayer<'static> {
+		let file_system = self.context.file_system.as_ref();
+		let size_format = self.context.size_format;
+		
+		let name = conflict.target.file_name().unwrap_or(conflict.target.as_os_str()).to_string_lossy();
+		let directory = conflict.target.parent().unwrap_or(&conflict.target).to_string_lossy();
+		
+		let message = vec![
+			Line::from(format!("{name} already exists in {directory}.")),
+			Line::default(),
+			Line::from(format!("Existing: {}", describe_entry(&file_system.stat(&conflict.target), size_format))),
+			Line::from(format!("Pasted:   {}", describe_entry(&file_system.stat(&conflict.source), size_format))),
+		];
+		
+		let labels = choices.iter().copied().map(get_choice_label).collect::<Vec<_>>();
+		let state = self.clone();
+		
+		MessageDialogLayer::build()
+			.y(self.context.dialog_y)
+			.style(theme().dialog_warning)
+			.title("Name Conflict")
+			.message(message)
+			.multi_choice(&labels, "all", move |index, for_all| {
+				let Some(resolution) = choices.get(index).copied().flatten() else {
+					return ActionResult::PopLayer;
+				};
+				
+				let mut state = state.clone();
+				
+				if for_all {
+					state.resolution_for_all = Some(resolution);
+				}
+				
+				state.resolve(conflict.clone(), resolution);
+				state.next_dialog().map_or(ActionResult::PopLayer, ActionResult::replace_layer)
+			})
+	}
+}
+
+const fn get_choice_label(choice: Option<ConflictResolution>) -> &'static str {
+	match choice {
+		Some(ConflictResolution::Overwrite) => "overwrite",
+		Some(ConflictResolution::Skip) => "skip",
+		Some(ConflictResolution::Rename) => "rename",
+		Some(ConflictResolution::Merge) => "merge",
+		Some(ConflictResolution::OverwriteIfNewer) => "newer",
+		None => "cancel",
+	}
+}
+
+fn describe_entry(entry: &FileEntry, size_format: SizeFormat) -> String {
+	let mut description = String::from(get_entry_kind_name(entry));
+	
+	if let FileKind::File { size } = entry.kind() {
+		description.push_str(", ");
+		description.push_str(&size_format.format(*size));
+	}
+	
+	if let Some(modified_time) = entry.modified_time() {
+		description.push_str(", modified ");
+		description.push_str(&DateTime::<Local>::from(*modified_time).format("%Y-%m-%d %H:%M:%S").to_string());
+	}
+	
+	description
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::VecDeque;
+	use std::path::{Path, PathBuf};
+	use std::rc::Rc;
+	use std::sync::Arc;
+	
+	use crate::component::filesystem::action::file::conflict::{ConflictContext, ConflictState, find_conflicts};
+	use crate::component::filesystem::jobs::{ConflictResolution, ConflictResolutions};
+	use crate::component::filesystem::render::SizeFormat;
+	use crate::file::MemoryFileSystem;
+	use crate::state::event::{EventQueue, EventResult};
+	
+	fn create_state(file_system: MemoryFileSystem, sources: &[&str], target_directory: &str) -> ConflictState {
+		let sources = sources.iter().map(PathBuf::from).collect::<Vec<_>>();
+		let conflicts = find_conflicts(&file_system, &sources, Path::new(target_directory));
+		
+		let context = Rc::new(ConflictContext {
+			file_system: Arc::new(file_system),
+			events: EventQueue::new(),
+			dialog_y: 0,
+			size_format: SizeFormat::Si,
+			on_resolved: Box::new(|_, _| EventResult::Nothing),
+		});
+		
+		ConflictState { context, pending: VecDeque::from(conflicts), resolutions: ConflictResolutions::default(), resolution_for_all: None }
+	}
+	
+	fn pending_targets(state: &ConflictState) -> Vec<PathBuf> {
+		state.pending.iter().map(|conflict| conflict.target.clone()).collect()
+	}
+	
+	fn resolve_next(state: &mut ConflictState, resolution: ConflictResolution) {
+		let conflict = state.pending.pop_front();
+		assert!(conflict.is_some(), "No conflict left to resolve");
+		
+		if let Some(conflict) = conflict {
+			state.resolve(conflict, resolution);
+		}
+	}
+	
+	#[test]
+	fn finds_only_taken_names() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/src/a", "")
+			.with_file("/src/b", "")
+			.with_file("/dst/b", "");
+		
+		let state = create_state(file_system, &["/src/a", "/src/b"], "/dst");
+		assert_eq!(pending_targets(&state), vec![PathBuf::from("/dst/b")]);
+	}
+	
+	#[test]
+	fn asks_about_conflicts_inside_merged_directories_first() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/src/dir/sub/deep", "")
+			.with_file("/src/dir/x", "")
+			.with_file("/src/dir/y", "")
+			.with_file("/src/dir/z", "")
+			.with_file("/src/other", "")
+			.with_file("/dst/dir/sub/deep", "")
+			.with_file("/dst/dir/x", "")
+			.with_file("/dst/dir/z", "")
+			.with_file("/dst/other", "");
+		
+		let mut state = create_state(file_system, &["/src/dir", "/src/other"], "/dst");
+		assert_eq!(pending_targets(&state), vec![PathBuf::from("/dst/dir"), PathBuf::from("/dst/other")]);
+		
+		resolve_next(&mut state, ConflictResolution::Merge);
+		assert_eq!(pending_targets(&state), vec![
+			PathBuf::from("/dst/dir/sub"),
+			PathBuf::from("/dst/dir/x"),
+			PathBuf::from("/dst/dir/z"),
+			PathBuf::from("/dst/other"),
+		]);
+		
+		resolve_next(&mut state, ConflictResolution::Merge);
+		assert_eq!(pending_targets(&state), vec![
+			PathBuf::from("/dst/dir/sub/deep"),
+			PathBuf::from("/dst/dir/x"),
+			PathBuf::from("/dst/dir/z"),
+			PathBuf::from("/dst/other"),
+		]);
+		
+		resolve_next(&mut state, ConflictResolution::Skip);
+		assert_eq!(pending_targets(&state).len(), 3);
+		
+		assert_eq!(state.resolutions.get(Path::new("/dst/dir")), Some(ConflictResolution::Merge));
+		assert_eq!(state.resolutions.get(Path::new("/dst/dir/sub")), Some(ConflictResolution::Merge));
+		assert_eq!(state.resolutions.get(Path::new("/dst/dir/sub/deep")), Some(ConflictResolution::Skip));
+		assert_eq!(state.resolutions.get(Path::new("/dst/other")), None);
+	}
+	
+	#[test]
+	fn other_resolutions_do_not_look_inside_directories() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/src/dir/x", "")
+			.with_file("/dst/dir/x", "");
+		
+		let mut state = create_state(file_system, &["/src/dir"], "/dst");
+		resolve_next(&mut state, ConflictResolution::Overwrite);
+		
+		assert_eq!(pending_targets(&state), Vec::<PathBuf>::new());
+	}
+	
+	#[test]
+	fn offers_merge_only_for_two_directories() {
+		let file_system = MemoryFileSystem::new()
+			.with_directory("/src/dir")
+			.with_file("/src/file", "")
+			.with_directory("/dst/dir")
+			.with_directory("/dst/file");
+		
+		let state = create_state(file_system, &["/src/dir", "/src/file"], "/dst");
+		let choices = state.pending.iter().map(|conflict| state.get_choices(conflict)).collect::<Vec<_>>();
+		
+		assert!(choices.first().is_some_and(|choices| choices.contains(&Some(ConflictResolution::Merge))), "Merge is not offered for two directories");
+		assert!(choices.get(1).is_some_and(|choices| !choices.contains(&Some(ConflictResolution::Merge))), "Merge is offered for a file");
+	}
+}
@@ -1,22 +1,22 @@
-use std::{fs, io};
+use std::io;
 use std::path::{Path, PathBuf};
 
-use ratatui::style::Color;
-
 use slab_tree::NodeId;
 
 use crate::component::dialog::input::InputFieldDialogLayer;
 use crate::component::dialog::message::MessageDialogLayer;
 use crate::component::filesystem::action::file::{FileNode, get_selected_file, RefreshParentDirectoryAndSelectFile};
 use crate::component::filesystem::FsLayer;
-use crate::file::FileKind;
+use crate::component::filesystem::status::StatusMessage;
+use crate::file::{FileKind, FileSystem};
 use crate::state::action::{Action, ActionResult};
 use crate::state::Environment;
+use crate::theme::theme;
 
 trait CreateEntry {
 	fn title() -> &'static str;
 	fn kind() -> &'static str;
-	fn create(path: PathBuf) -> io::Result<()>;
+	fn create(file_system: &dyn FileSystem, path: &Path) -> io::Result<()>;
 }
 
 pub struct CreateFile;
@@ -30,8 +30,8 @@ impl CreateEntry for CreateFile {
 		"file"
 	}
 	
-	fn create(path: PathBuf) -> io::Result<()> {
-		fs::write(path, b"")
+	fn create(file_system: &dyn FileSystem, path: &Path) -> io::Result<()> {
+		file_system.create_file(path)
 	}
 }
 
@@ -46,8 +46,8 @@ impl CreateEntry for CreateDirectory {
 		"directory"
 	}
 	
-	fn create(path: PathBuf) -> io::Result<()> {
-		fs::create_dir(path)
+	fn create(file_system: &dyn FileSystem, path: &Path) -> io::Result<()> {
+		file_system.create_directory(path)
 	}
 }
 
@@ -67,7 +67,7 @@ impl Action<FsLayer> for CreateDirectoryInSelectedDirectory {
 	}
 }
 
-fn create_in_selected_directory<T: CreateEntry>(layer: &mut FsLayer) -> ActionResult {
+fn create_in_selected_directory<T: CreateEntry>(layer: &FsLayer) -> ActionResult {
 	if let Some(FileNode { node, path, .. }) = get_selected_file(layer).filter(|n| matches!(n.entry.kind(), FileKind::Directory)) {
 		ActionResult::push_layer(create_new_name_prompt::<T>(layer, path.to_owned(), node.node_id()))
 	} else {
@@ -91,7 +91,7 @@ impl Action<FsLayer> for CreateDirectoryInParentOfSelectedEntry {
 	}
 }
 
-fn create_in_parent_of_selected_file<T: CreateEntry>(layer: &mut FsLayer) -> ActionResult {
+fn create_in_parent_of_selected_file<T: CreateEntry>(layer: &FsLayer) -> ActionResult {
 	if let Some((parent_node_id, parent_path)) = get_parent_of_selected_file(layer) {
 		ActionResult::push_layer(create_new_name_prompt::<T>(layer, parent_path.to_owned(), parent_node_id))
 	} else {
@@ -106,11 +106,12 @@ fn get_parent_of_selected_file(layer: &FsLayer) -> Option<(NodeId, &Path)> {
 fn create_new_name_prompt<'b, T: CreateEntry>(layer: &FsLayer, parent_folder: PathBuf, parent_view_node_id: NodeId) -> InputFieldDialogLayer<'b> {
 	let y = layer.dialog_y();
 	let events = layer.events();
+	let file_system = layer.tree.file_system();
 	
 	InputFieldDialogLayer::build()
 		.y(y)
 		.min_width(40)
-		.color(Color::LightCyan, Color::Cyan)
+		.style(theme().dialog_info)
 		.title(T::title())
 		.message(format!("Creating {} in {}", T::kind(), parent_folder.to_string_lossy()))
 		.on_confirm(move |new_name| {
@@ -119,13 +120,14 @@ fn create_new_name_prompt<'b, T: CreateEntry>(layer: &FsLayer, parent_folder: Pa
 			}
 			
 			let new_path = parent_folder.join(&new_name);
-			if new_path.exists() {
+			if file_system.exists(&new_path) {
 				return ActionResult::push_layer(MessageDialogLayer::error(y, "Something with this name already exists."));
 			}
 			
-			match T::create(new_path) {
-				Ok(_) => {
-					events.enqueue(RefreshParentDirectoryAndSelectFile { parent_view_node_id, child_file_name: new_name });
+			match T::create(file_system.as_ref(), &new_path) {
+				Ok(()) => {
+					events.enqueue(StatusMessage::info(format!("Created {} {new_name}", T::kind())));
+					events.enqueue(RefreshParentDirectoryAndSelectFile { parent_view_node_id, child_path: new_path });
 					ActionResult::PopLayer
 				}
 				Err(e) => {
@@ -1,87 +1,167 @@
 use std::io;
 use std::path::{Path, PathBuf};
-use std::time::{Duration, Instant};
+use std::sync::{Arc, Mutex};
 
-use ratatui::style::Color;
 use ratatui::text::Line;
-use slab_tree::NodeId;
 
 use crate::component::dialog::message::MessageDialogLayer;
 use crate::component::filesystem::action::file::{FileNode, get_entry_kind_name, get_selected_file};
 use crate::component::filesystem::FsLayer;
-use crate::file::{FileEntry, FileKind};
+use crate::component::filesystem::jobs::{delete_entries, Job, JobContext, JobKind, JobProgress};
+use crate::file::{FileEntry, FileKind, FileSystem};
 use crate::state::action::{Action, ActionResult};
 use crate::state::Environment;
 use crate::state::event::EventResult;
+use crate::theme::theme;
 
 pub struct DeleteSelectedEntry;
 
 impl Action<FsLayer> for DeleteSelectedEntry {
 	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
-		if let Some(FileNode { node, entry, path }) = get_selected_file(layer) {
-			ActionResult::push_layer(create_delete_confirmation_dialog(layer, node.node_id(), entry, path.to_owned()))
+		if let Some(FileNode { entry, path, .. }) = get_selected_file(layer) {
+			let kind_name = get_entry_kind_name(entry);
+			
+			if matches!(entry.kind(), FileKind::Directory) {
+				count_files_and_confirm(layer, kind_name, path.to_owned());
+				ActionResult::Draw
+			} else {
+				let count = CountFiles { files: 1, directories: 0 }.into_result(CountFilesResultKind::Success);
+				ActionResult::push_layer(create_delete_confirmation_dialog(layer, kind_name, path.to_owned(), &count))
+			}
 		} else {
 			ActionResult::Nothing
 		}
 	}
 }
 
-fn create_delete_confirmation_dialog<'a>(layer: &FsLayer, view_node_id: NodeId, entry: &FileEntry, path: PathBuf) -> MessageDialogLayer<'a> {
-	let y = layer.dialog_y();
-	let events = layer.events();
+const MAX_COUNTED_ENTRIES: usize = 100_000;
+
+/// Counts everything inside the directory in a job, and asks for confirmation once the job finishes.
+fn count_files_and_confirm(layer: &FsLayer, kind_name: &'static str, path: PathBuf) {
+	let file_system = layer.tree.file_system();
+	let result = Arc::new(Mutex::new(None));
+	let job_result = Arc::clone(&result);
+	let job_path = path.clone();
 	
-	let total_files = if matches!(entry.kind(), FileKind::Directory) {
-		count_files(path.clone())
-	} else {
-		CountFiles { files: 1, directories: 0 }.into_result(CountFilesResultKind::Success)
-	};
+	Job::build(JobKind::Scan, format!("Counting entries in {}", path.to_string_lossy()))
+		.on_success(move |layer, _| {
+			if let Some(count) = result.lock().ok().and_then(|mut result| result.take()) {
+				let dialog = create_delete_confirmation_dialog(layer, kind_name, path.clone(), &count);
+				layer.open_dialog(dialog);
+			}
+			
+			EventResult::Draw
+		})
+		.start(&layer.jobs, move |context| {
+			let count = count_files(file_system.as_ref(), context, job_path)?;
+			
+			if let Ok(mut result) = job_result.lock() {
+				*result = Some(count);
+			}
+			
+			Ok(())
+		});
+}
+
+fn count_files(file_system: &dyn FileSystem, context: &JobContext, path: PathBuf) -> io::Result<CountFilesResult> {
+	let mut count = CountFiles { files: 0, directories: 1 };
+	context.add_total(1, 0);
+	
+	let result = traverse_recursively(file_system, path, |entry| {
+		if matches!(entry.kind(), FileKind::Directory) {
+			count.directories = count.directories.saturating_add(1);
+		} else {
+			count.files = count.files.saturating_add(1);
+		}
+		
+		context.add_total(1, 0);
+		context.checkpoint().is_ok() && count.files.saturating_add(count.directories) < MAX_COUNTED_ENTRIES
+	});
+	
+	context.checkpoint()?;
+	
+	Ok(match result {
+		None => count.into_result(CountFilesResultKind::LimitReached),
+		Some(0) => count.into_result(CountFilesResultKind::Success),
+		Some(errors) => count.into_result(CountFilesResultKind::WithErrors(errors)),
+	})
+}
+
+fn create_delete_confirmation_dialog<'a>(layer: &FsLayer, kind_name: &str, path: PathBuf, count: &CountFilesResult) -> MessageDialogLayer<'a> {
+	let events = layer.events();
 	
 	MessageDialogLayer::build()
-		.y(y)
-		.color(Color::LightRed)
-		.title(format!("Delete {}", get_entry_kind_name(entry)))
+		.y(layer.dialog_y())
+		.style(theme().dialog_error)
+		.title(format!("Delete {kind_name}"))
 		.message(vec![
 			Line::from(format!("Permanently delete {}?", path.to_string_lossy())),
-			Line::from(format!("This will affect {}.", total_files.describe())),
+			Line::from(format!("This will affect {}.", count.describe())),
 		])
 		.yes_no(move || {
-			match delete_path_recursively(&path) {
-				Ok(_) => {
-					events.enqueue_fn(move |layer, _| EventResult::draw_if(layer.tree.delete_node(view_node_id)));
-					ActionResult::PopLayer
-				}
-				Err(e) => {
-					ActionResult::replace_layer(MessageDialogLayer::error(y.saturating_add(1), e.to_string()))
-				}
-			}
+			let path = path.clone();
+			
+			events.enqueue_fn(move |layer, _| {
+				let file_system = layer.tree.file_system();
+				let paths = vec![path.clone()];
+				
+				Job::build(JobKind::Delete, format!("Deleting {}", path.to_string_lossy()))
+					.refresh_paths(path.parent().map(Path::to_path_buf).into_iter().collect())
+					.success_message(format!("Deleted {}", path.to_string_lossy()))
+					.on_success(remove_deleted_node(path.clone()))
+					.start(&layer.jobs, move |context| delete_entries(file_system.as_ref(), context, &paths));
+				
+				EventResult::Draw
+			});
+			
+			ActionResult::PopLayer
 		})
 }
 
-const MAX_COUNT_TIME: Duration = Duration::from_secs(5);
+/// Removes the deleted entry from the tree before its directory is refreshed, so that it is also removed from the disk usage totals of its ancestors.
+fn remove_deleted_node(path: PathBuf) -> impl Fn(&mut FsLayer, JobProgress) -> EventResult {
+	move |layer, _| {
+		let view_node_id = layer.tree.view_iter()
+			.find(|node| layer.tree.get_entry(node).and_then(FileEntry::path) == Some(path.as_path()))
+			.map(|node| node.node_id());
+		
+		EventResult::draw_if(view_node_id.is_some_and(|view_node_id| layer.tree.delete_node(view_node_id)))
+	}
+}
 
-#[allow(clippy::iter_with_drain, clippy::needless_collect)]
-fn count_files(path: PathBuf) -> CountFilesResult {
-	let start_time = Instant::now();
-	
+/// Visits all entries inside a directory and its subdirectories without following symbolic links. The visitor returns `false` to stop the traversal early, in which case this returns `None`. Otherwise, returns the number of directories that could not be fully read.
+pub fn traverse_recursively<F>(file_system: &dyn FileSystem, path: PathBuf, mut visitor: F) -> Option<usize> where F: FnMut(&FileEntry) -> bool {
 	let mut remaining_directories = vec![path];
-	let mut count = CountFiles { files: 0, directories: 1 };
 	let mut errors = 0_usize;
 	
 	while let Some(path) = remaining_directories.pop() {
-		if count.process_directory(&path, &mut remaining_directories).is_err() {
-			errors = errors.saturating_add(1);
+		match traverse_directory(file_system, &path, &mut remaining_directories, &mut visitor) {
+			Ok(true) => {}
+			Ok(false) => return None,
+			Err(_) => errors = errors.saturating_add(1),
+		}
+	}
+	
+	Some(errors)
+}
+
+fn traverse_directory<F>(file_system: &dyn FileSystem, path: &Path, found_directories: &mut Vec<PathBuf>, visitor: &mut F) -> io::Result<bool> where F: FnMut(&FileEntry) -> bool {
+	for entry in file_system.list(path)? {
+		// Entries that could not be read have no path.
+		let Some(entry_path) = entry.path() else {
+			return Err(io::Error::new(io::ErrorKind::Other, "Could not read an entry"));
+		};
+		
+		if matches!(entry.kind(), FileKind::Directory) {
+			found_directories.push(entry_path.to_path_buf());
 		}
 		
-		if start_time.elapsed() >= MAX_COUNT_TIME {
-			return count.into_result(CountFilesResultKind::Timeout);
+		if !visitor(&entry) {
+			return Ok(false);
 		}
 	}
 	
-	if errors == 0 {
-		count.into_result(CountFilesResultKind::Success)
-	} else {
-		count.into_result(CountFilesResultKind::WithErrors(errors))
-	}
+	Ok(true)
 }
 
 struct CountFiles {
@@ -90,21 +170,6 @@ struct CountFiles {
 }
 
 impl CountFiles {
-	fn process_directory(&mut self, path: &Path, found_directories: &mut Vec<PathBuf>) -> io::Result<()> {
-		for entry in path.read_dir()? {
-			let entry = entry?;
-			
-			if entry.file_type()?.is_dir() {
-				self.directories = self.directories.saturating_add(1);
-				found_directories.push(entry.path());
-			} else {
-				self.files = self.files.saturating_add(1);
-			}
-		}
-		
-		Ok(())
-	}
-	
 	const fn into_result(self, kind: CountFilesResultKind) -> CountFilesResult {
 		CountFilesResult { kind, count: self }
 	}
@@ -119,7 +184,7 @@ impl CountFilesResult {
 	fn describe(&self) -> String {
 		match &self.kind {
 			CountFilesResultKind::Success => self.describe_files_and_directories(),
-			CountFilesResultKind::Timeout => format!("at least {} (count terminated due to timeout)", self.describe_files_and_directories()),
+			CountFilesResultKind::LimitReached => format!("at least {} (count stopped after {MAX_COUNTED_ENTRIES} entries)", self.describe_files_and_directories()),
 			CountFilesResultKind::WithErrors(error_count) => {
 				let pluralized_errors = if *error_count == 1 { "error" } else { "errors" };
 				format!("at least {} (count is incomplete due to {} I/O {})", self.describe_files_and_directories(), error_count, pluralized_errors)
@@ -146,14 +211,43 @@ impl CountFilesResult {
 
 enum CountFilesResultKind {
 	Success,
-	Timeout,
+	LimitReached,
 	WithErrors(usize),
 }
 
-fn delete_path_recursively(path: impl AsRef<Path>) -> io::Result<()> {
-	if path.as_ref().is_dir() {
-		std::fs::remove_dir_all(path)
-	} else {
-		std::fs::remove_file(path)
+#[cfg(test)]
+mod tests {
+	use std::path::PathBuf;
+	
+	use crate::component::filesystem::action::file::delete::count_files;
+	use crate::component::filesystem::jobs::JobContext;
+	use crate::file::MemoryFileSystem;
+	
+	#[test]
+	fn counts_files_and_directories_inside_directory() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/dir/a", "a")
+			.with_file("/dir/sub/b", "b")
+			.with_directory("/dir/empty");
+		
+		let context = JobContext::detached();
+		let Ok(count) = count_files(&file_system, &context, PathBuf::from("/dir")) else {
+			panic!("counting failed");
+		};
+		
+		assert_eq!(count.describe(), "2 files and 3 directories");
+		assert_eq!(context.progress().total_entries, 5);
+	}
+	
+	#[test]
+	fn reports_incomplete_count() {
+		let file_system = MemoryFileSystem::new();
+		
+		let context = JobContext::detached();
+		let Ok(count) = count_files(&file_system, &context, PathBuf::from("/missing")) else {
+			panic!("counting failed");
+		};
+		
+		assert_eq!(count.describe(), "at least 1 directory (count is incomplete due to 1 I/O error)");
 	}
 }
@@ -1,18 +1,20 @@
-use std::{env, str};
+use std::{env, io, process, str};
 use std::ffi::OsString;
 use std::io::ErrorKind;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, ExitStatus};
 
 use slab_tree::NodeRef;
 
 use crate::component::dialog::message::MessageDialogLayer;
-use crate::component::filesystem::action::file::{FileNode, get_selected_file};
+use crate::component::filesystem::action::file::{FileNode, format_io_error, get_selected_file};
 use crate::component::filesystem::FsLayer;
 use crate::component::filesystem::tree::FsTreeViewNode;
+use crate::file::{ArchiveLocation, extract_archive_entry, FileKind, FileSystem};
 use crate::state::action::{Action, ActionResult};
 use crate::state::Environment;
 use crate::state::event::EventResult;
+use crate::state::view::TerminalView;
 
 pub struct EditSelectedEntry;
 
@@ -20,17 +22,65 @@ impl Action<FsLayer> for EditSelectedEntry {
 	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
 		if let Some(FileNode { node, path, .. }) = get_selected_file(layer) {
 			open_default_editor(layer, &node, path)
+		} else if let Some(location) = get_selected_archive_file(layer) {
+			open_archive_file_in_default_editor(layer, &location)
 		} else {
 			ActionResult::Nothing
 		}
 	}
 }
 
+fn get_selected_archive_file(layer: &FsLayer) -> Option<ArchiveLocation> {
+	let entry = layer.tree.selected_node().and_then(|node| layer.tree.get_entry(&node))?;
+	entry.archive_location().filter(|_| matches!(entry.kind(), FileKind::File { .. })).cloned()
+}
+
+/// Extracts a file from an archive into a temporary directory, and opens the copy in the default editor. Changes to the copy are not written back into the archive.
+fn open_archive_file_in_default_editor(layer: &FsLayer, location: &ArchiveLocation) -> ActionResult {
+	let file_system = layer.tree.file_system();
+	let temporary_directory = env::temp_dir().join(format!("bark-{}", process::id()));
+	
+	let result = create_directory_if_missing(file_system.as_ref(), &temporary_directory)
+		.and_then(|()| remove_previous_copy(file_system.as_ref(), &temporary_directory, location))
+		.and_then(|()| extract_archive_entry(location, &temporary_directory, |_| true));
+	
+	match result {
+		Ok(path) => {
+			let editor = get_editor();
+			
+			if run_editor(Command::new(&editor).arg(path)).is_err_and(|e| e.kind() == ErrorKind::NotFound) {
+				ActionResult::push_layer(MessageDialogLayer::error(layer.dialog_y(), format!("Default editor '{}' not found.", editor.to_string_lossy())))
+			} else {
+				ActionResult::Redraw
+			}
+		}
+		Err(e) => {
+			ActionResult::push_layer(MessageDialogLayer::error(layer.dialog_y(), format!("Could not extract file: {}", format_io_error(&e))))
+		}
+	}
+}
+
+fn create_directory_if_missing(file_system: &dyn FileSystem, path: &Path) -> io::Result<()> {
+	if file_system.exists(path) {
+		Ok(())
+	} else {
+		file_system.create_directory(path)
+	}
+}
+
+fn remove_previous_copy(file_system: &dyn FileSystem, temporary_directory: &Path, location: &ArchiveLocation) -> io::Result<()> {
+	let previous_copy = location.inner_path().file_name().map(|name| temporary_directory.join(name));
+	
+	if let Some(previous_copy) = previous_copy.filter(|path| file_system.exists(path)) {
+		file_system.remove(&previous_copy)
+	} else {
+		Ok(())
+	}
+}
+
 fn open_default_editor(layer: &FsLayer, node: &NodeRef<FsTreeViewNode>, path: &Path) -> ActionResult {
 	let editor = get_editor();
-	let status = Command::new(&editor)
-		.arg(path)
-		.status();
+	let status = run_editor(Command::new(&editor).arg(path));
 	
 	if status.is_err_and(|e| e.kind() == ErrorKind::NotFound) {
 		return ActionResult::push_layer(MessageDialogLayer::error(layer.dialog_y(), format!("Default editor '{}' not found.", editor.to_string_lossy())));
@@ -43,9 +93,28 @@ fn open_default_editor(layer: &FsLayer, node: &NodeRef<FsTreeViewNode>, path: &P
 	ActionResult::Redraw
 }
 
+/// Opens a file in the default editor with the cursor at the line, using the `+N` argument understood by `vim`, `nano`, `emacs`, and most other terminal editors.
+pub fn open_default_editor_at_line(path: &Path, line_number: usize) -> Result<(), String> {
+	let editor = get_editor();
+	let status = run_editor(Command::new(&editor)
+		.arg(format!("+{line_number}"))
+		.arg(path));
+	
+	if status.is_err_and(|e| e.kind() == ErrorKind::NotFound) {
+		Err(format!("Default editor '{}' not found.", editor.to_string_lossy()))
+	} else {
+		Ok(())
+	}
+}
+
+/// Runs the editor in the terminal, and waits for it to exit.
+pub(super) fn run_editor(command: &mut Command) -> io::Result<ExitStatus> {
+	TerminalView::run_external_program(|| command.status())
+}
+
 const DEFAULT_EDITOR: &str = "vim";
 
-fn get_editor() -> OsString {
+pub(super) fn get_editor() -> OsString {
 	env::var_os("VISUAL")
 		.or_else(|| env::var_os("EDITOR"))
 		.unwrap_or_else(|| OsString::from(DEFAULT_EDITOR))
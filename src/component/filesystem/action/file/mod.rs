@@ -1,25 +1,39 @@
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use ratatui::text::Line;
 use slab_tree::{NodeId, NodeRef};
 
+use crate::component::dialog::message::MessageDialogLayer;
 use crate::component::filesystem::FsLayer;
 use crate::component::filesystem::tree::FsTreeViewNode;
 use crate::file::{FileEntry, FileKind};
 use crate::state::Environment;
 use crate::state::event::{Event, EventResult};
+use crate::theme::theme;
 
+pub use self::archive::*;
+pub use self::bulk_rename::*;
+pub use self::clipboard::*;
 pub use self::create::*;
 pub use self::delete::*;
 pub use self::edit::*;
+pub use self::owner::*;
 pub use self::rename::*;
+pub use self::scan::*;
 
+mod archive;
+mod bulk_rename;
+mod clipboard;
+mod conflict;
 mod create;
 mod delete;
 mod edit;
+mod owner;
 mod rename;
+mod scan;
 
-fn get_selected_file(layer: &FsLayer) -> Option<FileNode> {
+fn get_selected_file(layer: &FsLayer) -> Option<FileNode<'_>> {
 	if let Some(node) = layer.tree.selected_node() {
 		if let Some(entry) = layer.tree.get_entry(&node) {
 			if let Some(path) = entry.path() {
@@ -31,6 +45,34 @@ fn get_selected_file(layer: &FsLayer) -> Option<FileNode> {
 	None
 }
 
+/// Returns paths of marked entries, or the path of the selected entry if no entries are marked. Entries inside archives are skipped.
+fn get_marked_or_selected_paths(layer: &FsLayer) -> Vec<PathBuf> {
+	let marked_model_node_ids = layer.tree.marked_model_node_ids();
+	
+	if marked_model_node_ids.is_empty() {
+		layer.tree.selected_node()
+		     .and_then(|node| layer.tree.get_entry(&node))
+		     .and_then(FileEntry::path)
+		     .map(|path| vec![path.to_path_buf()])
+		     .unwrap_or_default()
+	} else {
+		marked_model_node_ids.into_iter()
+		                     .filter_map(|model_node_id| layer.tree.get_model_entry(model_node_id))
+		                     .filter_map(FileEntry::path)
+		                     .map(Path::to_path_buf)
+		                     .collect()
+	}
+}
+
+/// Describes entries in messages by the name of the only entry, or by their number.
+fn describe_entries(paths: &[PathBuf]) -> String {
+	if let [path] = paths {
+		path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+	} else {
+		format!("{} entries", paths.len())
+	}
+}
+
 struct FileNode<'a> {
 	node: NodeRef<'a, FsTreeViewNode>,
 	entry: &'a FileEntry,
@@ -46,7 +88,7 @@ const fn get_entry_kind_name(entry: &FileEntry) -> &'static str {
 	}
 }
 
-fn format_io_error(err: &io::Error) -> String {
+pub fn format_io_error(err: &io::Error) -> String {
 	let mut str = if let Some(code) = err.raw_os_error() {
 		err.to_string().replace(&format!(" (os error {code})"), "")
 	} else {
@@ -57,15 +99,35 @@ fn format_io_error(err: &io::Error) -> String {
 	str
 }
 
+const MAX_REPORTED_PATH_ERRORS: usize = 10;
+
+pub fn create_path_errors_dialog<'a>(y: u16, errors: &[(PathBuf, io::Error)]) -> MessageDialogLayer<'a> {
+	let mut lines = errors.iter()
+		.take(MAX_REPORTED_PATH_ERRORS)
+		.map(|(path, e)| Line::from(format!("{}: {}", path.to_string_lossy(), format_io_error(e))))
+		.collect::<Vec<_>>();
+	
+	if let Some(remaining) = errors.len().checked_sub(MAX_REPORTED_PATH_ERRORS).filter(|remaining| *remaining > 0) {
+		lines.push(Line::from(format!("...and {remaining} more.")));
+	}
+	
+	MessageDialogLayer::build()
+		.y(y)
+		.style(theme().dialog_error)
+		.title("Error")
+		.message(lines)
+		.ok()
+}
+
 struct RefreshParentDirectoryAndSelectFile {
 	parent_view_node_id: NodeId,
-	child_file_name: String,
+	child_path: PathBuf,
 }
 
 impl Event<FsLayer> for RefreshParentDirectoryAndSelectFile {
 	fn dispatch(&self, layer: &mut FsLayer, _environment: &Environment) -> EventResult {
 		if layer.tree.refresh_children(self.parent_view_node_id) {
-			layer.tree.select_child_node_by_name(self.parent_view_node_id, &self.child_file_name);
+			layer.tree.select_path(&self.child_path);
 			EventResult::Draw
 		} else {
 			EventResult::Nothing
@@ -0,0 +1,200 @@
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+use crate::component::dialog::input::InputFieldDialogLayer;
+use crate::component::dialog::message::MessageDialogLayer;
+use crate::component::filesystem::action::file::{FileNode, get_entry_kind_name, get_selected_file};
+use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::jobs::{change_owner_of_entry, Job, JobKind};
+use crate::file::{FileOwnerName, FileOwnerNameCache, find_group_id_by_name, find_user_id_by_name, get_all_group_names, get_all_user_names};
+use crate::state::action::{Action, ActionResult};
+use crate::state::Environment;
+use crate::state::event::{EventQueue, EventResult};
+use crate::theme::theme;
+
+trait ChangeOwnership {
+	fn title() -> &'static str;
+	fn subject() -> &'static str;
+	fn initial_value(uid: Option<u32>, gid: Option<u32>, name_cache: &mut FileOwnerNameCache) -> Option<String>;
+	fn parse(text: &str) -> Result<OwnerIds, String>;
+	fn complete(text: &str) -> Vec<String>;
+}
+
+pub struct ChangeOwner;
+
+impl ChangeOwnership for ChangeOwner {
+	fn title() -> &'static str {
+		"Change Owner"
+	}
+	
+	fn subject() -> &'static str {
+		"owner"
+	}
+	
+	fn initial_value(uid: Option<u32>, gid: Option<u32>, name_cache: &mut FileOwnerNameCache) -> Option<String> {
+		let user = get_name_text(name_cache.get_user(uid))?;
+		let group = get_name_text(name_cache.get_group(gid))?;
+		Some(format!("{user}:{group}"))
+	}
+	
+	fn parse(text: &str) -> Result<OwnerIds, String> {
+		parse_owner_spec(text)
+	}
+	
+	fn complete(text: &str) -> Vec<String> {
+		if let Some((user, group)) = text.split_once(':') {
+			complete_names(group, get_all_group_names()).map(|group| format!("{user}:{group}")).collect()
+		} else {
+			complete_names(text, get_all_user_names()).collect()
+		}
+	}
+}
+
+pub struct ChangeGroup;
+
+impl ChangeOwnership for ChangeGroup {
+	fn title() -> &'static str {
+		"Change Group"
+	}
+	
+	fn subject() -> &'static str {
+		"group"
+	}
+	
+	fn initial_value(_uid: Option<u32>, gid: Option<u32>, name_cache: &mut FileOwnerNameCache) -> Option<String> {
+		get_name_text(name_cache.get_group(gid))
+	}
+	
+	fn parse(text: &str) -> Result<OwnerIds, String> {
+		parse_group_spec(text)
+	}
+	
+	fn complete(text: &str) -> Vec<String> {
+		complete_names(text, get_all_group_names()).collect()
+	}
+}
+
+pub struct ChangeOwnerOfSelectedEntry {
+	pub recursive: bool,
+}
+
+impl Action<FsLayer> for ChangeOwnerOfSelectedEntry {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		change_ownership_of_selected_entry::<ChangeOwner>(layer, self.recursive)
+	}
+}
+
+pub struct ChangeGroupOfSelectedEntry {
+	pub recursive: bool,
+}
+
+impl Action<FsLayer> for ChangeGroupOfSelectedEntry {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		change_ownership_of_selected_entry::<ChangeGroup>(layer, self.recursive)
+	}
+}
+
+fn change_ownership_of_selected_entry<T: ChangeOwnership + 'static>(layer: &mut FsLayer, recursive: bool) -> ActionResult {
+	let Some(FileNode { entry, path, .. }) = get_selected_file(layer) else {
+		return ActionResult::Nothing;
+	};
+	
+	let title = format!("{} of {}", T::title(), get_entry_kind_name(entry));
+	let path = path.to_owned();
+	let (uid, gid) = (entry.uid(), entry.gid());
+	
+	let initial_value = T::initial_value(uid, gid, &mut layer.file_owner_name_cache);
+	ActionResult::push_layer(create_change_ownership_dialog::<T>(layer, title, path, initial_value, recursive))
+}
+
+fn create_change_ownership_dialog<'b, T: ChangeOwnership + 'static>(layer: &FsLayer, title: String, path: PathBuf, initial_value: Option<String>, recursive: bool) -> InputFieldDialogLayer<'b> {
+	let y = layer.dialog_y();
+	let events = layer.events();
+	let recursively = if recursive { " recursively" } else { "" };
+	
+	InputFieldDialogLayer::build()
+		.y(y)
+		.min_width(40)
+		.style(theme().dialog_info)
+		.title(title)
+		.message(format!("Changing {} of {}{recursively}", T::subject(), path.to_string_lossy()))
+		.initial_value(initial_value)
+		.completion(T::complete)
+		.on_confirm(move |text| {
+			match T::parse(text.trim()) {
+				Ok(ids) => apply_ownership_change(&events, path.clone(), ids, recursive),
+				Err(e) => ActionResult::push_layer(MessageDialogLayer::error(y.saturating_add(1), e)),
+			}
+		})
+}
+
+/// User and group ids to assign. An id of `None` leaves the current user or group unchanged.
+#[derive(Copy, Clone)]
+pub struct OwnerIds {
+	uid: Option<u32>,
+	gid: Option<u32>,
+}
+
+/// Parses `user`, `user:group`, or `:group`, where each name can also be a numeric id.
+pub fn parse_owner_spec(spec: &str) -> Result<OwnerIds, String> {
+	let (user, group) = spec.split_once(':').unwrap_or((spec, ""));
+	
+	let uid = if user.is_empty() { None } else { Some(resolve_name(user, "user", find_user_id_by_name)?) };
+	let gid = if group.is_empty() { None } else { Some(resolve_name(group, "group", find_group_id_by_name)?) };
+	
+	if uid.is_none() && gid.is_none() {
+		Err(String::from("No user or group specified."))
+	} else {
+		Ok(OwnerIds { uid, gid })
+	}
+}
+
+/// Parses a group name or numeric id.
+pub fn parse_group_spec(spec: &str) -> Result<OwnerIds, String> {
+	if spec.is_empty() {
+		Err(String::from("No group specified."))
+	} else {
+		let gid = resolve_name(spec, "group", find_group_id_by_name)?;
+		Ok(OwnerIds { uid: None, gid: Some(gid) })
+	}
+}
+
+fn resolve_name<F>(name: &str, kind: &str, find_id_by_name: F) -> Result<u32, String> where F: FnOnce(&str) -> Option<u32> {
+	find_id_by_name(name)
+		.or_else(|| name.parse::<u32>().ok())
+		.ok_or_else(|| format!("Unknown {kind}: {name}"))
+}
+
+fn get_name_text(name: &FileOwnerName) -> Option<String> {
+	if matches!(name, FileOwnerName::Unknown) {
+		None
+	} else {
+		Some(Cow::from(name).into_owned())
+	}
+}
+
+fn complete_names(prefix: &str, names: Vec<String>) -> impl Iterator<Item = String> + '_ {
+	names.into_iter().filter(move |name| name.starts_with(prefix))
+}
+
+/// Changes ownership of the path in the background. Entries that could not be changed are reported once the job finishes.
+pub fn apply_ownership_change(events: &EventQueue<FsLayer>, path: PathBuf, ids: OwnerIds, recursive: bool) -> ActionResult {
+	events.enqueue_fn(move |layer, _| {
+		let file_system = layer.tree.file_system();
+		let path = path.clone();
+		
+		let mut refresh_paths = path.parent().map(Path::to_path_buf).into_iter().collect::<Vec<_>>();
+		if recursive {
+			refresh_paths.push(path.clone());
+		}
+		
+		Job::build(JobKind::Owner, format!("Changing ownership of {}", path.to_string_lossy()))
+			.refresh_paths(refresh_paths)
+			.success_message(format!("Changed ownership of {}", path.to_string_lossy()))
+			.start(&layer.jobs, move |context| change_owner_of_entry(file_system.as_ref(), context, &path, ids.uid, ids.gid, recursive));
+		
+		EventResult::Draw
+	});
+	
+	ActionResult::PopLayer
+}
@@ -1,8 +1,7 @@
 use io::ErrorKind;
-use std::{fs, io};
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
 
-use ratatui::style::Color;
 
 use slab_tree::NodeRef;
 
@@ -10,10 +9,12 @@ use crate::component::dialog::input::InputFieldDialogLayer;
 use crate::component::dialog::message::MessageDialogLayer;
 use crate::component::filesystem::action::file::{FileNode, format_io_error, get_entry_kind_name, get_selected_file, RefreshParentDirectoryAndSelectFile};
 use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::status::StatusMessage;
 use crate::component::filesystem::tree::FsTreeViewNode;
-use crate::file::FileEntry;
+use crate::file::{FileEntry, FileSystem};
 use crate::state::action::{Action, ActionResult};
 use crate::state::Environment;
+use crate::theme::theme;
 
 pub struct RenameSelectedEntry {
 	pub prefill: bool,
@@ -35,19 +36,23 @@ impl RenameSelectedEntry {
 		let y = layer.dialog_y();
 		let events = layer.events();
 		let parent_view_node_id = node.parent_id();
+		let old_name = entry.name().str().to_owned();
+		let file_system = layer.tree.file_system();
 		
 		InputFieldDialogLayer::build()
 			.y(y)
 			.min_width(40)
-			.color(Color::LightCyan, Color::Cyan)
+			.style(theme().dialog_info)
 			.title(format!("Rename {}", get_entry_kind_name(entry)))
 			.message(format!("Renaming {}", path.to_string_lossy()))
 			.initial_value(self.prefill.then(|| entry.name().str().to_owned()))
 			.on_confirm(move |new_name| {
-				match rename_file(&path, &new_name) {
-					Ok(_) => {
+				match rename_file(file_system.as_ref(), &path, &new_name) {
+					Ok(()) => {
+						events.enqueue(StatusMessage::info(format!("Renamed {old_name} → {new_name}")));
+						
 						if let Some(parent_view_node_id) = parent_view_node_id {
-							events.enqueue(RefreshParentDirectoryAndSelectFile { parent_view_node_id, child_file_name: new_name });
+							events.enqueue(RefreshParentDirectoryAndSelectFile { parent_view_node_id, child_path: path.with_file_name(&new_name) });
 						}
 						ActionResult::PopLayer
 					}
@@ -59,14 +64,12 @@ impl RenameSelectedEntry {
 	}
 }
 
-fn rename_file(path: &PathBuf, new_name: &String) -> io::Result<()> {
+fn rename_file(file_system: &dyn FileSystem, path: &Path, new_name: &String) -> io::Result<()> {
 	let new_path = path.with_file_name(new_name);
 	
-	if new_path.components().count() != path.components().count() {
-		Err(io::Error::new(ErrorKind::InvalidInput, "Invalid name"))
-	} else if fs::symlink_metadata(&new_path).is_ok() {
-		Err(io::Error::new(ErrorKind::AlreadyExists, "Something with this name already exists"))
+	if new_path.components().count() == path.components().count() {
+		file_system.rename(path, &new_path)
 	} else {
-		fs::rename(path, new_path)
+		Err(io::Error::new(ErrorKind::InvalidInput, "Invalid name"))
 	}
 }
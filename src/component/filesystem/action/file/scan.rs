@@ -0,0 +1,32 @@
+use crate::component::filesystem::action::file::{describe_entries, get_marked_or_selected_paths};
+use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::jobs::{Job, JobKind, scan_entries};
+use crate::component::filesystem::status::StatusMessage;
+use crate::state::action::{Action, ActionResult};
+use crate::state::Environment;
+use crate::state::event::EventResult;
+
+/// Counts the marked entries, or the selected entry, including everything inside directories, and shows their total size.
+pub struct ScanSelectedEntries;
+
+impl Action<FsLayer> for ScanSelectedEntries {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		let paths = get_marked_or_selected_paths(layer);
+		if paths.is_empty() {
+			return ActionResult::Nothing;
+		}
+		
+		let file_system = layer.tree.file_system();
+		let description = describe_entries(&paths);
+		
+		Job::build(JobKind::Scan, format!("Scanning {description}"))
+			.on_success(move |layer, progress| {
+				let size = layer.size_format.format(progress.total_bytes);
+				layer.show_message(StatusMessage::info(format!("{description}: {} entries, {size}", progress.total_entries)));
+				EventResult::Draw
+			})
+			.start(&layer.jobs, move |context| scan_entries(file_system.as_ref(), context, &paths, usize::MAX, u64::MAX).map(|_| ()));
+		
+		ActionResult::Draw
+	}
+}
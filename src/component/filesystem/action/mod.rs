@@ -1,5 +1,6 @@
 pub mod application;
 pub mod count;
+pub mod display;
 pub mod file;
 pub mod movement;
 pub mod tree;
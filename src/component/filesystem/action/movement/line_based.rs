@@ -28,12 +28,12 @@ pub struct MoveToFirst;
 
 impl MovementAction for MoveToFirst {
 	fn get_target(&self, layer: &mut FsLayer, _environment: &Environment) -> Option<NodeId> where Self: Sized {
-		Some(Self::get_target(&mut layer.tree))
+		Some(Self::get_target(&layer.tree))
 	}
 }
 
 impl MoveToFirst {
-	fn get_target(tree: &mut FsTree) -> NodeId where Self: Sized {
+	fn get_target(tree: &FsTree) -> NodeId where Self: Sized {
 		let mut target_node_id = tree.selected_view_node_id;
 		
 		while let Some(node_id) = tree.get_view_node(target_node_id).and_then(|node| <MoveToParent as SimpleMovementAction>::get_target(&node)) {
@@ -49,7 +49,7 @@ pub struct MoveToLast;
 
 impl MovementAction for MoveToLast {
 	fn get_target(&self, layer: &mut FsLayer, _environment: &Environment) -> Option<NodeId> where Self: Sized {
-		let first_node_id = MoveToFirst::get_target(&mut layer.tree);
+		let first_node_id = MoveToFirst::get_target(&layer.tree);
 		let last_node_id = layer.tree.get_view_node(first_node_id).map(|node| node.last_descendant_or_self().node_id());
 		last_node_id
 	}
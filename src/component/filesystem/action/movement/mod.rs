@@ -8,6 +8,7 @@ use crate::state::Environment;
 pub use self::expand_collapse::*;
 pub use self::hierarchy_based::*;
 pub use self::line_based::*;
+pub use self::with_count::FixedCount;
 pub use self::with_count::MovementWithCountFactory;
 pub use self::with_count::ScreenHeightRatio;
 pub use self::with_fallback::MovementWithFallbackFactory;
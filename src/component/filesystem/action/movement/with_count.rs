@@ -61,3 +61,12 @@ impl MovementCount for ScreenHeightRatio {
 		original_count.unwrap_or(1).saturating_mul(height_ratio)
 	}
 }
+
+/// Defines movement count as a constant, ignoring the count specified by the user.
+pub struct FixedCount(pub usize);
+
+impl MovementCount for FixedCount {
+	fn get_count(&self, _original_count: Option<usize>, _environment: &Environment) -> usize {
+		self.0
+	}
+}
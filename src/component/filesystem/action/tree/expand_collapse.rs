@@ -1,13 +1,15 @@
-use std::time::{Duration, Instant};
+use std::path::Path;
 
-use ratatui::style::Color;
 use slab_tree::NodeId;
 
-use crate::component::dialog::message::MessageDialogLayer;
 use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::jobs::{Job, JobKind, scan_entries};
+use crate::component::filesystem::status::StatusMessage;
 use crate::component::filesystem::tree::FsTree;
+use crate::file::FileEntry;
 use crate::state::action::{Action, ActionResult};
 use crate::state::Environment;
+use crate::state::event::EventResult;
 
 pub struct ExpandCollapse {
 	pub default_depth: usize,
@@ -20,22 +22,11 @@ impl Action<FsLayer> for ExpandCollapse {
 			return ActionResult::Nothing;
 		}
 		
-		if layer.tree.expand_or_collapse(layer.tree.selected_view_node_id) {
-			if depth > 1 {
-				if let Some(node) = layer.tree.selected_node() {
-					if node.data().is_expanded() {
-						let child_node_ids = node.children().map(|node| node.node_id()).collect();
-						let remaining_depth = depth.saturating_sub(1);
-						if !expand_children_to_depth(&mut layer.tree, child_node_ids, remaining_depth) {
-							return ActionResult::push_layer(MessageDialogLayer::build()
-								.y(layer.dialog_y())
-								.color(Color::LightYellow)
-								.title("Expansion Stopped")
-								.message(format!("Expansion was taking more than {} seconds, stopping now.", MAX_EXPANSION_TIME.as_secs()))
-								.ok());
-						}
-					}
-				}
+		let view_node_id = layer.tree.selected_view_node_id;
+		
+		if layer.tree.expand_or_collapse(view_node_id) {
+			if depth > 1 && layer.tree.selected_node().is_some_and(|node| node.data().is_expanded()) {
+				expand_children_in_background(layer, view_node_id, depth);
 			}
 			
 			ActionResult::Draw
@@ -45,27 +36,61 @@ impl Action<FsLayer> for ExpandCollapse {
 	}
 }
 
-const MAX_EXPANSION_TIME: Duration = Duration::from_secs(10);
+/// Expanding stops if there would be more entries than this, because the tree would take too long to show them.
+const MAX_EXPANDED_ENTRIES: u64 = 100_000;
+
+/// Scans directories that will be expanded in a background job first, so that the expansion can be cancelled, and reading them does not block the tree. Entries inside archives are already in memory, so they are expanded immediately.
+fn expand_children_in_background(layer: &mut FsLayer, view_node_id: NodeId, depth: usize) {
+	let remaining_depth = depth.saturating_sub(1);
+	
+	let Some(path) = layer.tree.selected_node().and_then(|node| layer.tree.get_entry(&node)).and_then(FileEntry::path).map(Path::to_path_buf) else {
+		expand_children_of_node(&mut layer.tree, view_node_id, remaining_depth);
+		return;
+	};
+	
+	let file_system = layer.tree.file_system();
+	let paths = vec![path.clone()];
+	
+	Job::build(JobKind::Scan, format!("Expanding {}", path.to_string_lossy()))
+		.on_success(move |layer, progress| {
+			if progress.total_entries >= MAX_EXPANDED_ENTRIES {
+				layer.show_message(StatusMessage::error(format!("Expansion would show more than {MAX_EXPANDED_ENTRIES} entries, stopped.")));
+			} else {
+				expand_children_of_node(&mut layer.tree, view_node_id, remaining_depth);
+			}
+			
+			EventResult::Draw
+		})
+		.start(&layer.jobs, move |context| scan_entries(file_system.as_ref(), context, &paths, depth, MAX_EXPANDED_ENTRIES).map(|_| ()));
+}
+
+/// Expands children of the node if it is still expanded, since it may have been collapsed while its directories were being scanned.
+fn expand_children_of_node(tree: &mut FsTree, view_node_id: NodeId, max_depth: usize) {
+	let child_node_ids = tree.get_view_node(view_node_id)
+		.filter(|node| node.data().is_expanded())
+		.map(|node| node.children().map(|child| child.node_id()).collect())
+		.unwrap_or_default();
+	
+	expand_children_to_depth(tree, child_node_ids, max_depth);
+}
 
-fn expand_children_to_depth(tree: &mut FsTree, mut child_node_ids: Vec<NodeId>, max_depth: usize) -> bool {
-	let start_time = Instant::now();
+fn expand_children_to_depth(tree: &mut FsTree, mut child_node_ids: Vec<NodeId>, max_depth: usize) {
 	let mut current_pass_node_ids = Vec::new();
 	
 	for _depth in 0..max_depth {
+		if child_node_ids.is_empty() {
+			break;
+		}
+		
+		current_pass_node_ids.clear();
 		current_pass_node_ids.append(&mut child_node_ids);
 		
 		for node_id in &current_pass_node_ids {
 			let node_id = *node_id;
 			tree.expand(node_id);
 			get_child_node_ids(tree, node_id, &mut child_node_ids);
-			
-			if start_time.elapsed() >= MAX_EXPANSION_TIME {
-				return false;
-			}
 		}
 	}
-	
-	true
 }
 
 fn get_child_node_ids(tree: &FsTree, node_id: NodeId, output_node_ids: &mut Vec<NodeId>) {
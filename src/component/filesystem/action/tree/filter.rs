@@ -0,0 +1,24 @@
+use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::tree::FsTreeViewFilter;
+use crate::state::action::{Action, ActionResult};
+use crate::state::Environment;
+
+/// Shows or hides entries whose names start with a dot.
+pub struct ToggleHiddenEntries;
+
+impl Action<FsLayer> for ToggleHiddenEntries {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		let filter = layer.tree.filter();
+		ActionResult::draw_if(layer.tree.set_filter(FsTreeViewFilter { show_hidden: !filter.show_hidden, ..filter }))
+	}
+}
+
+/// Shows or hides entries matched by `.gitignore`, `.ignore`, or `.git/info/exclude` files.
+pub struct ToggleIgnoredEntries;
+
+impl Action<FsLayer> for ToggleIgnoredEntries {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		let filter = layer.tree.filter();
+		ActionResult::draw_if(layer.tree.set_filter(FsTreeViewFilter { show_ignored: !filter.show_ignored, ..filter }))
+	}
+}
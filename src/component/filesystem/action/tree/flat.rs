@@ -0,0 +1,90 @@
+use std::path::{Path, PathBuf};
+
+use slab_tree::NodeId;
+
+use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::jobs::{Job, JobKind, scan_entries};
+use crate::component::filesystem::status::StatusMessage;
+use crate::component::filesystem::tree::{FsTree, MAX_FLATTENED_ENTRIES};
+use crate::file::{FileEntry, FileKind};
+use crate::state::action::{Action, ActionResult};
+use crate::state::Environment;
+use crate::state::event::EventResult;
+
+/// Turns the selected directory, or the directory that contains the selected entry, into a flat listing of all files inside it and its subdirectories. If the selection is already in a flat listing, turns the listing back into a tree. The selected entry stays selected in both cases.
+pub struct ToggleFlatListing;
+
+impl Action<FsLayer> for ToggleFlatListing {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		let tree = &mut layer.tree;
+		
+		let Some(selected_node) = tree.selected_node() else {
+			return ActionResult::Nothing;
+		};
+		
+		let selected_node_id = selected_node.node_id();
+		let selected_path = tree.get_entry(&selected_node).and_then(FileEntry::path).map(Path::to_path_buf);
+		let is_directory = tree.get_entry(&selected_node).is_some_and(|entry| matches!(entry.kind(), FileKind::Directory));
+		
+		let flattened_node_id = if selected_node.data().is_flattened() {
+			Some(selected_node_id)
+		} else {
+			selected_node.parent().filter(|parent| parent.data().is_flattened()).map(|parent| parent.node_id())
+		};
+		
+		let directory_node_id = if is_directory { Some(selected_node_id) } else { selected_node.parent_id() };
+		
+		if let Some(flattened_node_id) = flattened_node_id {
+			tree.collapse(flattened_node_id);
+			tree.expand(flattened_node_id);
+			tree.selected_view_node_id = flattened_node_id;
+			
+			if let Some(selected_path) = selected_path {
+				tree.select_path(&selected_path);
+			}
+			
+			ActionResult::Draw
+		} else if let Some(directory_node_id) = directory_node_id {
+			flatten_in_background(layer, directory_node_id, selected_path);
+			ActionResult::Draw
+		} else {
+			ActionResult::Nothing
+		}
+	}
+}
+
+/// Scans the directory in a background job first, so that flattening can be cancelled, and a directory with too many entries is not flattened. Entries inside archives are already in memory, so they are flattened immediately.
+fn flatten_in_background(layer: &mut FsLayer, directory_node_id: NodeId, selected_path: Option<PathBuf>) {
+	let Some(path) = layer.tree.get_view_node(directory_node_id).and_then(|node| layer.tree.get_entry(&node)).and_then(FileEntry::path).map(Path::to_path_buf) else {
+		flatten_and_select(&mut layer.tree, directory_node_id, selected_path.as_deref());
+		return;
+	};
+	
+	let file_system = layer.tree.file_system();
+	let paths = vec![path.clone()];
+	
+	Job::build(JobKind::Scan, format!("Flattening {}", path.to_string_lossy()))
+		.on_success(move |layer, progress| {
+			if progress.total_entries >= MAX_FLATTENED_ENTRIES {
+				layer.show_message(StatusMessage::error(format!("Flat listing would show more than {MAX_FLATTENED_ENTRIES} entries, stopped.")));
+				EventResult::Draw
+			} else {
+				EventResult::draw_if(flatten_and_select(&mut layer.tree, directory_node_id, selected_path.as_deref()))
+			}
+		})
+		.start(&layer.jobs, move |context| scan_entries(file_system.as_ref(), context, &paths, usize::MAX, MAX_FLATTENED_ENTRIES).map(|_| ()));
+}
+
+fn flatten_and_select(tree: &mut FsTree, directory_node_id: NodeId, selected_path: Option<&Path>) -> bool {
+	if !tree.flatten(directory_node_id) {
+		return false;
+	}
+	
+	tree.selected_view_node_id = directory_node_id;
+	
+	if let Some(selected_path) = selected_path {
+		tree.select_path(selected_path);
+	}
+	
+	true
+}
@@ -0,0 +1,33 @@
+use crate::component::filesystem::FsLayer;
+use crate::state::action::{Action, ActionResult};
+use crate::state::Environment;
+
+/// Toggles the mark on `count` entries starting with the selected one, and moves the selection below them.
+pub struct ToggleMarkAndMoveDown;
+
+impl Action<FsLayer> for ToggleMarkAndMoveDown {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		let tree = &mut layer.tree;
+		let mut changed = false;
+		
+		for _ in 0..layer.registers.count.unwrap_or(1) {
+			changed |= tree.toggle_mark(tree.selected_view_node_id);
+			
+			if let Some(below_id) = tree.selected_node().and_then(|node| node.below_id()) {
+				tree.selected_view_node_id = below_id;
+			} else {
+				break;
+			}
+		}
+		
+		ActionResult::draw_if(changed)
+	}
+}
+
+pub struct ClearMarks;
+
+impl Action<FsLayer> for ClearMarks {
+	fn perform(&self, layer: &mut FsLayer, _environment: &Environment) -> ActionResult {
+		ActionResult::draw_if(layer.tree.clear_marks())
+	}
+}
@@ -1,5 +1,11 @@
 pub use self::expand_collapse::*;
+pub use self::filter::*;
+pub use self::flat::*;
+pub use self::mark::*;
 pub use self::refresh::*;
 
 mod expand_collapse;
+mod filter;
+mod flat;
+mod mark;
 mod refresh;
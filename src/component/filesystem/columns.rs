@@ -0,0 +1,119 @@
+use std::time::SystemTime;
+
+use crate::file::FileEntry;
+
+/// Column displayed after the name of each entry. The name column is always displayed first.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum Column {
+	/// Only displayed in disk usage mode, which also adds it in front of other columns if it is not in the list.
+	DiskUsage,
+	GitStatus,
+	Size,
+	ModifiedTime,
+	AccessedTime,
+	ChangedTime,
+	BirthTime,
+	Owner,
+	Permissions,
+	OctalMode,
+	Inode,
+	LinkCount,
+	Blocks,
+	Extension,
+}
+
+impl Column {
+	const ALL: [Self; 14] = [
+		Self::DiskUsage,
+		Self::GitStatus,
+		Self::Size,
+		Self::ModifiedTime,
+		Self::AccessedTime,
+		Self::ChangedTime,
+		Self::BirthTime,
+		Self::Owner,
+		Self::Permissions,
+		Self::OctalMode,
+		Self::Inode,
+		Self::LinkCount,
+		Self::Blocks,
+		Self::Extension,
+	];
+	
+	#[cfg(unix)]
+	pub fn defaults() -> Vec<Self> {
+		vec![Self::GitStatus, Self::Size, Self::ModifiedTime, Self::Owner, Self::Permissions]
+	}
+	
+	#[cfg(not(unix))]
+	pub fn defaults() -> Vec<Self> {
+		vec![Self::GitStatus, Self::Size, Self::ModifiedTime, Self::Permissions]
+	}
+	
+	pub const fn name(self) -> &'static str {
+		match self {
+			Self::DiskUsage    => "usage",
+			Self::GitStatus    => "git",
+			Self::Size         => "size",
+			Self::ModifiedTime => "modified",
+			Self::AccessedTime => "accessed",
+			Self::ChangedTime  => "changed",
+			Self::BirthTime    => "created",
+			Self::Owner        => "owner",
+			Self::Permissions  => "permissions",
+			Self::OctalMode    => "mode",
+			Self::Inode        => "inode",
+			Self::LinkCount    => "links",
+			Self::Blocks       => "blocks",
+			Self::Extension    => "extension",
+		}
+	}
+	
+	/// Returns the time that the column shows, or `None` for columns that do not show a time.
+	pub fn get_date_time(self, entry: &FileEntry) -> Option<SystemTime> {
+		match self {
+			Self::ModifiedTime => entry.modified_time().copied(),
+			Self::AccessedTime => entry.accessed_time().copied(),
+			Self::ChangedTime  => entry.changed_time(),
+			Self::BirthTime    => entry.birth_time().copied(),
+			Self::DiskUsage | Self::GitStatus | Self::Size | Self::Owner | Self::Permissions | Self::OctalMode | Self::Inode | Self::LinkCount | Self::Blocks | Self::Extension => None,
+		}
+	}
+	
+	pub const fn is_date_time(self) -> bool {
+		matches!(self, Self::ModifiedTime | Self::AccessedTime | Self::ChangedTime | Self::BirthTime)
+	}
+	
+	pub fn from_name(name: &str) -> Option<Self> {
+		Self::ALL.into_iter().find(|column| column.name() == name)
+	}
+	
+	/// Columns with lower priority are hidden first when the terminal is too narrow to display all columns.
+	pub const fn priority(self) -> u8 {
+		match self {
+			Self::DiskUsage    => 13,
+			Self::Size         => 12,
+			Self::ModifiedTime => 11,
+			Self::GitStatus    => 10,
+			Self::Permissions  => 9,
+			Self::Owner        => 8,
+			Self::Extension    => 7,
+			Self::OctalMode    => 6,
+			Self::BirthTime    => 5,
+			Self::ChangedTime  => 4,
+			Self::AccessedTime => 3,
+			Self::LinkCount    => 2,
+			Self::Blocks       => 1,
+			Self::Inode        => 0,
+		}
+	}
+}
+
+/// Parses a comma-separated list of column names.
+pub fn parse_columns(names: &str) -> Result<Vec<Column>, String> {
+	names.split(',')
+	     .map(str::trim)
+	     .filter(|name| !name.is_empty())
+	     .map(|name| Column::from_name(name).ok_or_else(|| format!("Unknown column: {name}")))
+	     .collect()
+}
@@ -0,0 +1,309 @@
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use regex::Regex;
+
+use crate::component::dialog::message::MessageDialogLayer;
+use crate::component::filesystem::action::file::{apply_ownership_change, create_substitution_dialog, OwnerIds, parse_group_spec, parse_owner_spec, SubstitutionTargets};
+use crate::component::filesystem::columns::{Column, parse_columns};
+use crate::component::filesystem::defaults;
+use crate::component::filesystem::{ActionKeyMap, FsLayer};
+use crate::component::filesystem::dupes::DupesLayer;
+use crate::component::filesystem::grep::GrepLayer;
+use crate::component::filesystem::jobs::{Jobs, JobsLayer};
+use crate::component::filesystem::render::{DateFormat, SizeFormat, SizeKind};
+use crate::component::filesystem::tree::{FsTreeViewFilter, FsTreeViewSort};
+use crate::component::help::HelpLayer;
+use crate::file::{FileKind, FileSystem};
+use crate::state::action::ActionResult;
+use crate::state::event::{EventQueue, EventResult};
+
+/// State of the [`FsLayer`] captured when entering command mode, because commands run while the command line layer is on top.
+pub struct CommandContext {
+	y: u16,
+	events: EventQueue<FsLayer>,
+	action_map: &'static ActionKeyMap,
+	file_system: Arc<dyn FileSystem>,
+	filter: FsTreeViewFilter,
+	selected: Option<SelectedEntry>,
+	substitution_targets: Rc<SubstitutionTargets>,
+	jobs: Jobs,
+	size_format: SizeFormat,
+}
+
+struct SelectedEntry {
+	path: PathBuf,
+}
+
+impl SelectedEntry {
+	/// Returns the selected directory, or the directory that contains the selected file.
+	fn directory(&self, file_system: &dyn FileSystem) -> Option<&Path> {
+		if matches!(file_system.stat(&self.path).kind(), FileKind::Directory) { Some(self.path.as_path()) } else { self.path.parent() }
+	}
+}
+
+impl CommandContext {
+	pub fn new(layer: &FsLayer) -> Self {
+		let selected = layer.tree.selected_node().and_then(|node| {
+			let path = layer.tree.get_entry(&node)?.path()?.to_path_buf();
+			Some(SelectedEntry { path })
+		});
+		
+		Self {
+			y: layer.dialog_y(),
+			events: layer.events(),
+			action_map: layer.action_map,
+			file_system: layer.tree.file_system(),
+			filter: layer.tree.filter(),
+			selected,
+			substitution_targets: Rc::new(SubstitutionTargets::collect(&layer.tree)),
+			jobs: layer.jobs.rc_clone(),
+			size_format: layer.size_format,
+		}
+	}
+	
+	fn error(&self, message: impl Into<String>) -> ActionResult {
+		ActionResult::replace_layer(MessageDialogLayer::error(self.y, message.into()))
+	}
+}
+
+pub fn run(context: &CommandContext, command: &str) -> ActionResult {
+	if is_substitution(command) {
+		return substitute(context, command);
+	}
+	
+	let mut args = command.split_whitespace();
+	
+	match args.next() {
+		None => ActionResult::PopLayer,
+		Some("chown") => change_ownership(context, args, parse_owner_spec),
+		Some("chgrp") => change_ownership(context, args, parse_group_spec),
+		Some("du") => disk_usage(context, args),
+		Some("dupes") => find_duplicates(context),
+		Some("grep") => grep(context, command.trim_start().trim_start_matches("grep").trim_start()),
+		Some("jobs") => ActionResult::replace_layer(JobsLayer::new(context.jobs.rc_clone(), context.size_format)),
+		Some("help") => ActionResult::replace_layer(HelpLayer::new(defaults::describe_key_bindings(context.action_map))),
+		Some("set") => set_options(context, &split_escaped_args(command.trim_start().trim_start_matches("set"))),
+		Some(name) => context.error(format!("Unknown command: {name}")),
+	}
+}
+
+fn change_ownership<'a, F>(context: &CommandContext, args: impl Iterator<Item = &'a str>, parse: F) -> ActionResult where F: FnOnce(&str) -> Result<OwnerIds, String> {
+	let mut recursive = false;
+	let mut spec = None;
+	
+	for arg in args {
+		if arg == "-R" {
+			recursive = true;
+		} else if spec.is_none() {
+			spec = Some(arg);
+		} else {
+			return context.error("Too many arguments.");
+		}
+	}
+	
+	let Some(selected) = &context.selected else {
+		return ActionResult::PopLayer;
+	};
+	
+	match parse(spec.unwrap_or("")) {
+		Ok(ids) => apply_ownership_change(&context.events, selected.path.clone(), ids, recursive),
+		Err(e) => context.error(e),
+	}
+}
+
+/// Finds files with identical contents in the selected directory, or in the directory that contains the selected file.
+fn find_duplicates(context: &CommandContext) -> ActionResult {
+	if let Some(directory) = context.selected.as_ref().and_then(|selected| selected.directory(context.file_system.as_ref())) {
+		ActionResult::replace_layer(DupesLayer::new(directory, Arc::clone(&context.file_system), context.events.rc_clone()))
+	} else {
+		ActionResult::PopLayer
+	}
+}
+
+/// Searches contents of files in the selected directory, or in the directory that contains the selected file. The whole rest of the command is the regular expression, so it can include whitespace.
+fn grep(context: &CommandContext, pattern: &str) -> ActionResult {
+	if pattern.is_empty() {
+		return context.error("Missing regular expression.");
+	}
+	
+	let regex = match Regex::new(pattern) {
+		Ok(regex) => regex,
+		Err(e) => return context.error(format!("Invalid regular expression: {e}")),
+	};
+	
+	if let Some(directory) = context.selected.as_ref().and_then(|selected| selected.directory(context.file_system.as_ref())) {
+		ActionResult::replace_layer(GrepLayer::new(directory, context.filter, regex, Arc::clone(&context.file_system), context.events.rc_clone()))
+	} else {
+		ActionResult::PopLayer
+	}
+}
+
+/// Scans disk usage of the view root directory, and sorts entries by their total size. The scan stays on one file system unless `--cross-file-systems` is given, and `off` returns to the previous sort order.
+fn disk_usage<'a>(context: &CommandContext, args: impl Iterator<Item = &'a str>) -> ActionResult {
+	let mut cross_file_systems = false;
+	let mut turn_off = false;
+	
+	for arg in args {
+		match arg {
+			"--cross-file-systems" => cross_file_systems = true,
+			"off" => turn_off = true,
+			_ => return context.error(format!("Unknown argument: {arg}")),
+		}
+	}
+	
+	context.events.enqueue_fn(move |layer, _| {
+		if turn_off {
+			layer.stop_disk_usage();
+		} else {
+			layer.start_disk_usage(cross_file_systems);
+		}
+		
+		EventResult::Draw
+	});
+	
+	ActionResult::PopLayer
+}
+
+/// Splits arguments separated by whitespace. Whitespace and backslashes can be included in an argument by escaping them with a backslash.
+fn split_escaped_args(command: &str) -> Vec<String> {
+	let mut args = Vec::new();
+	let mut current = String::new();
+	let mut chars = command.chars();
+	
+	while let Some(c) = chars.next() {
+		if c == '\\' {
+			current.push(chars.next().unwrap_or(c));
+		} else if !c.is_whitespace() {
+			current.push(c);
+		} else if !current.is_empty() {
+			args.push(std::mem::take(&mut current));
+		}
+	}
+	
+	if !current.is_empty() {
+		args.push(current);
+	}
+	
+	args
+}
+
+fn set_options(context: &CommandContext, args: &[String]) -> ActionResult {
+	let mut changes = Vec::new();
+	
+	for arg in args {
+		match parse_option(arg) {
+			Ok(change) => changes.push(change),
+			Err(e) => return context.error(e),
+		}
+	}
+	
+	context.events.enqueue_fn(move |layer, _| {
+		for change in &changes {
+			change.apply(layer);
+		}
+		
+		EventResult::Draw
+	});
+	
+	ActionResult::PopLayer
+}
+
+enum OptionChange {
+	Columns(ListOperator, Vec<Column>),
+	DateFormat(DateFormat),
+	SizeFormat(SizeFormat),
+	SizeKind(SizeKind),
+	Sort(FsTreeViewSort),
+}
+
+/// Operator of an option assignment; lists can be replaced, extended, or have items removed.
+#[derive(Copy, Clone)]
+enum ListOperator {
+	Set,
+	Add,
+	Remove,
+}
+
+impl OptionChange {
+	fn apply(&self, layer: &mut FsLayer) {
+		match self {
+			Self::Columns(operator, columns) => {
+				operator.apply(&mut layer.columns, columns);
+				layer.column_width_cache = None;
+			}
+			Self::DateFormat(format) => {
+				layer.date_format.clone_from(format);
+				layer.column_width_cache = None;
+			}
+			Self::SizeFormat(format) => {
+				layer.set_size_format(*format, layer.size_kind);
+			}
+			Self::SizeKind(kind) => {
+				layer.set_size_format(layer.size_format, *kind);
+			}
+			Self::Sort(sort) => {
+				layer.tree.set_sort(*sort);
+			}
+		}
+	}
+}
+
+impl ListOperator {
+	fn apply<T: Copy + PartialEq>(self, list: &mut Vec<T>, items: &[T]) {
+		match self {
+			Self::Set => {
+				list.clear();
+				list.extend(items);
+			}
+			Self::Add => {
+				list.retain(|item| !items.contains(item));
+				list.extend(items);
+			}
+			Self::Remove => {
+				list.retain(|item| !items.contains(item));
+			}
+		}
+	}
+}
+
+/// Parses `option=value`, `option+=value` or `option-=value`.
+fn parse_option(arg: &str) -> Result<OptionChange, String> {
+	let Some((name, value)) = arg.split_once('=') else {
+		return Err(format!("Missing value: {arg}"));
+	};
+	
+	let (name, operator) = if let Some(name) = name.strip_suffix('+') {
+		(name, ListOperator::Add)
+	} else if let Some(name) = name.strip_suffix('-') {
+		(name, ListOperator::Remove)
+	} else {
+		(name, ListOperator::Set)
+	};
+	
+	match (name, operator) {
+		("columns", _) => Ok(OptionChange::Columns(operator, parse_columns(value)?)),
+		("dates", ListOperator::Set) => Ok(OptionChange::DateFormat(DateFormat::parse(value)?)),
+		("sizes", ListOperator::Set) => Ok(OptionChange::SizeFormat(SizeFormat::parse(value)?)),
+		("size-kind", ListOperator::Set) => Ok(OptionChange::SizeKind(SizeKind::parse(value)?)),
+		("sort", ListOperator::Set) => Ok(OptionChange::Sort(FsTreeViewSort::parse(value)?)),
+		("dates" | "sizes" | "size-kind" | "sort", _) => Err(format!("Option is not a list: {name}")),
+		_ => Err(format!("Unknown option: {name}")),
+	}
+}
+
+/// Checks whether the command is `s` or `%s`, optionally followed by a delimiter and the rest of the substitution expression.
+fn is_substitution(command: &str) -> bool {
+	let command = command.trim();
+	let command = command.strip_prefix('%').unwrap_or(command);
+	
+	command.strip_prefix('s').is_some_and(|rest| rest.chars().next().map_or(true, |delimiter| !delimiter.is_alphanumeric() && !delimiter.is_whitespace()))
+}
+
+fn substitute(context: &CommandContext, command: &str) -> ActionResult {
+	let command = command.trim();
+	let expression = if command.ends_with('s') { format!("{command}/") } else { command.to_owned() };
+	
+	ActionResult::replace_layer(create_substitution_dialog(context.y, context.events.rc_clone(), Rc::clone(&context.substitution_targets), &expression))
+}
@@ -0,0 +1,373 @@
+use std::collections::BTreeMap;
+use std::io::{self, BufReader, Read};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use crate::component::filesystem::tree::{FsTreeModel, FsTreeModelNode, FsTreeViewFilter};
+use crate::file::{FileEntry, FileKind, FileSystem, IgnoreRules};
+
+/// How many entries are compared between progress updates.
+const PROGRESS_INTERVAL: usize = 100;
+
+/// How an entry in one directory differs from the entry at the same relative path in the other directory.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Difference {
+	OnlyLeft,
+	OnlyRight,
+	Kind,
+	Size,
+	Content,
+	/// Contents are the same, but the modification time is not.
+	ModifiedTime,
+	/// Directory that exists on both sides, and contains entries that differ.
+	ContainsDifferences,
+}
+
+impl Difference {
+	pub const fn describe(self) -> &'static str {
+		match self {
+			Self::OnlyLeft => "only in A",
+			Self::OnlyRight => "only in B",
+			Self::Kind => "kind differs",
+			Self::Size => "size differs",
+			Self::Content => "contents differ",
+			Self::ModifiedTime => "modification time differs",
+			Self::ContainsDifferences => "contains differences",
+		}
+	}
+}
+
+pub struct CompareEntry {
+	pub path: PathBuf,
+	pub is_directory: bool,
+}
+
+/// Entry of the merged tree of both directories, with the entries it stands for on each side.
+pub struct CompareNode {
+	pub name: String,
+	pub relative_path: PathBuf,
+	pub left: Option<CompareEntry>,
+	pub right: Option<CompareEntry>,
+	pub difference: Option<Difference>,
+	pub children: Vec<Self>,
+}
+
+impl CompareNode {
+	pub fn is_directory(&self) -> bool {
+		[&self.left, &self.right].into_iter().flatten().any(|entry| entry.is_directory)
+	}
+}
+
+pub enum CompareMessage {
+	Progress(usize),
+	Finished(CompareNode),
+}
+
+/// Starts comparing two directories and all their subdirectories in a background thread. Entries hidden by the filter are left out on both sides. The comparison stops when the receiver is dropped.
+pub fn start(file_system: Arc<dyn FileSystem>, left_root: &Path, right_root: &Path, filter: FsTreeViewFilter) -> Receiver<CompareMessage> {
+	let (sender, receiver) = mpsc::channel();
+	let left_root = left_root.to_path_buf();
+	let right_root = right_root.to_path_buf();
+	
+	thread::spawn(move || {
+		let left = Side { node: FsTreeModelNode::from(file_system.stat(&left_root)), parent_ignore_rules: None };
+		let right = Side { node: FsTreeModelNode::from(file_system.stat(&right_root)), parent_ignore_rules: None };
+		let mut comparison = Comparison { file_system, filter, sender, compared_entries: 0 };
+		
+		if let Some(root) = comparison.compare(String::new(), PathBuf::new(), Some(left), Some(right)) {
+			let _ = comparison.sender.send(CompareMessage::Finished(root));
+		}
+	});
+	
+	receiver
+}
+
+/// Entry on one side of the comparison, with ignore rules needed to read its children.
+struct Side {
+	node: FsTreeModelNode,
+	parent_ignore_rules: Option<Rc<IgnoreRules>>,
+}
+
+impl Side {
+	const fn entry(&self) -> &FileEntry {
+		&self.node.entry
+	}
+	
+	const fn is_directory(&self) -> bool {
+		matches!(self.entry().kind(), FileKind::Directory)
+	}
+	
+	/// Reads visible children of a directory, keyed by name. Other entries have no children.
+	fn read_children(&mut self, file_system: &dyn FileSystem, filter: FsTreeViewFilter) -> BTreeMap<String, Self> {
+		if !self.is_directory() {
+			return BTreeMap::new();
+		}
+		
+		let children = FsTreeModel::get_directory_children(file_system, &mut self.node, self.parent_ignore_rules.clone()).unwrap_or_default();
+		let ignore_rules = self.node.ignore_rules();
+		
+		children.into_iter()
+			.filter(|child| child.entry.path().is_some() && filter.is_visible(child))
+			.map(|child| (child.entry.name().str().to_owned(), Self { node: child, parent_ignore_rules: ignore_rules.clone() }))
+			.collect()
+	}
+	
+	fn to_compare_entry(&self) -> Option<CompareEntry> {
+		let path = self.entry().path()?.to_path_buf();
+		Some(CompareEntry { path, is_directory: self.is_directory() })
+	}
+}
+
+struct Comparison {
+	file_system: Arc<dyn FileSystem>,
+	filter: FsTreeViewFilter,
+	sender: Sender<CompareMessage>,
+	compared_entries: usize,
+}
+
+impl Comparison {
+	/// Compares entries at the same relative path, and all their children. Returns `None` if the comparison was stopped.
+	fn compare(&mut self, name: String, relative_path: PathBuf, left: Option<Side>, right: Option<Side>) -> Option<CompareNode> {
+		self.compared_entries = self.compared_entries.saturating_add(1);
+		
+		if self.compared_entries % PROGRESS_INTERVAL == 0 && self.sender.send(CompareMessage::Progress(self.compared_entries)).is_err() {
+			return None;
+		}
+		
+		let mut left = left;
+		let mut right = right;
+		
+		let mut left_children = left.as_mut().map(|side| side.read_children(self.file_system.as_ref(), self.filter)).unwrap_or_default();
+		let mut right_children = right.as_mut().map(|side| side.read_children(self.file_system.as_ref(), self.filter)).unwrap_or_default();
+		
+		let mut names = left_children.keys().chain(right_children.keys()).cloned().collect::<Vec<_>>();
+		names.sort();
+		names.dedup();
+		
+		let mut children = Vec::with_capacity(names.len());
+		
+		for child_name in names {
+			let child_relative_path = relative_path.join(&child_name);
+			let child_left = left_children.remove(&child_name);
+			let child_right = right_children.remove(&child_name);
+			children.push(self.compare(child_name, child_relative_path, child_left, child_right)?);
+		}
+		
+		let difference = match (&left, &right) {
+			(Some(_), None) => Some(Difference::OnlyLeft),
+			(None, Some(_)) => Some(Difference::OnlyRight),
+			(Some(left), Some(right)) => compare_entries(self.file_system.as_ref(), left.entry(), right.entry(), &children),
+			(None, None) => None,
+		};
+		
+		Some(CompareNode {
+			name,
+			relative_path,
+			left: left.as_ref().and_then(Side::to_compare_entry),
+			right: right.as_ref().and_then(Side::to_compare_entry),
+			difference,
+			children,
+		})
+	}
+}
+
+fn compare_entries(file_system: &dyn FileSystem, left: &FileEntry, right: &FileEntry, children: &[CompareNode]) -> Option<Difference> {
+	if mem::discriminant(left.kind()) != mem::discriminant(right.kind()) {
+		return Some(Difference::Kind);
+	}
+	
+	#[allow(clippy::wildcard_enum_match_arm)]
+	match (left.kind(), left.path(), right.path()) {
+		(FileKind::Directory, _, _) => {
+			children.iter().any(|child| child.difference.is_some()).then_some(Difference::ContainsDifferences)
+		}
+		
+		(FileKind::File { size: left_size }, Some(left_path), Some(right_path)) => {
+			if !matches!(right.kind(), FileKind::File { size } if size == left_size) {
+				Some(Difference::Size)
+			} else if !have_same_contents(file_system, left_path, right_path).unwrap_or(false) {
+				Some(Difference::Content)
+			} else if left.modified_time() != right.modified_time() {
+				Some(Difference::ModifiedTime)
+			} else {
+				None
+			}
+		}
+		
+		(FileKind::Symlink, Some(left_path), Some(right_path)) => {
+			(file_system.read_link(left_path).ok() != file_system.read_link(right_path).ok()).then_some(Difference::Content)
+		}
+		
+		_ => None,
+	}
+}
+
+fn have_same_contents(file_system: &dyn FileSystem, left_path: &Path, right_path: &Path) -> io::Result<bool> {
+	let mut left = BufReader::new(file_system.open_file(left_path)?);
+	let mut right = BufReader::new(file_system.open_file(right_path)?);
+	let mut left_buffer = vec![0; 64 * 1024];
+	let mut right_buffer = vec![0; 64 * 1024];
+	
+	loop {
+		let left_read = read_full(&mut left, &mut left_buffer)?;
+		let right_read = read_full(&mut right, &mut right_buffer)?;
+		
+		if left_buffer.get(..left_read) != right_buffer.get(..right_read) {
+			return Ok(false);
+		}
+		
+		if left_read == 0 {
+			return Ok(true);
+		}
+	}
+}
+
+/// Reads until the buffer is full or the reader reaches its end, so that both files are compared in chunks of the same size.
+fn read_full(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+	let mut total = 0;
+	
+	while let Some(remaining) = buffer.get_mut(total..).filter(|remaining| !remaining.is_empty()) {
+		let read = reader.read(remaining)?;
+		
+		if read == 0 {
+			break;
+		}
+		
+		total = total.saturating_add(read);
+	}
+	
+	Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::Path;
+	use std::sync::Arc;
+	use std::thread;
+	use std::time::Duration;
+	
+	use crate::component::filesystem::compare::diff::{CompareMessage, CompareNode, Difference, start};
+	use crate::component::filesystem::tree::FsTreeViewFilter;
+	use crate::file::{FileSystem, MemoryFileSystem};
+	
+	/// Compares `/left` and `/right`, and returns the difference of every entry by its relative path, in pre-order.
+	fn compare(file_system: MemoryFileSystem, filter: FsTreeViewFilter) -> Vec<(String, Option<Difference>)> {
+		let receiver = start(Arc::new(file_system), Path::new("/left"), Path::new("/right"), filter);
+		let root = receiver.iter().find_map(|message| if let CompareMessage::Finished(root) = message { Some(root) } else { None });
+		
+		let mut differences = Vec::new();
+		
+		if let Some(root) = &root {
+			collect_differences(root, &mut differences);
+		}
+		
+		differences
+	}
+	
+	fn collect_differences(node: &CompareNode, differences: &mut Vec<(String, Option<Difference>)>) {
+		differences.push((node.relative_path.to_string_lossy().into_owned(), node.difference));
+		
+		for child in &node.children {
+			collect_differences(child, differences);
+		}
+	}
+	
+	fn copy_left_to_right(file_system: MemoryFileSystem) -> MemoryFileSystem {
+		assert!(file_system.copy(Path::new("/left"), Path::new("/right")).is_ok(), "Could not copy left directory");
+		file_system
+	}
+	
+	#[test]
+	fn finds_no_differences_between_copies() {
+		let file_system = copy_left_to_right(MemoryFileSystem::new()
+			.with_file("/left/a", "abc")
+			.with_file("/left/dir/b", "def")
+			.with_symlink("/left/link", "a"));
+		
+		assert_eq!(compare(file_system, FsTreeViewFilter::default()), vec![
+			(String::new(), None),
+			(String::from("a"), None),
+			(String::from("dir"), None),
+			(String::from("dir/b"), None),
+			(String::from("link"), None),
+		]);
+	}
+	
+	#[test]
+	fn finds_entries_on_one_side() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/left/only-left", "")
+			.with_directory("/right/only-right");
+		
+		assert_eq!(compare(file_system, FsTreeViewFilter::default()), vec![
+			(String::new(), Some(Difference::ContainsDifferences)),
+			(String::from("only-left"), Some(Difference::OnlyLeft)),
+			(String::from("only-right"), Some(Difference::OnlyRight)),
+		]);
+	}
+	
+	#[test]
+	fn compares_kinds_sizes_and_contents() {
+		let file_system = copy_left_to_right(MemoryFileSystem::new()
+			.with_file("/left/kind", "")
+			.with_file("/left/size", "abc")
+			.with_file("/left/content", "abc")
+			.with_symlink("/left/link", "a"))
+			.with_directory("/right/kind")
+			.with_file("/right/size", "abcd")
+			.with_file("/right/content", "abd")
+			.with_symlink("/right/link", "b");
+		
+		assert_eq!(compare(file_system, FsTreeViewFilter::default()), vec![
+			(String::new(), Some(Difference::ContainsDifferences)),
+			(String::from("content"), Some(Difference::Content)),
+			(String::from("kind"), Some(Difference::Kind)),
+			(String::from("link"), Some(Difference::Content)),
+			(String::from("size"), Some(Difference::Size)),
+		]);
+	}
+	
+	#[test]
+	fn finds_different_modification_times() {
+		let file_system = copy_left_to_right(MemoryFileSystem::new().with_file("/left/file", "abc"));
+		thread::sleep(Duration::from_millis(10));
+		let file_system = file_system.with_file("/right/file", "abc");
+		
+		assert_eq!(compare(file_system, FsTreeViewFilter::default()), vec![
+			(String::new(), Some(Difference::ContainsDifferences)),
+			(String::from("file"), Some(Difference::ModifiedTime)),
+		]);
+	}
+	
+	#[test]
+	fn marks_directories_that_contain_differences() {
+		let file_system = copy_left_to_right(MemoryFileSystem::new()
+			.with_file("/left/same/file", "abc")
+			.with_file("/left/outer/inner/file", "abc"))
+			.with_file("/right/outer/inner/file", "xyz");
+		
+		assert_eq!(compare(file_system, FsTreeViewFilter::default()), vec![
+			(String::new(), Some(Difference::ContainsDifferences)),
+			(String::from("outer"), Some(Difference::ContainsDifferences)),
+			(String::from("outer/inner"), Some(Difference::ContainsDifferences)),
+			(String::from("outer/inner/file"), Some(Difference::Content)),
+			(String::from("same"), None),
+			(String::from("same/file"), None),
+		]);
+	}
+	
+	#[test]
+	fn leaves_out_entries_hidden_by_the_filter() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/left/.hidden", "")
+			.with_directory("/right");
+		
+		assert_eq!(compare(file_system, FsTreeViewFilter { show_hidden: false, show_ignored: true }), vec![
+			(String::new(), None),
+		]);
+	}
+}
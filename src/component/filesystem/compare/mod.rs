@@ -0,0 +1,528 @@
+use std::cmp::min;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, Paragraph};
+
+use crate::component::dialog::message::MessageDialogLayer;
+use crate::component::filesystem::action::file::format_io_error;
+use crate::component::filesystem::compare::diff::{CompareMessage, CompareNode, Difference};
+use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::status::StatusMessage;
+use crate::component::filesystem::tree::FsTreeViewFilter;
+use crate::file::FileSystem;
+use crate::input::keymap::KeyBinding;
+use crate::state::action::ActionResult;
+use crate::state::Environment;
+use crate::state::event::{EventQueue, EventResult};
+use crate::state::layer::Layer;
+use crate::state::view::Frame;
+use crate::theme::theme;
+
+mod diff;
+
+const WHEEL_SCROLL_LINES: usize = 3;
+
+/// How many entries a copy confirmation lists before summarizing the rest.
+const MAX_LISTED_COPIES: usize = 10;
+
+/// Full screen merged tree of two directories, which marks entries that exist only in one of them, or differ between them. Entries can be copied from one directory to the other.
+pub struct CompareLayer {
+	left_root: PathBuf,
+	right_root: PathBuf,
+	filter: FsTreeViewFilter,
+	file_system: Arc<dyn FileSystem>,
+	fs_events: EventQueue<FsLayer>,
+	events: EventQueue<Self>,
+	comparison: Option<Receiver<CompareMessage>>,
+	compared_entries: usize,
+	root: Option<CompareNode>,
+	expanded: HashSet<PathBuf>,
+	differences_only: bool,
+	rows: Vec<Row>,
+	selected: usize,
+	scroll: usize,
+	page_height: usize,
+	list_area: Rect,
+}
+
+/// Visible node of the merged tree, identified by indices of the children leading to it from the root.
+struct Row {
+	indices: Vec<usize>,
+}
+
+impl Row {
+	fn level(&self) -> usize {
+		self.indices.len().saturating_sub(1)
+	}
+}
+
+/// Entry that is copied from one directory to the other. Entries that already exist in the target directory are replaced.
+struct Copy {
+	source: PathBuf,
+	target: PathBuf,
+	replace: bool,
+}
+
+impl CompareLayer {
+	pub fn new(left_root: &Path, right_root: &Path, filter: FsTreeViewFilter, file_system: Arc<dyn FileSystem>, fs_events: EventQueue<FsLayer>) -> Self {
+		Self {
+			left_root: left_root.to_path_buf(),
+			right_root: right_root.to_path_buf(),
+			filter,
+			comparison: Some(diff::start(Arc::clone(&file_system), left_root, right_root, filter)),
+			file_system,
+			fs_events,
+			events: EventQueue::new(),
+			compared_entries: 0,
+			root: None,
+			expanded: HashSet::new(),
+			differences_only: false,
+			rows: Vec::new(),
+			selected: 0,
+			scroll: 0,
+			page_height: 0,
+			list_area: Rect::default(),
+		}
+	}
+	
+	/// Compares both directories again. Expanded directories and the selected entry are kept.
+	fn restart_comparison(&mut self) {
+		self.comparison = Some(diff::start(Arc::clone(&self.file_system), &self.left_root, &self.right_root, self.filter));
+		self.compared_entries = 0;
+	}
+	
+	/// Receives progress of the comparison, and its result once it finishes. Returns `true` if anything changed.
+	fn receive_result(&mut self) -> bool {
+		let Some(receiver) = &self.comparison else {
+			return false;
+		};
+		
+		let mut changed = false;
+		
+		loop {
+			match receiver.try_recv() {
+				Ok(CompareMessage::Progress(compared_entries)) => {
+					self.compared_entries = compared_entries;
+					changed = true;
+				}
+				
+				Ok(CompareMessage::Finished(root)) => {
+					let selected_path = self.selected_node().map(|node| node.relative_path.clone());
+					self.comparison = None;
+					self.root = Some(root);
+					self.update_rows(selected_path.as_deref());
+					return true;
+				}
+				
+				Err(TryRecvError::Empty) => {
+					return changed;
+				}
+				
+				Err(TryRecvError::Disconnected) => {
+					self.comparison = None;
+					return true;
+				}
+			}
+		}
+	}
+	
+	fn get_node(&self, indices: &[usize]) -> Option<&CompareNode> {
+		indices.iter().try_fold(self.root.as_ref()?, |node, index| node.children.get(*index))
+	}
+	
+	fn selected_node(&self) -> Option<&CompareNode> {
+		self.rows.get(self.selected).and_then(|row| self.get_node(&row.indices))
+	}
+	
+	/// Collects visible rows from children of expanded directories, and selects the row with the given relative path if it is still visible.
+	fn update_rows(&mut self, selected_path: Option<&Path>) {
+		let mut rows = Vec::new();
+		
+		if let Some(root) = &self.root {
+			self.collect_rows(root, &mut Vec::new(), &mut rows);
+		}
+		
+		self.rows = rows;
+		
+		let selected_index = selected_path.and_then(|path| self.rows.iter().position(|row| self.get_node(&row.indices).is_some_and(|node| node.relative_path == path)));
+		self.selected = selected_index.unwrap_or_else(|| min(self.selected, self.rows.len().saturating_sub(1)));
+	}
+	
+	fn collect_rows(&self, node: &CompareNode, indices: &mut Vec<usize>, rows: &mut Vec<Row>) {
+		for (index, child) in node.children.iter().enumerate() {
+			if self.differences_only && child.difference.is_none() {
+				continue;
+			}
+			
+			indices.push(index);
+			rows.push(Row { indices: indices.clone() });
+			
+			if self.expanded.contains(&child.relative_path) {
+				self.collect_rows(child, indices, rows);
+			}
+			
+			indices.pop();
+		}
+	}
+	
+	fn set_expanded(&mut self, expanded: bool) -> ActionResult {
+		let Some(node) = self.selected_node().filter(|node| node.is_directory()) else {
+			return ActionResult::Nothing;
+		};
+		
+		let path = node.relative_path.clone();
+		let changed = if expanded { self.expanded.insert(path.clone()) } else { self.expanded.remove(&path) };
+		
+		self.update_rows(Some(&path));
+		ActionResult::draw_if(changed)
+	}
+	
+	fn toggle_expanded(&mut self) -> ActionResult {
+		let is_expanded = self.selected_node().is_some_and(|node| self.expanded.contains(&node.relative_path));
+		self.set_expanded(!is_expanded)
+	}
+	
+	fn collapse_or_move_to_parent(&mut self) -> ActionResult {
+		if self.selected_node().is_some_and(|node| self.expanded.contains(&node.relative_path)) {
+			return self.set_expanded(false);
+		}
+		
+		let Some(parent_indices) = self.rows.get(self.selected).and_then(|row| row.indices.split_last()).map(|(_, parent_indices)| parent_indices) else {
+			return ActionResult::Nothing;
+		};
+		
+		if let Some(parent_row) = self.rows.iter().position(|row| row.indices == parent_indices) {
+			self.move_selection_to(parent_row)
+		} else {
+			ActionResult::Nothing
+		}
+	}
+	
+	fn toggle_differences_only(&mut self) -> ActionResult {
+		let selected_path = self.selected_node().map(|node| node.relative_path.clone());
+		self.differences_only = !self.differences_only;
+		self.update_rows(selected_path.as_deref());
+		ActionResult::Draw
+	}
+	
+	fn move_selection_to(&mut self, index: usize) -> ActionResult {
+		let index = min(index, self.rows.len().saturating_sub(1));
+		let changed = self.selected != index;
+		self.selected = index;
+		ActionResult::draw_if(changed)
+	}
+	
+	fn move_selection_down(&mut self, lines: usize) -> ActionResult {
+		self.move_selection_to(self.selected.saturating_add(lines))
+	}
+	
+	fn move_selection_up(&mut self, lines: usize) -> ActionResult {
+		self.move_selection_to(self.selected.saturating_sub(lines))
+	}
+	
+	/// Asks to copy the selected entry to the other directory. Directories that exist on both sides are merged, so only their missing and differing entries are copied.
+	fn copy_selected(&self, from_left: bool) -> ActionResult {
+		let Some(node) = self.selected_node() else {
+			return ActionResult::Nothing;
+		};
+		
+		let target_root = if from_left { &self.right_root } else { &self.left_root };
+		let mut copies = Vec::new();
+		collect_copies(node, from_left, target_root, &mut copies);
+		
+		if copies.is_empty() {
+			self.fs_events.enqueue_fn(|layer, _| {
+				layer.show_message(StatusMessage::info("Nothing to copy"));
+				EventResult::Draw
+			});
+			return ActionResult::Nothing;
+		}
+		
+		let y = self.list_area.y;
+		let file_system = Arc::clone(&self.file_system);
+		let events = self.events.rc_clone();
+		let fs_events = self.fs_events.rc_clone();
+		let (source_name, target_name) = if from_left { ("A", "B") } else { ("B", "A") };
+		
+		let mut message = vec![Line::from(format!("Copy from {source_name} to {target_name}:"))];
+		
+		for copy in copies.iter().take(MAX_LISTED_COPIES) {
+			let suffix = if copy.replace { " (replace)" } else { "" };
+			message.push(Line::from(format!("  {}{suffix}", copy.target.to_string_lossy())));
+		}
+		
+		if copies.len() > MAX_LISTED_COPIES {
+			message.push(Line::from(format!("  ...and {} more", copies.len().saturating_sub(MAX_LISTED_COPIES))));
+		}
+		
+		ActionResult::push_layer(MessageDialogLayer::build()
+			.y(y)
+			.style(theme().dialog_warning)
+			.title(format!("Copy {source_name} → {target_name}"))
+			.message(message)
+			.yes_no(move || {
+				let result = perform_copies(file_system.as_ref(), &copies);
+				
+				let directories = copies.iter().filter_map(|copy| copy.target.parent()).map(Path::to_path_buf).collect::<HashSet<_>>();
+				
+				fs_events.enqueue_fn(move |layer, _| {
+					for directory in &directories {
+						layer.tree.refresh_model_directory(directory);
+					}
+					
+					EventResult::Draw
+				});
+				
+				events.enqueue_fn(|layer: &mut Self, _| {
+					layer.restart_comparison();
+					EventResult::Draw
+				});
+				
+				match result {
+					Ok(count) => {
+						fs_events.enqueue_fn(move |layer, _| {
+							layer.show_message(StatusMessage::info(format!("Copied {count} entries from {source_name} to {target_name}")));
+							EventResult::Draw
+						});
+						ActionResult::PopLayer
+					}
+					Err((path, e)) => {
+						ActionResult::replace_layer(MessageDialogLayer::error(y.saturating_add(1), format!("{}: {}", path.to_string_lossy(), format_io_error(&e))))
+					}
+				}
+			}))
+	}
+	
+	fn render_title(&self, frame: &mut Frame, area: Rect) {
+		let text = format!(" compare A: {}  B: {}", self.left_root.to_string_lossy(), self.right_root.to_string_lossy());
+		frame.render_widget(Paragraph::new(text).style(theme().status_bar.add_modifier(Modifier::BOLD)), area);
+	}
+	
+	fn render_rows(&self, frame: &mut Frame, area: Rect) {
+		let lines = self.rows.iter()
+			.skip(self.scroll)
+			.take(usize::from(area.height))
+			.map(|row| self.get_node(&row.indices).map(|node| self.get_row_line(node, row.level(), area.width)).unwrap_or_default())
+			.collect::<Vec<_>>();
+		
+		frame.render_widget(Paragraph::new(lines), area);
+		
+		if let Some(selected_y) = self.selected.checked_sub(self.scroll).and_then(|offset| u16::try_from(offset).ok()).filter(|offset| *offset < area.height && !self.rows.is_empty()) {
+			frame.render_widget(Block::default().style(theme().selected), Rect { y: area.y.saturating_add(selected_y), height: 1, ..area });
+		}
+	}
+	
+	fn get_row_line<'a>(&self, node: &'a CompareNode, level: usize, width: u16) -> Line<'a> {
+		let style = get_difference_style(node.difference);
+		
+		let marker = match node.difference {
+			Some(Difference::OnlyLeft) => "-",
+			Some(Difference::OnlyRight) => "+",
+			Some(_) => "~",
+			None => " ",
+		};
+		
+		let expander = if !node.is_directory() {
+			"  "
+		} else if self.expanded.contains(&node.relative_path) {
+			"▾ "
+		} else {
+			"▸ "
+		};
+		
+		let description = node.difference.map(Difference::describe).unwrap_or_default();
+		let name = if node.is_directory() { format!("{}/", node.name) } else { node.name.clone() };
+		
+		let left = vec![
+			Span::styled(format!(" {marker} "), style),
+			Span::raw("  ".repeat(level)),
+			Span::styled(expander, theme().dimmed),
+			Span::styled(name, style),
+		];
+		
+		let left_width = left.iter().map(Span::width).sum::<usize>();
+		let padding = usize::from(width).saturating_sub(left_width).saturating_sub(description.len()).saturating_sub(1);
+		
+		let mut spans = left;
+		spans.push(Span::raw(" ".repeat(padding)));
+		spans.push(Span::styled(description, style));
+		Line::from(spans)
+	}
+	
+	fn render_footer(&self, frame: &mut Frame, area: Rect) {
+		let text = if self.comparison.is_some() {
+			format!(" Comparing... {} entries", self.compared_entries)
+		} else {
+			let mut counts = (0, 0, 0);
+			count_differences(self.root.as_ref(), &mut counts);
+			
+			let (only_left, only_right, changed) = counts;
+			let filter = if self.differences_only { "all" } else { "differences only" };
+			format!(" {only_left} only in A, {only_right} only in B, {changed} differ  >: copy to B  <: copy to A  f: {filter}  r: compare again  q: close")
+		};
+		
+		frame.render_widget(Paragraph::new(text).style(theme().status_bar), area);
+	}
+}
+
+fn get_difference_style(difference: Option<Difference>) -> Style {
+	let theme = theme();
+	
+	match difference {
+		Some(Difference::OnlyLeft) => theme.diff_only_left,
+		Some(Difference::OnlyRight) => theme.diff_only_right,
+		Some(Difference::ContainsDifferences) => theme.secondary,
+		Some(_) => theme.diff_changed,
+		None => Style::default(),
+	}
+}
+
+/// Counts entries only in the left directory, only in the right directory, and entries that differ. Contents of directories that exist only on one side are not counted separately.
+fn count_differences(node: Option<&CompareNode>, counts: &mut (usize, usize, usize)) {
+	let Some(node) = node else {
+		return;
+	};
+	
+	for child in &node.children {
+		match child.difference {
+			Some(Difference::OnlyLeft) => counts.0 = counts.0.saturating_add(1),
+			Some(Difference::OnlyRight) => counts.1 = counts.1.saturating_add(1),
+			Some(Difference::ContainsDifferences) => count_differences(Some(child), counts),
+			Some(_) => counts.2 = counts.2.saturating_add(1),
+			None => {}
+		}
+	}
+}
+
+fn collect_copies(node: &CompareNode, from_left: bool, target_root: &Path, copies: &mut Vec<Copy>) {
+	let (source, target) = if from_left { (&node.left, &node.right) } else { (&node.right, &node.left) };
+	
+	let Some(source) = source else {
+		return;
+	};
+	
+	match target {
+		None => {
+			copies.push(Copy { source: source.path.clone(), target: target_root.join(&node.relative_path), replace: false });
+		}
+		
+		Some(target) if source.is_directory && target.is_directory => {
+			for child in &node.children {
+				collect_copies(child, from_left, target_root, copies);
+			}
+		}
+		
+		Some(target) => {
+			if node.difference.is_some() {
+				copies.push(Copy { source: source.path.clone(), target: target.path.clone(), replace: true });
+			}
+		}
+	}
+}
+
+/// Performs all copies, and returns how many were done, or the first error with the path where it happened.
+fn perform_copies(file_system: &dyn FileSystem, copies: &[Copy]) -> Result<usize, (PathBuf, std::io::Error)> {
+	for copy in copies {
+		if copy.replace {
+			file_system.remove(&copy.target).map_err(|e| (copy.target.clone(), e))?;
+		}
+		
+		file_system.copy(&copy.source, &copy.target).map_err(|e| (copy.target.clone(), e))?;
+	}
+	
+	Ok(copies.len())
+}
+
+impl Layer for CompareLayer {
+	#[allow(clippy::wildcard_enum_match_arm)]
+	fn handle_input(&mut self, _environment: &Environment, key_binding: KeyBinding) -> ActionResult {
+		match (key_binding.code(), key_binding.modifiers()) {
+			(KeyCode::Esc | KeyCode::Char('q'), KeyModifiers::NONE) |
+			(KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+				ActionResult::PopLayer
+			}
+			
+			(KeyCode::Enter, KeyModifiers::NONE) => self.toggle_expanded(),
+			(KeyCode::Char('l') | KeyCode::Right, KeyModifiers::NONE) => self.set_expanded(true),
+			(KeyCode::Char('h') | KeyCode::Left, KeyModifiers::NONE) => self.collapse_or_move_to_parent(),
+			(KeyCode::Char('f'), KeyModifiers::NONE) => self.toggle_differences_only(),
+			(KeyCode::Char('>'), _) => self.copy_selected(true),
+			(KeyCode::Char('<'), _) => self.copy_selected(false),
+			
+			(KeyCode::Char('r'), KeyModifiers::NONE) => {
+				self.restart_comparison();
+				ActionResult::Draw
+			}
+			
+			(KeyCode::Char('j') | KeyCode::Down, KeyModifiers::NONE) => self.move_selection_down(1),
+			(KeyCode::Char('k') | KeyCode::Up, KeyModifiers::NONE) => self.move_selection_up(1),
+			(KeyCode::Char('d'), KeyModifiers::CONTROL) => self.move_selection_down(self.page_height / 2),
+			(KeyCode::Char('u'), KeyModifiers::CONTROL) => self.move_selection_up(self.page_height / 2),
+			(KeyCode::Char('f'), KeyModifiers::CONTROL) | (KeyCode::PageDown, KeyModifiers::NONE) => self.move_selection_down(self.page_height),
+			(KeyCode::Char('b'), KeyModifiers::CONTROL) | (KeyCode::PageUp, KeyModifiers::NONE) => self.move_selection_up(self.page_height),
+			(KeyCode::Char('g') | KeyCode::Home, KeyModifiers::NONE) => self.move_selection_to(0),
+			(KeyCode::Char('G') | KeyCode::End, KeyModifiers::NONE) => self.move_selection_to(usize::MAX),
+			
+			_ => ActionResult::Nothing
+		}
+	}
+	
+	#[allow(clippy::wildcard_enum_match_arm)]
+	fn handle_mouse(&mut self, _environment: &Environment, event: MouseEvent) -> ActionResult {
+		match event.kind {
+			MouseEventKind::ScrollDown => self.move_selection_down(WHEEL_SCROLL_LINES),
+			MouseEventKind::ScrollUp => self.move_selection_up(WHEEL_SCROLL_LINES),
+			
+			MouseEventKind::Down(MouseButton::Left) => {
+				let area = self.list_area;
+				let index = self.scroll.saturating_add(usize::from(event.row.saturating_sub(area.y)));
+				
+				if event.row >= area.y && event.row < area.bottom() && index < self.rows.len() {
+					self.move_selection_to(index)
+				} else {
+					ActionResult::Nothing
+				}
+			}
+			
+			_ => ActionResult::Nothing
+		}
+	}
+	
+	fn handle_events(&mut self, environment: &Environment) -> EventResult {
+		let result = self.events.take().into_iter().fold(EventResult::Nothing, |result, event| result.merge(event.dispatch(self, environment)));
+		result.merge(EventResult::draw_if(self.receive_result()))
+	}
+	
+	fn render(&mut self, frame: &mut Frame) {
+		let size = frame.size();
+		if size.height < 3 {
+			return;
+		}
+		
+		let title_area = Rect { height: 1, ..size };
+		let list_area = Rect { y: size.y.saturating_add(1), height: size.height.saturating_sub(2), ..size };
+		let footer_area = Rect { y: size.bottom().saturating_sub(1), height: 1, ..size };
+		
+		self.list_area = list_area;
+		self.page_height = usize::from(list_area.height);
+		
+		// Keep the selected entry visible.
+		if self.selected < self.scroll {
+			self.scroll = self.selected;
+		} else if self.selected >= self.scroll.saturating_add(self.page_height) {
+			self.scroll = self.selected.saturating_add(1).saturating_sub(self.page_height);
+		}
+		
+		frame.render_widget(Clear, size);
+		self.render_title(frame, title_area);
+		self.render_rows(frame, list_area);
+		self.render_footer(frame, footer_area);
+	}
+}
@@ -1,98 +1,360 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use lazy_static::lazy_static;
+use ratatui::text::Line;
 
+use crate::component::dialog::message::MessageDialogLayer;
 use crate::component::filesystem::{ActionKeyMap, FsLayer};
-use crate::component::filesystem::action::application::{EnterCommandMode, Quit, RedrawScreen};
+use crate::component::filesystem::action::application::{CompareMarkedWithSelected, EnterCommandMode, OpenFinder, Quit, RedrawScreen, ShowHelp};
 use crate::component::filesystem::action::count::PushCountDigit;
-use crate::component::filesystem::action::file::{CreateDirectoryInParentOfSelectedEntry, CreateDirectoryInSelectedDirectory, CreateFileInParentOfSelectedEntry, CreateFileInSelectedDirectory, DeleteSelectedEntry, EditSelectedEntry, RenameSelectedEntry};
+use crate::component::filesystem::action::display::{CycleSizeFormat, ToggleAllocatedSize, ToggleDiskUsage};
+use crate::component::filesystem::action::file::{BulkRenameInEditor, ChangeGroupOfSelectedEntry, ChangeOwnerOfSelectedEntry, CompressSelectedEntries, CopySelectedEntries, CreateDirectoryInParentOfSelectedEntry, CreateDirectoryInSelectedDirectory, CreateFileInParentOfSelectedEntry, CreateFileInSelectedDirectory, CutSelectedEntries, DeleteSelectedEntry, EditSelectedEntry, ExtractSelectedArchiveEntry, PasteIntoSelectedDirectory, RenameSelectedEntry, ScanSelectedEntries};
 use crate::component::filesystem::action::movement::{CollapseSelectedOr, ExpandSelectedOr, MoveBetweenFirstAndLastSibling, MoveDown, MovementWithCountFactory, MovementWithFallbackFactory, MoveOrTraverseUpParent, MoveToFirst, MoveToLast, MoveToLineOr, MoveToNextSibling, MoveToParent, MoveToPreviousSibling, MoveUp, ScreenHeightRatio};
-use crate::component::filesystem::action::tree::{ExpandCollapse, RefreshChildrenOfSelected};
-use crate::input::keymap::KeyMapInsertError;
-use crate::state::action::Action;
+use crate::component::filesystem::action::tree::{ClearMarks, ExpandCollapse, RefreshChildrenOfSelected, ToggleFlatListing, ToggleHiddenEntries, ToggleIgnoredEntries, ToggleMarkAndMoveDown};
+use crate::component::help::HelpEntry;
+use crate::config;
+use crate::input::keymap::{KeyMapInsertError, KeyMapInsertErrorType};
+use crate::state::action::{Action, ActionResult};
+use crate::state::Environment;
+use crate::theme::theme;
 
 lazy_static! {
-	pub static ref ACTION_MAP: Result<ActionKeyMap, KeyMapInsertError> = create_action_map();
+	static ref ACTIONS: Vec<Arc<NamedAction>> = create_actions();
+	pub static ref ACTION_MAP: Result<(ActionKeyMap, Vec<String>), KeyMapInsertError> = create_action_map();
 }
 
 pub fn get_action_map() -> Result<&'static ActionKeyMap, &'static KeyMapInsertError> {
-	return ACTION_MAP.as_ref();
+	ACTION_MAP.as_ref().map(|(map, _)| map)
+}
+
+/// Returns errors in lines of the `keys` configuration file, which were skipped so that the rest of the file still applies.
+pub fn get_key_config_errors() -> &'static [String] {
+	ACTION_MAP.as_ref().map(|(_, errors)| errors.as_slice()).unwrap_or_default()
+}
+
+const MAX_REPORTED_KEY_CONFIG_ERRORS: usize = 10;
+
+pub fn create_key_config_errors_dialog<'a>(y: u16, errors: &[String]) -> MessageDialogLayer<'a> {
+	let mut lines = vec![Line::from("Some lines in the keys configuration file were skipped:")];
+	lines.extend(errors.iter().take(MAX_REPORTED_KEY_CONFIG_ERRORS).map(|error| Line::from(error.clone())));
+	
+	if let Some(remaining) = errors.len().checked_sub(MAX_REPORTED_KEY_CONFIG_ERRORS).filter(|remaining| *remaining > 0) {
+		lines.push(Line::from(format!("...and {remaining} more.")));
+	}
+	
+	MessageDialogLayer::build()
+		.y(y)
+		.style(theme().dialog_error)
+		.title("Invalid Key Bindings")
+		.message(lines)
+		.ok()
+}
+
+/// An action with a name that identifies it in the `keys` configuration file, and a description for the help screen.
+pub struct NamedAction {
+	name: &'static str,
+	description: &'static str,
+	action: Box<dyn Action<FsLayer> + Send + Sync>,
 }
 
-fn create_action_map() -> Result<ActionKeyMap, KeyMapInsertError> {
+impl NamedAction {
+	fn new(name: &'static str, description: &'static str, action: impl Action<FsLayer> + Send + Sync + 'static) -> Arc<Self> {
+		Arc::new(Self { name, description, action: Box::new(action) })
+	}
+	
+	pub const fn description(&self) -> &'static str {
+		self.description
+	}
+}
+
+impl Action<FsLayer> for NamedAction {
+	fn perform(&self, layer: &mut FsLayer, environment: &Environment) -> ActionResult {
+		self.action.perform(layer, environment)
+	}
+}
+
+#[allow(clippy::too_many_lines)]
+fn create_actions() -> Vec<Arc<NamedAction>> {
+	vec![
+		NamedAction::new("move-down", "Move to the next entry", MoveDown),
+		NamedAction::new("move-up", "Move to the previous entry", MoveUp),
+		NamedAction::new("move-to-next-sibling", "Move to the next entry in the same directory", MoveToNextSibling.with_fallback(MoveDown)),
+		NamedAction::new("move-to-previous-sibling", "Move to the previous entry in the same directory", MoveToPreviousSibling.with_fallback(MoveUp)),
+		NamedAction::new("move-between-first-and-last-sibling", "Move between the first and last entry in the same directory", MoveBetweenFirstAndLastSibling),
+		NamedAction::new("move-to-first", "Move to the first entry, or to the line given by the count", MoveToLineOr(MoveToFirst)),
+		NamedAction::new("move-to-last", "Move to the last entry, or to the line given by the count", MoveToLineOr(MoveToLast)),
+		NamedAction::new("move-to-parent", "Move to the parent directory", MoveOrTraverseUpParent),
+		NamedAction::new("collapse-or-move-to-parent", "Collapse the selected directory, or move to the parent directory", CollapseSelectedOr(MoveToParent)),
+		NamedAction::new("expand-or-move-down", "Expand the selected directory, or move to the next entry", ExpandSelectedOr(MoveDown)),
+		NamedAction::new("half-page-down", "Move down by half a screen, or by the count", MoveDown.with_default_count(ScreenHeightRatio(2))),
+		NamedAction::new("half-page-up", "Move up by half a screen, or by the count", MoveUp.with_default_count(ScreenHeightRatio(2))),
+		NamedAction::new("page-down", "Move down by a screen", MoveDown.with_custom_count(ScreenHeightRatio(1))),
+		NamedAction::new("page-up", "Move up by a screen", MoveUp.with_custom_count(ScreenHeightRatio(1))),
+		
+		NamedAction::new("expand-collapse", "Expand or collapse the selected directory", ExpandCollapse { default_depth: 1 }),
+		NamedAction::new("expand-collapse-recursively", "Expand or collapse the selected directory and its subdirectories", ExpandCollapse { default_depth: 1000 }),
+		NamedAction::new("toggle-mark", "Mark or unmark the selected entry, and move to the next entry", ToggleMarkAndMoveDown),
+		NamedAction::new("clear-marks", "Unmark all entries", ClearMarks),
+		NamedAction::new("refresh", "Reload the contents of the selected directory", RefreshChildrenOfSelected),
+		NamedAction::new("find", "Find an entry in any subdirectory by typing parts of its path", OpenFinder),
+		NamedAction::new("compare-directories", "Compare the marked directory with the selected directory", CompareMarkedWithSelected),
+		
+		NamedAction::new("create-file", "Create a file in the selected directory", CreateFileInSelectedDirectory),
+		NamedAction::new("create-directory", "Create a directory in the selected directory", CreateDirectoryInSelectedDirectory),
+		NamedAction::new("create-file-in-parent", "Create a file next to the selected entry", CreateFileInParentOfSelectedEntry),
+		NamedAction::new("create-directory-in-parent", "Create a directory next to the selected entry", CreateDirectoryInParentOfSelectedEntry),
+		NamedAction::new("rename", "Rename the selected entry", RenameSelectedEntry { prefill: true }),
+		NamedAction::new("rename-from-scratch", "Rename the selected entry, starting with an empty name", RenameSelectedEntry { prefill: false }),
+		NamedAction::new("bulk-rename", "Rename marked entries, or entries in the selected directory, in the default editor", BulkRenameInEditor),
+		NamedAction::new("edit", "Open the selected entry in the default editor, or a temporary copy of a file inside an archive", EditSelectedEntry),
+		NamedAction::new("extract", "Extract the selected archive, or the selected entry inside an archive, into a directory in the background", ExtractSelectedArchiveEntry),
+		NamedAction::new("compress", "Compress the marked entries, or the selected entry, into a .zip, .tar.gz, .tar.xz, or .tar.zst archive in the background", CompressSelectedEntries),
+		NamedAction::new("copy", "Copy the marked entries, or the selected entry, into the clipboard", CopySelectedEntries),
+		NamedAction::new("cut", "Cut the marked entries, or the selected entry, into the clipboard", CutSelectedEntries),
+		NamedAction::new("paste", "Copy or move entries from the clipboard into the selected directory in the background", PasteIntoSelectedDirectory),
+		NamedAction::new("delete", "Delete the selected entry in the background", DeleteSelectedEntry),
+		NamedAction::new("scan", "Count the marked entries, or the selected entry, with everything inside them, and show their total size", ScanSelectedEntries),
+		NamedAction::new("change-owner", "Change the owner of the selected entry", ChangeOwnerOfSelectedEntry { recursive: false }),
+		NamedAction::new("change-owner-recursively", "Change the owner of the selected entry and its contents", ChangeOwnerOfSelectedEntry { recursive: true }),
+		NamedAction::new("change-group", "Change the group of the selected entry", ChangeGroupOfSelectedEntry { recursive: false }),
+		NamedAction::new("change-group-recursively", "Change the group of the selected entry and its contents", ChangeGroupOfSelectedEntry { recursive: true }),
+		
+		NamedAction::new("toggle-hidden", "Show or hide dotfiles", ToggleHiddenEntries),
+		NamedAction::new("toggle-ignored", "Show or hide entries matched by ignore files", ToggleIgnoredEntries),
+		NamedAction::new("toggle-flat-listing", "List all files under the selected directory without nesting, or return to the tree", ToggleFlatListing),
+		NamedAction::new("cycle-size-format", "Switch between decimal units, binary units, and exact sizes", CycleSizeFormat),
+		NamedAction::new("toggle-allocated-size", "Switch between apparent sizes and disk usage", ToggleAllocatedSize),
+		NamedAction::new("toggle-disk-usage", "Sort entries by total size of directories and show what part of the parent directory they take, or return to the previous sort order", ToggleDiskUsage),
+		
+		NamedAction::new("command-mode", "Enter a command", EnterCommandMode),
+		NamedAction::new("help", "Show key bindings", ShowHelp),
+		NamedAction::new("redraw", "Redraw the screen", RedrawScreen),
+		NamedAction::new("quit", "Quit", Quit),
+		
+		NamedAction::new("count-0", "Append 0 to the count", PushCountDigit(0)),
+		NamedAction::new("count-1", "Append 1 to the count", PushCountDigit(1)),
+		NamedAction::new("count-2", "Append 2 to the count", PushCountDigit(2)),
+		NamedAction::new("count-3", "Append 3 to the count", PushCountDigit(3)),
+		NamedAction::new("count-4", "Append 4 to the count", PushCountDigit(4)),
+		NamedAction::new("count-5", "Append 5 to the count", PushCountDigit(5)),
+		NamedAction::new("count-6", "Append 6 to the count", PushCountDigit(6)),
+		NamedAction::new("count-7", "Append 7 to the count", PushCountDigit(7)),
+		NamedAction::new("count-8", "Append 8 to the count", PushCountDigit(8)),
+		NamedAction::new("count-9", "Append 9 to the count", PushCountDigit(9)),
+	]
+}
+
+/// Creates the key map with default key bindings, and applies custom key bindings on top of them. Errors in default key bindings are returned, while errors in custom key bindings are collected so that they can be reported after startup.
+fn create_action_map() -> Result<(ActionKeyMap, Vec<String>), KeyMapInsertError> {
+	let actions = ACTIONS.iter().map(|action| (action.name, action)).collect::<HashMap<_, _>>();
 	let mut me = ActionKeyMap::new();
+	let mut map = |key_binding_str: &str, action_name: &str| bind(&mut me, &actions, key_binding_str, action_name);
+	
+	map("0", "count-0")?;
+	map("1", "count-1")?;
+	map("2", "count-2")?;
+	map("3", "count-3")?;
+	map("4", "count-4")?;
+	map("5", "count-5")?;
+	map("6", "count-6")?;
+	map("7", "count-7")?;
+	map("8", "count-8")?;
+	map("9", "count-9")?;
+	
+	map("af", "create-file")?;
+	map("ad", "create-directory")?;
+	map("cg", "change-group")?;
+	map("cG", "change-group-recursively")?;
+	map("co", "change-owner")?;
+	map("cO", "change-owner-recursively")?;
+	map("C", "compress")?;
+	map("e", "edit")?;
+	map("x", "extract")?;
+	map("d", "delete")?;
+	map("gg", "move-to-first")?;
+	map("G", "move-to-last")?;
+	map("h", "collapse-or-move-to-parent")?;
+	map("H", "move-to-parent")?;
+	map("if", "create-file")?;
+	map("id", "create-directory")?;
+	map("j", "move-down")?;
+	map("J", "move-to-next-sibling")?;
+	map("k", "move-up")?;
+	map("K", "move-to-previous-sibling")?;
+	map("l", "expand-or-move-down")?;
+	map("m", "toggle-mark")?;
+	map("M", "clear-marks")?;
+	map("of", "create-file-in-parent")?;
+	map("p", "paste")?;
+	map("od", "create-directory-in-parent")?;
+	map("q", "quit")?;
+	map("r", "rename")?;
+	map("R", "rename-from-scratch")?;
+	map("S", "scan")?;
+	map("X", "cut")?;
+	map("y", "copy")?;
+	map("zf", "toggle-flat-listing")?;
+	map("zh", "toggle-hidden")?;
+	map("zi", "toggle-ignored")?;
+	map("zs", "cycle-size-format")?;
+	map("zu", "toggle-allocated-size")?;
+	map("zd", "toggle-disk-usage")?;
+	map("<Ctrl-R>", "bulk-rename")?;
+	map("<Ctrl-T>", "find")?;
+	
+	map("%", "move-between-first-and-last-sibling")?;
+	map(":", "command-mode")?;
+	map("?", "help")?;
+	map("=", "compare-directories")?;
+	map("/", "find")?;
+	
+	map("<Ctrl-B>", "page-up")?;
+	map("<Ctrl-C>", "quit")?;
+	map("<Ctrl-D>", "half-page-down")?;
+	map("<Ctrl-F>", "page-down")?;
+	map("<Ctrl-L>", "redraw")?;
+	map("<Ctrl-N>", "move-down")?;
+	map("<Ctrl-P>", "move-up")?;
+	map("<Ctrl-U>", "half-page-up")?;
+	
+	map("<Space>", "expand-collapse")?;
+	map("<Ctrl-Space>", "expand-collapse-recursively")?;
+	
+	map("<Down>", "move-down")?;
+	map("<Shift-Down>", "page-down")?;
+	map("<Alt-Down>", "move-to-next-sibling")?;
 	
-	map(&mut me, "0", PushCountDigit(0))?;
-	map(&mut me, "1", PushCountDigit(1))?;
-	map(&mut me, "2", PushCountDigit(2))?;
-	map(&mut me, "3", PushCountDigit(3))?;
-	map(&mut me, "4", PushCountDigit(4))?;
-	map(&mut me, "5", PushCountDigit(5))?;
-	map(&mut me, "6", PushCountDigit(6))?;
-	map(&mut me, "7", PushCountDigit(7))?;
-	map(&mut me, "8", PushCountDigit(8))?;
-	map(&mut me, "9", PushCountDigit(9))?;
-	
-	map(&mut me, "af", CreateFileInSelectedDirectory)?;
-	map(&mut me, "ad", CreateDirectoryInSelectedDirectory)?;
-	map(&mut me, "e", EditSelectedEntry)?;
-	map(&mut me, "d", DeleteSelectedEntry)?;
-	map(&mut me, "gg", MoveToLineOr(MoveToFirst))?;
-	map(&mut me, "G", MoveToLineOr(MoveToLast))?;
-	map(&mut me, "h", CollapseSelectedOr(MoveToParent))?;
-	map(&mut me, "H", MoveOrTraverseUpParent)?;
-	map(&mut me, "if", CreateFileInSelectedDirectory)?;
-	map(&mut me, "id", CreateDirectoryInSelectedDirectory)?;
-	map(&mut me, "j", MoveDown)?;
-	map(&mut me, "J", MoveToNextSibling.with_fallback(MoveDown))?;
-	map(&mut me, "k", MoveUp)?;
-	map(&mut me, "K", MoveToPreviousSibling.with_fallback(MoveUp))?;
-	map(&mut me, "l", ExpandSelectedOr(MoveDown))?;
-	map(&mut me, "of", CreateFileInParentOfSelectedEntry)?;
-	map(&mut me, "od", CreateDirectoryInParentOfSelectedEntry)?;
-	map(&mut me, "q", Quit)?;
-	map(&mut me, "r", RenameSelectedEntry { prefill: true })?;
-	map(&mut me, "R", RenameSelectedEntry { prefill: false })?;
-	
-	map(&mut me, "%", MoveBetweenFirstAndLastSibling)?;
-	map(&mut me, ":", EnterCommandMode)?;
-	
-	map(&mut me, "<Ctrl-B>", MoveUp.with_custom_count(ScreenHeightRatio(1)))?;
-	map(&mut me, "<Ctrl-C>", Quit)?;
-	map(&mut me, "<Ctrl-D>", MoveDown.with_default_count(ScreenHeightRatio(2)))?;
-	map(&mut me, "<Ctrl-F>", MoveDown.with_custom_count(ScreenHeightRatio(1)))?;
-	map(&mut me, "<Ctrl-L>", RedrawScreen)?;
-	map(&mut me, "<Ctrl-N>", MoveDown)?;
-	map(&mut me, "<Ctrl-P>", MoveUp)?;
-	map(&mut me, "<Ctrl-U>", MoveUp.with_default_count(ScreenHeightRatio(2)))?;
-	
-	map(&mut me, "<Space>", ExpandCollapse { default_depth: 1 })?;
-	map(&mut me, "<Ctrl-Space>", ExpandCollapse { default_depth: 1000 })?;
-	
-	map(&mut me, "<Down>", MoveDown)?;
-	map(&mut me, "<Shift-Down>", MoveDown.with_custom_count(ScreenHeightRatio(1)))?;
-	map(&mut me, "<Alt-Down>", MoveToNextSibling.with_fallback(MoveDown))?;
-	
-	map(&mut me, "<Up>", MoveUp)?;
-	map(&mut me, "<Shift-Up>", MoveUp.with_custom_count(ScreenHeightRatio(1)))?;
-	map(&mut me, "<Alt-Up>", MoveToPreviousSibling.with_fallback(MoveUp))?;
-	
-	map(&mut me, "<Left>", CollapseSelectedOr(MoveToParent))?;
-	map(&mut me, "<Alt-Left>", MoveOrTraverseUpParent)?;
-	
-	map(&mut me, "<Right>", ExpandSelectedOr(MoveDown))?;
-	
-	map(&mut me, "<Del>", DeleteSelectedEntry)?;
-	
-	map(&mut me, "<PageDown>", MoveDown.with_custom_count(ScreenHeightRatio(1)))?;
-	map(&mut me, "<PageUp>", MoveUp.with_custom_count(ScreenHeightRatio(1)))?;
-	
-	map(&mut me, "<F2>", RenameSelectedEntry { prefill: true })?;
-	map(&mut me, "<Shift-F2>", RenameSelectedEntry { prefill: false })?;
-	
-	map(&mut me, "<F5>", RefreshChildrenOfSelected)?;
-	
-	Ok(me)
+	map("<Up>", "move-up")?;
+	map("<Shift-Up>", "page-up")?;
+	map("<Alt-Up>", "move-to-previous-sibling")?;
+	
+	map("<Left>", "collapse-or-move-to-parent")?;
+	map("<Alt-Left>", "move-to-parent")?;
+	
+	map("<Right>", "expand-or-move-down")?;
+	
+	map("<Del>", "delete")?;
+	
+	map("<PageDown>", "page-down")?;
+	map("<PageUp>", "page-up")?;
+	
+	map("<F1>", "help")?;
+	map("<F2>", "rename")?;
+	map("<Shift-F2>", "rename-from-scratch")?;
+	
+	map("<F5>", "refresh")?;
+	
+	let errors = config::read_config_file("keys").map(|contents| apply_config(&mut map, &contents)).unwrap_or_default();
+	
+	Ok((me, errors))
+}
+
+/// Applies custom key bindings from the `keys` configuration file, where each line contains a key sequence and the name of an action, such as `<Ctrl-E> edit`. Lines starting with `#` are comments. Returns an error for each line that could not be applied, with its line number.
+fn apply_config<F>(map: &mut F, contents: &str) -> Vec<String> where F: FnMut(&str, &str) -> Result<(), KeyMapInsertError> {
+	let mut errors = Vec::new();
+	
+	for (index, line) in contents.lines().enumerate() {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+		
+		let line_number = index.saturating_add(1);
+		let mut tokens = line.split_whitespace();
+		
+		match (tokens.next(), tokens.next(), tokens.next()) {
+			(Some(key_binding_str), Some(action_name), None) => {
+				if let Err(e) = map(key_binding_str, action_name) {
+					errors.push(format!("Line {line_number}: {}: {}", e.sequence(), e.error()));
+				}
+			}
+			
+			(Some(_), Some(_), Some(extra_token)) => {
+				errors.push(format!("Line {line_number}: Unexpected text after action name: {extra_token}"));
+			}
+			
+			_ => {
+				errors.push(format!("Line {line_number}: Expected a key sequence and an action name."));
+			}
+		}
+	}
+	
+	errors
 }
 
-fn map(map: &mut ActionKeyMap, key_binding_str: &str, action: impl Action<FsLayer> + Sync + 'static) -> Result<(), KeyMapInsertError> {
-	map.insert(key_binding_str, Box::new(action))
+fn bind(map: &mut ActionKeyMap, actions: &HashMap<&str, &Arc<NamedAction>>, key_binding_str: &str, action_name: &str) -> Result<(), KeyMapInsertError> {
+	let Some(action) = actions.get(action_name) else {
+		return Err(KeyMapInsertError::new(key_binding_str.to_owned(), KeyMapInsertErrorType::UnknownAction(action_name.to_owned())));
+	};
+	
+	map.insert(key_binding_str, Arc::clone(action))
+}
+
+/// Lists all actions that are bound to at least one key sequence in the key map, with key sequences sorted from shortest to longest.
+pub fn describe_key_bindings(map: &ActionKeyMap) -> Vec<HelpEntry> {
+	let bindings = map.entries();
+	
+	ACTIONS.iter().filter_map(|action| {
+		let mut key_sequences = bindings.iter()
+			.filter(|(_, bound_action)| Arc::ptr_eq(bound_action, action))
+			.map(|(key_sequence, _)| key_sequence.iter().map(ToString::to_string).collect::<String>())
+			.collect::<Vec<_>>();
+		
+		if key_sequences.is_empty() {
+			return None;
+		}
+		
+		key_sequences.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+		
+		Some(HelpEntry { keys: key_sequences.join(", "), name: action.name, description: action.description })
+	}).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	
+	use crate::component::filesystem::ActionKeyMap;
+	use crate::component::filesystem::defaults::{ACTIONS, apply_config, bind, create_action_map, describe_key_bindings};
+	
+	fn apply(contents: &str) -> (Vec<String>, Vec<String>) {
+		let actions = ACTIONS.iter().map(|action| (action.name, action)).collect::<HashMap<_, _>>();
+		let mut map = ActionKeyMap::new();
+		let mut bind = |key_binding_str: &str, action_name: &str| bind(&mut map, &actions, key_binding_str, action_name);
+		
+		assert!(bind("j", "move-down").is_ok(), "default key binding could not be inserted");
+		
+		let errors = apply_config(&mut bind, contents);
+		let bindings = describe_key_bindings(&map).into_iter().map(|entry| format!("{} {}", entry.keys, entry.name)).collect();
+		(errors, bindings)
+	}
+	
+	#[test]
+	fn default_key_bindings_do_not_conflict() {
+		assert!(create_action_map().is_ok(), "default key bindings could not be inserted");
+	}
+	
+	#[test]
+	fn applies_key_bindings_and_skips_comments() {
+		let (errors, bindings) = apply("# Comment\n\n  <Ctrl-E>  edit  \nk move-up\n");
+		
+		assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+		assert_eq!(bindings, vec!["j move-down", "k move-up", "<Ctrl-e> edit"]);
+	}
+	
+	#[test]
+	fn reports_invalid_lines_with_line_numbers() {
+		let (errors, bindings) = apply("<Ctrl-E> edit typo\n# Comment\nk\nx unknown-action\njx quit\n<Foo> quit\nq quit\n");
+		
+		assert_eq!(errors, vec![
+			"Line 1: Unexpected text after action name: typo",
+			"Line 3: Expected a key sequence and an action name.",
+			"Line 4: x: Unknown action: unknown-action",
+			"Line 5: jx: Conflicts with a key sequence that starts with it, or that it starts with.",
+			"Line 6: <Foo>: Parse error: Invalid key name: Foo",
+		]);
+		
+		assert_eq!(bindings, vec!["j move-down", "q quit"]);
+	}
 }
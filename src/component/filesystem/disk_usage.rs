@@ -0,0 +1,112 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use crate::component::filesystem::tree::FsTreeViewSort;
+use crate::file::DiskUsage;
+
+/// State of the disk usage mode, in which the tree is sorted by total sizes of directories, and each entry shows what part of its parent directory it takes.
+pub struct DiskUsageMode {
+	root_path: PathBuf,
+	cross_file_systems: bool,
+	scan: Option<Receiver<ScanMessage>>,
+	scanned_entries: usize,
+	sort_before: FsTreeViewSort,
+}
+
+enum ScanMessage {
+	Progress(usize),
+	Finished(Option<DiskUsage>),
+}
+
+pub enum DiskUsagePoll {
+	Nothing,
+	Progress,
+	Finished(Option<DiskUsage>),
+}
+
+impl DiskUsageMode {
+	/// Starts scanning the directory in a background thread. The sort order is restored when the mode ends.
+	pub fn start(root_path: &Path, cross_file_systems: bool, sort_before: FsTreeViewSort) -> Self {
+		Self {
+			root_path: root_path.to_path_buf(),
+			cross_file_systems,
+			scan: Some(start_scan(root_path, cross_file_systems)),
+			scanned_entries: 0,
+			sort_before,
+		}
+	}
+	
+	/// Scans the directory again, cancelling the previous scan if it is still running.
+	pub fn restart(&mut self, root_path: &Path, cross_file_systems: bool) {
+		self.root_path = root_path.to_path_buf();
+		self.cross_file_systems = cross_file_systems;
+		self.scan = Some(start_scan(root_path, cross_file_systems));
+		self.scanned_entries = 0;
+	}
+	
+	pub fn root_path(&self) -> &Path {
+		&self.root_path
+	}
+	
+	pub const fn cross_file_systems(&self) -> bool {
+		self.cross_file_systems
+	}
+	
+	pub const fn is_scanning(&self) -> bool {
+		self.scan.is_some()
+	}
+	
+	pub const fn scanned_entries(&self) -> usize {
+		self.scanned_entries
+	}
+	
+	pub const fn sort_before(&self) -> FsTreeViewSort {
+		self.sort_before
+	}
+	
+	/// Receives progress of the background scan, and its result once it finishes.
+	pub fn poll(&mut self) -> DiskUsagePoll {
+		let Some(receiver) = &self.scan else {
+			return DiskUsagePoll::Nothing;
+		};
+		
+		let mut result = DiskUsagePoll::Nothing;
+		
+		loop {
+			match receiver.try_recv() {
+				Ok(ScanMessage::Progress(scanned_entries)) => {
+					self.scanned_entries = scanned_entries;
+					result = DiskUsagePoll::Progress;
+				}
+				
+				Ok(ScanMessage::Finished(disk_usage)) => {
+					self.scan = None;
+					return DiskUsagePoll::Finished(disk_usage);
+				}
+				
+				Err(TryRecvError::Empty) => {
+					return result;
+				}
+				
+				Err(TryRecvError::Disconnected) => {
+					self.scan = None;
+					return DiskUsagePoll::Finished(None);
+				}
+			}
+		}
+	}
+}
+
+/// The scan stops when the receiver is dropped, because sending progress fails.
+fn start_scan(root_path: &Path, cross_file_systems: bool) -> Receiver<ScanMessage> {
+	let (sender, receiver) = mpsc::channel();
+	let root_path = root_path.to_path_buf();
+	
+	thread::spawn(move || {
+		let disk_usage = DiskUsage::scan(&root_path, cross_file_systems, |scanned_entries| sender.send(ScanMessage::Progress(scanned_entries)).is_ok());
+		let _ = sender.send(ScanMessage::Finished(disk_usage));
+	});
+	
+	receiver
+}
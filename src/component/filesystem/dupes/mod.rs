@@ -0,0 +1,417 @@
+use std::cmp::min;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, Paragraph};
+
+use crate::component::dialog::message::MessageDialogLayer;
+use crate::component::filesystem::action::file::format_io_error;
+use crate::component::filesystem::dupes::scan::{DuplicateGroup, ScanMessage, ScanStage};
+use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::status::StatusMessage;
+use crate::file::FileSystem;
+use crate::input::keymap::KeyBinding;
+use crate::state::action::ActionResult;
+use crate::state::Environment;
+use crate::state::event::{EventQueue, EventResult};
+use crate::state::layer::Layer;
+use crate::state::view::Frame;
+use crate::theme::theme;
+
+mod scan;
+
+const WHEEL_SCROLL_LINES: usize = 3;
+
+/// Full screen list of groups of files with identical contents inside a directory. Files can be revealed in the tree, deleted, or used to replace the other files in their group with hard links.
+pub struct DupesLayer {
+	root_path: PathBuf,
+	file_system: Arc<dyn FileSystem>,
+	fs_events: EventQueue<FsLayer>,
+	events: EventQueue<Self>,
+	scan: Option<Receiver<ScanMessage>>,
+	progress: Option<(ScanStage, usize, usize)>,
+	groups: Vec<DuplicateGroup>,
+	rows: Vec<Row>,
+	file_rows: Vec<usize>,
+	selected: usize,
+	scroll: usize,
+	page_height: usize,
+	list_area: Rect,
+}
+
+#[derive(Copy, Clone)]
+enum Row {
+	Group(usize),
+	File(usize, usize),
+}
+
+impl DupesLayer {
+	pub fn new(root_path: &Path, file_system: Arc<dyn FileSystem>, fs_events: EventQueue<FsLayer>) -> Self {
+		Self {
+			root_path: root_path.to_path_buf(),
+			scan: Some(scan::start(Arc::clone(&file_system), root_path)),
+			file_system,
+			fs_events,
+			events: EventQueue::new(),
+			progress: None,
+			groups: Vec::new(),
+			rows: Vec::new(),
+			file_rows: Vec::new(),
+			selected: 0,
+			scroll: 0,
+			page_height: 0,
+			list_area: Rect::default(),
+		}
+	}
+	
+	/// Receives progress of the scan, and its results once it finishes. Returns `true` if anything changed.
+	fn receive_results(&mut self) -> bool {
+		let Some(receiver) = &self.scan else {
+			return false;
+		};
+		
+		let mut changed = false;
+		
+		loop {
+			match receiver.try_recv() {
+				Ok(ScanMessage::Progress { stage, processed, total }) => {
+					self.progress = Some((stage, processed, total));
+					changed = true;
+				}
+				
+				Ok(ScanMessage::Finished(groups)) => {
+					self.scan = None;
+					self.set_groups(groups);
+					return true;
+				}
+				
+				Err(TryRecvError::Empty) => {
+					return changed;
+				}
+				
+				Err(TryRecvError::Disconnected) => {
+					self.scan = None;
+					return true;
+				}
+			}
+		}
+	}
+	
+	fn set_groups(&mut self, groups: Vec<DuplicateGroup>) {
+		self.groups = groups;
+		self.rows.clear();
+		self.file_rows.clear();
+		
+		for (group_index, group) in self.groups.iter().enumerate() {
+			self.rows.push(Row::Group(group_index));
+			
+			for file_index in 0..group.paths.len() {
+				self.file_rows.push(self.rows.len());
+				self.rows.push(Row::File(group_index, file_index));
+			}
+		}
+		
+		self.selected = min(self.selected, self.file_rows.len().saturating_sub(1));
+	}
+	
+	/// Removes files that were deleted or replaced with hard links, and groups that have less than two files left.
+	fn remove_paths(&mut self, removed_paths: &[PathBuf]) {
+		let mut groups = std::mem::take(&mut self.groups);
+		
+		for group in &mut groups {
+			group.paths.retain(|path| !removed_paths.contains(path));
+		}
+		
+		groups.retain(|group| group.paths.len() > 1);
+		self.set_groups(groups);
+	}
+	
+	fn selected_file(&self) -> Option<(&DuplicateGroup, &PathBuf)> {
+		let row = self.file_rows.get(self.selected).and_then(|row_index| self.rows.get(*row_index))?;
+		
+		if let Row::File(group_index, file_index) = row {
+			let group = self.groups.get(*group_index)?;
+			Some((group, group.paths.get(*file_index)?))
+		} else {
+			None
+		}
+	}
+	
+	fn get_relative_path<'a>(&self, path: &'a Path) -> &'a Path {
+		path.strip_prefix(&self.root_path).unwrap_or(path)
+	}
+	
+	fn move_selection_to(&mut self, index: usize) -> ActionResult {
+		let index = min(index, self.file_rows.len().saturating_sub(1));
+		let changed = self.selected != index;
+		self.selected = index;
+		ActionResult::draw_if(changed)
+	}
+	
+	fn move_selection_down(&mut self, lines: usize) -> ActionResult {
+		self.move_selection_to(self.selected.saturating_add(lines))
+	}
+	
+	fn move_selection_up(&mut self, lines: usize) -> ActionResult {
+		self.move_selection_to(self.selected.saturating_sub(lines))
+	}
+	
+	fn reveal_selected(&self) -> ActionResult {
+		let Some((_, path)) = self.selected_file() else {
+			return ActionResult::Nothing;
+		};
+		
+		let path = path.clone();
+		
+		self.fs_events.enqueue_fn(move |layer, _| {
+			if !layer.tree.select_path(&path) {
+				layer.show_message(StatusMessage::error(format!("Entry is not visible in the tree: {}", path.to_string_lossy())));
+			}
+			
+			EventResult::Draw
+		});
+		
+		ActionResult::PopLayer
+	}
+	
+	fn delete_selected(&self) -> ActionResult {
+		let Some((_, path)) = self.selected_file() else {
+			return ActionResult::Nothing;
+		};
+		
+		let y = self.list_area.y;
+		let path = path.clone();
+		let file_system = Arc::clone(&self.file_system);
+		let events = self.events.rc_clone();
+		let fs_events = self.fs_events.rc_clone();
+		
+		ActionResult::push_layer(MessageDialogLayer::build()
+			.y(y)
+			.style(theme().dialog_error)
+			.title("Delete Duplicate")
+			.message(format!("Permanently delete {}?", path.to_string_lossy()))
+			.yes_no(move || {
+				match file_system.remove(&path) {
+					Ok(()) => {
+						notify_changed_files(&events, &fs_events, vec![path.clone()], format!("Deleted {}", path.to_string_lossy()));
+						ActionResult::PopLayer
+					}
+					Err(e) => {
+						ActionResult::replace_layer(MessageDialogLayer::error(y.saturating_add(1), format_io_error(&e)))
+					}
+				}
+			}))
+	}
+	
+	fn link_others_to_selected(&self) -> ActionResult {
+		let Some((group, path)) = self.selected_file() else {
+			return ActionResult::Nothing;
+		};
+		
+		let y = self.list_area.y;
+		let path = path.clone();
+		let other_paths = group.paths.iter().filter(|other_path| **other_path != path).cloned().collect::<Vec<_>>();
+		let file_system = Arc::clone(&self.file_system);
+		let events = self.events.rc_clone();
+		let fs_events = self.fs_events.rc_clone();
+		
+		let mut message = vec![Line::from(format!("Replace with hard links to {}:", path.to_string_lossy()))];
+		message.extend(other_paths.iter().map(|other_path| Line::from(format!("  {}", other_path.to_string_lossy()))));
+		
+		ActionResult::push_layer(MessageDialogLayer::build()
+			.y(y)
+			.style(theme().dialog_warning)
+			.title("Replace Duplicates with Hard Links")
+			.message(message)
+			.yes_no(move || {
+				let mut linked_paths = Vec::new();
+				
+				for other_path in &other_paths {
+					if let Err(e) = replace_with_hard_link(file_system.as_ref(), &path, other_path) {
+						notify_changed_files(&events, &fs_events, linked_paths, String::new());
+						return ActionResult::replace_layer(MessageDialogLayer::error(y.saturating_add(1), format!("{}: {}", other_path.to_string_lossy(), format_io_error(&e))));
+					}
+					
+					linked_paths.push(other_path.clone());
+				}
+				
+				let message = format!("Replaced {} duplicates with hard links", linked_paths.len());
+				notify_changed_files(&events, &fs_events, linked_paths, message);
+				ActionResult::PopLayer
+			}))
+	}
+	
+	fn render_title(&self, frame: &mut Frame, area: Rect) {
+		let text = format!(" dupes in {}", self.root_path.to_string_lossy());
+		frame.render_widget(Paragraph::new(text).style(theme().status_bar.add_modifier(Modifier::BOLD)), area);
+	}
+	
+	fn render_groups(&self, frame: &mut Frame, area: Rect) {
+		let theme = theme();
+		
+		let lines = self.rows.iter()
+			.skip(self.scroll)
+			.take(usize::from(area.height))
+			.map(|row| match row {
+				Row::Group(group_index) => {
+					let (count, size) = self.groups.get(*group_index).map_or((0, 0), |group| (group.paths.len(), group.size));
+					Line::from(Span::styled(format!(" {count} files, {size} bytes each"), theme.secondary))
+				}
+				Row::File(group_index, file_index) => {
+					let path = self.groups.get(*group_index).and_then(|group| group.paths.get(*file_index));
+					Line::from(format!("   {}", path.map(|path| self.get_relative_path(path).to_string_lossy()).unwrap_or_default()))
+				}
+			})
+			.collect::<Vec<_>>();
+		
+		frame.render_widget(Paragraph::new(lines), area);
+		
+		let selected_row = self.file_rows.get(self.selected).copied().unwrap_or(0);
+		
+		if !self.file_rows.is_empty() {
+			if let Some(selected_y) = selected_row.checked_sub(self.scroll).and_then(|offset| u16::try_from(offset).ok()).filter(|offset| *offset < area.height) {
+				frame.render_widget(Block::default().style(theme.selected), Rect { y: area.y.saturating_add(selected_y), height: 1, ..area });
+			}
+		}
+	}
+	
+	fn render_footer(&self, frame: &mut Frame, area: Rect) {
+		let text = if self.scan.is_some() {
+			match self.progress {
+				Some((stage, processed, 0)) => format!(" {}... {processed}", stage.describe()),
+				Some((stage, processed, total)) => format!(" {}... {processed} of {total}", stage.describe()),
+				None => String::from(" Listing files..."),
+			}
+		} else {
+			let wasted_size = self.groups.iter().map(DuplicateGroup::wasted_size).fold(0_u64, u64::saturating_add);
+			format!(" {} groups, {wasted_size} bytes in duplicates  Enter: reveal  d: delete  h: hard link others to this file  q: close", self.groups.len())
+		};
+		
+		frame.render_widget(Paragraph::new(text).style(theme().status_bar), area);
+	}
+}
+
+/// Updates the list after files were deleted or replaced, and refreshes directories that contain them in the tree.
+fn notify_changed_files(events: &EventQueue<DupesLayer>, fs_events: &EventQueue<FsLayer>, paths: Vec<PathBuf>, message: String) {
+	let directories = paths.iter().filter_map(|path| path.parent()).map(Path::to_path_buf).collect::<Vec<_>>();
+	
+	fs_events.enqueue_fn(move |layer, _| {
+		for directory in &directories {
+			layer.tree.refresh_model_directory(directory);
+		}
+		
+		if !message.is_empty() {
+			layer.show_message(StatusMessage::info(message.clone()));
+		}
+		
+		EventResult::Draw
+	});
+	
+	events.enqueue_fn(move |layer, _| {
+		layer.remove_paths(&paths);
+		EventResult::Draw
+	});
+}
+
+/// Replaces the target with a hard link to the original. The link is created under a temporary name first, so the target is not lost if linking fails. Renaming never replaces existing entries, so the target is deleted before the link takes its name; if renaming fails after that, the link stays under the temporary name, and still has the same contents.
+fn replace_with_hard_link(file_system: &dyn FileSystem, original: &Path, target: &Path) -> io::Result<()> {
+	let file_name = target.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+	let temporary_path = target.with_file_name(format!(".{file_name}.link"));
+	
+	file_system.hard_link(original, &temporary_path)?;
+	
+	if let Err(e) = file_system.remove(target) {
+		let _ = file_system.remove(&temporary_path);
+		return Err(e);
+	}
+	
+	file_system.rename(&temporary_path, target)
+}
+
+impl Layer for DupesLayer {
+	#[allow(clippy::wildcard_enum_match_arm)]
+	fn handle_input(&mut self, _environment: &Environment, key_binding: KeyBinding) -> ActionResult {
+		match (key_binding.code(), key_binding.modifiers()) {
+			(KeyCode::Esc | KeyCode::Char('q'), KeyModifiers::NONE) |
+			(KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+				ActionResult::PopLayer
+			}
+			
+			(KeyCode::Enter, KeyModifiers::NONE) => self.reveal_selected(),
+			(KeyCode::Char('d') | KeyCode::Delete, KeyModifiers::NONE) => self.delete_selected(),
+			(KeyCode::Char('h'), KeyModifiers::NONE) => self.link_others_to_selected(),
+			
+			(KeyCode::Char('j') | KeyCode::Down, KeyModifiers::NONE) => self.move_selection_down(1),
+			(KeyCode::Char('k') | KeyCode::Up, KeyModifiers::NONE) => self.move_selection_up(1),
+			(KeyCode::Char('d'), KeyModifiers::CONTROL) => self.move_selection_down(self.page_height / 2),
+			(KeyCode::Char('u'), KeyModifiers::CONTROL) => self.move_selection_up(self.page_height / 2),
+			(KeyCode::Char('f'), KeyModifiers::CONTROL) | (KeyCode::PageDown, KeyModifiers::NONE) => self.move_selection_down(self.page_height),
+			(KeyCode::Char('b'), KeyModifiers::CONTROL) | (KeyCode::PageUp, KeyModifiers::NONE) => self.move_selection_up(self.page_height),
+			(KeyCode::Char('g') | KeyCode::Home, KeyModifiers::NONE) => self.move_selection_to(0),
+			(KeyCode::Char('G') | KeyCode::End, KeyModifiers::NONE) => self.move_selection_to(usize::MAX),
+			
+			_ => ActionResult::Nothing
+		}
+	}
+	
+	#[allow(clippy::wildcard_enum_match_arm)]
+	fn handle_mouse(&mut self, _environment: &Environment, event: MouseEvent) -> ActionResult {
+		match event.kind {
+			MouseEventKind::ScrollDown => self.move_selection_down(WHEEL_SCROLL_LINES),
+			MouseEventKind::ScrollUp => self.move_selection_up(WHEEL_SCROLL_LINES),
+			
+			MouseEventKind::Down(MouseButton::Left) => {
+				let area = self.list_area;
+				let row_index = self.scroll.saturating_add(usize::from(event.row.saturating_sub(area.y)));
+				
+				if let Some(index) = self.file_rows.iter().position(|file_row| *file_row == row_index).filter(|_| event.row >= area.y && event.row < area.bottom()) {
+					self.move_selection_to(index)
+				} else {
+					ActionResult::Nothing
+				}
+			}
+			
+			_ => ActionResult::Nothing
+		}
+	}
+	
+	fn handle_events(&mut self, environment: &Environment) -> EventResult {
+		let result = self.events.take().into_iter().fold(EventResult::Nothing, |result, event| result.merge(event.dispatch(self, environment)));
+		result.merge(EventResult::draw_if(self.receive_results()))
+	}
+	
+	fn render(&mut self, frame: &mut Frame) {
+		let size = frame.size();
+		if size.height < 3 {
+			return;
+		}
+		
+		let title_area = Rect { height: 1, ..size };
+		let list_area = Rect { y: size.y.saturating_add(1), height: size.height.saturating_sub(2), ..size };
+		let footer_area = Rect { y: size.bottom().saturating_sub(1), height: 1, ..size };
+		
+		self.list_area = list_area;
+		self.page_height = usize::from(list_area.height);
+		
+		// Keep the selected file and the header of its group visible.
+		let selected_row = self.file_rows.get(self.selected).copied().unwrap_or(0);
+		let group_row = selected_row.saturating_sub(1);
+		
+		if group_row < self.scroll {
+			self.scroll = group_row;
+		} else if selected_row >= self.scroll.saturating_add(self.page_height) {
+			self.scroll = selected_row.saturating_add(1).saturating_sub(self.page_height);
+		}
+		
+		frame.render_widget(Clear, size);
+		self.render_title(frame, title_area);
+		self.render_groups(frame, list_area);
+		self.render_footer(frame, footer_area);
+	}
+}
@@ -0,0 +1,236 @@
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use crate::component::filesystem::action::file::traverse_recursively;
+use crate::file::{FileKind, FileSystem};
+
+/// How many bytes from the start of each file are hashed before hashing whole files.
+const PARTIAL_HASH_LENGTH: u64 = 4096;
+
+/// How many files are processed between progress updates.
+const PROGRESS_INTERVAL: usize = 100;
+
+/// Files with identical contents. Paths are sorted, and each group has at least two.
+pub struct DuplicateGroup {
+	pub size: u64,
+	pub paths: Vec<PathBuf>,
+}
+
+impl DuplicateGroup {
+	/// Returns how much space would be freed by keeping only one of the files.
+	pub fn wasted_size(&self) -> u64 {
+		self.size.saturating_mul(u64::try_from(self.paths.len().saturating_sub(1)).unwrap_or(u64::MAX))
+	}
+}
+
+#[derive(Copy, Clone)]
+pub enum ScanStage {
+	ListingFiles,
+	ComparingBeginnings,
+	ComparingContents,
+}
+
+impl ScanStage {
+	pub const fn describe(self) -> &'static str {
+		match self {
+			Self::ListingFiles => "Listing files",
+			Self::ComparingBeginnings => "Comparing beginnings of files",
+			Self::ComparingContents => "Comparing contents of files",
+		}
+	}
+}
+
+pub enum ScanMessage {
+	/// Listing files has no known total, so it is zero.
+	Progress { stage: ScanStage, processed: usize, total: usize },
+	Finished(Vec<DuplicateGroup>),
+}
+
+/// Starts looking for duplicate files in the root directory and its subdirectories in a background thread. Files are grouped by size, then by a hash of their beginning, and then by a hash of their whole contents. The scan stops when the receiver is dropped.
+pub fn start(file_system: Arc<dyn FileSystem>, root_path: &Path) -> Receiver<ScanMessage> {
+	let (sender, receiver) = mpsc::channel();
+	let root_path = root_path.to_path_buf();
+	
+	thread::spawn(move || {
+		if let Some(groups) = find_duplicates(file_system.as_ref(), root_path, &sender) {
+			let _ = sender.send(ScanMessage::Finished(groups));
+		}
+	});
+	
+	receiver
+}
+
+fn find_duplicates(file_system: &dyn FileSystem, root_path: PathBuf, sender: &Sender<ScanMessage>) -> Option<Vec<DuplicateGroup>> {
+	let groups = group_files_by_size(file_system, root_path, sender)?;
+	let groups = split_groups_by_hash(file_system, groups, Some(PARTIAL_HASH_LENGTH), ScanStage::ComparingBeginnings, sender)?;
+	
+	// Files that fit in the partial hash were already compared whole.
+	let (small_groups, large_groups) = groups.into_iter().partition::<Vec<_>, _>(|group| group.size <= PARTIAL_HASH_LENGTH);
+	
+	let mut groups = split_groups_by_hash(file_system, large_groups, None, ScanStage::ComparingContents, sender)?;
+	groups.extend(small_groups);
+	
+	for group in &mut groups {
+		group.paths.sort();
+	}
+	
+	groups.sort_by(|a, b| b.wasted_size().cmp(&a.wasted_size()).then_with(|| a.paths.cmp(&b.paths)));
+	Some(groups)
+}
+
+/// Finds all non-empty files, and groups them by size. Only one path is kept for files with multiple hard links, since they cannot be deduplicated further.
+fn group_files_by_size(file_system: &dyn FileSystem, root_path: PathBuf, sender: &Sender<ScanMessage>) -> Option<Vec<DuplicateGroup>> {
+	let mut files_by_size = HashMap::<u64, Vec<PathBuf>>::new();
+	let mut seen_hard_links = HashSet::new();
+	let mut listed_files = 0_usize;
+	
+	traverse_recursively(file_system, root_path, |entry| {
+		let (FileKind::File { size }, Some(path)) = (entry.kind(), entry.path()) else {
+			return true;
+		};
+		
+		if *size > 0 && entry.hard_link_id().map_or(true, |id| seen_hard_links.insert(id)) {
+			files_by_size.entry(*size).or_default().push(path.to_path_buf());
+		}
+		
+		listed_files = listed_files.saturating_add(1);
+		listed_files % PROGRESS_INTERVAL != 0 || send_progress(sender, ScanStage::ListingFiles, listed_files, 0)
+	})?;
+	
+	Some(files_by_size.into_iter()
+		.filter(|(_, paths)| paths.len() > 1)
+		.map(|(size, paths)| DuplicateGroup { size, paths })
+		.collect())
+}
+
+/// Splits each group into smaller groups of files whose hashes match, and drops files that have no match or cannot be read. Returns `None` if the scan was stopped.
+fn split_groups_by_hash(file_system: &dyn FileSystem, groups: Vec<DuplicateGroup>, length: Option<u64>, stage: ScanStage, sender: &Sender<ScanMessage>) -> Option<Vec<DuplicateGroup>> {
+	let total = groups.iter().map(|group| group.paths.len()).sum();
+	let mut processed = 0_usize;
+	let mut result = Vec::new();
+	
+	for group in groups {
+		let mut paths_by_hash = HashMap::<u64, Vec<PathBuf>>::new();
+		
+		for path in group.paths {
+			if processed % PROGRESS_INTERVAL == 0 && !send_progress(sender, stage, processed, total) {
+				return None;
+			}
+			
+			processed = processed.saturating_add(1);
+			
+			if let Ok(hash) = hash_file(file_system, &path, length) {
+				paths_by_hash.entry(hash).or_default().push(path);
+			}
+		}
+		
+		result.extend(paths_by_hash.into_values().filter(|paths| paths.len() > 1).map(|paths| DuplicateGroup { size: group.size, paths }));
+	}
+	
+	Some(result)
+}
+
+/// Hashes the file contents, or only their beginning if a length is given.
+fn hash_file(file_system: &dyn FileSystem, path: &Path, length: Option<u64>) -> io::Result<u64> {
+	let file = file_system.open_file(path)?;
+	let mut reader: Box<dyn Read> = if let Some(length) = length { Box::new(file.take(length)) } else { Box::new(file) };
+	let mut hasher = DefaultHasher::new();
+	let mut buffer = vec![0; 64 * 1024];
+	
+	loop {
+		let read_bytes = reader.read(&mut buffer)?;
+		
+		if let Some(bytes) = buffer.get(..read_bytes).filter(|bytes| !bytes.is_empty()) {
+			hasher.write(bytes);
+		} else {
+			return Ok(hasher.finish());
+		}
+	}
+}
+
+fn send_progress(sender: &Sender<ScanMessage>, stage: ScanStage, processed: usize, total: usize) -> bool {
+	sender.send(ScanMessage::Progress { stage, processed, total }).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::PathBuf;
+	use std::sync::mpsc;
+	
+	use crate::component::filesystem::dupes::scan::{find_duplicates, PARTIAL_HASH_LENGTH, ScanMessage};
+	use crate::file::MemoryFileSystem;
+	
+	fn find_groups(file_system: &MemoryFileSystem) -> Vec<(u64, Vec<PathBuf>)> {
+		let (sender, _receiver) = mpsc::channel::<ScanMessage>();
+		let groups = find_duplicates(file_system, PathBuf::from("/root"), &sender).unwrap_or_default();
+		groups.into_iter().map(|group| (group.size, group.paths)).collect()
+	}
+	
+	fn group(size: u64, paths: &[&str]) -> (u64, Vec<PathBuf>) {
+		(size, paths.iter().map(PathBuf::from).collect())
+	}
+	
+	#[test]
+	fn groups_files_with_identical_contents() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/root/a", "same")
+			.with_file("/root/sub/b", "same")
+			.with_file("/root/sub/deeper/c", "same")
+			.with_file("/root/d", "diff")
+			.with_file("/root/e", "other size");
+		
+		assert_eq!(find_groups(&file_system), vec![group(4, &["/root/a", "/root/sub/b", "/root/sub/deeper/c"])]);
+	}
+	
+	#[test]
+	fn ignores_empty_files_and_symlinks() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/root/a", "")
+			.with_file("/root/b", "")
+			.with_file("/root/c", "x")
+			.with_symlink("/root/link", "/root/c");
+		
+		assert!(find_groups(&file_system).is_empty(), "Found duplicates of empty files or symbolic links");
+	}
+	
+	#[test]
+	fn compares_whole_contents_of_large_files() {
+		let length = usize::try_from(PARTIAL_HASH_LENGTH).unwrap_or_default();
+		let mut same_beginning = vec![b'a'; length];
+		same_beginning.push(b'b');
+		let mut different_end = vec![b'a'; length];
+		different_end.push(b'c');
+		
+		let file_system = MemoryFileSystem::new()
+			.with_file("/root/a", same_beginning.clone())
+			.with_file("/root/b", same_beginning)
+			.with_file("/root/c", different_end);
+		
+		let size = PARTIAL_HASH_LENGTH.saturating_add(1);
+		assert_eq!(find_groups(&file_system), vec![group(size, &["/root/a", "/root/b"])]);
+	}
+	
+	#[test]
+	fn sorts_groups_by_wasted_size() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/root/small1", "12")
+			.with_file("/root/small2", "12")
+			.with_file("/root/small3", "12")
+			.with_file("/root/large1", "12345")
+			.with_file("/root/large2", "12345")
+			.with_file("/root/medium1", "123")
+			.with_file("/root/medium2", "123");
+		
+		assert_eq!(find_groups(&file_system), vec![
+			group(5, &["/root/large1", "/root/large2"]),
+			group(2, &["/root/small1", "/root/small2", "/root/small3"]),
+			group(3, &["/root/medium1", "/root/medium2"]),
+		]);
+	}
+}
@@ -0,0 +1,347 @@
+use std::cmp::{min, Reverse};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, Paragraph};
+
+use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::finder::score::Query;
+use crate::component::filesystem::finder::walk::{MAX_VISITED_ENTRIES, WalkMessage};
+use crate::component::filesystem::status::StatusMessage;
+use crate::component::filesystem::tree::FsTreeViewFilter;
+use crate::component::filesystem::walk::WalkEntry;
+use crate::component::input::InputField;
+use crate::file::FileSystem;
+use crate::input::keymap::KeyBinding;
+use crate::state::action::ActionResult;
+use crate::state::Environment;
+use crate::state::event::{EventQueue, EventResult};
+use crate::state::layer::Layer;
+use crate::state::view::Frame;
+use crate::theme::theme;
+
+mod score;
+mod walk;
+
+const PROMPT: &str = "> ";
+const WHEEL_SCROLL_LINES: usize = 3;
+
+struct FinderMatch {
+	entry_index: usize,
+	score: i32,
+	positions: Vec<usize>,
+}
+
+/// Full screen list of all entries in the view root directory and its subdirectories, which is filled by a background walk and ranked by a fuzzy query. Choosing an entry selects it in the tree.
+pub struct FinderLayer {
+	events: EventQueue<FsLayer>,
+	walk: Option<Receiver<WalkMessage>>,
+	reached_limit: bool,
+	entries: Vec<WalkEntry>,
+	field: InputField,
+	query: Query,
+	matches: Vec<FinderMatch>,
+	selected: usize,
+	scroll: usize,
+	page_height: usize,
+	list_area: Rect,
+}
+
+impl FinderLayer {
+	pub fn new(root_path: &Path, filter: FsTreeViewFilter, file_system: Arc<dyn FileSystem>, events: EventQueue<FsLayer>) -> Self {
+		Self {
+			events,
+			walk: Some(walk::start(file_system, root_path, filter)),
+			reached_limit: false,
+			entries: Vec::new(),
+			field: InputField::new(),
+			query: Query::new(""),
+			matches: Vec::new(),
+			selected: 0,
+			scroll: 0,
+			page_height: 0,
+			list_area: Rect::default(),
+		}
+	}
+	
+	/// Receives entries found by the walk since the last check, and returns `true` if anything changed.
+	fn receive_entries(&mut self) -> bool {
+		let Some(receiver) = &self.walk else {
+			return false;
+		};
+		
+		let first_new_entry_index = self.entries.len();
+		let mut is_finished = false;
+		
+		loop {
+			match receiver.try_recv() {
+				Ok(WalkMessage::Entries(entries)) => {
+					self.entries.extend(entries);
+				}
+				
+				Ok(WalkMessage::Finished { reached_limit }) => {
+					self.reached_limit = reached_limit;
+					is_finished = true;
+					break;
+				}
+				
+				Err(TryRecvError::Empty) => {
+					break;
+				}
+				
+				Err(TryRecvError::Disconnected) => {
+					is_finished = true;
+					break;
+				}
+			}
+		}
+		
+		if is_finished {
+			self.walk = None;
+		}
+		
+		let has_new_entries = self.entries.len() > first_new_entry_index;
+		if has_new_entries {
+			self.add_matches(first_new_entry_index);
+		}
+		
+		has_new_entries || is_finished
+	}
+	
+	fn set_query(&mut self, text: &str) {
+		self.query = Query::new(text);
+		self.matches.clear();
+		self.selected = 0;
+		self.scroll = 0;
+		self.add_matches(0);
+	}
+	
+	/// Scores entries starting at the index, and keeps all matches ordered from the best score. Matches with equal scores are ordered by path length, and then by the order in which the walk found them.
+	fn add_matches(&mut self, first_entry_index: usize) {
+		let new_entries = self.entries.iter().enumerate().skip(first_entry_index);
+		
+		if self.query.is_empty() {
+			self.matches.extend(new_entries.map(|(entry_index, _)| FinderMatch { entry_index, score: 0, positions: Vec::new() }));
+			return;
+		}
+		
+		self.matches.extend(new_entries.filter_map(|(entry_index, entry)| {
+			self.query.find(&entry.relative_path).map(|m| FinderMatch { entry_index, score: m.score, positions: m.positions })
+		}));
+		
+		let entries = &self.entries;
+		self.matches.sort_by_key(|m| (Reverse(m.score), entries.get(m.entry_index).map_or(0, |entry| entry.relative_path.len()), m.entry_index));
+	}
+	
+	fn move_selection_to(&mut self, index: usize) -> ActionResult {
+		let index = min(index, self.matches.len().saturating_sub(1));
+		let changed = self.selected != index;
+		self.selected = index;
+		ActionResult::draw_if(changed)
+	}
+	
+	fn move_selection_down(&mut self, lines: usize) -> ActionResult {
+		self.move_selection_to(self.selected.saturating_add(lines))
+	}
+	
+	fn move_selection_up(&mut self, lines: usize) -> ActionResult {
+		self.move_selection_to(self.selected.saturating_sub(lines))
+	}
+	
+	fn choose_selected(&self) -> ActionResult {
+		let Some(entry) = self.matches.get(self.selected).and_then(|m| self.entries.get(m.entry_index)) else {
+			return ActionResult::Nothing;
+		};
+		
+		let relative_path = entry.relative_path.clone();
+		let path = entry.path.clone();
+		
+		self.events.enqueue_fn(move |layer, _| {
+			if !layer.tree.select_path(&path) {
+				layer.show_message(StatusMessage::error(format!("Entry no longer exists: {relative_path}")));
+			}
+			
+			EventResult::Draw
+		});
+		
+		ActionResult::PopLayer
+	}
+	
+	fn render_matches(&self, frame: &mut Frame, area: Rect) {
+		let theme = theme();
+		
+		let lines = self.matches.iter()
+			.enumerate()
+			.skip(self.scroll)
+			.take(usize::from(area.height))
+			.filter_map(|(index, m)| self.entries.get(m.entry_index).map(|entry| (index == self.selected, entry, m)))
+			.map(|(is_selected, entry, m)| {
+				let marker = Span::styled(if is_selected { PROMPT } else { "  " }, theme.marker);
+				let suffix = if entry.is_directory { "/" } else { "" };
+				
+				let available_width = usize::from(area.width).saturating_sub(2).saturating_sub(suffix.len());
+				let mut spans = vec![marker];
+				spans.extend(get_highlighted_path_spans(&entry.relative_path, &m.positions, available_width));
+				spans.push(Span::styled(suffix, theme.secondary));
+				
+				Line::from(spans)
+			})
+			.collect::<Vec<_>>();
+		
+		frame.render_widget(Paragraph::new(lines), area);
+		
+		if let Some(selected_y) = self.selected.checked_sub(self.scroll).and_then(|offset| u16::try_from(offset).ok()).filter(|offset| *offset < area.height) {
+			frame.render_widget(Block::default().style(theme.selected), Rect { y: area.y.saturating_add(selected_y), height: 1, ..area });
+		}
+	}
+	
+	fn render_info(&self, frame: &mut Frame, area: Rect) {
+		let counts = format!(" {}/{}", self.matches.len(), self.entries.len());
+		
+		let text = if self.walk.is_some() {
+			format!("{counts}  Searching...")
+		} else if self.reached_limit {
+			format!("{counts}  Stopped after {MAX_VISITED_ENTRIES} entries")
+		} else {
+			counts
+		};
+		
+		frame.render_widget(Paragraph::new(text).style(theme().status_bar), area);
+	}
+	
+	fn render_prompt(&self, frame: &mut Frame, area: Rect) {
+		let theme = theme();
+		let prompt_width = min(u16::try_from(PROMPT.len()).unwrap_or(u16::MAX), area.width);
+		
+		frame.render_widget(Paragraph::new(PROMPT).style(theme.command_line.input), Rect { width: prompt_width, ..area });
+		self.field.render(frame, area.x.saturating_add(prompt_width), area.y, area.width.saturating_sub(prompt_width), theme.command_line);
+	}
+}
+
+/// Creates spans of the path with matched characters highlighted. If the path is wider than the available width, its start is replaced with an ellipsis, so that the file name stays visible.
+fn get_highlighted_path_spans(path: &str, positions: &[usize], available_width: usize) -> Vec<Span<'static>> {
+	let theme = theme();
+	let highlight_style = theme.dialog_key.add_modifier(Modifier::BOLD);
+	
+	let chars = path.chars().map(String::from).collect::<Vec<_>>();
+	let char_widths = chars.iter().map(|c| Span::raw(c.as_str()).width()).collect::<Vec<_>>();
+	
+	let mut spans = Vec::new();
+	let mut first_char_index = 0;
+	
+	if char_widths.iter().sum::<usize>() > available_width {
+		let mut remaining_width = available_width.saturating_sub(1);
+		first_char_index = chars.len();
+		
+		while let Some(width) = first_char_index.checked_sub(1).and_then(|index| char_widths.get(index)) {
+			if *width > remaining_width {
+				break;
+			}
+			
+			remaining_width = remaining_width.saturating_sub(*width);
+			first_char_index = first_char_index.saturating_sub(1);
+		}
+		
+		spans.push(Span::styled("…", theme.dimmed));
+	}
+	
+	for (char_index, c) in chars.into_iter().enumerate().skip(first_char_index) {
+		let style = if positions.contains(&char_index) { highlight_style } else { Style::default() };
+		spans.push(Span::styled(c, style));
+	}
+	
+	spans
+}
+
+impl Layer for FinderLayer {
+	#[allow(clippy::wildcard_enum_match_arm)]
+	fn handle_input(&mut self, _environment: &Environment, key_binding: KeyBinding) -> ActionResult {
+		match (key_binding.code(), key_binding.modifiers()) {
+			(KeyCode::Esc, KeyModifiers::NONE) |
+			(KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+				ActionResult::PopLayer
+			}
+			
+			(KeyCode::Enter, KeyModifiers::NONE) => {
+				self.choose_selected()
+			}
+			
+			(KeyCode::Down, KeyModifiers::NONE) |
+			(KeyCode::Char('n' | 'j'), KeyModifiers::CONTROL) => {
+				self.move_selection_down(1)
+			}
+			
+			(KeyCode::Up, KeyModifiers::NONE) |
+			(KeyCode::Char('p' | 'k'), KeyModifiers::CONTROL) => {
+				self.move_selection_up(1)
+			}
+			
+			(KeyCode::PageDown, KeyModifiers::NONE) => self.move_selection_down(self.page_height),
+			(KeyCode::PageUp, KeyModifiers::NONE) => self.move_selection_up(self.page_height),
+			
+			_ => {
+				if self.field.handle_input(key_binding) {
+					let text = self.field.text().to_owned();
+					self.set_query(&text);
+					ActionResult::Draw
+				} else {
+					ActionResult::Nothing
+				}
+			}
+		}
+	}
+	
+	#[allow(clippy::wildcard_enum_match_arm)]
+	fn handle_mouse(&mut self, _environment: &Environment, event: MouseEvent) -> ActionResult {
+		match event.kind {
+			MouseEventKind::ScrollDown => self.move_selection_down(WHEEL_SCROLL_LINES),
+			MouseEventKind::ScrollUp => self.move_selection_up(WHEEL_SCROLL_LINES),
+			
+			MouseEventKind::Down(MouseButton::Left) => {
+				let area = self.list_area;
+				if event.row >= area.y && event.row < area.bottom() && self.scroll.saturating_add(usize::from(event.row.saturating_sub(area.y))) < self.matches.len() {
+					self.move_selection_to(self.scroll.saturating_add(usize::from(event.row.saturating_sub(area.y))))
+				} else {
+					ActionResult::Nothing
+				}
+			}
+			
+			_ => ActionResult::Nothing
+		}
+	}
+	
+	fn handle_events(&mut self, _environment: &Environment) -> EventResult {
+		EventResult::draw_if(self.receive_entries())
+	}
+	
+	fn render(&mut self, frame: &mut Frame) {
+		let size = frame.size();
+		if size.height < 3 {
+			return;
+		}
+		
+		let list_area = Rect { height: size.height.saturating_sub(2), ..size };
+		let info_area = Rect { y: list_area.bottom(), height: 1, ..size };
+		let prompt_area = Rect { y: info_area.bottom(), height: 1, ..size };
+		
+		self.list_area = list_area;
+		self.page_height = usize::from(list_area.height);
+		
+		// Keep the selected match visible.
+		if self.selected < self.scroll {
+			self.scroll = self.selected;
+		} else if self.selected >= self.scroll.saturating_add(self.page_height) {
+			self.scroll = self.selected.saturating_add(1).saturating_sub(self.page_height);
+		}
+		
+		frame.render_widget(Clear, size);
+		self.render_matches(frame, list_area);
+		self.render_info(frame, info_area);
+		self.render_prompt(frame, prompt_area);
+	}
+}
@@ -0,0 +1,154 @@
+/// Maximum number of positions of the first query character that are tried as the start of a match.
+const MAX_MATCH_STARTS: usize = 16;
+
+const MATCHED_CHAR_SCORE: i32 = 16;
+const CONSECUTIVE_BONUS: i32 = 12;
+const PATH_SEGMENT_START_BONUS: i32 = 10;
+const WORD_START_BONUS: i32 = 8;
+const FILE_NAME_BONUS: i32 = 4;
+
+/// Query of the fuzzy finder. Matching ignores case, unless the query contains an uppercase character.
+pub struct Query {
+	chars: Vec<char>,
+	case_sensitive: bool,
+}
+
+pub struct Match {
+	pub score: i32,
+	/// Character indices of matched characters.
+	pub positions: Vec<usize>,
+}
+
+impl Query {
+	pub fn new(text: &str) -> Self {
+		let case_sensitive = text.chars().any(char::is_uppercase);
+		let chars = text.chars().map(|c| if case_sensitive { c } else { fold_case(c) }).collect();
+		Self { chars, case_sensitive }
+	}
+	
+	pub fn is_empty(&self) -> bool {
+		self.chars.is_empty()
+	}
+	
+	/// Finds characters of the query in the same order in the text. Matches are preferred when characters are consecutive, start words or path segments, and are in the file name.
+	pub fn find(&self, text: &str) -> Option<Match> {
+		let original_chars = text.chars().collect::<Vec<_>>();
+		let chars = if self.case_sensitive { original_chars.clone() } else { original_chars.iter().copied().map(fold_case).collect() };
+		
+		let first_query_char = *self.chars.first()?;
+		let file_name_start = original_chars.iter().rposition(|c| *c == '/').map_or(0, |index| index.saturating_add(1));
+		
+		chars.iter()
+		     .enumerate()
+		     .filter(|(_, c)| **c == first_query_char)
+		     .take(MAX_MATCH_STARTS)
+		     .filter_map(|(start, _)| self.find_from(&chars, start))
+		     .map(|positions| Match { score: get_score(&original_chars, &positions, file_name_start), positions })
+		     .max_by_key(|m| m.score)
+	}
+	
+	/// Matches query characters to the earliest possible text characters, starting at the given position.
+	fn find_from(&self, chars: &[char], start: usize) -> Option<Vec<usize>> {
+		let mut positions = Vec::with_capacity(self.chars.len());
+		let mut next_index = start;
+		
+		for query_char in &self.chars {
+			let offset = chars.get(next_index..)?.iter().position(|c| c == query_char)?;
+			let index = next_index.saturating_add(offset);
+			positions.push(index);
+			next_index = index.saturating_add(1);
+		}
+		
+		Some(positions)
+	}
+}
+
+fn get_score(chars: &[char], positions: &[usize], file_name_start: usize) -> i32 {
+	let mut score: i32 = 0;
+	let mut previous_position = None;
+	
+	for position in positions {
+		score = score.saturating_add(MATCHED_CHAR_SCORE);
+		
+		let previous_char = position.checked_sub(1).and_then(|index| chars.get(index));
+		let current_char = chars.get(*position);
+		
+		score = score.saturating_add(match (previous_char, current_char) {
+			(None | Some('/'), _) => PATH_SEGMENT_START_BONUS,
+			(Some('_' | '-' | '.' | ' '), _) => WORD_START_BONUS,
+			(Some(previous), Some(current)) if previous.is_lowercase() && current.is_uppercase() => WORD_START_BONUS,
+			_ => 0,
+		});
+		
+		if *position >= file_name_start {
+			score = score.saturating_add(FILE_NAME_BONUS);
+		}
+		
+		if let Some(previous_position) = previous_position {
+			let gap = position.saturating_sub(previous_position).saturating_sub(1);
+			if gap == 0 {
+				score = score.saturating_add(CONSECUTIVE_BONUS);
+			} else {
+				score = score.saturating_sub(i32::try_from(gap).unwrap_or(i32::MAX));
+			}
+		}
+		
+		previous_position = Some(*position);
+	}
+	
+	score
+}
+
+fn fold_case(c: char) -> char {
+	c.to_lowercase().next().unwrap_or(c)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::component::filesystem::finder::score::Query;
+	
+	fn positions(query: &str, text: &str) -> Option<Vec<usize>> {
+		Query::new(query).find(text).map(|m| m.positions)
+	}
+	
+	fn score(query: &str, text: &str) -> i32 {
+		Query::new(query).find(text).map_or(i32::MIN, |m| m.score)
+	}
+	
+	#[test]
+	fn matches_characters_in_order() {
+		assert_eq!(positions("abc", "aXbXc"), Some(vec![0, 2, 4]));
+		assert_eq!(positions("abc", "acb"), None);
+		assert_eq!(positions("abc", "ab"), None);
+		assert_eq!(positions("", "abc"), None);
+		assert!(Query::new("").is_empty());
+	}
+	
+	#[test]
+	fn ignores_case_unless_query_has_uppercase() {
+		assert_eq!(positions("readme", "README.md"), Some(vec![0, 1, 2, 3, 4, 5]));
+		assert_eq!(positions("ReadMe", "readme.md"), None);
+		assert_eq!(positions("ReadMe", "src/ReadMe"), Some(vec![4, 5, 6, 7, 8, 9]));
+	}
+	
+	#[test]
+	fn prefers_consecutive_characters() {
+		assert_eq!(positions("ab", "a_xab"), Some(vec![3, 4]));
+		assert!(score("ab", "ab") > score("ab", "axb"));
+		assert!(score("ab", "axb") > score("ab", "axxxxb"));
+	}
+	
+	#[test]
+	fn prefers_word_and_path_segment_starts() {
+		assert!(score("b", "a/b") > score("b", "a_b"));
+		assert!(score("b", "a_b") > score("b", "ab"));
+		assert!(score("b", "aB") > score("b", "ab"));
+		assert!(score("fb", "foo_bar") > score("fb", "fxxb"));
+	}
+	
+	#[test]
+	fn prefers_matches_in_file_name() {
+		assert!(score("main", "src/main.rs") > score("main", "main/lib.rs"));
+		assert_eq!(positions("mod", "mod/x/mod.rs"), Some(vec![6, 7, 8]));
+	}
+}
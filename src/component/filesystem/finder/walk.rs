@@ -0,0 +1,49 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use crate::component::filesystem::tree::FsTreeViewFilter;
+use crate::component::filesystem::walk::{self, WalkEntry};
+use crate::file::FileSystem;
+
+/// Maximum number of entries visited by a walk, which keeps memory use and scoring time bounded in huge directory trees.
+pub const MAX_VISITED_ENTRIES: usize = 200_000;
+
+/// Number of entries collected before they are sent to the finder.
+const BATCH_SIZE: usize = 1_000;
+
+pub enum WalkMessage {
+	Entries(Vec<WalkEntry>),
+	Finished { reached_limit: bool },
+}
+
+/// Starts walking the root directory in a background thread, which sends entries until it visits all entries allowed by the filter, reaches the limit, or the receiver is dropped.
+pub fn start(file_system: Arc<dyn FileSystem>, root_path: &Path, filter: FsTreeViewFilter) -> Receiver<WalkMessage> {
+	let (sender, receiver) = mpsc::channel();
+	let root_path = root_path.to_path_buf();
+	
+	thread::spawn(move || {
+		let mut batch = Vec::with_capacity(BATCH_SIZE);
+		let mut visited_entries: usize = 0;
+		let mut is_cancelled = false;
+		
+		walk::walk(file_system.as_ref(), &root_path, filter, |entry| {
+			batch.push(entry);
+			visited_entries = visited_entries.saturating_add(1);
+			
+			if batch.len() >= BATCH_SIZE && sender.send(WalkMessage::Entries(std::mem::replace(&mut batch, Vec::with_capacity(BATCH_SIZE)))).is_err() {
+				is_cancelled = true;
+			}
+			
+			!is_cancelled && visited_entries < MAX_VISITED_ENTRIES
+		});
+		
+		if !is_cancelled {
+			let _ = sender.send(WalkMessage::Entries(batch));
+			let _ = sender.send(WalkMessage::Finished { reached_limit: visited_entries >= MAX_VISITED_ENTRIES });
+		}
+	});
+	
+	receiver
+}
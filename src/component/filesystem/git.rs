@@ -0,0 +1,67 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use crate::file::{GitRepositoryStatus, GitStatus};
+
+/// Keeps git status of the repository containing the root directory, and reads it again in a background thread when requested.
+pub struct GitStatusTracker {
+	status: Option<GitRepositoryStatus>,
+	pending: Option<Receiver<Option<GitRepositoryStatus>>>,
+	requested_path: Option<PathBuf>,
+}
+
+impl GitStatusTracker {
+	pub const fn new() -> Self {
+		Self { status: None, pending: None, requested_path: None }
+	}
+	
+	/// Requests reading git status of the repository containing the path. If a previous read is still running, the new read starts after it finishes.
+	pub fn refresh(&mut self, path: &Path) {
+		self.requested_path = Some(path.to_path_buf());
+		
+		if self.pending.is_none() {
+			self.start_pending_refresh();
+		}
+	}
+	
+	fn start_pending_refresh(&mut self) {
+		let Some(path) = self.requested_path.take() else {
+			return;
+		};
+		
+		let (sender, receiver) = mpsc::channel();
+		
+		thread::spawn(move || {
+			let _ = sender.send(GitRepositoryStatus::read(&path).ok().flatten());
+		});
+		
+		self.pending = Some(receiver);
+	}
+	
+	/// Checks whether the background thread finished reading git status, and returns `true` if the status was updated.
+	pub fn poll(&mut self) -> bool {
+		let Some(receiver) = &self.pending else {
+			return false;
+		};
+		
+		let status = match receiver.try_recv() {
+			Ok(status) => status,
+			Err(TryRecvError::Empty) => return false,
+			Err(TryRecvError::Disconnected) => None,
+		};
+		
+		self.pending = None;
+		self.status = status;
+		self.start_pending_refresh();
+		true
+	}
+	
+	pub const fn is_in_repository(&self) -> bool {
+		self.status.is_some()
+	}
+	
+	pub fn get(&self, path: &Path) -> Option<GitStatus> {
+		self.status.as_ref().and_then(|status| status.get(path))
+	}
+}
@@ -0,0 +1,289 @@
+use std::cmp::min;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, Paragraph};
+use regex::Regex;
+
+use crate::component::dialog::message::MessageDialogLayer;
+use crate::component::filesystem::action::file::open_default_editor_at_line;
+use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::grep::search::{GrepMatch, MAX_MATCHES, SearchMessage};
+use crate::component::filesystem::status::StatusMessage;
+use crate::component::filesystem::tree::FsTreeViewFilter;
+use crate::file::FileSystem;
+use crate::input::keymap::KeyBinding;
+use crate::state::action::ActionResult;
+use crate::state::Environment;
+use crate::state::event::{EventQueue, EventResult};
+use crate::state::layer::Layer;
+use crate::state::view::Frame;
+use crate::theme::theme;
+
+mod search;
+
+const WHEEL_SCROLL_LINES: usize = 3;
+
+/// Full screen list of lines that match a regular expression, in all files inside a directory. Matches are found by a background search and appear while it runs.
+pub struct GrepLayer {
+	root_path: PathBuf,
+	regex: Regex,
+	events: EventQueue<FsLayer>,
+	search: Option<Receiver<SearchMessage>>,
+	reached_limit: bool,
+	searched_files: usize,
+	matches: Vec<GrepMatch>,
+	selected: usize,
+	scroll: usize,
+	page_height: usize,
+	list_area: Rect,
+}
+
+impl GrepLayer {
+	pub fn new(root_path: &Path, filter: FsTreeViewFilter, regex: Regex, file_system: Arc<dyn FileSystem>, events: EventQueue<FsLayer>) -> Self {
+		Self {
+			root_path: root_path.to_path_buf(),
+			search: Some(search::start(file_system, root_path, filter, regex.clone())),
+			regex,
+			events,
+			reached_limit: false,
+			searched_files: 0,
+			matches: Vec::new(),
+			selected: 0,
+			scroll: 0,
+			page_height: 0,
+			list_area: Rect::default(),
+		}
+	}
+	
+	/// Receives matches found by the search since the last check, and returns `true` if anything changed.
+	fn receive_matches(&mut self) -> bool {
+		let Some(receiver) = &self.search else {
+			return false;
+		};
+		
+		let mut changed = false;
+		
+		loop {
+			match receiver.try_recv() {
+				Ok(SearchMessage::Progress { matches, searched_files }) => {
+					self.matches.extend(matches);
+					self.searched_files = searched_files;
+					changed = true;
+				}
+				
+				Ok(SearchMessage::Finished { reached_limit }) => {
+					self.reached_limit = reached_limit;
+					self.search = None;
+					return true;
+				}
+				
+				Err(TryRecvError::Empty) => {
+					return changed;
+				}
+				
+				Err(TryRecvError::Disconnected) => {
+					self.search = None;
+					return true;
+				}
+			}
+		}
+	}
+	
+	fn move_selection_to(&mut self, index: usize) -> ActionResult {
+		let index = min(index, self.matches.len().saturating_sub(1));
+		let changed = self.selected != index;
+		self.selected = index;
+		ActionResult::draw_if(changed)
+	}
+	
+	fn move_selection_down(&mut self, lines: usize) -> ActionResult {
+		self.move_selection_to(self.selected.saturating_add(lines))
+	}
+	
+	fn move_selection_up(&mut self, lines: usize) -> ActionResult {
+		self.move_selection_to(self.selected.saturating_sub(lines))
+	}
+	
+	fn reveal_selected(&self) -> ActionResult {
+		let Some(selected) = self.matches.get(self.selected) else {
+			return ActionResult::Nothing;
+		};
+		
+		let path = selected.path.clone();
+		let relative_path = selected.relative_path.clone();
+		
+		self.events.enqueue_fn(move |layer, _| {
+			if !layer.tree.select_path(&path) {
+				layer.show_message(StatusMessage::error(format!("Entry is not visible in the tree: {relative_path}")));
+			}
+			
+			EventResult::Draw
+		});
+		
+		ActionResult::PopLayer
+	}
+	
+	fn edit_selected(&self) -> ActionResult {
+		let Some(selected) = self.matches.get(self.selected) else {
+			return ActionResult::Nothing;
+		};
+		
+		match open_default_editor_at_line(&selected.path, selected.line_number) {
+			Ok(()) => ActionResult::Redraw,
+			Err(message) => ActionResult::push_layer(MessageDialogLayer::error(self.list_area.y, message)),
+		}
+	}
+	
+	fn render_title(&self, frame: &mut Frame, area: Rect) {
+		let text = format!(" grep {} in {}", self.regex.as_str(), self.root_path.to_string_lossy());
+		frame.render_widget(Paragraph::new(text).style(theme().status_bar.add_modifier(Modifier::BOLD)), area);
+	}
+	
+	fn render_matches(&self, frame: &mut Frame, area: Rect) {
+		let theme = theme();
+		
+		let lines = self.matches.iter()
+			.skip(self.scroll)
+			.take(usize::from(area.height))
+			.map(|m| {
+				let line = m.line.trim_start();
+				
+				let mut spans = vec![
+					Span::raw(" "),
+					Span::styled(m.relative_path.as_str(), theme.secondary),
+					Span::raw(":"),
+					Span::styled(m.line_number.to_string(), theme.dialog_key),
+					Span::raw(": "),
+				];
+				
+				spans.extend(get_highlighted_line_spans(line, &self.regex));
+				Line::from(spans)
+			})
+			.collect::<Vec<_>>();
+		
+		frame.render_widget(Paragraph::new(lines), area);
+		
+		if let Some(selected_y) = self.selected.checked_sub(self.scroll).and_then(|offset| u16::try_from(offset).ok()).filter(|offset| *offset < area.height) {
+			frame.render_widget(Block::default().style(theme.selected), Rect { y: area.y.saturating_add(selected_y), height: 1, ..area });
+		}
+	}
+	
+	fn render_footer(&self, frame: &mut Frame, area: Rect) {
+		let counts = format!(" {} matches in {} searched files", self.matches.len(), self.searched_files);
+		
+		let text = if self.search.is_some() {
+			format!("{counts}  Searching...")
+		} else if self.reached_limit {
+			format!("{counts}  Stopped after {MAX_MATCHES} matches")
+		} else {
+			format!("{counts}  Enter: reveal  e: edit  q: close")
+		};
+		
+		frame.render_widget(Paragraph::new(text).style(theme().status_bar), area);
+	}
+}
+
+/// Creates spans of the line with all matches of the regular expression highlighted.
+fn get_highlighted_line_spans<'a>(line: &'a str, regex: &Regex) -> Vec<Span<'a>> {
+	let highlight_style = theme().dialog_key.add_modifier(Modifier::BOLD);
+	let mut spans = Vec::new();
+	let mut last_end = 0;
+	
+	for m in regex.find_iter(line) {
+		if let Some(before) = line.get(last_end..m.start()) {
+			spans.push(Span::styled(before, Style::default()));
+		}
+		
+		spans.push(Span::styled(m.as_str(), highlight_style));
+		last_end = m.end();
+	}
+	
+	if let Some(after) = line.get(last_end..) {
+		spans.push(Span::styled(after, Style::default()));
+	}
+	
+	spans
+}
+
+impl Layer for GrepLayer {
+	#[allow(clippy::wildcard_enum_match_arm)]
+	fn handle_input(&mut self, _environment: &Environment, key_binding: KeyBinding) -> ActionResult {
+		match (key_binding.code(), key_binding.modifiers()) {
+			(KeyCode::Esc | KeyCode::Char('q'), KeyModifiers::NONE) |
+			(KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+				ActionResult::PopLayer
+			}
+			
+			(KeyCode::Enter, KeyModifiers::NONE) => self.reveal_selected(),
+			(KeyCode::Char('e'), KeyModifiers::NONE) => self.edit_selected(),
+			
+			(KeyCode::Char('j') | KeyCode::Down, KeyModifiers::NONE) => self.move_selection_down(1),
+			(KeyCode::Char('k') | KeyCode::Up, KeyModifiers::NONE) => self.move_selection_up(1),
+			(KeyCode::Char('d'), KeyModifiers::CONTROL) => self.move_selection_down(self.page_height / 2),
+			(KeyCode::Char('u'), KeyModifiers::CONTROL) => self.move_selection_up(self.page_height / 2),
+			(KeyCode::Char('f'), KeyModifiers::CONTROL) | (KeyCode::PageDown, KeyModifiers::NONE) => self.move_selection_down(self.page_height),
+			(KeyCode::Char('b'), KeyModifiers::CONTROL) | (KeyCode::PageUp, KeyModifiers::NONE) => self.move_selection_up(self.page_height),
+			(KeyCode::Char('g') | KeyCode::Home, KeyModifiers::NONE) => self.move_selection_to(0),
+			(KeyCode::Char('G') | KeyCode::End, KeyModifiers::NONE) => self.move_selection_to(usize::MAX),
+			
+			_ => ActionResult::Nothing
+		}
+	}
+	
+	#[allow(clippy::wildcard_enum_match_arm)]
+	fn handle_mouse(&mut self, _environment: &Environment, event: MouseEvent) -> ActionResult {
+		match event.kind {
+			MouseEventKind::ScrollDown => self.move_selection_down(WHEEL_SCROLL_LINES),
+			MouseEventKind::ScrollUp => self.move_selection_up(WHEEL_SCROLL_LINES),
+			
+			MouseEventKind::Down(MouseButton::Left) => {
+				let area = self.list_area;
+				let index = self.scroll.saturating_add(usize::from(event.row.saturating_sub(area.y)));
+				
+				if event.row >= area.y && event.row < area.bottom() && index < self.matches.len() {
+					self.move_selection_to(index)
+				} else {
+					ActionResult::Nothing
+				}
+			}
+			
+			_ => ActionResult::Nothing
+		}
+	}
+	
+	fn handle_events(&mut self, _environment: &Environment) -> EventResult {
+		EventResult::draw_if(self.receive_matches())
+	}
+	
+	fn render(&mut self, frame: &mut Frame) {
+		let size = frame.size();
+		if size.height < 3 {
+			return;
+		}
+		
+		let title_area = Rect { height: 1, ..size };
+		let list_area = Rect { y: size.y.saturating_add(1), height: size.height.saturating_sub(2), ..size };
+		let footer_area = Rect { y: size.bottom().saturating_sub(1), height: 1, ..size };
+		
+		self.list_area = list_area;
+		self.page_height = usize::from(list_area.height);
+		
+		// Keep the selected match visible.
+		if self.selected < self.scroll {
+			self.scroll = self.selected;
+		} else if self.selected >= self.scroll.saturating_add(self.page_height) {
+			self.scroll = self.selected.saturating_add(1).saturating_sub(self.page_height);
+		}
+		
+		frame.render_widget(Clear, size);
+		self.render_title(frame, title_area);
+		self.render_matches(frame, list_area);
+		self.render_footer(frame, footer_area);
+	}
+}
@@ -0,0 +1,122 @@
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use regex::Regex;
+
+use crate::component::filesystem::tree::FsTreeViewFilter;
+use crate::component::filesystem::walk::{self, WalkEntry};
+use crate::file::{FileKind, FileSystem};
+
+/// Maximum number of matching lines, after which the search stops.
+pub const MAX_MATCHES: usize = 10_000;
+
+/// Files larger than this are skipped, because they are read into memory at once.
+const MAX_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Files with a zero byte within this many bytes from the start are considered binary, which is the same heuristic `grep` uses.
+const BINARY_CHECK_LENGTH: usize = 8 * 1024;
+
+/// Longer lines are truncated, since only their beginning fits on the screen.
+const MAX_LINE_LENGTH: usize = 1_000;
+
+/// How many files are searched between progress updates, if they contain no matches.
+const PROGRESS_INTERVAL: usize = 100;
+
+pub struct GrepMatch {
+	pub path: PathBuf,
+	pub relative_path: String,
+	pub line_number: usize,
+	pub line: String,
+}
+
+pub enum SearchMessage {
+	Progress { matches: Vec<GrepMatch>, searched_files: usize },
+	Finished { reached_limit: bool },
+}
+
+/// Starts searching contents of all files in the root directory and its subdirectories in a background thread. The search skips binary files and entries hidden by the filter, and stops when the receiver is dropped.
+pub fn start(file_system: Arc<dyn FileSystem>, root_path: &Path, filter: FsTreeViewFilter, regex: Regex) -> Receiver<SearchMessage> {
+	let (sender, receiver) = mpsc::channel();
+	let root_path = root_path.to_path_buf();
+	
+	thread::spawn(move || {
+		let mut search = Search { file_system: Arc::clone(&file_system), regex, sender, pending_matches: Vec::new(), total_matches: 0, searched_files: 0, is_cancelled: false };
+		
+		walk::walk(file_system.as_ref(), &root_path, filter, |entry| search.search_file(&entry));
+		
+		if !search.is_cancelled {
+			search.send_progress();
+			let _ = search.sender.send(SearchMessage::Finished { reached_limit: search.total_matches >= MAX_MATCHES });
+		}
+	});
+	
+	receiver
+}
+
+struct Search {
+	file_system: Arc<dyn FileSystem>,
+	regex: Regex,
+	sender: Sender<SearchMessage>,
+	pending_matches: Vec<GrepMatch>,
+	total_matches: usize,
+	searched_files: usize,
+	is_cancelled: bool,
+}
+
+impl Search {
+	/// Searches a file, and returns whether the search should continue.
+	fn search_file(&mut self, entry: &WalkEntry) -> bool {
+		if entry.is_directory || !matches!(self.file_system.stat(&entry.path).kind(), FileKind::File { size } if *size <= MAX_FILE_SIZE) {
+			return true;
+		}
+		
+		self.searched_files = self.searched_files.saturating_add(1);
+		
+		if let Ok(bytes) = self.read_file(&entry.path) {
+			if !bytes.iter().take(BINARY_CHECK_LENGTH).any(|byte| *byte == 0) {
+				self.find_matches(entry, &String::from_utf8_lossy(&bytes));
+			}
+		}
+		
+		if !self.pending_matches.is_empty() || self.searched_files % PROGRESS_INTERVAL == 0 {
+			self.send_progress();
+		}
+		
+		!self.is_cancelled && self.total_matches < MAX_MATCHES
+	}
+	
+	fn read_file(&self, path: &Path) -> io::Result<Vec<u8>> {
+		let mut bytes = Vec::new();
+		self.file_system.open_file(path)?.read_to_end(&mut bytes)?;
+		Ok(bytes)
+	}
+	
+	fn find_matches(&mut self, entry: &WalkEntry, contents: &str) {
+		for (line_index, line) in contents.lines().enumerate() {
+			if self.total_matches >= MAX_MATCHES {
+				return;
+			}
+			
+			if self.regex.is_match(line) {
+				self.total_matches = self.total_matches.saturating_add(1);
+				self.pending_matches.push(GrepMatch {
+					path: entry.path.clone(),
+					relative_path: entry.relative_path.clone(),
+					line_number: line_index.saturating_add(1),
+					line: line.trim_end().chars().take(MAX_LINE_LENGTH).collect(),
+				});
+			}
+		}
+	}
+	
+	fn send_progress(&mut self) {
+		let matches = std::mem::take(&mut self.pending_matches);
+		
+		if self.sender.send(SearchMessage::Progress { matches, searched_files: self.searched_files }).is_err() {
+			self.is_cancelled = true;
+		}
+	}
+}
@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use crate::file::{FileEntry, FileKind, FileSystem, strip_archive_extension};
+
+/// What to do with a copied or moved entry whose name is already taken in the target directory.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ConflictResolution {
+	/// Replaces the existing entry once the copied or moved entry is in place next to it.
+	Overwrite,
+	/// Leaves both entries where they are.
+	Skip,
+	/// Adds a number to the name of the copied or moved entry.
+	Rename,
+	/// Copies or moves the contents of a directory into the existing directory. Conflicts inside it are resolved separately.
+	Merge,
+	/// Overwrites the existing entry if it was modified before the copied or moved entry, and skips it otherwise.
+	OverwriteIfNewer,
+}
+
+/// Resolutions of conflicts chosen before a job starts, keyed by the path that is already taken. Conflicts without a resolution fail the job, which happens if something was created in the target directory after the resolutions were chosen.
+#[derive(Clone, Default)]
+pub struct ConflictResolutions {
+	resolutions: HashMap<PathBuf, ConflictResolution>,
+}
+
+impl ConflictResolutions {
+	pub fn insert(&mut self, target: PathBuf, resolution: ConflictResolution) {
+		self.resolutions.insert(target, resolution);
+	}
+	
+	pub fn get(&self, target: &Path) -> Option<ConflictResolution> {
+		self.resolutions.get(target).copied()
+	}
+}
+
+pub(super) enum ResolvedTarget {
+	/// Nothing exists at the path.
+	Create(PathBuf),
+	/// Something exists at the path, and is replaced using [`replace_target`].
+	Replace(PathBuf),
+	Merge,
+	Skip,
+}
+
+pub(super) fn resolve_target(file_system: &dyn FileSystem, resolutions: &ConflictResolutions, entry: &FileEntry, target: &Path) -> io::Result<ResolvedTarget> {
+	if !file_system.exists(target) {
+		return Ok(ResolvedTarget::Create(target.to_path_buf()));
+	}
+	
+	match resolutions.get(target) {
+		None => {
+			Err(io::Error::new(ErrorKind::AlreadyExists, format!("Something named {} already exists", target.to_string_lossy())))
+		}
+		
+		Some(ConflictResolution::Skip) => {
+			Ok(ResolvedTarget::Skip)
+		}
+		
+		Some(ConflictResolution::Overwrite) => {
+			Ok(ResolvedTarget::Replace(target.to_path_buf()))
+		}
+		
+		Some(ConflictResolution::OverwriteIfNewer) => {
+			if is_newer(entry, &file_system.stat(target)) {
+				Ok(ResolvedTarget::Replace(target.to_path_buf()))
+			} else {
+				Ok(ResolvedTarget::Skip)
+			}
+		}
+		
+		Some(ConflictResolution::Rename) => {
+			find_free_name(file_system, target).map(ResolvedTarget::Create)
+		}
+		
+		Some(ConflictResolution::Merge) => {
+			if matches!(entry.kind(), FileKind::Directory) && matches!(file_system.stat(target).kind(), FileKind::Directory) {
+				Ok(ResolvedTarget::Merge)
+			} else {
+				Err(io::Error::new(ErrorKind::AlreadyExists, format!("Cannot merge {} into something that is not a directory", target.to_string_lossy())))
+			}
+		}
+	}
+}
+
+/// Creates the new entry under a free temporary name next to the target, and replaces the target with it only once it was fully created, so that the target is kept if creating the new entry fails. If the target cannot be replaced, the new entry keeps the temporary name.
+pub(super) fn replace_target<F>(file_system: &dyn FileSystem, target: &Path, create: F) -> io::Result<()> where F: FnOnce(&Path) -> io::Result<()> {
+	let temporary = find_temporary_name(file_system, target)?;
+	
+	if let Err(e) = create(&temporary) {
+		if file_system.exists(&temporary) {
+			let _ = file_system.remove(&temporary);
+		}
+		
+		return Err(e);
+	}
+	
+	file_system.remove(target)
+	           .and_then(|()| file_system.rename(&temporary, target))
+	           .map_err(|e| io::Error::new(e.kind(), format!("Could not replace {}, the new entry was kept as {}: {}", target.to_string_lossy(), temporary.to_string_lossy(), e.kind())))
+}
+
+/// Finds the first free name of the form `.name.bark-1` next to the target.
+fn find_temporary_name(file_system: &dyn FileSystem, target: &Path) -> io::Result<PathBuf> {
+	let (Some(parent), Some(name)) = (target.parent(), target.file_name()) else {
+		return Err(io::Error::new(ErrorKind::InvalidInput, format!("Cannot replace {}", target.to_string_lossy())));
+	};
+	
+	for number in 1_u32.. {
+		let mut temporary_name = OsString::from(".");
+		temporary_name.push(name);
+		temporary_name.push(format!(".bark-{number}"));
+		
+		let candidate = parent.join(temporary_name);
+		if !file_system.exists(&candidate) {
+			return Ok(candidate);
+		}
+	}
+	
+	Err(io::Error::new(ErrorKind::AlreadyExists, format!("Cannot find a temporary name for {}", target.to_string_lossy())))
+}
+
+/// Entries without a modification time are never considered newer.
+fn is_newer(entry: &FileEntry, existing: &FileEntry) -> bool {
+	match (entry.modified_time(), existing.modified_time()) {
+		(Some(entry_time), Some(existing_time)) => entry_time > existing_time,
+		(Some(_), None) => true,
+		(None, _) => false,
+	}
+}
+
+/// Finds the first free name of the form `name (1).ext` next to the taken path. Archive extensions such as `.tar.gz` are kept together.
+fn find_free_name(file_system: &dyn FileSystem, taken: &Path) -> io::Result<PathBuf> {
+	let (Some(parent), Some((stem, extension))) = (taken.parent(), split_extension(taken)) else {
+		return Err(io::Error::new(ErrorKind::InvalidInput, format!("Cannot rename {}", taken.to_string_lossy())));
+	};
+	
+	for number in 1_u32.. {
+		let mut name = stem.clone();
+		name.push(format!(" ({number})"));
+		name.push(&extension);
+		
+		let candidate = parent.join(name);
+		if !file_system.exists(&candidate) {
+			return Ok(candidate);
+		}
+	}
+	
+	Err(io::Error::new(ErrorKind::AlreadyExists, format!("Cannot find a free name for {}", taken.to_string_lossy())))
+}
+
+/// Splits the file name into the stem and the extension including its dot, which is empty if there is no extension.
+fn split_extension(path: &Path) -> Option<(OsString, OsString)> {
+	let name = path.file_name()?;
+	
+	if let Some(stem) = name.to_str().and_then(strip_archive_extension).filter(|stem| !stem.is_empty()) {
+		let extension = name.to_str().and_then(|name| name.get(stem.len()..)).unwrap_or_default();
+		return Some((OsString::from(stem), OsString::from(extension)));
+	}
+	
+	let stem = path.file_stem()?.to_os_string();
+	let extension = path.extension().map_or_else(OsString::new, |extension| {
+		let mut dot_extension = OsString::from(".");
+		dot_extension.push(extension);
+		dot_extension
+	});
+	
+	Some((stem, extension))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::{Path, PathBuf};
+	
+	use crate::component::filesystem::jobs::conflict::find_free_name;
+	use crate::file::MemoryFileSystem;
+	
+	fn free_name(file_system: &MemoryFileSystem, taken: &str) -> Option<PathBuf> {
+		find_free_name(file_system, Path::new(taken)).ok()
+	}
+	
+	#[test]
+	fn adds_number_before_extension() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/a/file.txt", "")
+			.with_file("/a/file (1).txt", "")
+			.with_directory("/a/dir.d")
+			.with_file("/a/.hidden", "")
+			.with_file("/a/plain", "");
+		
+		assert_eq!(free_name(&file_system, "/a/file.txt"), Some(PathBuf::from("/a/file (2).txt")));
+		assert_eq!(free_name(&file_system, "/a/dir.d"), Some(PathBuf::from("/a/dir (1).d")));
+		assert_eq!(free_name(&file_system, "/a/.hidden"), Some(PathBuf::from("/a/.hidden (1)")));
+		assert_eq!(free_name(&file_system, "/a/plain"), Some(PathBuf::from("/a/plain (1)")));
+	}
+	
+	#[test]
+	fn keeps_archive_extensions_together() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/a/x.tar.gz", "")
+			.with_file("/a/x.tar.xz", "")
+			.with_file("/a/x.TAR.ZST", "")
+			.with_file("/a/.tar.gz", "");
+		
+		assert_eq!(free_name(&file_system, "/a/x.tar.gz"), Some(PathBuf::from("/a/x (1).tar.gz")));
+		assert_eq!(free_name(&file_system, "/a/x.tar.xz"), Some(PathBuf::from("/a/x (1).tar.xz")));
+		assert_eq!(free_name(&file_system, "/a/x.TAR.ZST"), Some(PathBuf::from("/a/x (1).TAR.ZST")));
+		assert_eq!(free_name(&file_system, "/a/.tar.gz"), Some(PathBuf::from("/a/.tar (1).gz")));
+	}
+}
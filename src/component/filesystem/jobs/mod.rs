@@ -0,0 +1,464 @@
+use std::cell::RefCell;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::component::filesystem::action::file::format_io_error;
+use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::render::SizeFormat;
+use crate::state::event::EventResult;
+
+pub use self::conflict::{ConflictResolution, ConflictResolutions};
+pub use self::panel::JobsLayer;
+pub use self::work::*;
+
+mod conflict;
+mod panel;
+mod work;
+
+/// How often the tree is redrawn while jobs report progress.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How often a paused job checks whether it was resumed or cancelled.
+const PAUSE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How many finished jobs are kept in the list, so that their results can be seen in the jobs panel.
+const MAX_FINISHED_JOBS: usize = 50;
+
+/// List of file operations that run in background threads. Clones share the same list, so that the jobs panel can show and control jobs that the tree owns.
+pub struct Jobs {
+	list: Rc<RefCell<JobList>>,
+}
+
+struct JobList {
+	jobs: Vec<Job>,
+	next_id: u32,
+	last_redraw: Instant,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct JobId(u32);
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum JobKind {
+	Copy,
+	Move,
+	Delete,
+	Scan,
+	Owner,
+	Extract,
+	Compress,
+}
+
+impl JobKind {
+	pub const fn name(self) -> &'static str {
+		match self {
+			Self::Copy => "copy",
+			Self::Move => "move",
+			Self::Delete => "delete",
+			Self::Scan => "scan",
+			Self::Owner => "owner",
+			Self::Extract => "extract",
+			Self::Compress => "compress",
+		}
+	}
+}
+
+#[derive(Clone)]
+pub enum JobStatus {
+	Running,
+	Succeeded,
+	Failed(String),
+	Cancelled,
+}
+
+/// Number of entries and bytes that a job has processed, out of the totals it found while scanning.
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
+pub struct JobProgress {
+	pub scanning: bool,
+	pub done_entries: u64,
+	pub total_entries: u64,
+	pub done_bytes: u64,
+	pub total_bytes: u64,
+}
+
+impl JobProgress {
+	/// Returns how much of the work is done. Bytes are used if there are any, because copying large files takes longer than creating entries.
+	pub fn percent(&self) -> u64 {
+		let (done, total) = if self.total_bytes > 0 { (self.done_bytes, self.total_bytes) } else { (self.done_entries, self.total_entries) };
+		
+		if total == 0 {
+			0
+		} else {
+			done.min(total).saturating_mul(100).checked_div(total).unwrap_or(0)
+		}
+	}
+	
+	pub fn describe(&self, size_format: SizeFormat) -> String {
+		if self.scanning {
+			format!("scanning, {} entries, {}", self.total_entries, size_format.format(self.total_bytes))
+		} else {
+			format!("{} of {} entries, {} of {}", self.done_entries, self.total_entries, size_format.format(self.done_bytes), size_format.format(self.total_bytes))
+		}
+	}
+}
+
+/// State of a job that its background thread shares with the tree.
+struct JobControl {
+	cancelled: AtomicBool,
+	paused: AtomicBool,
+	scanning: AtomicBool,
+	done_entries: AtomicU64,
+	total_entries: AtomicU64,
+	done_bytes: AtomicU64,
+	total_bytes: AtomicU64,
+	errors: Mutex<Vec<(PathBuf, io::Error)>>,
+}
+
+impl JobControl {
+	const fn new() -> Self {
+		Self {
+			cancelled: AtomicBool::new(false),
+			paused: AtomicBool::new(false),
+			scanning: AtomicBool::new(true),
+			done_entries: AtomicU64::new(0),
+			total_entries: AtomicU64::new(0),
+			done_bytes: AtomicU64::new(0),
+			total_bytes: AtomicU64::new(0),
+			errors: Mutex::new(Vec::new()),
+		}
+	}
+	
+	fn progress(&self) -> JobProgress {
+		JobProgress {
+			scanning: self.scanning.load(Ordering::Relaxed),
+			done_entries: self.done_entries.load(Ordering::Relaxed),
+			total_entries: self.total_entries.load(Ordering::Relaxed),
+			done_bytes: self.done_bytes.load(Ordering::Relaxed),
+			total_bytes: self.total_bytes.load(Ordering::Relaxed),
+		}
+	}
+}
+
+/// Handle that the work of a job uses to report progress, and to find out whether it should pause or stop.
+pub struct JobContext {
+	control: Arc<JobControl>,
+}
+
+impl JobContext {
+	/// Creates a context that does not belong to any job.
+	#[cfg(test)]
+	pub fn detached() -> Self {
+		Self { control: Arc::new(JobControl::new()) }
+	}
+	
+	#[cfg(test)]
+	pub fn progress(&self) -> JobProgress {
+		self.control.progress()
+	}
+	
+	/// Waits while the job is paused. Returns false once the job is cancelled, in which case the work must stop.
+	pub fn wait_if_paused(&self) -> bool {
+		while self.control.paused.load(Ordering::Relaxed) && !self.is_cancelled() {
+			thread::sleep(PAUSE_INTERVAL);
+		}
+		
+		!self.is_cancelled()
+	}
+	
+	/// Same as [`JobContext::wait_if_paused`], but fails with an error once the job is cancelled, so that it can be used with `?`.
+	pub fn checkpoint(&self) -> io::Result<()> {
+		if self.wait_if_paused() {
+			Ok(())
+		} else {
+			Err(io::Error::new(io::ErrorKind::Other, "Cancelled"))
+		}
+	}
+	
+	fn is_cancelled(&self) -> bool {
+		self.control.cancelled.load(Ordering::Relaxed)
+	}
+	
+	pub fn add_total(&self, entries: u64, bytes: u64) {
+		self.control.total_entries.fetch_add(entries, Ordering::Relaxed);
+		self.control.total_bytes.fetch_add(bytes, Ordering::Relaxed);
+	}
+	
+	pub fn add_done(&self, entries: u64, bytes: u64) {
+		self.control.done_entries.fetch_add(entries, Ordering::Relaxed);
+		self.control.done_bytes.fetch_add(bytes, Ordering::Relaxed);
+	}
+	
+	/// Records an error of an entry that the work skipped, so that it can be shown once the job finishes.
+	pub fn add_error(&self, path: &Path, error: io::Error) {
+		if let Ok(mut errors) = self.control.errors.lock() {
+			errors.push((path.to_path_buf(), error));
+		}
+	}
+	
+	/// Marks the end of scanning, after which the totals are known.
+	pub fn finish_scanning(&self) {
+		self.control.scanning.store(false, Ordering::Relaxed);
+	}
+}
+
+type JobCallback = Box<dyn Fn(&mut FsLayer, JobProgress) -> EventResult>;
+
+pub struct Job {
+	id: JobId,
+	kind: JobKind,
+	description: String,
+	control: Arc<JobControl>,
+	receiver: Option<Receiver<io::Result<()>>>,
+	status: JobStatus,
+	refresh_paths: Vec<PathBuf>,
+	success_message: Option<String>,
+	on_success: Option<JobCallback>,
+}
+
+impl Job {
+	pub fn build(kind: JobKind, description: impl Into<String>) -> JobBuilder {
+		JobBuilder {
+			kind,
+			description: description.into(),
+			refresh_paths: Vec::new(),
+			success_message: None,
+			on_success: None,
+		}
+	}
+	
+	pub const fn id(&self) -> JobId {
+		self.id
+	}
+	
+	pub const fn kind(&self) -> JobKind {
+		self.kind
+	}
+	
+	pub fn description(&self) -> &str {
+		&self.description
+	}
+	
+	pub const fn status(&self) -> &JobStatus {
+		&self.status
+	}
+	
+	pub const fn is_running(&self) -> bool {
+		matches!(self.status, JobStatus::Running)
+	}
+	
+	pub fn is_paused(&self) -> bool {
+		self.is_running() && self.control.paused.load(Ordering::Relaxed)
+	}
+	
+	pub fn is_cancelling(&self) -> bool {
+		self.is_running() && self.control.cancelled.load(Ordering::Relaxed)
+	}
+	
+	pub fn progress(&self) -> JobProgress {
+		self.control.progress()
+	}
+}
+
+pub struct JobBuilder {
+	kind: JobKind,
+	description: String,
+	refresh_paths: Vec<PathBuf>,
+	success_message: Option<String>,
+	on_success: Option<JobCallback>,
+}
+
+impl JobBuilder {
+	/// Directories that are refreshed in the tree once the job finishes, even if it failed or was cancelled, because it may have changed them partially.
+	pub fn refresh_paths(mut self, paths: Vec<PathBuf>) -> Self {
+		self.refresh_paths = paths;
+		self
+	}
+	
+	/// Message shown in the status bar once the job succeeds.
+	pub fn success_message(mut self, message: impl Into<String>) -> Self {
+		self.success_message = Some(message.into());
+		self
+	}
+	
+	/// Runs after the job succeeds, and receives its final progress.
+	pub fn on_success<F>(mut self, callback: F) -> Self where F: Fn(&mut FsLayer, JobProgress) -> EventResult + 'static {
+		self.on_success = Some(Box::new(callback));
+		self
+	}
+	
+	/// Runs the work in a background thread, and adds the job to the list.
+	pub fn start<W>(self, jobs: &Jobs, work: W) -> JobId where W: FnOnce(&JobContext) -> io::Result<()> + Send + 'static {
+		let control = Arc::new(JobControl::new());
+		let context = JobContext { control: Arc::clone(&control) };
+		let (sender, receiver) = mpsc::channel();
+		
+		thread::spawn(move || {
+			let result = work(&context);
+			context.finish_scanning();
+			let _ = sender.send(result);
+		});
+		
+		let mut list = jobs.list.borrow_mut();
+		let id = JobId(list.next_id);
+		list.next_id = list.next_id.wrapping_add(1);
+		
+		list.jobs.push(Job {
+			id,
+			kind: self.kind,
+			description: self.description,
+			control,
+			receiver: Some(receiver),
+			status: JobStatus::Running,
+			refresh_paths: self.refresh_paths,
+			success_message: self.success_message,
+			on_success: self.on_success,
+		});
+		
+		id
+	}
+}
+
+/// Result of a job that finished since the last poll, with everything the tree needs to show it.
+pub struct FinishedJob {
+	pub description: String,
+	pub status: JobStatus,
+	pub progress: JobProgress,
+	pub refresh_paths: Vec<PathBuf>,
+	pub success_message: Option<String>,
+	pub on_success: Option<JobCallback>,
+	pub errors: Vec<(PathBuf, io::Error)>,
+}
+
+impl Jobs {
+	pub fn new() -> Self {
+		Self {
+			list: Rc::new(RefCell::new(JobList {
+				jobs: Vec::new(),
+				next_id: 0,
+				last_redraw: Instant::now(),
+			})),
+		}
+	}
+	
+	pub fn rc_clone(&self) -> Self {
+		Self { list: Rc::clone(&self.list) }
+	}
+	
+	/// Calls the function with all jobs, oldest first.
+	pub fn with_jobs<R, F>(&self, f: F) -> R where F: FnOnce(&[Job]) -> R {
+		f(&self.list.borrow().jobs)
+	}
+	
+	pub fn running_count(&self) -> usize {
+		self.with_jobs(|jobs| jobs.iter().filter(|job| job.is_running()).count())
+	}
+	
+	/// Returns combined progress of all running jobs.
+	pub fn running_progress(&self) -> JobProgress {
+		self.with_jobs(|jobs| jobs.iter().filter(|job| job.is_running()).map(Job::progress).fold(JobProgress::default(), |total, progress| JobProgress {
+			scanning: total.scanning || progress.scanning,
+			done_entries: total.done_entries.saturating_add(progress.done_entries),
+			total_entries: total.total_entries.saturating_add(progress.total_entries),
+			done_bytes: total.done_bytes.saturating_add(progress.done_bytes),
+			total_bytes: total.total_bytes.saturating_add(progress.total_bytes),
+		}))
+	}
+	
+	/// Pauses a running job, or resumes it if it is paused. Returns `true` if the job is running.
+	pub fn toggle_pause(&self, id: JobId) -> bool {
+		self.with_running_job(id, |job| {
+			job.control.paused.fetch_xor(true, Ordering::Relaxed);
+		})
+	}
+	
+	/// Asks a running job to stop. The job finishes as cancelled once its background thread notices. Returns `true` if the job is running.
+	pub fn cancel(&self, id: JobId) -> bool {
+		self.with_running_job(id, |job| {
+			job.control.cancelled.store(true, Ordering::Relaxed);
+		})
+	}
+	
+	fn with_running_job<F>(&self, id: JobId, f: F) -> bool where F: FnOnce(&Job) {
+		if let Some(job) = self.list.borrow().jobs.iter().find(|job| job.id == id && job.is_running()) {
+			f(job);
+			true
+		} else {
+			false
+		}
+	}
+	
+	/// Removes finished jobs from the list. Returns `true` if any were removed.
+	pub fn clear_finished(&self) -> bool {
+		let jobs = &mut self.list.borrow_mut().jobs;
+		let count = jobs.len();
+		jobs.retain(Job::is_running);
+		jobs.len() != count
+	}
+	
+	/// Collects jobs whose background threads finished since the last poll. Also returns whether running jobs made progress that should be drawn.
+	pub fn poll(&self) -> (Vec<FinishedJob>, bool) {
+		let mut list = self.list.borrow_mut();
+		let mut finished = Vec::new();
+		
+		for job in &mut list.jobs {
+			let Some(receiver) = &job.receiver else {
+				continue;
+			};
+			
+			let result = match receiver.try_recv() {
+				Ok(result) => result,
+				Err(TryRecvError::Empty) => continue,
+				Err(TryRecvError::Disconnected) => Err(io::Error::new(io::ErrorKind::Other, "Background thread stopped unexpectedly")),
+			};
+			
+			job.receiver = None;
+			job.status = match result {
+				Ok(()) => JobStatus::Succeeded,
+				Err(_) if job.control.cancelled.load(Ordering::Relaxed) => JobStatus::Cancelled,
+				Err(e) => JobStatus::Failed(format_io_error(&e)),
+			};
+			
+			finished.push(FinishedJob {
+				description: job.description.clone(),
+				status: job.status.clone(),
+				progress: job.control.progress(),
+				refresh_paths: std::mem::take(&mut job.refresh_paths),
+				success_message: job.success_message.take(),
+				on_success: job.on_success.take(),
+				errors: job.control.errors.lock().map(|mut errors| std::mem::take(&mut *errors)).unwrap_or_default(),
+			});
+		}
+		
+		if !finished.is_empty() {
+			remove_oldest_finished_jobs(&mut list.jobs);
+		}
+		
+		let is_running = list.jobs.iter().any(Job::is_running);
+		let redraw = is_running && list.last_redraw.elapsed() >= PROGRESS_INTERVAL;
+		
+		if redraw {
+			list.last_redraw = Instant::now();
+		}
+		
+		(finished, redraw)
+	}
+}
+
+fn remove_oldest_finished_jobs(jobs: &mut Vec<Job>) {
+	let mut excess = jobs.iter().filter(|job| !job.is_running()).count().saturating_sub(MAX_FINISHED_JOBS);
+	
+	jobs.retain(|job| {
+		if excess > 0 && !job.is_running() {
+			excess = excess.saturating_sub(1);
+			false
+		} else {
+			true
+		}
+	});
+}
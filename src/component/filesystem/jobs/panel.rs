@@ -0,0 +1,211 @@
+use std::cmp::min;
+
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, Paragraph};
+
+use crate::component::filesystem::jobs::{Job, JobId, Jobs, JobStatus};
+use crate::component::filesystem::render::SizeFormat;
+use crate::input::keymap::KeyBinding;
+use crate::state::action::ActionResult;
+use crate::state::Environment;
+use crate::state::event::EventResult;
+use crate::state::layer::Layer;
+use crate::state::view::Frame;
+use crate::theme::theme;
+
+const WHEEL_SCROLL_LINES: usize = 3;
+
+/// Full screen list of background jobs with their progress. Running jobs can be paused, resumed, or cancelled, and finished jobs can be cleared. The tree keeps polling the jobs while the panel is open, so the list updates as they progress.
+pub struct JobsLayer {
+	jobs: Jobs,
+	size_format: SizeFormat,
+	selected: usize,
+	scroll: usize,
+	page_height: usize,
+	list_area: Rect,
+}
+
+impl JobsLayer {
+	pub fn new(jobs: Jobs, size_format: SizeFormat) -> Self {
+		Self {
+			jobs,
+			size_format,
+			selected: 0,
+			scroll: 0,
+			page_height: 0,
+			list_area: Rect::default(),
+		}
+	}
+	
+	fn job_count(&self) -> usize {
+		self.jobs.with_jobs(<[Job]>::len)
+	}
+	
+	fn selected_job_id(&self) -> Option<JobId> {
+		self.jobs.with_jobs(|jobs| jobs.get(self.selected).map(Job::id))
+	}
+	
+	fn move_selection_to(&mut self, index: usize) -> ActionResult {
+		let index = min(index, self.job_count().saturating_sub(1));
+		let changed = self.selected != index;
+		self.selected = index;
+		ActionResult::draw_if(changed)
+	}
+	
+	fn move_selection_down(&mut self, lines: usize) -> ActionResult {
+		self.move_selection_to(self.selected.saturating_add(lines))
+	}
+	
+	fn move_selection_up(&mut self, lines: usize) -> ActionResult {
+		self.move_selection_to(self.selected.saturating_sub(lines))
+	}
+	
+	fn toggle_pause_selected(&self) -> ActionResult {
+		ActionResult::draw_if(self.selected_job_id().is_some_and(|id| self.jobs.toggle_pause(id)))
+	}
+	
+	fn cancel_selected(&self) -> ActionResult {
+		ActionResult::draw_if(self.selected_job_id().is_some_and(|id| self.jobs.cancel(id)))
+	}
+	
+	fn clear_finished(&mut self) -> ActionResult {
+		let selected_job_id = self.selected_job_id();
+		
+		if !self.jobs.clear_finished() {
+			return ActionResult::Nothing;
+		}
+		
+		// Keep the same job selected if it is still running.
+		self.selected = self.jobs.with_jobs(|jobs| jobs.iter().position(|job| Some(job.id()) == selected_job_id)).unwrap_or(0);
+		ActionResult::Draw
+	}
+	
+	fn render_title(frame: &mut Frame, area: Rect) {
+		frame.render_widget(Paragraph::new(" jobs").style(theme().status_bar.add_modifier(Modifier::BOLD)), area);
+	}
+	
+	fn render_jobs(&self, frame: &mut Frame, area: Rect) {
+		let theme = theme();
+		
+		let lines = self.jobs.with_jobs(|jobs| {
+			jobs.iter()
+			    .skip(self.scroll)
+			    .take(usize::from(area.height))
+			    .map(|job| {
+				    let (state, details, style) = match job.status() {
+					    JobStatus::Running if job.is_cancelling() => (String::from("cancelling"), String::new(), theme.secondary),
+					    JobStatus::Running if job.is_paused() => (String::from("paused"), job.progress().describe(self.size_format), theme.secondary),
+					    JobStatus::Running => (format!("{}%", job.progress().percent()), job.progress().describe(self.size_format), theme.secondary),
+					    JobStatus::Succeeded => (String::from("done"), job.progress().describe(self.size_format), theme.secondary),
+					    JobStatus::Failed(error) => (String::from("failed"), error.clone(), theme.error_text),
+					    JobStatus::Cancelled => (String::from("cancelled"), String::new(), theme.secondary),
+				    };
+				
+				    Line::from(vec![
+					    Span::raw(format!(" {state:<10} {:<6} {}  ", job.kind().name(), job.description())),
+					    Span::styled(details, style),
+				    ])
+			    })
+			    .collect::<Vec<_>>()
+		});
+		
+		let has_jobs = !lines.is_empty();
+		frame.render_widget(Paragraph::new(lines), area);
+		
+		if has_jobs {
+			if let Some(selected_y) = self.selected.checked_sub(self.scroll).and_then(|offset| u16::try_from(offset).ok()).filter(|offset| *offset < area.height) {
+				frame.render_widget(Block::default().style(theme.selected), Rect { y: area.y.saturating_add(selected_y), height: 1, ..area });
+			}
+		}
+	}
+	
+	fn render_footer(&self, frame: &mut Frame, area: Rect) {
+		let text = format!(" {} jobs, {} running  p: pause or resume  c: cancel  x: clear finished  q: close", self.job_count(), self.jobs.running_count());
+		frame.render_widget(Paragraph::new(text).style(theme().status_bar), area);
+	}
+}
+
+impl Layer for JobsLayer {
+	#[allow(clippy::wildcard_enum_match_arm)]
+	fn handle_input(&mut self, _environment: &Environment, key_binding: KeyBinding) -> ActionResult {
+		match (key_binding.code(), key_binding.modifiers()) {
+			(KeyCode::Esc | KeyCode::Char('q'), KeyModifiers::NONE) |
+			(KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+				ActionResult::PopLayer
+			}
+			
+			(KeyCode::Char('p' | ' '), KeyModifiers::NONE) => self.toggle_pause_selected(),
+			(KeyCode::Char('c') | KeyCode::Delete, KeyModifiers::NONE) => self.cancel_selected(),
+			(KeyCode::Char('x'), KeyModifiers::NONE) => self.clear_finished(),
+			
+			(KeyCode::Char('j') | KeyCode::Down, KeyModifiers::NONE) => self.move_selection_down(1),
+			(KeyCode::Char('k') | KeyCode::Up, KeyModifiers::NONE) => self.move_selection_up(1),
+			(KeyCode::Char('d'), KeyModifiers::CONTROL) => self.move_selection_down(self.page_height / 2),
+			(KeyCode::Char('u'), KeyModifiers::CONTROL) => self.move_selection_up(self.page_height / 2),
+			(KeyCode::Char('g') | KeyCode::Home, KeyModifiers::NONE) => self.move_selection_to(0),
+			(KeyCode::Char('G') | KeyCode::End, KeyModifiers::NONE) => self.move_selection_to(usize::MAX),
+			
+			_ => ActionResult::Nothing
+		}
+	}
+	
+	#[allow(clippy::wildcard_enum_match_arm)]
+	fn handle_mouse(&mut self, _environment: &Environment, event: MouseEvent) -> ActionResult {
+		match event.kind {
+			MouseEventKind::ScrollDown => self.move_selection_down(WHEEL_SCROLL_LINES),
+			MouseEventKind::ScrollUp => self.move_selection_up(WHEEL_SCROLL_LINES),
+			
+			MouseEventKind::Down(MouseButton::Left) if event.row >= self.list_area.y && event.row < self.list_area.bottom() => {
+				let index = self.scroll.saturating_add(usize::from(event.row.saturating_sub(self.list_area.y)));
+				
+				if index < self.job_count() {
+					self.move_selection_to(index)
+				} else {
+					ActionResult::Nothing
+				}
+			}
+			
+			_ => ActionResult::Nothing
+		}
+	}
+	
+	fn handle_events(&mut self, _environment: &Environment) -> EventResult {
+		// Jobs may be removed from the list while the panel is open.
+		let job_count = self.job_count();
+		
+		if self.selected >= job_count && self.selected > 0 {
+			self.selected = job_count.saturating_sub(1);
+			EventResult::Draw
+		} else {
+			EventResult::Nothing
+		}
+	}
+	
+	fn render(&mut self, frame: &mut Frame) {
+		let size = frame.size();
+		if size.height < 3 {
+			return;
+		}
+		
+		let title_area = Rect { height: 1, ..size };
+		let list_area = Rect { y: size.y.saturating_add(1), height: size.height.saturating_sub(2), ..size };
+		let footer_area = Rect { y: size.bottom().saturating_sub(1), height: 1, ..size };
+		
+		self.list_area = list_area;
+		self.page_height = usize::from(list_area.height);
+		
+		if self.selected < self.scroll {
+			self.scroll = self.selected;
+		} else if self.selected >= self.scroll.saturating_add(self.page_height) {
+			self.scroll = self.selected.saturating_add(1).saturating_sub(self.page_height);
+		}
+		
+		frame.render_widget(Clear, size);
+		Self::render_title(frame, title_area);
+		self.render_jobs(frame, list_area);
+		self.render_footer(frame, footer_area);
+	}
+}
@@ -0,0 +1,535 @@
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use crate::component::filesystem::jobs::conflict::{ConflictResolutions, replace_target, resolve_target, ResolvedTarget};
+use crate::component::filesystem::jobs::JobContext;
+use crate::file::{ArchiveProgress, FileEntry, FileKind, FileSystem};
+
+/// Adds the entries, and everything inside directories up to the depth, to the totals of the job. Stops early once the number of entries reaches the limit. Returns the number of directories that could not be read, which are skipped.
+pub fn scan_entries(file_system: &dyn FileSystem, context: &JobContext, paths: &[PathBuf], max_depth: usize, entry_limit: u64) -> io::Result<usize> {
+	let mut remaining = paths.iter().map(|path| (file_system.stat(path), 0_usize)).collect::<Vec<_>>();
+	let mut scanned_entries = 0_u64;
+	let mut errors = 0_usize;
+	
+	while let Some((entry, depth)) = remaining.pop() {
+		context.checkpoint()?;
+		context.add_total(1, get_file_size(&entry));
+		
+		scanned_entries = scanned_entries.saturating_add(1);
+		if scanned_entries >= entry_limit {
+			break;
+		}
+		
+		if matches!(entry.kind(), FileKind::Directory) && depth < max_depth {
+			match entry.path().map(|path| file_system.list(path)) {
+				Some(Ok(children)) => remaining.extend(children.into_iter().map(|child| (child, depth.saturating_add(1)))),
+				Some(Err(_)) | None => errors = errors.saturating_add(1),
+			}
+		}
+	}
+	
+	Ok(errors)
+}
+
+/// Copies the entries into the directory, including everything inside directories. Copies keep the names of the originals, unless a resolution of a conflict renames them. Conflicts without a resolution fail the job. Entries that were copied before the job failed or stopped are kept.
+pub fn copy_entries(file_system: &dyn FileSystem, context: &JobContext, sources: &[PathBuf], target_directory: &Path, resolutions: &ConflictResolutions) -> io::Result<()> {
+	scan_entries(file_system, context, sources, usize::MAX, u64::MAX)?;
+	context.finish_scanning();
+	
+	for source in sources {
+		copy_tree(file_system, context, resolutions, &file_system.stat(source), &get_target_path(source, target_directory)?)?;
+	}
+	
+	Ok(())
+}
+
+fn copy_tree(file_system: &dyn FileSystem, context: &JobContext, resolutions: &ConflictResolutions, entry: &FileEntry, target: &Path) -> io::Result<()> {
+	context.checkpoint()?;
+	
+	match resolve_target(file_system, resolutions, entry, target)? {
+		ResolvedTarget::Create(target) => {
+			copy_new_tree(file_system, context, resolutions, entry, &target)
+		}
+		
+		ResolvedTarget::Replace(target) => {
+			replace_target(file_system, &target, |temporary| copy_new_tree(file_system, context, resolutions, entry, temporary))
+		}
+		
+		// Merged directories keep their own attributes.
+		ResolvedTarget::Merge => {
+			context.add_done(1, 0);
+			copy_children(file_system, context, resolutions, get_entry_path(entry)?, target)
+		}
+		
+		ResolvedTarget::Skip => {
+			context.add_done(1, get_file_size(entry));
+			Ok(())
+		}
+	}
+}
+
+/// Copies the entry, including everything inside a directory, to a path where nothing exists yet.
+fn copy_new_tree(file_system: &dyn FileSystem, context: &JobContext, resolutions: &ConflictResolutions, entry: &FileEntry, target: &Path) -> io::Result<()> {
+	let source = get_entry_path(entry)?;
+	
+	match entry.kind() {
+		FileKind::Directory => {
+			file_system.create_directory(target)?;
+			context.add_done(1, 0);
+			
+			copy_children(file_system, context, resolutions, source, target)?;
+			file_system.copy_attributes(source, target)
+		}
+		
+		FileKind::File { .. } | FileKind::Symlink => {
+			let mut reported_bytes = 0_u64;
+			
+			file_system.copy_file(source, target, &mut |copied_bytes| {
+				context.add_done(0, copied_bytes.saturating_sub(reported_bytes));
+				reported_bytes = copied_bytes;
+				context.wait_if_paused()
+			})?;
+			
+			context.add_done(1, 0);
+			Ok(())
+		}
+		
+		FileKind::BlockDevice | FileKind::CharDevice | FileKind::Pipe | FileKind::Socket | FileKind::Unknown => {
+			Err(io::Error::new(ErrorKind::Unsupported, format!("Cannot copy special file {}", source.to_string_lossy())))
+		}
+	}
+}
+
+fn copy_children(file_system: &dyn FileSystem, context: &JobContext, resolutions: &ConflictResolutions, source: &Path, target: &Path) -> io::Result<()> {
+	for child in file_system.list(source)? {
+		let child_path = get_entry_path(&child)?;
+		copy_tree(file_system, context, resolutions, &child, &get_target_path(child_path, target)?)?;
+	}
+	
+	Ok(())
+}
+
+/// Moves the entries into the directory. Entries are renamed if possible, and copied and then deleted if they are on a different file system. Skipped entries stay where they are, and merged directories are deleted only once everything inside them was moved.
+pub fn move_entries(file_system: &dyn FileSystem, context: &JobContext, sources: &[PathBuf], target_directory: &Path, resolutions: &ConflictResolutions) -> io::Result<()> {
+	// Entries are only scanned if they have to be copied, so the totals grow while the job runs.
+	context.finish_scanning();
+	
+	for source in sources {
+		move_tree(file_system, context, resolutions, &file_system.stat(source), &get_target_path(source, target_directory)?)?;
+	}
+	
+	Ok(())
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Moved {
+	Renamed,
+	Copied,
+}
+
+fn move_tree(file_system: &dyn FileSystem, context: &JobContext, resolutions: &ConflictResolutions, entry: &FileEntry, target: &Path) -> io::Result<()> {
+	context.checkpoint()?;
+	
+	let source = get_entry_path(entry)?;
+	
+	let moved = match resolve_target(file_system, resolutions, entry, target)? {
+		ResolvedTarget::Skip => {
+			context.add_total(1, 0);
+			context.add_done(1, 0);
+			return Ok(());
+		}
+		
+		ResolvedTarget::Merge => {
+			for child in file_system.list(source)? {
+				let child_path = get_entry_path(&child)?;
+				move_tree(file_system, context, resolutions, &child, &get_target_path(child_path, target)?)?;
+			}
+			
+			if file_system.list(source)?.is_empty() {
+				file_system.remove(source)?;
+			}
+			
+			context.add_total(1, 0);
+			context.add_done(1, 0);
+			return Ok(());
+		}
+		
+		ResolvedTarget::Create(target) => {
+			move_new_tree(file_system, context, resolutions, entry, &target)?
+		}
+		
+		ResolvedTarget::Replace(target) => {
+			let mut moved = Moved::Renamed;
+			
+			replace_target(file_system, &target, |temporary| {
+				moved = move_new_tree(file_system, context, resolutions, entry, temporary)?;
+				Ok(())
+			})?;
+			
+			moved
+		}
+	};
+	
+	// Entries copied from a different file system are deleted only once the copy is in place.
+	if moved == Moved::Copied {
+		file_system.remove(source)
+	} else {
+		Ok(())
+	}
+}
+
+/// Moves the entry to a path where nothing exists yet, or copies it if the path is on a different file system. Copied entries are not deleted.
+fn move_new_tree(file_system: &dyn FileSystem, context: &JobContext, resolutions: &ConflictResolutions, entry: &FileEntry, target: &Path) -> io::Result<Moved> {
+	let source = get_entry_path(entry)?;
+	
+	match file_system.rename(source, target) {
+		Ok(()) => {
+			context.add_total(1, 0);
+			context.add_done(1, 0);
+			Ok(Moved::Renamed)
+		}
+		Err(e) if is_cross_device_error(&e) => {
+			scan_entries(file_system, context, &[source.to_path_buf()], usize::MAX, u64::MAX)?;
+			copy_new_tree(file_system, context, resolutions, entry, target)?;
+			Ok(Moved::Copied)
+		}
+		Err(e) => {
+			Err(e)
+		}
+	}
+}
+
+/// Deletes the entries, including everything inside directories. Contents of directories are deleted one by one, so that the job can report progress and stop in between.
+pub fn delete_entries(file_system: &dyn FileSystem, context: &JobContext, paths: &[PathBuf]) -> io::Result<()> {
+	scan_entries(file_system, context, paths, usize::MAX, u64::MAX)?;
+	context.finish_scanning();
+	
+	for path in paths {
+		delete_tree(file_system, context, &file_system.stat(path))?;
+	}
+	
+	Ok(())
+}
+
+fn delete_tree(file_system: &dyn FileSystem, context: &JobContext, entry: &FileEntry) -> io::Result<()> {
+	context.checkpoint()?;
+	
+	let path = get_entry_path(entry)?;
+	
+	if matches!(entry.kind(), FileKind::Directory) {
+		for child in file_system.list(path)? {
+			delete_tree(file_system, context, &child)?;
+		}
+	}
+	
+	file_system.remove(path)?;
+	context.add_done(1, get_file_size(entry));
+	Ok(())
+}
+
+/// Changes the owner and/or group of the entry, and of everything inside it if the change is recursive. Entries that cannot be changed are skipped and reported to the job, which fails once everything else was changed.
+pub fn change_owner_of_entry(file_system: &dyn FileSystem, context: &JobContext, path: &Path, uid: Option<u32>, gid: Option<u32>, recursive: bool) -> io::Result<()> {
+	let max_depth = if recursive { usize::MAX } else { 0 };
+	scan_entries(file_system, context, &[path.to_path_buf()], max_depth, u64::MAX)?;
+	context.finish_scanning();
+	
+	let mut remaining = vec![file_system.stat(path)];
+	let mut failed_count = 0_usize;
+	
+	while let Some(entry) = remaining.pop() {
+		context.checkpoint()?;
+		
+		let path = get_entry_path(&entry)?;
+		let mut errors = Vec::new();
+		
+		if let Err(e) = file_system.change_owner(path, uid, gid) {
+			errors.push(e);
+		}
+		
+		// Children are changed even if the directory itself could not be changed.
+		if recursive && matches!(entry.kind(), FileKind::Directory) {
+			match file_system.list(path) {
+				Ok(children) => remaining.extend(children),
+				Err(e) => errors.push(e),
+			}
+		}
+		
+		if !errors.is_empty() {
+			failed_count = failed_count.saturating_add(1);
+			
+			for e in errors {
+				context.add_error(path, e);
+			}
+		}
+		
+		context.add_done(1, get_file_size(&entry));
+	}
+	
+	if failed_count == 0 {
+		Ok(())
+	} else {
+		Err(io::Error::new(ErrorKind::Other, format!("Could not change {failed_count} entries")))
+	}
+}
+
+/// Runs work that creates or extracts an archive, and adds the bytes it reports to the progress of the job. The archive counts as one entry. The work stops once the job is cancelled.
+pub fn run_archive_work<W>(context: &JobContext, work: W) -> io::Result<()> where W: FnOnce(&mut dyn FnMut(ArchiveProgress) -> bool) -> io::Result<()> {
+	context.add_total(1, 0);
+	context.finish_scanning();
+	
+	let mut reported = ArchiveProgress::default();
+	
+	work(&mut |progress| {
+		context.add_total(0, progress.total_bytes.saturating_sub(reported.total_bytes));
+		context.add_done(0, progress.done_bytes.saturating_sub(reported.done_bytes));
+		reported = progress;
+		context.wait_if_paused()
+	})?;
+	
+	context.add_done(1, 0);
+	Ok(())
+}
+
+#[allow(clippy::wildcard_enum_match_arm)]
+const fn get_file_size(entry: &FileEntry) -> u64 {
+	match entry.kind() {
+		FileKind::File { size } => *size,
+		_ => 0,
+	}
+}
+
+/// Entries that could not be read have no path, and must not be skipped silently, because moving deletes what was copied.
+fn get_entry_path(entry: &FileEntry) -> io::Result<&Path> {
+	entry.path().ok_or_else(|| io::Error::new(ErrorKind::Other, format!("Could not read {}", entry.name().str())))
+}
+
+fn get_target_path(source: &Path, target_directory: &Path) -> io::Result<PathBuf> {
+	source.file_name()
+	      .map(|name| target_directory.join(name))
+	      .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, format!("Cannot copy or move {}", source.to_string_lossy())))
+}
+
+#[cfg(unix)]
+fn is_cross_device_error(e: &io::Error) -> bool {
+	e.raw_os_error() == Some(libc::EXDEV)
+}
+
+/// Windows reports `ERROR_NOT_SAME_DEVICE`.
+#[cfg(not(unix))]
+fn is_cross_device_error(e: &io::Error) -> bool {
+	e.raw_os_error() == Some(17)
+}
+
+
+#[cfg(test)]
+mod tests {
+	use std::io::{self, ErrorKind};
+	use std::path::{Path, PathBuf};
+	use std::sync::Arc;
+	use std::thread;
+	use std::time::Duration;
+	
+	use crate::component::filesystem::jobs::{ConflictResolution, ConflictResolutions, copy_entries, JobContext, JobControl, move_entries};
+	use crate::file::{FileSystem, MemoryFileSystem};
+	
+	type Work = fn(&dyn FileSystem, &JobContext, &[PathBuf], &Path, &ConflictResolutions) -> io::Result<()>;
+	
+	fn run(work: Work, file_system: &MemoryFileSystem, sources: &[&str], target_directory: &str, resolutions: &[(&str, ConflictResolution)]) -> io::Result<()> {
+		let context = JobContext { control: Arc::new(JobControl::new()) };
+		let sources = sources.iter().map(PathBuf::from).collect::<Vec<_>>();
+		
+		let mut conflict_resolutions = ConflictResolutions::default();
+		for (target, resolution) in resolutions {
+			conflict_resolutions.insert(PathBuf::from(target), *resolution);
+		}
+		
+		work(file_system, &context, &sources, Path::new(target_directory), &conflict_resolutions)
+	}
+	
+	fn contents(file_system: &MemoryFileSystem, path: &str) -> Option<String> {
+		file_system.read(Path::new(path)).ok().map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+	}
+	
+	fn names(file_system: &MemoryFileSystem, path: &str) -> Vec<String> {
+		let mut names = file_system.list(Path::new(path)).unwrap_or_default().iter().map(|entry| entry.name().str().to_owned()).collect::<Vec<_>>();
+		names.sort();
+		names
+	}
+	
+	fn create_conflict() -> MemoryFileSystem {
+		MemoryFileSystem::new()
+			.with_file("/src/file", "new")
+			.with_file("/dst/file", "old")
+	}
+	
+	/// Creates the file in the target directory, and a newer file with the same name in the source directory.
+	fn create_conflict_with_newer_source() -> MemoryFileSystem {
+		let file_system = MemoryFileSystem::new().with_file("/dst/file", "old");
+		thread::sleep(Duration::from_millis(10));
+		file_system.with_file("/src/file", "new")
+	}
+	
+	#[test]
+	fn copies_directories_with_their_contents() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/src/dir/a", "a")
+			.with_file("/src/dir/sub/b", "b")
+			.with_directory("/dst");
+		
+		assert!(run(copy_entries, &file_system, &["/src/dir"], "/dst", &[]).is_ok(), "Copy failed");
+		assert_eq!(contents(&file_system, "/dst/dir/a").as_deref(), Some("a"));
+		assert_eq!(contents(&file_system, "/dst/dir/sub/b").as_deref(), Some("b"));
+		assert_eq!(contents(&file_system, "/src/dir/sub/b").as_deref(), Some("b"));
+	}
+	
+	#[test]
+	fn fails_on_conflicts_without_resolution() {
+		for work in [copy_entries as Work, move_entries] {
+			let file_system = create_conflict();
+			
+			assert_eq!(run(work, &file_system, &["/src/file"], "/dst", &[]).err().map(|e| e.kind()), Some(ErrorKind::AlreadyExists));
+			assert_eq!(contents(&file_system, "/dst/file").as_deref(), Some("old"));
+			assert_eq!(contents(&file_system, "/src/file").as_deref(), Some("new"));
+		}
+	}
+	
+	#[test]
+	fn skip_keeps_both_entries() {
+		for work in [copy_entries as Work, move_entries] {
+			let file_system = create_conflict();
+			
+			assert!(run(work, &file_system, &["/src/file"], "/dst", &[("/dst/file", ConflictResolution::Skip)]).is_ok(), "Skip failed");
+			assert_eq!(contents(&file_system, "/dst/file").as_deref(), Some("old"));
+			assert_eq!(contents(&file_system, "/src/file").as_deref(), Some("new"));
+		}
+	}
+	
+	#[test]
+	fn overwrite_replaces_target() {
+		let file_system = create_conflict();
+		
+		assert!(run(copy_entries, &file_system, &["/src/file"], "/dst", &[("/dst/file", ConflictResolution::Overwrite)]).is_ok(), "Copy failed");
+		assert_eq!(contents(&file_system, "/dst/file").as_deref(), Some("new"));
+		assert_eq!(contents(&file_system, "/src/file").as_deref(), Some("new"));
+		assert_eq!(names(&file_system, "/dst"), vec!["file"]);
+		
+		let file_system = create_conflict();
+		
+		assert!(run(move_entries, &file_system, &["/src/file"], "/dst", &[("/dst/file", ConflictResolution::Overwrite)]).is_ok(), "Move failed");
+		assert_eq!(contents(&file_system, "/dst/file").as_deref(), Some("new"));
+		assert_eq!(names(&file_system, "/src"), Vec::<String>::new());
+		assert_eq!(names(&file_system, "/dst"), vec!["file"]);
+	}
+	
+	#[test]
+	fn overwrite_replaces_directory_with_file() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/src/entry", "new")
+			.with_file("/dst/entry/inner", "old");
+		
+		assert!(run(copy_entries, &file_system, &["/src/entry"], "/dst", &[("/dst/entry", ConflictResolution::Overwrite)]).is_ok(), "Copy failed");
+		assert_eq!(contents(&file_system, "/dst/entry").as_deref(), Some("new"));
+		assert!(!file_system.exists(Path::new("/dst/entry/inner")), "Contents of replaced directory were kept");
+	}
+	
+	#[test]
+	fn overwrite_keeps_target_if_copy_fails() {
+		let file_system = MemoryFileSystem::new()
+			.with_directory("/src")
+			.with_file("/dst/missing", "old");
+		
+		assert!(run(copy_entries, &file_system, &["/src/missing"], "/dst", &[("/dst/missing", ConflictResolution::Overwrite)]).is_err(), "Copy of a missing entry succeeded");
+		assert_eq!(contents(&file_system, "/dst/missing").as_deref(), Some("old"));
+		assert_eq!(names(&file_system, "/dst"), vec!["missing"]);
+	}
+	
+	#[test]
+	fn overwrite_uses_free_temporary_name() {
+		let file_system = create_conflict().with_file("/dst/.file.bark-1", "unrelated");
+		
+		assert!(run(copy_entries, &file_system, &["/src/file"], "/dst", &[("/dst/file", ConflictResolution::Overwrite)]).is_ok(), "Copy failed");
+		assert_eq!(contents(&file_system, "/dst/file").as_deref(), Some("new"));
+		assert_eq!(contents(&file_system, "/dst/.file.bark-1").as_deref(), Some("unrelated"));
+		assert_eq!(names(&file_system, "/dst"), vec![".file.bark-1", "file"]);
+	}
+	
+	#[test]
+	fn overwrite_if_newer_replaces_older_target() {
+		for work in [copy_entries as Work, move_entries] {
+			let file_system = create_conflict_with_newer_source();
+			
+			assert!(run(work, &file_system, &["/src/file"], "/dst", &[("/dst/file", ConflictResolution::OverwriteIfNewer)]).is_ok(), "Overwrite failed");
+			assert_eq!(contents(&file_system, "/dst/file").as_deref(), Some("new"));
+		}
+	}
+	
+	#[test]
+	fn overwrite_if_newer_skips_newer_target() {
+		for work in [copy_entries as Work, move_entries] {
+			let file_system = MemoryFileSystem::new().with_file("/src/file", "new");
+			thread::sleep(Duration::from_millis(10));
+			let file_system = file_system.with_file("/dst/file", "old");
+			
+			assert!(run(work, &file_system, &["/src/file"], "/dst", &[("/dst/file", ConflictResolution::OverwriteIfNewer)]).is_ok(), "Skip failed");
+			assert_eq!(contents(&file_system, "/dst/file").as_deref(), Some("old"));
+			assert_eq!(contents(&file_system, "/src/file").as_deref(), Some("new"));
+		}
+	}
+	
+	#[test]
+	fn rename_adds_number_to_name() {
+		let file_system = create_conflict();
+		
+		assert!(run(copy_entries, &file_system, &["/src/file"], "/dst", &[("/dst/file", ConflictResolution::Rename)]).is_ok(), "Copy failed");
+		assert_eq!(contents(&file_system, "/dst/file").as_deref(), Some("old"));
+		assert_eq!(contents(&file_system, "/dst/file (1)").as_deref(), Some("new"));
+		
+		assert!(run(move_entries, &file_system, &["/src/file"], "/dst", &[("/dst/file", ConflictResolution::Rename)]).is_ok(), "Move failed");
+		assert_eq!(contents(&file_system, "/dst/file (2)").as_deref(), Some("new"));
+		assert_eq!(names(&file_system, "/src"), Vec::<String>::new());
+	}
+	
+	#[test]
+	fn merge_resolves_conflicts_inside_directory() {
+		let resolutions = [
+			("/dst/dir", ConflictResolution::Merge),
+			("/dst/dir/both", ConflictResolution::Overwrite),
+			("/dst/dir/kept", ConflictResolution::Skip),
+		];
+		
+		let create_file_system = || MemoryFileSystem::new()
+			.with_file("/src/dir/added", "added")
+			.with_file("/src/dir/both", "new")
+			.with_file("/src/dir/kept", "new")
+			.with_file("/dst/dir/both", "old")
+			.with_file("/dst/dir/kept", "old")
+			.with_file("/dst/dir/existing", "existing");
+		
+		let file_system = create_file_system();
+		
+		assert!(run(copy_entries, &file_system, &["/src/dir"], "/dst", &resolutions).is_ok(), "Copy failed");
+		assert_eq!(names(&file_system, "/dst/dir"), vec!["added", "both", "existing", "kept"]);
+		assert_eq!(contents(&file_system, "/dst/dir/both").as_deref(), Some("new"));
+		assert_eq!(contents(&file_system, "/dst/dir/kept").as_deref(), Some("old"));
+		assert_eq!(names(&file_system, "/src/dir"), vec!["added", "both", "kept"]);
+		
+		let file_system = create_file_system();
+		
+		assert!(run(move_entries, &file_system, &["/src/dir"], "/dst", &resolutions).is_ok(), "Move failed");
+		assert_eq!(names(&file_system, "/dst/dir"), vec!["added", "both", "existing", "kept"]);
+		assert_eq!(contents(&file_system, "/dst/dir/both").as_deref(), Some("new"));
+		assert_eq!(contents(&file_system, "/dst/dir/kept").as_deref(), Some("old"));
+		assert_eq!(names(&file_system, "/src/dir"), vec!["kept"]);
+	}
+	
+	#[test]
+	fn move_removes_merged_directory_once_empty() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/src/dir/a", "a")
+			.with_file("/dst/dir/b", "b");
+		
+		assert!(run(move_entries, &file_system, &["/src/dir"], "/dst", &[("/dst/dir", ConflictResolution::Merge)]).is_ok(), "Move failed");
+		assert_eq!(names(&file_system, "/dst/dir"), vec!["a", "b"]);
+		assert_eq!(names(&file_system, "/src"), Vec::<String>::new());
+	}
+}
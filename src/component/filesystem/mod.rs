@@ -1,8 +1,21 @@
 use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use crossterm::event::{KeyCode, KeyModifiers, MouseEvent};
+use slab_tree::NodeId;
+
+use crate::component::filesystem::action::file::create_path_errors_dialog;
+use crate::component::filesystem::columns::Column;
+use crate::component::filesystem::defaults::NamedAction;
+use crate::component::filesystem::disk_usage::{DiskUsageMode, DiskUsagePoll};
+use crate::component::filesystem::git::GitStatusTracker;
+use crate::component::filesystem::jobs::{Jobs, JobStatus};
 use crate::component::filesystem::registers::FsTreeRegisters;
-use crate::component::filesystem::tree::FsTree;
-use crate::file::FileOwnerNameCache;
+use crate::component::filesystem::status::StatusMessage;
+use crate::component::filesystem::render::{DateFormat, SizeFormat, SizeKind};
+use crate::component::filesystem::tree::{FsTree, FsTreeViewSort};
+use crate::file::{FileOwnerNameCache, FileSystem};
 use crate::input::keymap::{KeyBinding, KeyMap, KeyMapLookupResult};
 use crate::state::action::{Action, ActionResult};
 use crate::state::Environment;
@@ -11,38 +24,80 @@ use crate::state::layer::Layer;
 use crate::state::view::Frame;
 
 mod action;
+mod columns;
 mod command;
+mod compare;
+mod disk_usage;
+mod dupes;
+mod finder;
+mod git;
+mod grep;
+mod jobs;
+mod mouse;
 mod registers;
 mod render;
+mod status;
 mod tree;
+mod walk;
 pub mod defaults;
 
-pub type ActionKeyMap = KeyMap<Box<dyn Action<FsLayer> + Sync>>;
+pub type ActionKeyMap = KeyMap<Arc<NamedAction>>;
+
+/// How long to wait after typing a prefix of a key sequence before showing its possible continuations.
+const KEY_CONTINUATIONS_DELAY: Duration = Duration::from_millis(500);
 
 pub struct FsLayer {
 	action_map: &'static ActionKeyMap,
 	pub tree: FsTree,
 	tree_structure_version: u32,
 	cursor_y: u16,
+	displayed_node_ids: Vec<NodeId>,
+	last_click: Option<(NodeId, Instant)>,
 	pub registers: FsTreeRegisters,
 	pending_keys: Vec<KeyBinding>,
-	event_queue: EventQueue<FsLayer>,
+	pending_keys_time: Option<Instant>,
+	show_key_continuations: bool,
+	status_message: Option<StatusMessage>,
+	event_queue: EventQueue<Self>,
+	opened_dialogs: Vec<Box<dyn Layer>>,
 	file_owner_name_cache: FileOwnerNameCache,
+	columns: Vec<Column>,
+	date_format: DateFormat,
+	size_format: SizeFormat,
+	size_kind: SizeKind,
 	column_width_cache: Option<ColumnWidths>,
+	git_status: GitStatusTracker,
+	git_status_refresh_version: Option<u32>,
+	disk_usage: Option<DiskUsageMode>,
+	jobs: Jobs,
 }
 
 impl FsLayer {
-	pub fn new(root_path: &Path, action_map: &'static ActionKeyMap) -> Self {
+	pub fn new(root_path: &Path, file_system: Arc<dyn FileSystem>, action_map: &'static ActionKeyMap) -> Self {
 		Self {
 			action_map,
-			tree: FsTree::with_root_path(root_path),
+			tree: FsTree::new(root_path, file_system),
 			tree_structure_version: 0,
 			cursor_y: 0,
+			displayed_node_ids: Vec::new(),
+			last_click: None,
 			registers: FsTreeRegisters::new(),
 			pending_keys: Vec::new(),
+			pending_keys_time: None,
+			show_key_continuations: false,
+			status_message: None,
 			event_queue: EventQueue::new(),
+			opened_dialogs: Vec::new(),
 			file_owner_name_cache: FileOwnerNameCache::new(),
+			columns: Column::defaults(),
+			date_format: DateFormat::Ls,
+			size_format: SizeFormat::Si,
+			size_kind: SizeKind::Apparent,
 			column_width_cache: None,
+			git_status: GitStatusTracker::new(),
+			git_status_refresh_version: None,
+			disk_usage: None,
+			jobs: Jobs::new(),
 		}
 	}
 	
@@ -53,19 +108,166 @@ impl FsLayer {
 	pub const fn dialog_y(&self) -> u16 {
 		self.cursor_y.saturating_add(1)
 	}
+	
+	fn clear_pending_keys(&mut self) {
+		self.pending_keys.clear();
+		self.pending_keys_time = None;
+		self.show_key_continuations = false;
+	}
+	
+	pub fn show_message(&mut self, message: StatusMessage) {
+		self.status_message = Some(message);
+	}
+	
+	/// Opens a dialog from an event, once all events were handled.
+	pub fn open_dialog<T>(&mut self, dialog: T) where T: Layer + 'static {
+		self.opened_dialogs.push(Box::new(dialog));
+	}
+	
+	fn set_size_format(&mut self, format: SizeFormat, kind: SizeKind) {
+		self.size_format = format;
+		self.size_kind = kind;
+		self.column_width_cache = None;
+		
+		if matches!(self.tree.sort(), FsTreeViewSort::DiskUsage(_)) {
+			self.tree.set_sort(FsTreeViewSort::DiskUsage(kind));
+		}
+	}
+	
+	/// Scans disk usage of the view root directory, or scans it again if disk usage mode is already on. Entries are sorted by their total size once the scan finishes.
+	fn start_disk_usage(&mut self, cross_file_systems: bool) {
+		let Some(root_path) = self.tree.view_root_path().map(Path::to_path_buf) else {
+			return;
+		};
+		
+		if let Some(disk_usage) = &mut self.disk_usage {
+			disk_usage.restart(&root_path, cross_file_systems);
+		} else {
+			self.disk_usage = Some(DiskUsageMode::start(&root_path, cross_file_systems, self.tree.sort()));
+		}
+	}
+	
+	/// Turns off disk usage mode, and restores the previous sort order.
+	fn stop_disk_usage(&mut self) -> bool {
+		let Some(disk_usage) = self.disk_usage.take() else {
+			return false;
+		};
+		
+		self.tree.set_disk_usage(None);
+		self.tree.set_sort(disk_usage.sort_before());
+		self.column_width_cache = None;
+		true
+	}
+	
+	fn poll_disk_usage(&mut self) -> EventResult {
+		let Some(disk_usage) = &mut self.disk_usage else {
+			return EventResult::Nothing;
+		};
+		
+		// Totals only cover the scanned directory, so the scan must be repeated when the view root changes.
+		if let Some(root_path) = self.tree.view_root_path().filter(|root_path| *root_path != disk_usage.root_path()) {
+			let root_path = root_path.to_path_buf();
+			let cross_file_systems = disk_usage.cross_file_systems();
+			disk_usage.restart(&root_path, cross_file_systems);
+			return EventResult::Draw;
+		}
+		
+		match disk_usage.poll() {
+			DiskUsagePoll::Nothing => EventResult::Nothing,
+			DiskUsagePoll::Progress => EventResult::Draw,
+			
+			DiskUsagePoll::Finished(Some(result)) => {
+				self.tree.set_disk_usage(Some(result));
+				self.tree.set_sort(FsTreeViewSort::DiskUsage(self.size_kind));
+				self.column_width_cache = None;
+				EventResult::Draw
+			}
+			
+			DiskUsagePoll::Finished(None) => {
+				let message = format!("Could not scan disk usage of {}", disk_usage.root_path().to_string_lossy());
+				self.stop_disk_usage();
+				self.show_message(StatusMessage::error(message));
+				EventResult::Draw
+			}
+		}
+	}
+	
+	/// Reports how finished jobs ended, and refreshes directories that they changed.
+	fn poll_jobs(&mut self) -> EventResult {
+		let (finished_jobs, progressed) = self.jobs.poll();
+		let mut result = EventResult::draw_if(progressed);
+		
+		for job in finished_jobs {
+			match &job.status {
+				JobStatus::Running => {}
+				JobStatus::Succeeded => {
+					if let Some(message) = job.success_message {
+						self.show_message(StatusMessage::info(message));
+					}
+					
+					if let Some(on_success) = &job.on_success {
+						result = result.merge(on_success(self, job.progress));
+					}
+				}
+				JobStatus::Failed(error) => {
+					self.show_message(StatusMessage::error(format!("{} failed: {error}", job.description)));
+				}
+				JobStatus::Cancelled => {
+					self.show_message(StatusMessage::info(format!("{} was cancelled", job.description)));
+				}
+			}
+			
+			if !job.errors.is_empty() {
+				self.open_dialog(create_path_errors_dialog(self.dialog_y(), &job.errors));
+			}
+			
+			for path in &job.refresh_paths {
+				self.tree.refresh_model_directory(path);
+			}
+			
+			result = result.merge(EventResult::Draw);
+		}
+		
+		result
+	}
+	
+	fn refresh_git_status_if_needed(&mut self) {
+		if !self.columns.contains(&Column::GitStatus) {
+			return;
+		}
+		
+		let refresh_version = self.tree.refresh_version();
+		if self.git_status_refresh_version == Some(refresh_version) {
+			return;
+		}
+		
+		self.git_status_refresh_version = Some(refresh_version);
+		
+		if let Some(root_path) = self.tree.view_root_path() {
+			self.git_status.refresh(root_path);
+		}
+	}
 }
 
 impl Layer for FsLayer {
 	fn handle_input(&mut self, environment: &Environment, key_binding: KeyBinding) -> ActionResult {
+		if key_binding == KeyBinding::new(KeyCode::Esc, KeyModifiers::NONE) && (!self.pending_keys.is_empty() || self.registers.count.is_some()) {
+			self.clear_pending_keys();
+			self.registers.count = None;
+			return ActionResult::Draw;
+		}
+		
 		self.pending_keys.push(key_binding);
 		
 		match self.action_map.lookup(&self.pending_keys) {
 			KeyMapLookupResult::Prefix => {
-				ActionResult::Nothing
+				self.pending_keys_time = Some(Instant::now());
+				ActionResult::Draw
 			}
 			
 			KeyMapLookupResult::Found(action) => {
-				self.pending_keys.clear();
+				let had_pending_keys = self.pending_keys.len() > 1;
+				self.clear_pending_keys();
 				
 				let old_count = self.registers.count;
 				let result = action.perform(self, environment);
@@ -75,19 +277,56 @@ impl Layer for FsLayer {
 					self.registers.count = None;
 				}
 				
-				result
+				// The status bar shows pending keys and count, so it must be redrawn when they change.
+				if matches!(result, ActionResult::Nothing) && (had_pending_keys || old_count != self.registers.count) {
+					ActionResult::Draw
+				} else {
+					result
+				}
 			}
 			
 			KeyMapLookupResult::None => {
-				self.pending_keys.clear();
+				self.clear_pending_keys();
 				self.registers.count = None;
-				ActionResult::Nothing
+				ActionResult::Draw
 			}
 		}
 	}
 	
+	fn handle_mouse(&mut self, environment: &Environment, event: MouseEvent) -> ActionResult {
+		mouse::handle(self, environment, event)
+	}
+	
 	fn handle_events(&mut self, environment: &Environment) -> EventResult {
-		self.event_queue.take().into_iter().fold(EventResult::Nothing, |result, event| result.merge(event.dispatch(self, environment)))
+		let result = self.event_queue.take().into_iter().fold(EventResult::Nothing, |result, event| result.merge(event.dispatch(self, environment)));
+		
+		let result = if self.status_message.as_ref().is_some_and(StatusMessage::is_expired) {
+			self.status_message = None;
+			result.merge(EventResult::Draw)
+		} else {
+			result
+		};
+		
+		let result = if !self.show_key_continuations && self.pending_keys_time.is_some_and(|time| time.elapsed() >= KEY_CONTINUATIONS_DELAY) {
+			self.show_key_continuations = true;
+			result.merge(EventResult::Draw)
+		} else {
+			result
+		};
+		
+		let result = result.merge(self.poll_disk_usage());
+		let result = result.merge(self.poll_jobs());
+		
+		self.refresh_git_status_if_needed();
+		result.merge(EventResult::draw_if(self.git_status.poll()))
+	}
+	
+	fn take_opened_layers(&mut self) -> Vec<Box<dyn Layer>> {
+		std::mem::take(&mut self.opened_dialogs)
+	}
+	
+	fn has_background_work(&self) -> bool {
+		self.jobs.running_count() > 0
 	}
 	
 	fn render(&mut self, frame: &mut Frame) {
@@ -103,8 +342,15 @@ impl Layer for FsLayer {
 #[derive(Copy, Clone, Default)]
 pub struct ColumnWidths {
 	pub name: u16,
+	pub disk_usage: u16,
+	pub size: u16,
 	pub user: u16,
 	pub group: u16,
+	pub inode: u16,
+	pub link_count: u16,
+	pub blocks: u16,
+	pub extension: u16,
+	pub date_time: u16,
 }
 
 impl ColumnWidths {
@@ -0,0 +1,69 @@
+use std::time::{Duration, Instant};
+
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+
+use crate::component::filesystem::action::file::EditSelectedEntry;
+use crate::component::filesystem::action::movement::{FixedCount, MoveDown, MovementWithCountFactory, MoveUp};
+use crate::component::filesystem::action::tree::ExpandCollapse;
+use crate::component::filesystem::FsLayer;
+use crate::file::FileKind;
+use crate::state::action::{Action, ActionResult};
+use crate::state::Environment;
+
+/// Terminals do not report double-clicks, so two clicks on the same row within this interval are treated as one.
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+
+const WHEEL_SCROLL_LINES: usize = 3;
+
+pub fn handle(layer: &mut FsLayer, environment: &Environment, event: MouseEvent) -> ActionResult {
+	#[allow(clippy::wildcard_enum_match_arm)]
+	match event.kind {
+		MouseEventKind::Down(MouseButton::Left) => {
+			layer.clear_pending_keys();
+			layer.registers.count = None;
+			click(layer, environment, event.row)
+		}
+		
+		MouseEventKind::ScrollDown => {
+			MoveDown.with_custom_count(FixedCount(WHEEL_SCROLL_LINES)).perform(layer, environment)
+		}
+		
+		MouseEventKind::ScrollUp => {
+			MoveUp.with_custom_count(FixedCount(WHEEL_SCROLL_LINES)).perform(layer, environment)
+		}
+		
+		_ => ActionResult::Nothing
+	}
+}
+
+/// Selects the clicked row. Double-clicking a directory expands or collapses it, and double-clicking any other entry opens it in the default editor.
+fn click(layer: &mut FsLayer, environment: &Environment, row: u16) -> ActionResult {
+	let Some(node_id) = layer.displayed_node_ids.get(usize::from(row)).copied() else {
+		return ActionResult::Nothing;
+	};
+	
+	let now = Instant::now();
+	let is_double_click = layer.last_click.is_some_and(|(last_node_id, last_time)| last_node_id == node_id && now.duration_since(last_time) <= DOUBLE_CLICK_INTERVAL);
+	
+	layer.last_click = if is_double_click { None } else { Some((node_id, now)) };
+	layer.tree.selected_view_node_id = node_id;
+	
+	if !is_double_click {
+		return ActionResult::Draw;
+	}
+	
+	let is_directory = layer.tree.selected_node().and_then(|node| layer.tree.get_entry(&node)).is_some_and(|entry| matches!(entry.kind(), FileKind::Directory));
+	
+	let result = if is_directory {
+		ExpandCollapse { default_depth: 1 }.perform(layer, environment)
+	} else {
+		EditSelectedEntry.perform(layer, environment)
+	};
+	
+	// The selection may have changed even if the action did nothing.
+	if matches!(result, ActionResult::Nothing) {
+		ActionResult::Draw
+	} else {
+		result
+	}
+}
@@ -1,11 +1,27 @@
+use std::path::PathBuf;
+
 pub struct FsTreeRegisters {
 	pub count: Option<usize>,
+	pub clipboard: Option<Clipboard>,
 }
 
 impl FsTreeRegisters {
 	pub const fn new() -> Self {
 		Self {
 			count: None,
+			clipboard: None,
 		}
 	}
 }
+
+/// Entries that were copied or cut, and will be copied or moved once pasted.
+pub struct Clipboard {
+	pub paths: Vec<PathBuf>,
+	pub mode: ClipboardMode,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum ClipboardMode {
+	Copy,
+	Cut,
+}
@@ -2,13 +2,23 @@
 
 use std::time::SystemTime;
 
-use chrono::{Datelike, DateTime, Local, Timelike, Utc};
+use chrono::{Datelike, DateTime, Local, Timelike};
+use chrono::format::{Item, StrftimeItems};
 use ratatui::buffer::Buffer;
 use ratatui::style::Style;
+use ratatui::text::Span;
+
+use crate::component::filesystem::render::column;
 
 // Month + Space + Day + Space + Hour + Colon + Minute
 // Month + Space + Day + Space + Year + Space
-pub const COLUMN_WIDTH: u16 = 3 + 1 + 2 + 1 + 2 + 1 + 2;
+const LS_COLUMN_WIDTH: u16 = 3 + 1 + 2 + 1 + 2 + 1 + 2;
+
+// Year + Dash + Month + Dash + Day + Space + Hour + Colon + Minute
+const ISO_COLUMN_WIDTH: u16 = 4 + 1 + 2 + 1 + 2 + 1 + 2 + 1 + 2;
+
+// Number + Unit + Space + "ago"
+const RELATIVE_COLUMN_WIDTH: u16 = 2 + 1 + 1 + 3;
 
 const MONTHS: &[&str] = &[
 	"Jan", "Feb", "Mar",
@@ -17,26 +27,145 @@ const MONTHS: &[&str] = &[
 	"Oct", "Nov", "Dec",
 ];
 
-pub fn print(buf: &mut Buffer, x: u16, y: u16, system_time: Option<&SystemTime>) {
-	if let Some(system_time) = system_time {
-		let date_time = DateTime::<Utc>::from(*system_time).naive_local();
+#[derive(Clone)]
+pub enum DateFormat {
+	/// Month and day, followed by time for dates in the current year, or by the year for older and newer dates.
+	Ls,
+	/// Date and time in the ISO 8601 format.
+	Iso,
+	/// Time elapsed since the date, such as `3h ago`.
+	Relative,
+	/// Date and time in a custom `strftime` format.
+	Custom(String),
+}
+
+impl DateFormat {
+	/// Parses `ls`, `iso`, `relative`, or a custom `strftime` format prefixed with `+`.
+	pub fn parse(value: &str) -> Result<Self, String> {
+		match value {
+			"ls" => Ok(Self::Ls),
+			"iso" => Ok(Self::Iso),
+			"relative" => Ok(Self::Relative),
+			_ => {
+				let Some(format) = value.strip_prefix('+').filter(|format| !format.is_empty()) else {
+					return Err(format!("Unknown date format: {value}"));
+				};
+				
+				if StrftimeItems::new(format).any(|item| item == Item::Error) {
+					return Err(format!("Invalid date format: {format}"));
+				}
+				
+				Ok(Self::Custom(format.to_owned()))
+			}
+		}
+	}
+	
+	/// Returns the width of the date in this format. Built-in formats always have the same width, but the width of custom formats depends on the date, for example with names of months or `%s`, so it is measured.
+	pub fn get_width(&self, system_time: Option<&SystemTime>) -> u16 {
+		match self {
+			Self::Ls => LS_COLUMN_WIDTH,
+			Self::Iso => ISO_COLUMN_WIDTH,
+			Self::Relative => RELATIVE_COLUMN_WIDTH,
+			Self::Custom(format) => {
+				let text = format_custom(system_time.map(|system_time| DateTime::<Local>::from(*system_time)), format);
+				u16::try_from(Span::from(text).width()).unwrap_or(u16::MAX)
+			}
+		}
+	}
+}
+
+/// Prints dates in the selected format, relative to the current time which is only retrieved once per frame.
+pub struct DateTimePrinter<'a> {
+	format: &'a DateFormat,
+	now: DateTime<Local>,
+}
+
+impl<'a> DateTimePrinter<'a> {
+	pub fn new(format: &'a DateFormat) -> Self {
+		Self { format, now: Local::now() }
+	}
+	
+	pub fn print(&self, buf: &mut Buffer, x: u16, y: u16, column_width: u16, system_time: Option<&SystemTime>) {
+		let date_time = system_time.map(|system_time| DateTime::<Local>::from(*system_time));
+		
+		match self.format {
+			DateFormat::Ls => self.print_ls(buf, x, y, date_time),
+			DateFormat::Iso => print_iso(buf, x, y, date_time),
+			DateFormat::Relative => self.print_relative(buf, x, y, date_time),
+			DateFormat::Custom(format) => print_custom(buf, x, y, column_width, date_time, format),
+		}
+	}
+	
+	fn print_ls(&self, buf: &mut Buffer, x: u16, y: u16, date_time: Option<DateTime<Local>>) {
+		let Some(date_time) = date_time else {
+			buf.set_string(x, y, "??? ?? ??:??", Style::default());
+			return;
+		};
 		
 		print_month(buf, x, y, date_time.month0() as usize);
 		print_day_padded(buf, x + 4, y, date_time.day());
 		
 		let year = date_time.year();
-		if year == Local::now().year() { // TODO cache
+		if year == self.now.year() {
 			print_hour_minute(buf, x + 7, y, date_time.hour());
 			buf.get_mut(x + 9, y).set_char(':');
 			print_hour_minute(buf, x + 10, y, date_time.minute());
 		} else {
 			print_year(buf, x + 7, y, year);
 		}
+	}
+	
+	fn print_relative(&self, buf: &mut Buffer, x: u16, y: u16, date_time: Option<DateTime<Local>>) {
+		let text = date_time.map_or_else(|| String::from("?"), |date_time| format_relative(self.now.signed_duration_since(date_time).num_seconds()));
+		let width = u16::try_from(text.len()).unwrap_or(RELATIVE_COLUMN_WIDTH);
+		buf.set_string(x + RELATIVE_COLUMN_WIDTH.saturating_sub(width), y, text, Style::default());
+	}
+}
+
+fn print_iso(buf: &mut Buffer, x: u16, y: u16, date_time: Option<DateTime<Local>>) {
+	if let Some(date_time) = date_time {
+		buf.set_string(x, y, date_time.format("%Y-%m-%d %H:%M").to_string(), Style::default());
 	} else {
-		buf.set_string(x, y, "??? ?? ??:??", Style::default());
+		buf.set_string(x, y, "????-??-?? ??:??", Style::default());
 	}
 }
 
+fn print_custom(buf: &mut Buffer, x: u16, y: u16, column_width: u16, date_time: Option<DateTime<Local>>, format: &str) {
+	column::print_fixed_width_cell(buf, x, y, column_width, vec![Span::raw(format_custom(date_time, format))]);
+}
+
+fn format_custom(date_time: Option<DateTime<Local>>, format: &str) -> String {
+	date_time.map_or_else(|| String::from("?"), |date_time| date_time.format(format).to_string())
+}
+
+/// Formats the number of elapsed seconds using the largest unit that fits. Dates in the future are formatted as `in 3h`.
+fn format_relative(elapsed_seconds: i64) -> String {
+	const UNITS: &[(i64, &str)] = &[
+		(60, "s"),
+		(60, "m"),
+		(24, "h"),
+		(7, "d"),
+		(52, "w"),
+		(i64::MAX, "y"),
+	];
+	
+	let mut value = elapsed_seconds.saturating_abs();
+	
+	if value < 10 {
+		return String::from("now");
+	}
+	
+	for (count_in_next_unit, unit) in UNITS {
+		if value < *count_in_next_unit {
+			return if elapsed_seconds < 0 { format!("in {value}{unit}") } else { format!("{value}{unit} ago") };
+		}
+		
+		value /= count_in_next_unit;
+	}
+	
+	String::from("?")
+}
+
 fn print_year(buf: &mut Buffer, x: u16, y: u16, year: i32) {
 	buf.set_string(x, y, year.to_string(), Style::default());
 }
@@ -57,3 +186,61 @@ fn print_hour_minute(buf: &mut Buffer, x: u16, y: u16, value: u32) {
 		buf.set_string(x, y, value.to_string(), Style::default());
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, SystemTime};
+	
+	use chrono::{Local, TimeZone};
+	
+	use crate::component::filesystem::render::date_time::{DateFormat, format_relative};
+	
+	fn local_time(year: i32, month: u32, day: u32) -> SystemTime {
+		Local.with_ymd_and_hms(year, month, day, 12, 0, 0).earliest().map_or(SystemTime::UNIX_EPOCH, SystemTime::from)
+	}
+	
+	fn custom_width(format: &str, system_time: Option<SystemTime>) -> u16 {
+		DateFormat::Custom(String::from(format)).get_width(system_time.as_ref())
+	}
+	
+	#[test]
+	fn parses_date_formats() {
+		assert!(matches!(DateFormat::parse("ls"), Ok(DateFormat::Ls)));
+		assert!(matches!(DateFormat::parse("iso"), Ok(DateFormat::Iso)));
+		assert!(matches!(DateFormat::parse("relative"), Ok(DateFormat::Relative)));
+		assert!(matches!(DateFormat::parse("+%Y %B"), Ok(DateFormat::Custom(format)) if format == "%Y %B"));
+		assert!(matches!(DateFormat::parse("+"), Err(e) if e == "Unknown date format: +"));
+		assert!(matches!(DateFormat::parse("long"), Err(e) if e == "Unknown date format: long"));
+		assert!(matches!(DateFormat::parse("+%Y %"), Err(e) if e == "Invalid date format: %Y %"));
+	}
+	
+	#[test]
+	fn built_in_formats_have_fixed_width() {
+		assert_eq!(DateFormat::Ls.get_width(None), 12);
+		assert_eq!(DateFormat::Iso.get_width(Some(&local_time(2023, 9, 15))), 16);
+		assert_eq!(DateFormat::Relative.get_width(None), 7);
+	}
+	
+	#[test]
+	fn measures_custom_formats_for_each_date() {
+		assert_eq!(custom_width("%B", Some(local_time(2023, 5, 15))), 3);
+		assert_eq!(custom_width("%B", Some(local_time(2023, 9, 15))), 9);
+		assert_eq!(custom_width("%s", Some(SystemTime::UNIX_EPOCH + Duration::from_secs(999_999_999))), 9);
+		assert_eq!(custom_width("%s", Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000))), 10);
+		assert_eq!(custom_width("%Y-%m-%d", Some(local_time(12345, 1, 15))), 12);
+		assert_eq!(custom_width("%Y-%m-%d", None), 1);
+	}
+	
+	#[test]
+	fn formats_relative_times() {
+		assert_eq!(format_relative(0), "now");
+		assert_eq!(format_relative(9), "now");
+		assert_eq!(format_relative(59), "59s ago");
+		assert_eq!(format_relative(60), "1m ago");
+		assert_eq!(format_relative(3 * 60 * 60), "3h ago");
+		assert_eq!(format_relative(-3 * 60 * 60), "in 3h");
+		assert_eq!(format_relative(2 * 24 * 60 * 60), "2d ago");
+		assert_eq!(format_relative(3 * 7 * 24 * 60 * 60), "3w ago");
+		assert_eq!(format_relative(2 * 52 * 7 * 24 * 60 * 60), "2y ago");
+	}
+}
@@ -0,0 +1,27 @@
+use ratatui::buffer::Buffer;
+use ratatui::style::Style;
+
+use crate::theme::theme;
+
+const BAR_WIDTH: u16 = 10;
+
+// Percentage + Space + Bar in brackets
+pub const COLUMN_WIDTH: u16 = 6 + 1 + 1 + BAR_WIDTH + 1;
+
+/// Prints the percentage of the parent directory's total size that the entry takes, followed by a bar of the same proportion.
+pub fn print(buf: &mut Buffer, x: u16, y: u16, size: Option<u64>, parent_size: Option<u64>) {
+	let (Some(size), Some(parent_size)) = (size, parent_size) else {
+		return;
+	};
+	
+	let permille = u128::from(size).saturating_mul(1000).checked_div(u128::from(parent_size)).map_or(0, |permille| u16::try_from(permille).unwrap_or(1000).min(1000));
+	let filled_width = permille.saturating_mul(BAR_WIDTH).saturating_add(500) / 1000;
+	
+	let theme = theme();
+	let bar_x = x.saturating_add(7);
+	
+	buf.set_string(x, y, format!("{:>3}.{}%", permille / 10, permille % 10), Style::default());
+	buf.set_string(bar_x, y, "[", theme.dimmed);
+	buf.set_string(bar_x.saturating_add(1), y, "#".repeat(usize::from(filled_width)), theme.disk_usage_bar);
+	buf.set_string(bar_x.saturating_add(1).saturating_add(BAR_WIDTH), y, "]", theme.dimmed);
+}
@@ -0,0 +1,16 @@
+use ratatui::buffer::Buffer;
+use ratatui::text::Span;
+
+use crate::component::filesystem::render::column;
+use crate::theme::theme;
+
+/// Longer extensions are truncated.
+pub const MAX_COLUMN_WIDTH: u16 = 10;
+
+pub fn print(buf: &mut Buffer, x: u16, y: u16, column_width: u16, extension: Option<&str>) {
+	if let Some(extension) = extension {
+		column::print_fixed_width_cell(buf, x, y, column_width, vec![
+			Span::styled(extension, theme().secondary),
+		]);
+	}
+}
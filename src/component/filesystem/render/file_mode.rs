@@ -0,0 +1,16 @@
+use ratatui::buffer::Buffer;
+use ratatui::style::Style;
+
+use crate::file::FileMode;
+use crate::theme::theme;
+
+// Special + Owner + Group + Other
+pub const COLUMN_WIDTH: u16 = 4;
+
+pub fn print(buf: &mut Buffer, x: u16, y: u16, mode: FileMode) {
+	if let FileMode::Known(mode) = mode {
+		buf.set_string(x, y, format!("{:04o}", mode & 0o7777), Style::default());
+	} else {
+		buf.set_string(x, y, "????", theme().dimmed);
+	}
+}
@@ -1,45 +1,44 @@
 use ratatui::buffer::Buffer;
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::style::Style;
 use ratatui::text::Span;
 
-use crate::component::filesystem::render::column;
-use crate::file::{FileEntry, FileKind};
+use crate::component::filesystem::render::{column, NodeRow};
+use crate::file::FileEntry;
+use crate::theme::theme;
 
-pub fn print(buf: &mut Buffer, x: u16, y: u16, entry: &FileEntry, level: usize, column_width: u16, is_selected: bool) {
-	column::print_fixed_width_cell(buf, x, y, column_width, vec![
-		Span::styled(" ".repeat(level), Style::default()),
-		Span::styled(entry.name().str(), get_style(entry, is_selected)),
-	]);
+/// Prints the name of the entry. Entries in flat listings are prefixed with the path of their parent directory.
+pub fn print(buf: &mut Buffer, x: u16, y: u16, column_width: u16, row: &NodeRow) {
+	let style = get_style(row.entry, row.is_selected, row.is_marked);
+	let mut spans = vec![get_indent(row.level, row.is_marked)];
+	
+	if let Some(prefix) = &row.name_prefix {
+		spans.push(Span::styled(prefix.clone(), style.patch(theme().secondary)));
+	}
+	
+	spans.push(Span::styled(row.entry.name().str(), style));
+	column::print_fixed_width_cell(buf, x, y, column_width, spans);
 }
 
-fn get_style(entry: &FileEntry, is_selected: bool) -> Style {
-	let style = Style::default().fg(get_color(entry));
-	
-	if is_selected {
-		style.add_modifier(Modifier::REVERSED)
+/// Marked entries have a marker in place of the last indentation character.
+fn get_indent(level: usize, is_marked: bool) -> Span<'static> {
+	if is_marked && level > 0 {
+		Span::styled(format!("{}*", " ".repeat(level.saturating_sub(1))), theme().marker)
 	} else {
-		style
+		Span::raw(" ".repeat(level))
 	}
 }
 
-fn get_color(entry: &FileEntry) -> Color {
-	match entry.kind() {
-		FileKind::File { .. } => {
-			if entry.mode().is_executable_by_any() == Some(true) {
-				Color::LightGreen
-			} else {
-				Color::White
-			}
-		}
-		
-		FileKind::Directory => Color::LightBlue,
-		FileKind::Symlink => Color::LightCyan,
-		FileKind::Socket => Color::LightMagenta,
-		
-		FileKind::BlockDevice |
-		FileKind::CharDevice |
-		FileKind::Pipe => Color::LightYellow,
-		
-		FileKind::Unknown => Color::White,
+fn get_style(entry: &FileEntry, is_selected: bool, is_marked: bool) -> Style {
+	let theme = theme();
+	let mut style = theme.file_names.get_style(entry);
+	
+	if is_selected {
+		style = style.patch(theme.selected);
 	}
+	
+	if is_marked {
+		style = style.patch(theme.marked);
+	}
+	
+	style
 }
@@ -1,16 +1,11 @@
 use ratatui::buffer::Buffer;
-use ratatui::style::{Color, Style};
+use ratatui::style::Style;
 use ratatui::text::Span;
 
 use crate::component::filesystem::ColumnWidths;
 use crate::component::filesystem::render::column;
 use crate::file::{FileOwnerName, FileOwnerNameCache};
-
-#[cfg(unix)]
-pub const fn visible() -> bool { true }
-
-#[cfg(not(unix))]
-pub const fn visible() -> bool { false }
+use crate::theme::theme;
 
 #[allow(clippy::similar_names)]
 #[allow(clippy::trivially_copy_pass_by_ref)]
@@ -23,8 +18,8 @@ pub fn print_user_group(buf: &mut Buffer, x: u16, y: u16, uid: Option<u32>, gid:
 fn print_name(buf: &mut Buffer, x: u16, y: u16, name: &FileOwnerName, column_width: u16) {
 	let style = match name {
 		FileOwnerName::Named(_)   => Style::default(),
-		FileOwnerName::Numeric(_) => Style::default().fg(Color::Indexed(248 /* Grey66 */)),
-		FileOwnerName::Unknown    => Style::default().fg(Color::DarkGray),
+		FileOwnerName::Numeric(_) => theme().numeric_owner,
+		FileOwnerName::Unknown    => theme().dimmed,
 	};
 	
 	column::print_fixed_width_cell(buf, x, y, column_width, vec![
@@ -1,9 +1,10 @@
 #![allow(clippy::arithmetic_side_effects)]
 
 use ratatui::buffer::Buffer;
-use ratatui::style::Color;
+use ratatui::style::Style;
 
 use crate::file::{FileKind, FileMode};
+use crate::theme::theme;
 
 pub const COLUMN_WIDTH: u16 = system::COLUMN_WIDTH;
 
@@ -17,8 +18,8 @@ pub fn print(buf: &mut Buffer, x: u16, y: u16, kind: &FileKind, _mode: FileMode)
 	system::print(buf, x, y, kind);
 }
 
-fn print_char(buf: &mut Buffer, x: u16, y: u16, char: char, color: Color) {
-	buf.get_mut(x, y).set_char(char).set_fg(color);
+fn print_char(buf: &mut Buffer, x: u16, y: u16, char: char, style: Style) {
+	buf.get_mut(x, y).set_char(char).set_style(style);
 }
 
 fn print_kind(buf: &mut Buffer, x: u16, y: u16, kind: &FileKind) {
@@ -33,69 +34,72 @@ fn print_kind(buf: &mut Buffer, x: u16, y: u16, kind: &FileKind) {
 		FileKind::Unknown     => { '?' }
 	};
 	
-	print_char(buf, x, y, c, Color::Gray);
+	print_char(buf, x, y, c, theme().secondary);
 }
 
 #[cfg(unix)]
 mod system {
 	use ratatui::buffer::Buffer;
-	use ratatui::style::Color;
+	use ratatui::style::Style;
 	
 	use crate::component::filesystem::render::file_permissions::{print_char, print_kind};
 	use crate::file::{FileKind, FileMode, Permission};
+	use crate::theme::theme;
 	
 	// Kind + Owner + Group + Other
 	pub const COLUMN_WIDTH: u16 = 1 + 3 + 3 + 3;
 	
-	const READ_BIT_COLOR: Color = Color::LightBlue;
-	const WRITE_BIT_COLOR: Color = Color::LightRed;
-	const EXECUTE_BIT_COLOR: Color = Color::LightGreen;
-	
 	pub fn print(buf: &mut Buffer, x: u16, y: u16, kind: &FileKind, mode: FileMode) {
 		print_kind(buf, x, y, kind);
 		print_permissions(buf, x, y, mode);
 	}
 	
 	fn print_permissions(buf: &mut Buffer, x: u16, y: u16, mode: FileMode) {
+		let theme = theme();
+		let read = theme.permission_read;
+		let write = theme.permission_write;
+		let execute = theme.permission_execute;
+		
 		let user = mode.user();
 		let group = mode.group();
 		let others = mode.others();
 		
-		print_permission(buf, x + 1, y, user.read(), 'r', READ_BIT_COLOR);
-		print_permission(buf, x + 2, y, user.write(), 'w', WRITE_BIT_COLOR);
-		print_permission_or_special(buf, x + 3, y, user.execute(), mode.is_setuid(), 'x', 'S', 's', EXECUTE_BIT_COLOR);
+		print_permission(buf, x + 1, y, user.read(), 'r', read);
+		print_permission(buf, x + 2, y, user.write(), 'w', write);
+		print_permission_or_special(buf, x + 3, y, user.execute(), mode.is_setuid(), ['x', 'S', 's'], execute);
 		
-		print_permission(buf, x + 4, y, group.read(), 'r', READ_BIT_COLOR);
-		print_permission(buf, x + 5, y, group.write(), 'w', WRITE_BIT_COLOR);
-		print_permission_or_special(buf, x + 6, y, group.execute(), mode.is_setgid(), 'x', 'S', 's', EXECUTE_BIT_COLOR);
+		print_permission(buf, x + 4, y, group.read(), 'r', read);
+		print_permission(buf, x + 5, y, group.write(), 'w', write);
+		print_permission_or_special(buf, x + 6, y, group.execute(), mode.is_setgid(), ['x', 'S', 's'], execute);
 		
-		print_permission(buf, x + 7, y, others.read(), 'r', READ_BIT_COLOR);
-		print_permission(buf, x + 8, y, others.write(), 'w', WRITE_BIT_COLOR);
-		print_permission_or_special(buf, x + 9, y, others.execute(), mode.is_sticky(), 'x', 'T', 't', EXECUTE_BIT_COLOR);
+		print_permission(buf, x + 7, y, others.read(), 'r', read);
+		print_permission(buf, x + 8, y, others.write(), 'w', write);
+		print_permission_or_special(buf, x + 9, y, others.execute(), mode.is_sticky(), ['x', 'T', 't'], execute);
 	}
 	
-	fn print_permission(buf: &mut Buffer, x: u16, y: u16, permission: Permission, c: char, color: Color) {
-		let (c, color) = match permission {
+	fn print_permission(buf: &mut Buffer, x: u16, y: u16, permission: Permission, c: char, style: Style) {
+		let (c, style) = match permission {
 			Permission::Yes => {
-				(c, color)
+				(c, style)
 			}
 			Permission::No => {
-				('-', Color::Gray)
+				('-', theme().permission_none)
 			}
 			Permission::Unknown => {
-				('?', Color::DarkGray)
+				('?', theme().dimmed)
 			}
 		};
 		
-		print_char(buf, x, y, c, color);
+		print_char(buf, x, y, c, style);
 	}
 	
-	fn print_permission_or_special(buf: &mut Buffer, x: u16, y: u16, permission: Permission, special: Option<bool>, permission_only_char: char, special_only_char: char, permission_and_special_char: char, color: Color) {
+	/// The characters are shown when only the permission is set, when only the special bit is set, and when both are set.
+	fn print_permission_or_special(buf: &mut Buffer, x: u16, y: u16, permission: Permission, special: Option<bool>, [permission_only_char, special_only_char, permission_and_special_char]: [char; 3], style: Style) {
 		if special == Some(true) {
 			let char = if permission == Permission::Yes { permission_and_special_char } else { special_only_char };
-			print_char(buf, x, y, char, color);
+			print_char(buf, x, y, char, style);
 		} else {
-			print_permission(buf, x, y, permission, permission_only_char, color);
+			print_permission(buf, x, y, permission, permission_only_char, style);
 		}
 	}
 }
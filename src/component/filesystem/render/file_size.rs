@@ -3,54 +3,301 @@
 use ratatui::buffer::Buffer;
 use ratatui::style::Style;
 
-const VALUE_MAX_WIDTH: u16 = 3;
-pub const COLUMN_WIDTH: u16 = VALUE_MAX_WIDTH + 1 + 2;
+use crate::file::{DiskUsageTotal, FileEntry, FileKind};
 
-const UNITS: &[&str] = &[
+// Value + Space + Unit
+const SI_COLUMN_WIDTH: u16 = 3 + 1 + 2;
+const IEC_COLUMN_WIDTH: u16 = 4 + 1 + 3;
+
+const SI_UNITS: &[&str] = &[
 	"B", "kB", "MB", "GB", "TB", "PB", "EB", "ZB", "YB"
 ];
 
-pub fn print(buf: &mut Buffer, x: u16, y: u16, size: Option<u64>) {
-	if let Some(size) = size {
-		print_size_with_auto_unit(buf, x, y, size);
+const IEC_UNITS: &[&str] = &[
+	"B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB", "ZiB", "YiB"
+];
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SizeFormat {
+	/// Decimal units, where `1 kB` is 1000 bytes.
+	Si,
+	/// Binary units, where `1 KiB` is 1024 bytes.
+	Iec,
+	/// Number of bytes with thousands separators.
+	Exact,
+}
+
+impl SizeFormat {
+	pub fn parse(value: &str) -> Result<Self, String> {
+		match value {
+			"si" => Ok(Self::Si),
+			"iec" => Ok(Self::Iec),
+			"exact" => Ok(Self::Exact),
+			_ => Err(format!("Unknown size format: {value}")),
+		}
+	}
+	
+	pub const fn next(self) -> Self {
+		match self {
+			Self::Si => Self::Iec,
+			Self::Iec => Self::Exact,
+			Self::Exact => Self::Si,
+		}
+	}
+	
+	/// Returns the column width needed to print all sizes up to the largest size.
+	pub fn get_column_width(self, largest_size: u64) -> u16 {
+		match self {
+			Self::Si => SI_COLUMN_WIDTH,
+			Self::Iec => IEC_COLUMN_WIDTH,
+			Self::Exact => u16::try_from(format_exact(largest_size).len()).unwrap_or(u16::MAX),
+		}
+	}
+	
+	/// Formats the size for messages, with the unit in all formats.
+	pub fn format(self, size: u64) -> String {
+		match self {
+			Self::Si => format_with_unit(format_size_with_si_unit(size)),
+			Self::Iec => format_with_unit(format_size_with_iec_unit(size)),
+			Self::Exact => format!("{} B", format_exact(size)),
+		}
+	}
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum SizeKind {
+	/// Number of bytes in the file.
+	Apparent,
+	/// Number of bytes allocated on disk, which differs for sparse and compressed files, and files that do not fill their last block.
+	Allocated,
+}
+
+impl SizeKind {
+	pub fn parse(value: &str) -> Result<Self, String> {
+		match value {
+			"apparent" => Ok(Self::Apparent),
+			"allocated" => Ok(Self::Allocated),
+			_ => Err(format!("Unknown size kind: {value}")),
+		}
+	}
+	
+	pub const fn toggle(self) -> Self {
+		match self {
+			Self::Apparent => Self::Allocated,
+			Self::Allocated => Self::Apparent,
+		}
+	}
+	
+	/// Returns the size of files. Other kinds of entries have no size.
+	pub fn get(self, entry: &FileEntry) -> Option<u64> {
+		let FileKind::File { size } = entry.kind() else {
+			return None;
+		};
+		
+		match self {
+			Self::Apparent => Some(*size),
+			Self::Allocated => entry.stats().map(|stats| stats.blocks().saturating_mul(512)),
+		}
+	}
+	
+	pub const fn get_total(self, total: DiskUsageTotal) -> u64 {
+		match self {
+			Self::Apparent => total.apparent,
+			Self::Allocated => total.allocated,
+		}
+	}
+}
+
+pub fn print(buf: &mut Buffer, x: u16, y: u16, column_width: u16, format: SizeFormat, size: Option<u64>) {
+	let Some(size) = size else {
+		return;
+	};
+	
+	match format {
+		SizeFormat::Si => print_size_with_si_unit(buf, x + column_width - SI_COLUMN_WIDTH, y, size),
+		SizeFormat::Iec => print_size_with_iec_unit(buf, x + column_width - IEC_COLUMN_WIDTH, y, size),
+		SizeFormat::Exact => print_right_aligned(buf, x, y, column_width, format_exact(size)),
 	}
 }
 
-fn print_size_with_auto_unit(buf: &mut Buffer, x: u16, y: u16, size: u64) {
+fn print_size_with_si_unit(buf: &mut Buffer, x: u16, y: u16, size: u64) {
+	let (size_text, unit_symbol) = format_size_with_si_unit(size);
+	print_size_with_unit(buf, x, y, SI_COLUMN_WIDTH, size_text, unit_symbol);
+}
+
+fn format_size_with_si_unit(size: u64) -> (String, &'static str) {
 	let mut size = size;
 	let mut unit = 0;
 	
-	while size >= 1_000_000 && unit < UNITS.len() - 1 {
+	while size >= 1_000_000 && unit < SI_UNITS.len() - 1 {
 		size /= 1_000;
 		unit += 1;
 	}
 	
-	if size < 1_000 || unit == UNITS.len() - 1 {
+	if size < 1_000 || unit == SI_UNITS.len() - 1 {
 		#[allow(clippy::indexing_slicing)] // Guarded by previous loop.
-		print_size_with_unit(buf, x, y, size.to_string(), UNITS[unit]);
-		return;
+		return (size.to_string(), SI_UNITS[unit]);
 	}
 	
 	#[allow(clippy::indexing_slicing)] // Guarded by previous condition.
-	let unit_symbol = UNITS[unit + 1];
+	let unit_symbol = SI_UNITS[unit + 1];
 	
 	if size >= 10_000 || (size / 1_000) * 1_000 == size {
-		size /= 1_000;
-		print_size_with_unit(buf, x, y, size.to_string(), unit_symbol);
+		((size / 1_000).to_string(), unit_symbol)
 	} else {
 		let whole_part = size / 1_000;
 		let decimal_part = (size % 1_000) / 100;
-		print_size_with_unit(buf, x, y, format!("{whole_part}.{decimal_part}"), unit_symbol);
+		(format!("{whole_part}.{decimal_part}"), unit_symbol)
+	}
+}
+
+fn print_size_with_iec_unit(buf: &mut Buffer, x: u16, y: u16, size: u64) {
+	let (size_text, unit_symbol) = format_size_with_iec_unit(size);
+	print_size_with_unit(buf, x, y, IEC_COLUMN_WIDTH, size_text, unit_symbol);
+}
+
+/// Formats the size in the largest unit where the value stays below 1024. Values below 10 include one decimal digit.
+fn format_size_with_iec_unit(size: u64) -> (String, &'static str) {
+	let mut whole_part = size;
+	let mut remainder = 0;
+	let mut unit = 0;
+	
+	while whole_part >= 1024 && unit < IEC_UNITS.len() - 1 {
+		remainder = whole_part % 1024;
+		whole_part /= 1024;
+		unit += 1;
+	}
+	
+	#[allow(clippy::indexing_slicing)] // Guarded by previous loop.
+	let unit_symbol = IEC_UNITS[unit];
+	
+	if unit > 0 && whole_part < 10 {
+		let decimal_part = (remainder * 10) / 1024;
+		(format!("{whole_part}.{decimal_part}"), unit_symbol)
+	} else {
+		(whole_part.to_string(), unit_symbol)
 	}
 }
 
-fn print_size_with_unit(buf: &mut Buffer, x: u16, y: u16, size_text: String, unit_symbol: &str) {
+fn print_size_with_unit(buf: &mut Buffer, x: u16, y: u16, column_width: u16, size_text: String, unit_symbol: &str) {
 	let symbol_width = unit_symbol.len();
 	let total_width = size_text.len() + 1 + symbol_width;
 	
 	#[allow(clippy::cast_possible_truncation)] // Widths are always small enough.
-	buf.set_string(x + COLUMN_WIDTH - total_width as u16, y, size_text, Style::default());
+	buf.set_string(x + column_width - total_width as u16, y, size_text, Style::default());
 	
 	#[allow(clippy::cast_possible_truncation)] // Widths are always small enough.
-	buf.set_string(x + COLUMN_WIDTH - symbol_width as u16, y, unit_symbol, Style::default());
+	buf.set_string(x + column_width - symbol_width as u16, y, unit_symbol, Style::default());
+}
+
+fn format_with_unit((size_text, unit_symbol): (String, &str)) -> String {
+	format!("{size_text} {unit_symbol}")
+}
+
+fn print_right_aligned(buf: &mut Buffer, x: u16, y: u16, column_width: u16, text: String) {
+	let width = u16::try_from(text.len()).unwrap_or(column_width);
+	buf.set_string(x + column_width.saturating_sub(width), y, text, Style::default());
+}
+
+/// Formats the number of bytes with commas separating groups of thousands.
+fn format_exact(size: u64) -> String {
+	let digits = size.to_string();
+	let mut result = String::with_capacity(digits.len() + digits.len() / 3);
+	
+	for (index, digit) in digits.chars().enumerate() {
+		if index > 0 && (digits.len() - index) % 3 == 0 {
+			result.push(',');
+		}
+		
+		result.push(digit);
+	}
+	
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::component::filesystem::render::file_size::{format_exact, format_size_with_iec_unit, format_size_with_si_unit, SizeFormat, IEC_COLUMN_WIDTH, SI_COLUMN_WIDTH};
+	
+	fn iec(size: u64) -> String {
+		let (size_text, unit_symbol) = format_size_with_iec_unit(size);
+		format!("{size_text} {unit_symbol}")
+	}
+	
+	fn si(size: u64) -> String {
+		let (size_text, unit_symbol) = format_size_with_si_unit(size);
+		format!("{size_text} {unit_symbol}")
+	}
+	
+	#[test]
+	fn parses_size_formats() {
+		assert_eq!(SizeFormat::parse("si"), Ok(SizeFormat::Si));
+		assert_eq!(SizeFormat::parse("iec"), Ok(SizeFormat::Iec));
+		assert_eq!(SizeFormat::parse("exact"), Ok(SizeFormat::Exact));
+		assert_eq!(SizeFormat::parse("SI"), Err(String::from("Unknown size format: SI")));
+	}
+	
+	#[test]
+	fn cycles_through_size_formats() {
+		assert_eq!(SizeFormat::Si.next(), SizeFormat::Iec);
+		assert_eq!(SizeFormat::Iec.next(), SizeFormat::Exact);
+		assert_eq!(SizeFormat::Exact.next(), SizeFormat::Si);
+	}
+	
+	#[test]
+	fn formats_exact_sizes_with_separators() {
+		assert_eq!(format_exact(0), "0");
+		assert_eq!(format_exact(999), "999");
+		assert_eq!(format_exact(1_000), "1,000");
+		assert_eq!(format_exact(123_456), "123,456");
+		assert_eq!(format_exact(1_234_567), "1,234,567");
+		assert_eq!(format_exact(u64::MAX), "18,446,744,073,709,551,615");
+	}
+	
+	#[test]
+	fn formats_sizes_with_iec_units() {
+		assert_eq!(iec(0), "0 B");
+		assert_eq!(iec(1023), "1023 B");
+		assert_eq!(iec(1024), "1.0 KiB");
+		assert_eq!(iec(1536), "1.5 KiB");
+		assert_eq!(iec(10 * 1024 - 1), "9.9 KiB");
+		assert_eq!(iec(10 * 1024), "10 KiB");
+		assert_eq!(iec(1024 * 1024 - 1), "1023 KiB");
+		assert_eq!(iec(1024 * 1024), "1.0 MiB");
+		assert_eq!(iec(5 * 1024 * 1024 * 1024), "5.0 GiB");
+		assert_eq!(iec(u64::MAX), "15 EiB");
+	}
+	
+	#[test]
+	fn formats_sizes_with_si_units() {
+		assert_eq!(si(0), "0 B");
+		assert_eq!(si(999), "999 B");
+		assert_eq!(si(1_000), "1 kB");
+		assert_eq!(si(1_500), "1.5 kB");
+		assert_eq!(si(999_999), "999 kB");
+		assert_eq!(si(1_000_000), "1 MB");
+		assert_eq!(si(u64::MAX), "18 EB");
+	}
+	
+	#[test]
+	fn formatted_sizes_fit_in_columns() {
+		let mut size = 1_u64;
+		
+		while let Some(next_size) = size.checked_mul(3) {
+			for size in [size - 1, size, size + 1] {
+				assert!(si(size).len() <= usize::from(SI_COLUMN_WIDTH), "{} does not fit", si(size));
+				assert!(iec(size).len() <= usize::from(IEC_COLUMN_WIDTH), "{} does not fit", iec(size));
+			}
+			
+			size = next_size;
+		}
+	}
+	
+	#[test]
+	fn formats_sizes_for_messages() {
+		assert_eq!(SizeFormat::Si.format(1_500), "1.5 kB");
+		assert_eq!(SizeFormat::Iec.format(1_536), "1.5 KiB");
+		assert_eq!(SizeFormat::Exact.format(1_536), "1,536 B");
+		assert_eq!(SizeFormat::Exact.get_column_width(1_234_567), 9);
+	}
 }
@@ -0,0 +1,22 @@
+use ratatui::buffer::Buffer;
+
+use crate::file::GitStatus;
+use crate::theme::theme;
+
+pub const COLUMN_WIDTH: u16 = 1;
+
+pub fn print(buf: &mut Buffer, x: u16, y: u16, status: Option<GitStatus>) {
+	let Some(status) = status else {
+		return;
+	};
+	
+	let marker = match status {
+		GitStatus::Ignored    => "I",
+		GitStatus::Untracked  => "?",
+		GitStatus::Staged     => "S",
+		GitStatus::Modified   => "M",
+		GitStatus::Conflicted => "C",
+	};
+	
+	buf.set_string(x, y, marker, theme().git_status(status));
+}
@@ -0,0 +1,47 @@
+use std::cmp::{max, min};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Widget};
+
+use crate::component::filesystem::FsLayer;
+use crate::theme::theme;
+
+/// Prints a popup at the bottom of the area, which lists keys that can follow the pending keys, and descriptions of their actions.
+pub fn print(buf: &mut Buffer, area: Rect, layer: &FsLayer) {
+	let mut continuations = layer.action_map.continuations(&layer.pending_keys)
+		.into_iter()
+		.map(|(keys, action)| (keys.iter().map(ToString::to_string).collect::<String>(), action.description()))
+		.collect::<Vec<_>>();
+	
+	if continuations.is_empty() {
+		return;
+	}
+	
+	continuations.sort_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+	
+	let theme = theme();
+	let keys_width = continuations.iter().fold(0, |width, (keys, _)| max(width, Span::from(keys.as_str()).width()));
+	
+	let lines = continuations.into_iter()
+		.map(|(keys, description)| Line::from(vec![
+			Span::raw(" "),
+			Span::styled(format!("{keys:keys_width$}"), theme.dialog_key),
+			Span::raw("  "),
+			Span::raw(description),
+		]))
+		.collect::<Vec<_>>();
+	
+	let height = min(u16::try_from(lines.len()).unwrap_or(u16::MAX).saturating_add(2), area.height);
+	let popup_area = Rect { y: area.bottom().saturating_sub(height), height, ..area };
+	
+	let prefix = layer.pending_keys.iter().map(ToString::to_string).collect::<String>();
+	let block = Block::default()
+		.title(format!(" {prefix} "))
+		.borders(Borders::TOP | Borders::BOTTOM)
+		.border_style(theme.dialog_info.border);
+	
+	Clear.render(popup_area, buf);
+	Paragraph::new(lines).block(block).render(popup_area, buf);
+}
@@ -7,73 +7,195 @@ use ratatui::widgets::{Clear, Widget};
 use slab_tree::{NodeId, NodeRef};
 
 use crate::component::filesystem::{ColumnWidths, FsLayer};
+use crate::component::filesystem::columns::Column;
+use crate::component::filesystem::render::date_time::DateTimePrinter;
+use crate::component::filesystem::git::GitStatusTracker;
 use crate::component::filesystem::tree::{FsTree, FsTreeViewNode};
-use crate::file::{FileEntry, FileKind, FileOwnerNameCache};
+use crate::file::{FileEntry, FileOwnerNameCache, FileStats, GitStatus};
 use crate::state::view::Frame;
 
+pub use self::date_time::DateFormat;
+pub use self::file_size::{SizeFormat, SizeKind};
+
 mod column;
 mod date_time;
+mod disk_usage;
+mod file_extension;
+mod file_mode;
 mod file_name;
 mod file_owner;
 mod file_permissions;
 mod file_size;
+mod git_status;
+mod key_continuations;
+mod number;
+mod status_bar;
+
+/// The name column is only shrunk below this width when there are no other columns left to hide.
+const MIN_NAME_COLUMN_WIDTH: u16 = 20;
 
 pub fn render(layer: &mut FsLayer, frame: &mut Frame) {
 	let size = frame.size();
+	let (tree_area, status_bar_area) = split_status_bar(size);
 	
-	let column_widths = get_or_update_column_widths(layer, size.width);
+	let show_git_status = layer.git_status.is_in_repository();
+	let column_widths = get_or_update_column_widths(layer);
+	let date_time_printer = DateTimePrinter::new(&layer.date_format);
+	let (name_column_width, columns) = get_visible_columns(&get_columns(layer), &column_widths, show_git_status, tree_area.width);
 	let file_owner_name_cache = &mut layer.file_owner_name_cache;
 	
-	let (rows, cursor_y) = collect_displayed_rows(&layer.tree, layer.tree.selected_view_node_id, size.height as usize);
+	let (rows, cursor_y) = collect_displayed_rows(&layer.tree, &layer.git_status, layer.size_kind, layer.tree.selected_view_node_id, tree_area.height as usize);
 	layer.cursor_y = cursor_y;
+	layer.displayed_node_ids = rows.iter().map(|row| row.node_id).collect();
+	
+	let row_renderer = RowRenderer { name_column_width, columns, column_widths, date_time_printer, size_format: layer.size_format, size_kind: layer.size_kind, file_owner_name_cache };
 	
 	frame.render_widget(Clear, size);
-	frame.render_widget(FsWidget { rows, column_widths, file_owner_name_cache }, size);
+	frame.render_widget(FsWidget { rows, row_renderer }, tree_area);
+	
+	if layer.show_key_continuations {
+		frame.render_widget(KeyContinuationsWidget { layer }, tree_area);
+	}
+	
+	if let Some(status_bar_area) = status_bar_area {
+		frame.render_widget(StatusBarWidget { layer }, status_bar_area);
+	}
 }
 
-fn get_or_update_column_widths(layer: &mut FsLayer, cols: u16) -> ColumnWidths {
-	let mut column_widths = *layer.column_width_cache.get_or_insert_with(|| {
+/// Reserves the last row for the status bar, unless the terminal is too small.
+fn split_status_bar(size: Rect) -> (Rect, Option<Rect>) {
+	if size.height < 2 {
+		return (size, None);
+	}
+	
+	let tree_area = Rect { height: size.height.saturating_sub(1), ..size };
+	let status_bar_area = Rect { y: tree_area.bottom(), height: 1, ..size };
+	(tree_area, Some(status_bar_area))
+}
+
+/// Adds the disk usage column in front of other columns when disk usage is known, unless the column is already in the list.
+fn get_columns(layer: &FsLayer) -> Vec<Column> {
+	if layer.tree.disk_usage().is_some() && !layer.columns.contains(&Column::DiskUsage) {
+		std::iter::once(Column::DiskUsage).chain(layer.columns.iter().copied()).collect()
+	} else {
+		layer.columns.clone()
+	}
+}
+
+fn get_or_update_column_widths(layer: &mut FsLayer) -> ColumnWidths {
+	*layer.column_width_cache.get_or_insert_with(|| {
 		let mut name: usize = 0;
+		let mut largest_size: u64 = 0;
 		let mut user: usize = 0;
 		let mut group: usize = 0;
+		let mut inode: u16 = 0;
+		let mut link_count: u16 = 0;
+		let mut blocks: u16 = 0;
+		let mut extension: u16 = 0;
+		let mut date_time: u16 = 0;
+		
+		let date_time_columns = layer.columns.iter().copied().filter(|column| column.is_date_time()).collect::<Vec<_>>();
 		
 		for node in layer.tree.view_iter() {
 			let entry = layer.tree.get_entry(&node).unwrap_or_else(|| FileEntry::dummy_as_ref());
 			
-			name = max(name, get_node_level(&node).saturating_add(Span::from(entry.name().str()).width()));
+			let prefix_width = layer.tree.get_flat_listing_prefix(&node).map_or(0, |prefix| Span::from(prefix).width());
+			name = max(name, get_node_level(&node).saturating_add(prefix_width).saturating_add(Span::from(entry.name().str()).width()));
+			largest_size = max(largest_size, layer.tree.get_disk_usage(&node, layer.size_kind).or_else(|| layer.size_kind.get(entry)).unwrap_or(0));
 			user = max(user, layer.file_owner_name_cache.get_user(entry.uid()).len());
 			group = max(group, layer.file_owner_name_cache.get_group(entry.gid()).len());
+			
+			if let Some(stats) = entry.stats() {
+				inode = max(inode, number::get_width(stats.inode()));
+				link_count = max(link_count, number::get_width(stats.link_count()));
+				blocks = max(blocks, number::get_width(stats.blocks()));
+			}
+			
+			if let Some(entry_extension) = entry.extension() {
+				extension = max(extension, u16::try_from(Span::from(entry_extension).width()).unwrap_or(u16::MAX));
+			}
+			
+			for column in &date_time_columns {
+				date_time = max(date_time, layer.date_format.get_width(column.get_date_time(entry).as_ref()));
+			}
 		}
 		
 		ColumnWidths {
 			name: u16::try_from(name).unwrap_or(u16::MAX),
+			disk_usage: if layer.tree.disk_usage().is_some() { disk_usage::COLUMN_WIDTH } else { 0 },
+			size: layer.size_format.get_column_width(largest_size),
 			user: u16::try_from(user).unwrap_or(u16::MAX),
 			group: u16::try_from(group).unwrap_or(u16::MAX),
+			inode,
+			link_count,
+			blocks,
+			extension: min(extension, file_extension::MAX_COLUMN_WIDTH),
+			date_time,
 		}
-	});
+	})
+}
+
+/// Hides columns with the lowest priority until all remaining columns fit next to the name column, and then shrinks the name column to the remaining width.
+fn get_visible_columns(columns: &[Column], column_widths: &ColumnWidths, show_git_status: bool, cols: u16) -> (u16, Vec<(Column, u16)>) {
+	let mut visible_columns = columns.iter()
+		.filter(|column| show_git_status || **column != Column::GitStatus)
+		.map(|column| (*column, get_column_width(*column, column_widths)))
+		.filter(|(_, width)| *width > 0)
+		.collect::<Vec<_>>();
 	
-	let owner_column_width_padded = file_owner::visible().then(|| column_widths.user_and_group().saturating_add(2)).unwrap_or(0);
-	let max_name_column_width = cols.saturating_sub(2 + file_size::COLUMN_WIDTH + 2 + date_time::COLUMN_WIDTH + 2 + file_permissions::COLUMN_WIDTH).saturating_sub(owner_column_width_padded);
+	let min_name_column_width = min(column_widths.name, MIN_NAME_COLUMN_WIDTH);
 	
-	column_widths.name = min(column_widths.name, max_name_column_width);
-	column_widths
+	loop {
+		let columns_width = visible_columns.iter().fold(0_u16, |total, (_, width)| total.saturating_add(2).saturating_add(*width));
+		
+		if min_name_column_width.saturating_add(columns_width) <= cols {
+			return (min(column_widths.name, cols.saturating_sub(columns_width)), visible_columns);
+		}
+		
+		let lowest_priority_index = visible_columns.iter()
+			.enumerate()
+			.min_by_key(|(_, (column, _))| column.priority())
+			.map(|(index, _)| index);
+		
+		if let Some(index) = lowest_priority_index {
+			visible_columns.remove(index);
+		} else {
+			return (cols, visible_columns);
+		}
+	}
+}
+
+const fn get_column_width(column: Column, column_widths: &ColumnWidths) -> u16 {
+	match column {
+		Column::DiskUsage => column_widths.disk_usage,
+		Column::GitStatus => git_status::COLUMN_WIDTH,
+		Column::Size => column_widths.size,
+		Column::ModifiedTime | Column::AccessedTime | Column::ChangedTime | Column::BirthTime => column_widths.date_time,
+		Column::Owner => column_widths.user_and_group(),
+		Column::Permissions => file_permissions::COLUMN_WIDTH,
+		Column::OctalMode => file_mode::COLUMN_WIDTH,
+		Column::Inode => column_widths.inode,
+		Column::LinkCount => column_widths.link_count,
+		Column::Blocks => column_widths.blocks,
+		Column::Extension => column_widths.extension,
+	}
 }
 
-fn collect_displayed_rows(tree: &FsTree, selected_node_id: NodeId, terminal_rows: usize) -> (Vec<NodeRow>, u16) {
+fn collect_displayed_rows<'a>(tree: &'a FsTree, git_status: &GitStatusTracker, size_kind: SizeKind, selected_node_id: NodeId, terminal_rows: usize) -> (Vec<NodeRow<'a>>, u16) {
 	let mut displayed_rows = Vec::with_capacity(terminal_rows);
 	let mut cursor_y: u16 = 0;
 	
 	if let Some(middle_node) = tree.selected_node().or_else(|| tree.view_root_node()) {
 		let middle_node_id = middle_node.node_id();
 		
-		displayed_rows.push(NodeRow::from(&middle_node, tree, middle_node_id == selected_node_id));
+		displayed_rows.push(NodeRow::from(&middle_node, tree, git_status, size_kind, middle_node_id == selected_node_id));
 		
 		let mut cursor_up_id = Some(middle_node_id);
 		let mut cursor_down_id = Some(middle_node_id);
 		
 		while displayed_rows.len() < terminal_rows {
 			if let Some(next_node_up) = move_cursor(tree, &mut cursor_up_id, |node| node.above_id()) {
-				displayed_rows.insert(0, NodeRow::from(&next_node_up, tree, false));
+				displayed_rows.insert(0, NodeRow::from(&next_node_up, tree, git_status, size_kind, false));
 				cursor_y = cursor_y.saturating_add(1);
 			}
 			
@@ -82,7 +204,7 @@ fn collect_displayed_rows(tree: &FsTree, selected_node_id: NodeId, terminal_rows
 			}
 			
 			if let Some(next_node_down) = move_cursor(tree, &mut cursor_down_id, |node| node.below_id()) {
-				displayed_rows.push(NodeRow::from(&next_node_down, tree, false));
+				displayed_rows.push(NodeRow::from(&next_node_down, tree, git_status, size_kind, false));
 			}
 			
 			if cursor_up_id.is_none() && cursor_down_id.is_none() {
@@ -107,15 +229,14 @@ fn move_cursor<'a, F>(tree: &'a FsTree, cursor: &mut Option<NodeId>, func: F) ->
 
 struct FsWidget<'a> {
 	rows: Vec<NodeRow<'a>>,
-	column_widths: ColumnWidths,
-	file_owner_name_cache: &'a mut FileOwnerNameCache,
+	row_renderer: RowRenderer<'a>,
 }
 
 impl Widget for FsWidget<'_> {
-	fn render(self, _area: Rect, buf: &mut Buffer) {
+	fn render(mut self, _area: Rect, buf: &mut Buffer) {
 		for (index, row) in self.rows.iter().enumerate() {
 			if let Ok(row_index) = u16::try_from(index) {
-				row.render(buf, row_index, &self.column_widths, self.file_owner_name_cache);
+				row.render(buf, row_index, &mut self.row_renderer);
 			} else {
 				break;
 			}
@@ -123,67 +244,105 @@ impl Widget for FsWidget<'_> {
 	}
 }
 
+struct KeyContinuationsWidget<'a> {
+	layer: &'a FsLayer,
+}
+
+impl Widget for KeyContinuationsWidget<'_> {
+	fn render(self, area: Rect, buf: &mut Buffer) {
+		key_continuations::print(buf, area, self.layer);
+	}
+}
+
+struct StatusBarWidget<'a> {
+	layer: &'a FsLayer,
+}
+
+impl Widget for StatusBarWidget<'_> {
+	fn render(self, area: Rect, buf: &mut Buffer) {
+		status_bar::print(buf, area, self.layer);
+	}
+}
+
+/// Layout of visible columns, and state shared by all rows rendered in one frame.
+struct RowRenderer<'a> {
+	name_column_width: u16,
+	columns: Vec<(Column, u16)>,
+	column_widths: ColumnWidths,
+	date_time_printer: DateTimePrinter<'a>,
+	size_format: SizeFormat,
+	size_kind: SizeKind,
+	file_owner_name_cache: &'a mut FileOwnerNameCache,
+}
+
 struct NodeRow<'a> {
+	node_id: NodeId,
 	level: usize,
 	entry: &'a FileEntry,
+	name_prefix: Option<String>,
 	is_selected: bool,
+	is_marked: bool,
+	git_status: Option<GitStatus>,
+	disk_usage: Option<u64>,
+	parent_disk_usage: Option<u64>,
 }
 
 impl<'a> NodeRow<'a> {
-	fn from(view_node: &NodeRef<'a, FsTreeViewNode>, tree: &'a FsTree, is_selected: bool) -> Self {
-		return Self {
+	fn from(view_node: &NodeRef<'a, FsTreeViewNode>, tree: &'a FsTree, git_status: &GitStatusTracker, size_kind: SizeKind, is_selected: bool) -> Self {
+		let entry = tree.get_entry(view_node).unwrap_or_else(|| FileEntry::dummy_as_ref());
+		
+		Self {
+			node_id: view_node.node_id(),
 			level: get_node_level(view_node),
-			entry: tree.get_entry(view_node).unwrap_or_else(|| FileEntry::dummy_as_ref()),
+			entry,
+			name_prefix: tree.get_flat_listing_prefix(view_node),
 			is_selected,
-		};
+			is_marked: tree.is_marked(view_node),
+			git_status: entry.path().and_then(|path| git_status.get(path)),
+			disk_usage: tree.get_disk_usage(view_node, size_kind),
+			parent_disk_usage: view_node.parent().and_then(|parent| tree.get_disk_usage(&parent, size_kind)),
+		}
 	}
 	
-	#[allow(clippy::trivially_copy_pass_by_ref)]
-	fn render(&self, buf: &mut Buffer, y: u16, column_widths: &ColumnWidths, file_owner_name_cache: &mut FileOwnerNameCache) {
-		let entry = self.entry;
-		
-		let width = buf.area().width;
-		let mut x = 0;
-		
-		file_name::print(buf, x, y, entry, self.level, column_widths.name, self.is_selected);
-		x = x.saturating_add(column_widths.name).saturating_add(2);
-		
-		if exceeds_width(x, file_size::COLUMN_WIDTH, width) {
-			return;
-		}
-		
-		file_size::print(buf, x, y, if let FileKind::File { size } = entry.kind() { Some(*size) } else { None });
-		x = x.saturating_add(file_size::COLUMN_WIDTH).saturating_add(2);
-		
-		if exceeds_width(x, date_time::COLUMN_WIDTH, width) {
-			return;
-		}
-		
-		date_time::print(buf, x, y, entry.modified_time());
-		x = x.saturating_add(date_time::COLUMN_WIDTH).saturating_add(2);
+	fn render(&self, buf: &mut Buffer, y: u16, renderer: &mut RowRenderer) {
+		file_name::print(buf, 0, y, renderer.name_column_width, self);
+		let mut x = renderer.name_column_width;
 		
-		if file_owner::visible() {
-			if exceeds_width(x, column_widths.user_and_group(), width) {
-				return;
-			}
+		for index in 0..renderer.columns.len() {
+			let Some((column, column_width)) = renderer.columns.get(index).copied() else {
+				break;
+			};
 			
-			file_owner::print_user_group(buf, x, y, entry.uid(), entry.gid(), file_owner_name_cache, column_widths);
-			x = x.saturating_add(column_widths.user_and_group()).saturating_add(2);
+			x = x.saturating_add(2);
+			self.render_column(buf, x, y, column, column_width, renderer);
+			x = x.saturating_add(column_width);
 		}
+	}
+	
+	fn render_column(&self, buf: &mut Buffer, x: u16, y: u16, column: Column, column_width: u16, renderer: &mut RowRenderer) {
+		let entry = self.entry;
+		let date_time_printer = &renderer.date_time_printer;
+		let stats = entry.stats();
 		
-		if exceeds_width(x, file_permissions::COLUMN_WIDTH, width) {
-			return;
+		match column {
+			Column::DiskUsage => disk_usage::print(buf, x, y, self.disk_usage, self.parent_disk_usage),
+			Column::GitStatus => git_status::print(buf, x, y, self.git_status),
+			Column::Size => file_size::print(buf, x, y, column_width, renderer.size_format, self.disk_usage.or_else(|| renderer.size_kind.get(entry))),
+			Column::ModifiedTime => date_time_printer.print(buf, x, y, column_width, entry.modified_time()),
+			Column::AccessedTime => date_time_printer.print(buf, x, y, column_width, entry.accessed_time()),
+			Column::ChangedTime => date_time_printer.print(buf, x, y, column_width, entry.changed_time().as_ref()),
+			Column::BirthTime => date_time_printer.print(buf, x, y, column_width, entry.birth_time()),
+			Column::Owner => file_owner::print_user_group(buf, x, y, entry.uid(), entry.gid(), renderer.file_owner_name_cache, &renderer.column_widths),
+			Column::Permissions => file_permissions::print(buf, x, y, entry.kind(), entry.mode()),
+			Column::OctalMode => file_mode::print(buf, x, y, entry.mode()),
+			Column::Inode => number::print(buf, x, y, column_width, stats.map(FileStats::inode)),
+			Column::LinkCount => number::print(buf, x, y, column_width, stats.map(FileStats::link_count)),
+			Column::Blocks => number::print(buf, x, y, column_width, stats.map(FileStats::blocks)),
+			Column::Extension => file_extension::print(buf, x, y, column_width, entry.extension()),
 		}
-		
-		file_permissions::print(buf, x, y, entry.kind(), entry.mode());
 	}
 }
 
-fn exceeds_width(x: u16, column_width: u16, terminal_width: u16) -> bool {
-	let x = x.checked_add(column_width);
-	x.is_none() || x.is_some_and(|x| x > terminal_width)
-}
-
 fn get_node_level<T>(node: &NodeRef<T>) -> usize {
 	node.ancestors().count()
 }
@@ -0,0 +1,16 @@
+use ratatui::buffer::Buffer;
+use ratatui::style::Style;
+
+use crate::util;
+
+/// Returns the number of digits needed to print the number.
+pub fn get_width(value: u64) -> u16 {
+	u16::try_from(util::int_len(value)).unwrap_or(u16::MAX)
+}
+
+/// Prints the number aligned to the right side of the column.
+pub fn print(buf: &mut Buffer, x: u16, y: u16, column_width: u16, value: Option<u64>) {
+	if let Some(value) = value {
+		buf.set_string(x.saturating_add(column_width.saturating_sub(get_width(value))), y, value.to_string(), Style::default());
+	}
+}
@@ -0,0 +1,80 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::text::Span;
+
+use crate::component::filesystem::FsLayer;
+use crate::component::filesystem::render::column;
+use crate::file::FileEntry;
+use crate::theme::theme;
+
+/// Prints a status message or the path of the selected entry on the left, and pending keys and the number of entries in the selected directory on the right.
+pub fn print(buf: &mut Buffer, area: Rect, layer: &FsLayer) {
+	let style = theme().status_bar;
+	buf.set_style(area, style);
+	
+	let right_text = get_right_text(layer);
+	let right_width = u16::try_from(Span::from(right_text.as_str()).width()).unwrap_or(u16::MAX);
+	let right_x = area.right().saturating_sub(right_width.saturating_add(1));
+	
+	buf.set_string(right_x, area.y, right_text, style);
+	
+	let left_width = right_x.saturating_sub(area.x).saturating_sub(2);
+	let left = if let Some(message) = &layer.status_message {
+		Span::styled(message.text(), style.patch(message.style()))
+	} else {
+		Span::raw(get_selected_path(layer))
+	};
+	
+	if left_width > 0 {
+		column::print_fixed_width_cell(buf, area.x.saturating_add(1), area.y, left_width, vec![left]);
+	}
+}
+
+fn get_selected_path(layer: &FsLayer) -> String {
+	layer.tree.selected_node()
+	     .and_then(|node| layer.tree.get_entry(&node))
+	     .and_then(FileEntry::display_path)
+	     .map(|path| path.to_string_lossy().into_owned())
+	     .unwrap_or_default()
+}
+
+fn get_right_text(layer: &FsLayer) -> String {
+	let mut parts = Vec::new();
+	
+	let count = layer.registers.count.map(|count| count.to_string()).unwrap_or_default();
+	let pending_keys = format!("{count}{}", layer.pending_keys.iter().map(ToString::to_string).collect::<String>());
+	
+	if !pending_keys.is_empty() {
+		parts.push(pending_keys);
+	}
+	
+	if let Some(disk_usage) = layer.disk_usage.as_ref().filter(|disk_usage| disk_usage.is_scanning()) {
+		parts.push(format!("Scanning disk usage: {} entries", disk_usage.scanned_entries()));
+	}
+	
+	let running_jobs = layer.jobs.running_count();
+	
+	if running_jobs > 0 {
+		let progress = layer.jobs.running_progress();
+		let state = if progress.scanning { String::from("scanning") } else { format!("{}%", progress.percent()) };
+		parts.push(if running_jobs == 1 { format!("1 job: {state}") } else { format!("{running_jobs} jobs: {state}") });
+	}
+	
+	if let Some(count) = count_entries_in_selected_directory(layer) {
+		parts.push(if count == 1 { String::from("1 entry") } else { format!("{count} entries") });
+	}
+	
+	parts.join("  ")
+}
+
+/// Counts visible entries in the selected directory or archive if it is expanded, or in the parent directory of the selected entry otherwise.
+fn count_entries_in_selected_directory(layer: &FsLayer) -> Option<usize> {
+	let tree = &layer.tree;
+	let selected_node = tree.selected_node()?;
+	
+	if selected_node.data().is_expanded() {
+		Some(selected_node.children().count())
+	} else {
+		selected_node.parent().map(|parent| parent.children().count())
+	}
+}
@@ -0,0 +1,52 @@
+use std::time::{Duration, Instant};
+
+use ratatui::style::Style;
+
+use crate::component::filesystem::FsLayer;
+use crate::state::Environment;
+use crate::state::event::{Event, EventResult};
+use crate::theme::theme;
+
+const MESSAGE_DURATION: Duration = Duration::from_secs(5);
+
+/// Short notice shown in the status bar in place of the selected path, which disappears after a few seconds.
+#[derive(Clone)]
+pub struct StatusMessage {
+	text: String,
+	style: Style,
+	created_at: Instant,
+}
+
+impl StatusMessage {
+	fn new(text: String, style: Style) -> Self {
+		Self { text, style, created_at: Instant::now() }
+	}
+	
+	pub fn info(text: impl Into<String>) -> Self {
+		Self::new(text.into(), theme().status_info)
+	}
+	
+	pub fn error(text: impl Into<String>) -> Self {
+		Self::new(text.into(), theme().status_error)
+	}
+	
+	pub fn text(&self) -> &str {
+		&self.text
+	}
+	
+	pub const fn style(&self) -> Style {
+		self.style
+	}
+	
+	pub fn is_expired(&self) -> bool {
+		self.created_at.elapsed() >= MESSAGE_DURATION
+	}
+}
+
+/// Shows the message once the event is dispatched, so that it can be sent from dialog callbacks.
+impl Event<FsLayer> for StatusMessage {
+	fn dispatch(&self, layer: &mut FsLayer, _environment: &Environment) -> EventResult {
+		layer.show_message(self.clone());
+		EventResult::Draw
+	}
+}
@@ -1,14 +1,20 @@
+use std::collections::HashSet;
 use std::path::Path;
+use std::sync::Arc;
 
 use slab_tree::{NodeId, NodeRef};
 
+use crate::component::filesystem::render::SizeKind;
 use crate::component::filesystem::tree::view::FsTreeViewIterator;
-use crate::file::FileEntry;
+use crate::file::{DiskUsage, FileEntry, FileSystem};
 
 pub use self::model::FsTreeModel;
 pub use self::model::FsTreeModelNode;
 pub use self::view::FsTreeView;
+pub use self::view::FsTreeViewFilter;
 pub use self::view::FsTreeViewNode;
+pub use self::view::FsTreeViewSort;
+pub use self::view::MAX_FLATTENED_ENTRIES;
 
 mod model;
 mod view;
@@ -18,11 +24,13 @@ pub struct FsTree {
 	view: FsTreeView,
 	pub selected_view_node_id: NodeId,
 	structure_version: u32,
+	refresh_version: u32,
+	filtered_expanded_model_node_ids: HashSet<NodeId>,
 }
 
 impl FsTree {
-	pub fn with_root_path(path: &Path) -> Self {
-		let model = FsTreeModel::with_root_path(path);
+	pub fn new(root_path: &Path, file_system: Arc<dyn FileSystem>) -> Self {
+		let model = FsTreeModel::new(root_path, file_system);
 		let view = FsTreeView::from_model_root(&model);
 		let root_id = view.root_id();
 		
@@ -31,38 +39,101 @@ impl FsTree {
 			view,
 			selected_view_node_id: root_id,
 			structure_version: 0,
+			refresh_version: 0,
+			filtered_expanded_model_node_ids: HashSet::new(),
 		};
 		
 		tree.expand(root_id);
 		tree
 	}
 	
+	pub fn file_system(&self) -> Arc<dyn FileSystem> {
+		self.model.file_system()
+	}
+	
 	pub const fn structure_version(&self) -> u32 {
 		self.structure_version
 	}
 	
-	pub fn selected_node(&self) -> Option<NodeRef<FsTreeViewNode>> {
-		return self.view.get(self.selected_view_node_id);
+	/// Returns a number that changes every time entries are read again from the filesystem, or the root directory changes.
+	pub const fn refresh_version(&self) -> u32 {
+		self.refresh_version
 	}
 	
-	pub fn view_root_node(&self) -> Option<NodeRef<FsTreeViewNode>> {
+	pub fn selected_node(&self) -> Option<NodeRef<'_, FsTreeViewNode>> {
+		self.view.get(self.selected_view_node_id)
+	}
+	
+	pub fn view_root_node(&self) -> Option<NodeRef<'_, FsTreeViewNode>> {
 		self.view.root()
 	}
 	
-	pub fn view_iter(&self) -> FsTreeViewIterator {
+	pub fn view_root_path(&self) -> Option<&Path> {
+		self.view_root_node().and_then(|node| self.get_entry(&node)).and_then(FileEntry::path)
+	}
+	
+	pub fn view_iter(&self) -> FsTreeViewIterator<'_> {
 		self.view.into_iter()
 	}
 	
-	pub fn get_view_node(&self, view_node_id: NodeId) -> Option<NodeRef<FsTreeViewNode>> {
+	pub fn get_view_node(&self, view_node_id: NodeId) -> Option<NodeRef<'_, FsTreeViewNode>> {
 		self.view.get(view_node_id)
 	}
 	
 	pub fn get_entry(&self, node: &NodeRef<FsTreeViewNode>) -> Option<&FileEntry> {
+		self.get_model_entry(node.data().model_node_id())
+	}
+	
+	fn get_entry_node(&self, node: &NodeRef<FsTreeViewNode>) -> Option<&FsTreeModelNode> {
+		self.model.get(node.data().model_node_id()).map(|model_node| model_node.data())
+	}
+	
+	pub fn get_model_entry(&self, model_node_id: NodeId) -> Option<&FileEntry> {
 		self.model
-		    .get(node.data().model_node_id())
+		    .get(model_node_id)
 		    .map(|node| &node.data().entry)
 	}
 	
+	pub fn get_model_parent_id(&self, model_node_id: NodeId) -> Option<NodeId> {
+		self.model.get(model_node_id).and_then(|node| node.parent_id())
+	}
+	
+	pub fn is_marked(&self, node: &NodeRef<FsTreeViewNode>) -> bool {
+		self.model.get(node.data().model_node_id()).is_some_and(|node| node.data().is_marked)
+	}
+	
+	pub fn toggle_mark(&mut self, view_node_id: NodeId) -> bool {
+		if let Some(mut model_node) = self.view.get(view_node_id).and_then(|node| self.model.get_mut(node.data().model_node_id())) {
+			let data = model_node.data();
+			data.is_marked = !data.is_marked;
+			true
+		} else {
+			false
+		}
+	}
+	
+	pub fn clear_marks(&mut self) -> bool {
+		let marked_model_node_ids = self.marked_model_node_ids();
+		
+		for model_node_id in &marked_model_node_ids {
+			if let Some(mut model_node) = self.model.get_mut(*model_node_id) {
+				model_node.data().is_marked = false;
+			}
+		}
+		
+		!marked_model_node_ids.is_empty()
+	}
+	
+	/// Returns ids of all marked model nodes, including nodes hidden inside collapsed directories.
+	pub fn marked_model_node_ids(&self) -> Vec<NodeId> {
+		self.model.root().map(|root| {
+			root.traverse_pre_order()
+			    .filter(|node| node.data().is_marked)
+			    .map(|node| node.node_id())
+			    .collect()
+		}).unwrap_or_default()
+	}
+	
 	pub fn expand(&mut self, view_node_id: NodeId) -> bool {
 		let result = self.view.expand(view_node_id, &mut self.model);
 		self.structure_changed_if_true(result)
@@ -80,12 +151,18 @@ impl FsTree {
 	
 	pub fn traverse_up_root(&mut self) -> Option<NodeId> {
 		let new_root_id = self.view.traverse_up_root(&mut self.model);
+		
+		if new_root_id.is_some() {
+			self.refreshed();
+		}
+		
 		self.structure_changed_if(new_root_id, Option::is_some)
 	}
 	
 	pub fn refresh_children(&mut self, view_node_id: NodeId) -> bool {
-		if let Some(view_node) = self.view.get(view_node_id) {
-			let result = self.model.refresh_children(view_node.data().model_node_id()) && self.view.refresh_children(view_node_id, &self.model);
+		if let Some(model_node_id) = self.view.get(view_node_id).map(|view_node| view_node.data().model_node_id()) {
+			self.refreshed();
+			let result = self.model.refresh_children(model_node_id) && self.view.refresh_children(view_node_id, &mut self.model);
 			if result && self.selected_node().is_none() {
 				self.selected_view_node_id = view_node_id;
 			}
@@ -95,6 +172,161 @@ impl FsTree {
 		}
 	}
 	
+	/// Refreshes children of a model node, and of every view node that displays it, including flat listings of its ancestors. Unlike [`FsTree::refresh_children`], this also works for directories that are not visible.
+	pub fn refresh_model_children(&mut self, model_node_id: NodeId) -> bool {
+		self.refreshed();
+		
+		if !self.model.refresh_children(model_node_id) {
+			return false;
+		}
+		
+		let model_ancestor_ids = self.model.get(model_node_id).map(|node| node.ancestors().map(|ancestor| ancestor.node_id()).collect::<HashSet<_>>()).unwrap_or_default();
+		
+		let view_node_ids = self.view_iter()
+		                        .filter(|node| node.data().model_node_id() == model_node_id || (node.data().is_flattened() && model_ancestor_ids.contains(&node.data().model_node_id())))
+		                        .map(|node| node.node_id())
+		                        .collect::<Vec<_>>();
+		
+		for view_node_id in view_node_ids {
+			self.view.refresh_children(view_node_id, &mut self.model);
+		}
+		
+		if self.selected_node().is_none() {
+			self.selected_view_node_id = self.view.root_id();
+		}
+		
+		self.structure_changed();
+		true
+	}
+	
+	/// Refreshes children of the directory at the path if its model node exists, which is the case for directories that were expanded at least once.
+	pub fn refresh_model_directory(&mut self, path: &Path) -> bool {
+		let model_node_id = self.model.root().and_then(|root| {
+			root.traverse_pre_order()
+			    .find(|node| node.data().entry.path() == Some(path))
+			    .map(|node| node.node_id())
+		});
+		
+		model_node_id.is_some_and(|model_node_id| self.refresh_model_children(model_node_id))
+	}
+	
+	pub const fn filter(&self) -> FsTreeViewFilter {
+		self.view.filter()
+	}
+	
+	/// Changes which entries are visible, and filters children of all expanded directories again without collapsing them. If the selected entry is filtered out, its closest visible ancestor is selected instead.
+	pub fn set_filter(&mut self, filter: FsTreeViewFilter) -> bool {
+		if self.view.filter() == filter {
+			return false;
+		}
+		
+		let mut new_selected_view_node_id = self.selected_view_node_id;
+		let mut node = self.selected_node();
+		
+		while let Some(current_node) = node {
+			let parent_id = current_node.parent_id();
+			
+			if let Some(parent_id) = parent_id {
+				if self.get_entry_node(&current_node).is_some_and(|model_node| !filter.is_visible(model_node)) {
+					new_selected_view_node_id = parent_id;
+				}
+			}
+			
+			node = parent_id.and_then(|id| self.view.get(id));
+		}
+		
+		let mut expanded_model_node_ids = std::mem::take(&mut self.filtered_expanded_model_node_ids);
+		expanded_model_node_ids.extend(self.view_iter().filter(|node| node.data().is_expanded()).map(|node| node.data().model_node_id()));
+		
+		self.view.set_filter(filter);
+		self.view.refresh_children(self.view.root_id(), &mut self.model);
+		self.selected_view_node_id = new_selected_view_node_id;
+		self.expand_model_nodes(&mut expanded_model_node_ids);
+		
+		// Directories that are still filtered out are expanded again once they become visible.
+		self.filtered_expanded_model_node_ids = expanded_model_node_ids;
+		
+		self.structure_changed();
+		true
+	}
+	
+	pub const fn sort(&self) -> FsTreeViewSort {
+		self.view.sort()
+	}
+	
+	/// Changes the order of entries in all expanded directories and flat listings.
+	pub fn set_sort(&mut self, sort: FsTreeViewSort) -> bool {
+		if self.view.sort() == sort {
+			return false;
+		}
+		
+		self.view.set_sort(sort);
+		self.view.refresh_children(self.view.root_id(), &mut self.model);
+		self.structure_changed();
+		true
+	}
+	
+	pub const fn disk_usage(&self) -> Option<&DiskUsage> {
+		self.model.disk_usage()
+	}
+	
+	/// Replaces the scanned total sizes of directories, and sorts all expanded directories again if they are sorted by disk usage.
+	pub fn set_disk_usage(&mut self, disk_usage: Option<DiskUsage>) {
+		self.model.set_disk_usage(disk_usage);
+		self.sort_again_if_by_disk_usage();
+	}
+	
+	/// Returns the scanned total size of a directory, or the size of any other entry. Returns `None` if disk usage was not scanned.
+	pub fn get_disk_usage(&self, node: &NodeRef<FsTreeViewNode>, kind: SizeKind) -> Option<u64> {
+		self.get_entry(node).and_then(|entry| self.model.get_disk_usage(entry, kind))
+	}
+	
+	fn sort_again_if_by_disk_usage(&mut self) {
+		if matches!(self.view.sort(), FsTreeViewSort::DiskUsage(_)) {
+			self.view.refresh_children(self.view.root_id(), &mut self.model);
+		}
+		
+		self.structure_changed();
+	}
+	
+	/// Turns a directory into a flat listing of all its descendants that are not directories. Entries in the listing can be selected and modified like any other entries.
+	pub fn flatten(&mut self, view_node_id: NodeId) -> bool {
+		let result = self.view.flatten(view_node_id, &mut self.model);
+		self.structure_changed_if_true(result)
+	}
+	
+	/// Returns the path of the directory that contains the entry, relative to the flattened directory whose listing includes the entry. Returns `None` for entries that are not in a flat listing, or are directly inside the flattened directory.
+	pub fn get_flat_listing_prefix(&self, node: &NodeRef<FsTreeViewNode>) -> Option<String> {
+		let flattened_node = node.parent().filter(|parent| parent.data().is_flattened())?;
+		let flattened_path = self.get_entry(&flattened_node)?.path()?;
+		let relative_parent = self.get_entry(node)?.path()?.parent()?.strip_prefix(flattened_path).ok()?;
+		
+		if relative_parent.as_os_str().is_empty() {
+			None
+		} else {
+			Some(format!("{}/", relative_parent.to_string_lossy()))
+		}
+	}
+	
+	/// Expands every visible directory whose model node is in the set, including directories that become visible by expanding their parents. Expanded model nodes are removed from the set.
+	fn expand_model_nodes(&mut self, model_node_ids: &mut HashSet<NodeId>) {
+		let mut remaining_view_node_ids = vec![self.view.root_id()];
+		
+		while let Some(view_node_id) = remaining_view_node_ids.pop() {
+			let Some(model_node_id) = self.view.get(view_node_id).map(|node| node.data().model_node_id()) else {
+				continue;
+			};
+			
+			if model_node_ids.remove(&model_node_id) {
+				self.view.expand(view_node_id, &mut self.model);
+			}
+			
+			if let Some(node) = self.view.get(view_node_id) {
+				remaining_view_node_ids.extend(node.children().map(|child| child.node_id()));
+			}
+		}
+	}
+	
 	pub fn select_child_node_by_name(&mut self, parent_view_node_id: NodeId, child_file_name: &str) -> bool {
 		self.expand(parent_view_node_id);
 		
@@ -110,6 +342,46 @@ impl FsTree {
 		false
 	}
 	
+	/// Selects the entry at a path inside the view root directory, expanding all its ancestors. Directories whose children do not include the next path component are refreshed once, in case the entry was created after they were read.
+	pub fn select_path(&mut self, path: &Path) -> bool {
+		let Some(relative_path) = self.view_root_path().and_then(|root_path| path.strip_prefix(root_path).ok()).map(Path::to_path_buf) else {
+			return false;
+		};
+		
+		let mut view_node_id = self.view.root_id();
+		
+		for component in &relative_path {
+			if self.view.get(view_node_id).is_some_and(|node| node.data().is_flattened()) {
+				return self.select_child_node_by_path(view_node_id, path) || (self.refresh_children(view_node_id) && self.select_child_node_by_path(view_node_id, path));
+			}
+			
+			let name = component.to_string_lossy();
+			let found = self.select_child_node_by_name(view_node_id, &name) || (self.refresh_children(view_node_id) && self.select_child_node_by_name(view_node_id, &name));
+			
+			if !found {
+				return false;
+			}
+			
+			view_node_id = self.selected_view_node_id;
+		}
+		
+		self.selected_view_node_id = view_node_id;
+		true
+	}
+	
+	fn select_child_node_by_path(&mut self, parent_view_node_id: NodeId, child_path: &Path) -> bool {
+		let child_node_id = self.view.get(parent_view_node_id).and_then(|parent_node| {
+			parent_node.children().find(|child_node| self.get_entry(child_node).and_then(FileEntry::path) == Some(child_path)).map(|child_node| child_node.node_id())
+		});
+		
+		if let Some(child_node_id) = child_node_id {
+			self.selected_view_node_id = child_node_id;
+			true
+		} else {
+			false
+		}
+	}
+	
 	pub fn delete_node(&mut self, view_node_id: NodeId) -> bool {
 		let view = &mut self.view;
 		
@@ -118,13 +390,26 @@ impl FsTree {
 		}
 		
 		if let Some(view_node) = view.remove(view_node_id) {
-			self.model.remove(view_node.model_node_id());
+			let model_node_id = view_node.model_node_id();
+			
+			if self.model.disk_usage().is_some() {
+				self.model.remove_from_disk_usage(model_node_id);
+				self.model.remove(model_node_id);
+				self.sort_again_if_by_disk_usage();
+			} else {
+				self.model.remove(model_node_id);
+			}
+			
 			true
 		} else {
 			false
 		}
 	}
 	
+	fn refreshed(&mut self) {
+		self.refresh_version = self.refresh_version.wrapping_add(1);
+	}
+	
 	fn structure_changed(&mut self) {
 		self.structure_version = self.structure_version.wrapping_add(1);
 	}
@@ -140,3 +425,79 @@ impl FsTree {
 		self.structure_changed_if(result, |result| *result)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::path::Path;
+	use std::sync::Arc;
+	
+	use crate::component::filesystem::tree::FsTree;
+	use crate::file::{FileSystem, MemoryFileSystem};
+	
+	fn visible_names(tree: &FsTree) -> Vec<String> {
+		tree.view_iter().filter_map(|node| tree.get_entry(&node)).map(|entry| entry.name().str().to_owned()).collect()
+	}
+	
+	fn create_tree() -> (Arc<MemoryFileSystem>, FsTree) {
+		let file_system = Arc::new(MemoryFileSystem::new()
+			.with_file("/root/b.txt", "b")
+			.with_directory("/root/a")
+			.with_file("/root/a/inner", "x"));
+		
+		let tree = FsTree::new(Path::new("/root"), Arc::clone(&file_system) as Arc<dyn FileSystem>);
+		(file_system, tree)
+	}
+	
+	#[test]
+	fn reads_entries_from_the_file_system() {
+		let (_, tree) = create_tree();
+		assert_eq!(visible_names(&tree), vec!["root", "a", "b.txt"]);
+	}
+	
+	#[test]
+	fn refresh_shows_created_entries() {
+		let (file_system, mut tree) = create_tree();
+		assert!(file_system.create_file(Path::new("/root/c.txt")).is_ok(), "Could not create file");
+		assert!(file_system.create_directory(Path::new("/root/0")).is_ok(), "Could not create directory");
+		
+		assert_eq!(visible_names(&tree), vec!["root", "a", "b.txt"]);
+		assert!(tree.refresh_model_directory(Path::new("/root")), "Root directory was not refreshed");
+		assert_eq!(visible_names(&tree), vec!["root", "0", "a", "b.txt", "c.txt"]);
+	}
+	
+	#[test]
+	fn refresh_shows_renamed_entries() {
+		let (file_system, mut tree) = create_tree();
+		assert!(file_system.rename(Path::new("/root/b.txt"), Path::new("/root/0.txt")).is_ok(), "Could not rename file");
+		
+		assert!(tree.refresh_model_directory(Path::new("/root")), "Root directory was not refreshed");
+		assert_eq!(visible_names(&tree), vec!["root", "0.txt", "a"]);
+	}
+	
+	#[test]
+	fn refresh_removes_deleted_entries() {
+		let (file_system, mut tree) = create_tree();
+		
+		if let Some(a) = tree.view_iter().find(|node| tree.get_entry(node).is_some_and(|entry| entry.name().str() == "a")).map(|node| node.node_id()) {
+			tree.expand(a);
+		}
+		
+		assert_eq!(visible_names(&tree), vec!["root", "a", "inner", "b.txt"]);
+		assert!(file_system.remove(Path::new("/root/a")).is_ok(), "Could not delete directory");
+		
+		assert!(tree.refresh_model_directory(Path::new("/root")), "Root directory was not refreshed");
+		assert_eq!(visible_names(&tree), vec!["root", "b.txt"]);
+	}
+	
+	#[test]
+	fn flat_listing_is_sorted_by_name() {
+		let (_, mut tree) = create_tree();
+		
+		let Some(root_id) = tree.view_root_node().map(|node| node.node_id()) else {
+			panic!("Tree has no root");
+		};
+		
+		assert!(tree.flatten(root_id), "Root directory was not flattened");
+		assert_eq!(visible_names(&tree), vec!["root", "b.txt", "inner"]);
+	}
+}
@@ -1,30 +1,25 @@
-use std::fs::DirEntry;
-use std::io;
+use std::rc::Rc;
 
 use slab_tree::NodeId;
 
 use crate::component::filesystem::tree::{FsTreeModel, FsTreeModelNode};
-use crate::file::FileEntry;
+use crate::file::{ArchiveLocation, FileEntry, FileKind, FileSystem, IgnoreRules, read_archive_children};
 
 impl FsTreeModel {
 	pub fn resolve_children(&mut self, node_id: NodeId) -> Option<Vec<NodeId>> {
-		if let Some(mut node) = self.get_mut(node_id) {
-			let data = node.data();
+		if !self.get(node_id)?.data().are_children_known {
+			let children = self.read_directory_children(node_id);
 			
-			if !data.are_children_known {
-				data.are_children_known = true;
+			if let Some(mut node) = self.get_mut(node_id) {
+				node.data().are_children_known = true;
 				
-				if let Some(children) = Self::get_directory_children(&data.entry) {
-					for child in children {
-						node.append(FsTreeModelNode::from(child));
-					}
+				for child in children.into_iter().flatten() {
+					node.append(child);
 				}
 			}
-			
-			node.first_child_id().map(|id| self.collect_next_siblings(id))
-		} else {
-			None
 		}
+		
+		self.get_children(node_id)
 	}
 	
 	pub fn get_children(&self, node_id: NodeId) -> Option<Vec<NodeId>> {
@@ -43,14 +38,57 @@ impl FsTreeModel {
 		children
 	}
 	
-	pub fn get_directory_children(entry: &FileEntry) -> Option<Vec<FileEntry>> {
-		entry.path()
-		     .and_then(|path| std::fs::read_dir(path).ok())
-		     .map(|reader| reader.map(read_entry).collect())
+	/// Reads children of a directory node, using ignore rules of its parent node to determine which children are ignored.
+	pub fn read_directory_children(&mut self, node_id: NodeId) -> Option<Vec<FsTreeModelNode>> {
+		let parent_ignore_rules = self.get(node_id)?.parent().and_then(|parent| parent.data().ignore_rules.clone());
+		let file_system = self.file_system();
+		let mut node = self.get_mut(node_id)?;
+		Self::get_directory_children(file_system.as_ref(), node.data(), parent_ignore_rules)
+	}
+	
+	/// Reads children of a directory node, and updates its ignore rules. Children are ignored if the directory is ignored, or if they are matched by ignore rules of the directory or any of its parents.
+	pub fn get_directory_children(file_system: &dyn FileSystem, node: &mut FsTreeModelNode, parent_ignore_rules: Option<Rc<IgnoreRules>>) -> Option<Vec<FsTreeModelNode>> {
+		if let Some(location) = Self::get_archive_location(&node.entry) {
+			return Some(Self::get_archive_children(node, &location, parent_ignore_rules));
+		}
+		
+		let path = node.entry.path()?;
+		let entries = file_system.list(path).ok()?;
+		
+		let ignore_rules = match parent_ignore_rules {
+			Some(parent_ignore_rules) => IgnoreRules::for_directory(file_system, Some(parent_ignore_rules), path),
+			None => IgnoreRules::for_root(file_system, path),
+		};
+		
+		let is_directory_ignored = node.is_ignored;
+		
+		let children = entries.into_iter().map(|entry| {
+			let is_ignored = is_directory_ignored || entry.path().is_some_and(|path| ignore_rules.is_ignored(path, matches!(entry.kind(), FileKind::Directory)));
+			FsTreeModelNode { is_ignored, ..FsTreeModelNode::from(entry) }
+		}).collect();
+		
+		node.ignore_rules = Some(ignore_rules);
+		Some(children)
+	}
+	
+	/// Returns the location of entries inside an archive file, or inside a directory in an archive file.
+	fn get_archive_location(entry: &FileEntry) -> Option<ArchiveLocation> {
+		if let Some(location) = entry.archive_location() {
+			matches!(entry.kind(), FileKind::Directory).then(|| location.clone())
+		} else {
+			Some(ArchiveLocation::root(entry.path()?, entry.archive_kind()?))
+		}
+	}
+	
+	/// Reads children of an archive node. Archives have no ignore files of their own, so their entries are only ignored if the archive is, and ignore rules of the parent are passed down unchanged.
+	fn get_archive_children(node: &mut FsTreeModelNode, location: &ArchiveLocation, parent_ignore_rules: Option<Rc<IgnoreRules>>) -> Vec<FsTreeModelNode> {
+		let is_ignored = node.is_ignored;
+		
+		let children = read_archive_children(location).unwrap_or_default().into_iter().map(|entry| {
+			FsTreeModelNode { is_ignored, ..FsTreeModelNode::from(entry) }
+		}).collect();
+		
+		node.ignore_rules = parent_ignore_rules;
+		children
 	}
-}
-
-#[allow(clippy::needless_pass_by_value)]
-fn read_entry(entry: io::Result<DirEntry>) -> FileEntry {
-	entry.as_ref().ok().map_or_else(FileEntry::dummy, FileEntry::from)
 }
@@ -1,8 +1,11 @@
 use std::path::Path;
+use std::rc::Rc;
+use std::sync::Arc;
 
 use slab_tree::{NodeId, NodeMut, NodeRef, RemoveBehavior, Tree};
 
-use crate::file::FileEntry;
+use crate::component::filesystem::render::SizeKind;
+use crate::file::{DiskUsage, DiskUsageTotal, FileEntry, FileKind, FileSystem, IgnoreRules};
 
 mod children;
 mod parents;
@@ -11,40 +14,96 @@ mod refresh;
 pub struct FsTreeModel {
 	inner: Tree<FsTreeModelNode>,
 	root_id: NodeId,
+	file_system: Arc<dyn FileSystem>,
+	disk_usage: Option<DiskUsage>,
 }
 
 impl FsTreeModel {
-	pub fn with_root_path(path: &Path) -> Self {
+	pub fn new(root_path: &Path, file_system: Arc<dyn FileSystem>) -> Self {
 		let mut inner = Tree::new();
-		let root_id = inner.set_root(FsTreeModelNode::from(FileEntry::from(path)));
+		let root_id = inner.set_root(FsTreeModelNode::from(file_system.stat(root_path)));
 		
-		Self { inner, root_id }
+		Self { inner, root_id, file_system, disk_usage: None }
+	}
+	
+	/// Returns the file system that entries are read from, which file actions should also use.
+	pub fn file_system(&self) -> Arc<dyn FileSystem> {
+		Arc::clone(&self.file_system)
 	}
 	
 	pub const fn root_id(&self) -> NodeId {
 		self.root_id
 	}
 	
-	pub fn get(&self, node_id: NodeId) -> Option<NodeRef<FsTreeModelNode>> {
+	pub fn root(&self) -> Option<NodeRef<'_, FsTreeModelNode>> {
+		self.inner.root()
+	}
+	
+	pub fn get(&self, node_id: NodeId) -> Option<NodeRef<'_, FsTreeModelNode>> {
 		self.inner.get(node_id)
 	}
 	
-	pub fn get_mut(&mut self, node_id: NodeId) -> Option<NodeMut<FsTreeModelNode>> {
+	pub fn get_mut(&mut self, node_id: NodeId) -> Option<NodeMut<'_, FsTreeModelNode>> {
 		self.inner.get_mut(node_id)
 	}
 	
 	pub fn remove(&mut self, node_id: NodeId) -> Option<FsTreeModelNode> {
 		self.inner.remove(node_id, RemoveBehavior::DropChildren)
 	}
+	
+	pub const fn disk_usage(&self) -> Option<&DiskUsage> {
+		self.disk_usage.as_ref()
+	}
+	
+	pub fn set_disk_usage(&mut self, disk_usage: Option<DiskUsage>) {
+		self.disk_usage = disk_usage;
+	}
+	
+	/// Returns the scanned total size of a directory, or the size of any other entry. Returns `None` if disk usage was not scanned, or the directory was not part of the scan.
+	pub fn get_disk_usage(&self, entry: &FileEntry, kind: SizeKind) -> Option<u64> {
+		let disk_usage = self.disk_usage.as_ref()?;
+		
+		if matches!(entry.kind(), FileKind::Directory) {
+			entry.path().and_then(|path| disk_usage.get(path)).map(|total| kind.get_total(total))
+		} else {
+			Some(kind.get_total(DiskUsageTotal::of_entry(entry)))
+		}
+	}
+	
+	/// Subtracts the size of an entry that is about to be deleted from the scanned totals of all directories that contain it.
+	pub fn remove_from_disk_usage(&mut self, node_id: NodeId) {
+		let Some(entry) = self.inner.get(node_id).map(|node| &node.data().entry) else {
+			return;
+		};
+		
+		if let (Some(disk_usage), Some(path)) = (self.disk_usage.as_mut(), entry.path()) {
+			disk_usage.remove(path, DiskUsageTotal::of_entry(entry));
+		}
+	}
 }
 
 pub struct FsTreeModelNode {
 	pub entry: FileEntry,
+	pub is_marked: bool,
+	is_ignored: bool,
+	ignore_rules: Option<Rc<IgnoreRules>>,
 	are_children_known: bool,
 }
 
+impl FsTreeModelNode {
+	/// Returns whether the entry is matched by an ignore file, or is inside an ignored directory.
+	pub const fn is_ignored(&self) -> bool {
+		self.is_ignored
+	}
+	
+	/// Returns ignore rules that apply to children of this directory, once they were read.
+	pub fn ignore_rules(&self) -> Option<Rc<IgnoreRules>> {
+		self.ignore_rules.clone()
+	}
+}
+
 impl From<FileEntry> for FsTreeModelNode {
 	fn from(entry: FileEntry) -> Self {
-		Self { entry, are_children_known: false }
+		Self { entry, is_marked: false, is_ignored: false, ignore_rules: None, are_children_known: false }
 	}
 }
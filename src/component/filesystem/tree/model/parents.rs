@@ -3,7 +3,7 @@ use std::path::Path;
 use slab_tree::{NodeId, NodeMut};
 
 use crate::component::filesystem::tree::{FsTreeModel, FsTreeModelNode};
-use crate::file::FileEntry;
+use crate::file::FileSystem;
 
 impl FsTreeModel {
 	pub fn traverse_up_root(&mut self) -> Option<NodeId> {
@@ -13,8 +13,9 @@ impl FsTreeModel {
 		if let Some(new_root) = self.find_parent_of_root() {
 			self.root_id = self.inner.set_root(new_root);
 			
+			let file_system = self.file_system();
 			if let Some(mut new_root) = self.inner.root_mut() {
-				Self::resolve_new_root_children(&mut new_root, old_root_path);
+				Self::resolve_new_root_children(file_system.as_ref(), &mut new_root, old_root_path);
 			}
 			
 			Some(self.root_id)
@@ -23,20 +24,20 @@ impl FsTreeModel {
 		}
 	}
 	
-	fn find_parent_of_root(&mut self) -> Option<FsTreeModelNode> {
+	fn find_parent_of_root(&self) -> Option<FsTreeModelNode> {
 		self.inner.get(self.root_id)
 		    .and_then(|root| root.data().entry.path())
 		    .and_then(Path::parent)
-		    .map(FileEntry::from)
+		    .map(|path| self.file_system.stat(path))
 		    .map(FsTreeModelNode::from)
 	}
 	
-	fn resolve_new_root_children(new_root: &mut NodeMut<FsTreeModelNode>, old_root_path: Option<&Path>) {
+	fn resolve_new_root_children(file_system: &dyn FileSystem, new_root: &mut NodeMut<FsTreeModelNode>, old_root_path: Option<&Path>) {
 		new_root.data().are_children_known = true;
 		
-		for child in Self::get_directory_children(&new_root.data().entry).unwrap_or_default() {
-			if child.path() != old_root_path {
-				new_root.append(FsTreeModelNode::from(child));
+		for child in Self::get_directory_children(file_system, new_root.data(), None).unwrap_or_default() {
+			if child.entry.path() != old_root_path {
+				new_root.append(child);
 			} else if let Some(mut old_root) = new_root.first_child() {
 				old_root.make_last_sibling();
 			}
@@ -4,13 +4,20 @@ use std::path::Path;
 use slab_tree::{NodeId, NodeRef};
 
 use crate::component::filesystem::tree::{FsTreeModel, FsTreeModelNode};
-use crate::file::FileEntry;
+use crate::file::{ArchiveLocation, FileEntry};
 
 impl FsTreeModel {
 	pub fn refresh_children(&mut self, parent_node_id: NodeId) -> bool {
-		if let Some(parent_node) = self.get(parent_node_id).filter(|node| node.data().are_children_known) {
+		if self.get(parent_node_id).is_some_and(|node| node.data().are_children_known) {
+			let mut remaining_new_entries = self.read_directory_children(parent_node_id)
+			                                    .map(|children| children.into_iter().map(Some).collect::<Vec<_>>())
+			                                    .unwrap_or_default();
+			
+			let Some(parent_node) = self.get(parent_node_id) else {
+				return false;
+			};
+			
 			let old_children = collect_old_nodes(&parent_node);
-			let mut remaining_new_entries = collect_new_entries_as_optionals(&parent_node);
 			
 			let (update_node_ids, remove_node_ids) = compare_nodes(old_children, &remaining_new_entries);
 			
@@ -21,7 +28,9 @@ impl FsTreeModel {
 			for (update_node_id, new_entry_index) in &update_node_ids {
 				if let Some(mut node) = self.get_mut(*update_node_id) {
 					if let Some(new_entry) = remaining_new_entries.get_mut(*new_entry_index).and_then(Option::take) {
-						node.data().entry = new_entry;
+						let data = node.data();
+						data.entry = new_entry.entry;
+						data.is_ignored = new_entry.is_ignored;
 					} else {
 						self.remove(*update_node_id);
 					}
@@ -30,7 +39,7 @@ impl FsTreeModel {
 			
 			if let Some(mut parent_node) = self.get_mut(parent_node_id) {
 				for new_entry in remaining_new_entries.into_iter().flatten() {
-					parent_node.append(FsTreeModelNode::from(new_entry));
+					parent_node.append(new_entry);
 				}
 			}
 			
@@ -52,34 +61,39 @@ fn collect_old_nodes<'a>(parent_node: &'a NodeRef<FsTreeModelNode>) -> Vec<(Node
 		.collect::<Vec<_>>()
 }
 
-fn collect_new_entries_as_optionals(parent_node: &NodeRef<FsTreeModelNode>) -> Vec<Option<FileEntry>> {
-	let parent_entry = &parent_node.data().entry;
-	
-	FsTreeModel::get_directory_children(parent_entry)
-		.map(|children| children.into_iter().map(Some).collect::<Vec<_>>())
-		.unwrap_or_default()
+/// Identifies an entry across refreshes. Entries inside archives have no path, so they are identified by their location in the archive instead.
+#[derive(Eq, PartialEq, Hash)]
+enum EntryKey<'a> {
+	Path(&'a Path),
+	Archive(&'a ArchiveLocation),
+}
+
+impl<'a> EntryKey<'a> {
+	fn of(entry: &'a FileEntry) -> Option<Self> {
+		entry.path().map(Self::Path).or_else(|| entry.archive_location().map(Self::Archive))
+	}
 }
 
-fn create_file_entry_index(entries: &[Option<FileEntry>]) -> HashMap<&Path, usize> {
+fn create_file_entry_index(entries: &[Option<FsTreeModelNode>]) -> HashMap<EntryKey<'_>, usize> {
 	let mut map = HashMap::new();
 	
 	for (i, entry) in entries.iter().enumerate() {
-		if let Some(path) = entry.as_ref().and_then(FileEntry::path) {
-			map.insert(path, i);
+		if let Some(key) = entry.as_ref().and_then(|node| EntryKey::of(&node.entry)) {
+			map.insert(key, i);
 		}
 	}
 	
 	map
 }
 
-fn compare_nodes(old_entries: Vec<(NodeId, &FileEntry)>, new_entries: &[Option<FileEntry>]) -> (Vec<(NodeId, usize)>, Vec<NodeId>) {
+fn compare_nodes(old_entries: Vec<(NodeId, &FileEntry)>, new_entries: &[Option<FsTreeModelNode>]) -> (Vec<(NodeId, usize)>, Vec<NodeId>) {
 	let new_entry_index = create_file_entry_index(new_entries);
 	
 	let mut update_node_ids = vec![];
 	let mut remove_node_ids = vec![];
 	
 	for (old_node_id, old_entry) in old_entries {
-		if let Some(new_entry_index) = old_entry.path().and_then(|path| new_entry_index.get(path)) {
+		if let Some(new_entry_index) = EntryKey::of(old_entry).and_then(|key| new_entry_index.get(&key)) {
 			update_node_ids.push((old_node_id, *new_entry_index));
 		} else {
 			remove_node_ids.push(old_node_id);
@@ -1,10 +1,11 @@
 use slab_tree::{NodeId, NodeMut, RemoveBehavior};
 
-use crate::component::filesystem::tree::{FsTreeModel, FsTreeView, FsTreeViewNode};
+use crate::component::filesystem::tree::{FsTreeModel, FsTreeView, FsTreeViewFilter, FsTreeViewNode, FsTreeViewSort};
 
 impl FsTreeView {
 	pub fn expand(&mut self, view_node_id: NodeId, model: &mut FsTreeModel) -> bool {
-		self.get_mut(view_node_id).map(|mut node| expand(&mut node, model)).unwrap_or(false)
+		let (filter, sort) = (self.filter, self.sort);
+		self.get_mut(view_node_id).map(|mut node| expand(&mut node, model, filter, sort)).unwrap_or(false)
 	}
 	
 	pub fn collapse(&mut self, view_node_id: NodeId) -> bool {
@@ -12,12 +13,13 @@ impl FsTreeView {
 	}
 	
 	pub fn expand_or_collapse(&mut self, view_node_id: NodeId, model: &mut FsTreeModel) -> bool {
-		self.get_mut(view_node_id).map(|mut node| expand_or_collapse(&mut node, model)).unwrap_or(false)
+		let (filter, sort) = (self.filter, self.sort);
+		self.get_mut(view_node_id).map(|mut node| expand_or_collapse(&mut node, model, filter, sort)).unwrap_or(false)
 	}
 	
 }
 
-pub fn expand(node: &mut NodeMut<FsTreeViewNode>, model: &mut FsTreeModel) -> bool {
+pub fn expand(node: &mut NodeMut<FsTreeViewNode>, model: &mut FsTreeModel, filter: FsTreeViewFilter, sort: FsTreeViewSort) -> bool {
 	let data = node.data();
 	if data.is_expanded {
 		return false;
@@ -26,7 +28,7 @@ pub fn expand(node: &mut NodeMut<FsTreeViewNode>, model: &mut FsTreeModel) -> bo
 	if let Some(mut children) = model.resolve_children(data.model_node_id) {
 		data.is_expanded = true;
 		
-		FsTreeView::sort_children(&mut children, model);
+		FsTreeView::filter_and_sort_children(&mut children, model, filter, sort);
 		
 		for child in children {
 			node.append(FsTreeViewNode::from_model_node_id(child));
@@ -45,15 +47,16 @@ pub fn collapse(node: &mut NodeMut<FsTreeViewNode>) -> bool {
 	}
 	
 	data.is_expanded = false;
+	data.is_flattened = false;
 	while node.remove_first(RemoveBehavior::DropChildren).is_some() {}
 	
 	true
 }
 
-fn expand_or_collapse(node: &mut NodeMut<FsTreeViewNode>, model: &mut FsTreeModel) -> bool {
+fn expand_or_collapse(node: &mut NodeMut<FsTreeViewNode>, model: &mut FsTreeModel, filter: FsTreeViewFilter, sort: FsTreeViewSort) -> bool {
 	if node.data().is_expanded() {
 		collapse(node)
 	} else {
-		expand(node, model)
+		expand(node, model, filter, sort)
 	}
 }
@@ -0,0 +1,69 @@
+use slab_tree::NodeId;
+
+use crate::component::filesystem::tree::{FsTreeModel, FsTreeView, FsTreeViewFilter, FsTreeViewNode, FsTreeViewSort};
+use crate::component::filesystem::tree::view::expand_collapse::collapse;
+use crate::file::FileKind;
+
+/// Flat listings stop at this many entries, because the tree would take too long to show them.
+pub const MAX_FLATTENED_ENTRIES: u64 = 100_000;
+
+impl FsTreeView {
+	/// Replaces children of a directory with all its descendants that are not directories, which turns it into a flat listing. Directories hidden by the filter are skipped with all their contents.
+	pub fn flatten(&mut self, view_node_id: NodeId, model: &mut FsTreeModel) -> bool {
+		let Some(model_node_id) = self.get(view_node_id).map(|node| node.data().model_node_id) else {
+			return false;
+		};
+		
+		if !model.get(model_node_id).is_some_and(|node| matches!(node.data().entry.kind(), FileKind::Directory)) {
+			return false;
+		}
+		
+		let children = Self::collect_flattened_children(model, model_node_id, self.filter, self.sort);
+		
+		let Some(mut node) = self.get_mut(view_node_id) else {
+			return false;
+		};
+		
+		collapse(&mut node);
+		
+		let data = node.data();
+		data.is_expanded = true;
+		data.is_flattened = true;
+		
+		for child in children {
+			node.append(FsTreeViewNode::from_model_node_id(child));
+		}
+		
+		true
+	}
+	
+	/// Collects descendants of a directory for its flat listing. Stops reading directories once [`MAX_FLATTENED_ENTRIES`] entries were visited, so the listing of a huge directory is incomplete instead of blocking the tree.
+	pub(super) fn collect_flattened_children(model: &mut FsTreeModel, model_node_id: NodeId, filter: FsTreeViewFilter, sort: FsTreeViewSort) -> Vec<NodeId> {
+		let mut descendants = Vec::new();
+		let mut remaining_directories = vec![model_node_id];
+		let mut visited_entries = 0_u64;
+		
+		while let Some(directory_id) = remaining_directories.pop() {
+			for child_id in model.resolve_children(directory_id).unwrap_or_default() {
+				let Some(child) = model.get(child_id).map(|node| node.data()).filter(|child| filter.is_visible(child)) else {
+					continue;
+				};
+				
+				visited_entries = visited_entries.saturating_add(1);
+				if visited_entries > MAX_FLATTENED_ENTRIES {
+					remaining_directories.clear();
+					break;
+				}
+				
+				if matches!(child.entry.kind(), FileKind::Directory) {
+					remaining_directories.push(child_id);
+				} else {
+					descendants.push(child_id);
+				}
+			}
+		}
+		
+		Self::filter_and_sort_children(&mut descendants, model, filter, sort);
+		descendants
+	}
+}
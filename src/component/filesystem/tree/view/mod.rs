@@ -1,10 +1,16 @@
+use std::cmp::{Ordering, Reverse};
+
 use slab_tree::{NodeId, NodeMut, NodeRef, RemoveBehavior, Tree};
 
-use crate::component::filesystem::tree::FsTreeModel;
+use crate::component::filesystem::render::SizeKind;
+use crate::component::filesystem::tree::{FsTreeModel, FsTreeModelNode};
+use crate::file::{FileEntry, FileKind};
 
+pub use self::flatten::MAX_FLATTENED_ENTRIES;
 pub use self::iterator::FsTreeViewIterator;
 
 mod expand_collapse;
+mod flatten;
 mod iterator;
 mod refresh;
 mod set_root;
@@ -12,6 +18,8 @@ mod set_root;
 pub struct FsTreeView {
 	inner: Tree<FsTreeViewNode>,
 	root_id: NodeId,
+	filter: FsTreeViewFilter,
+	sort: FsTreeViewSort,
 }
 
 impl FsTreeView {
@@ -19,14 +27,14 @@ impl FsTreeView {
 		let mut inner = Tree::new();
 		let root_id = inner.set_root(FsTreeViewNode::from_model_node_id(model.root_id()));
 		
-		Self { inner, root_id }
+		Self { inner, root_id, filter: FsTreeViewFilter::default(), sort: FsTreeViewSort::Name }
 	}
 	
 	pub const fn root_id(&self) -> NodeId {
 		self.root_id
 	}
 	
-	pub fn root(&self) -> Option<NodeRef<FsTreeViewNode>> {
+	pub fn root(&self) -> Option<NodeRef<'_, FsTreeViewNode>> {
 		self.inner.root()
 	}
 	
@@ -34,11 +42,11 @@ impl FsTreeView {
 		self.root_id = self.inner.set_root(FsTreeViewNode::from_model_node_id(model_id));
 	}
 	
-	pub fn get(&self, node_id: NodeId) -> Option<NodeRef<FsTreeViewNode>> {
+	pub fn get(&self, node_id: NodeId) -> Option<NodeRef<'_, FsTreeViewNode>> {
 		self.inner.get(node_id)
 	}
 	
-	pub fn get_mut(&mut self, node_id: NodeId) -> Option<NodeMut<FsTreeViewNode>> {
+	pub fn get_mut(&mut self, node_id: NodeId) -> Option<NodeMut<'_, FsTreeViewNode>> {
 		self.inner.get_mut(node_id)
 	}
 	
@@ -46,19 +54,108 @@ impl FsTreeView {
 		self.inner.remove(node_id, RemoveBehavior::DropChildren)
 	}
 	
-	fn sort_children(children: &mut [NodeId], model: &FsTreeModel) {
-		children.sort_by_key(|id| model.get(*id).map(|node| node.data().entry.name().str()));
+	pub const fn filter(&self) -> FsTreeViewFilter {
+		self.filter
+	}
+	
+	/// Changes which entries are visible. Children of expanded directories must be refreshed afterwards.
+	pub fn set_filter(&mut self, filter: FsTreeViewFilter) {
+		self.filter = filter;
+	}
+	
+	pub const fn sort(&self) -> FsTreeViewSort {
+		self.sort
+	}
+	
+	/// Changes the order of entries. Children of expanded directories must be refreshed afterwards.
+	pub fn set_sort(&mut self, sort: FsTreeViewSort) {
+		self.sort = sort;
+	}
+	
+	fn filter_and_sort_children(children: &mut Vec<NodeId>, model: &FsTreeModel, filter: FsTreeViewFilter, sort: FsTreeViewSort) {
+		children.retain(|id| model.get(*id).is_some_and(|node| filter.is_visible(node.data())));
+		children.sort_by(|a, b| sort.compare(model, model.get(*a).map(|node| &node.data().entry), model.get(*b).map(|node| &node.data().entry)));
+	}
+}
+
+/// Determines which entries are shown in the view. Entries that are filtered out remain in the model.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct FsTreeViewFilter {
+	pub show_hidden: bool,
+	pub show_ignored: bool,
+}
+
+impl FsTreeViewFilter {
+	pub fn is_visible(self, node: &FsTreeModelNode) -> bool {
+		(self.show_hidden || !node.entry.is_hidden()) && (self.show_ignored || !node.is_ignored())
+	}
+}
+
+impl Default for FsTreeViewFilter {
+	fn default() -> Self {
+		Self { show_hidden: true, show_ignored: true }
+	}
+}
+
+/// Determines the order of entries in each directory, and in flat listings.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum FsTreeViewSort {
+	/// Alphabetical order, which orders flat listings by relative paths.
+	Name,
+	/// Largest files first. Entries without a size are placed after all files.
+	Size,
+	/// Most recently modified entries first.
+	Modified,
+	/// Largest entries first, using total sizes of directories from a disk usage scan.
+	DiskUsage(SizeKind),
+}
+
+impl FsTreeViewSort {
+	pub fn parse(value: &str) -> Result<Self, String> {
+		match value {
+			"name" => Ok(Self::Name),
+			"size" => Ok(Self::Size),
+			"modified" => Ok(Self::Modified),
+			_ => Err(format!("Unknown sort order: {value}")),
+		}
+	}
+	
+	/// Compares two entries, falling back to comparing their names when the sort keys are equal. Names are compared instead of paths, because entries inside archives have no paths, and entries in flat listings come from different directories. Entries with equal names are ordered by their paths.
+	fn compare(self, model: &FsTreeModel, a: Option<&FileEntry>, b: Option<&FileEntry>) -> Ordering {
+		let by_name = || {
+			a.map(|entry| entry.name().str()).cmp(&b.map(|entry| entry.name().str()))
+			 .then_with(|| a.and_then(FileEntry::path).cmp(&b.and_then(FileEntry::path)))
+		};
+		
+		match self {
+			Self::Name => by_name(),
+			Self::Size => Reverse(a.and_then(get_size)).cmp(&Reverse(b.and_then(get_size))).then_with(by_name),
+			Self::Modified => Reverse(a.and_then(FileEntry::modified_time)).cmp(&Reverse(b.and_then(FileEntry::modified_time))).then_with(by_name),
+			Self::DiskUsage(kind) => {
+				let get_disk_usage = |entry: Option<&FileEntry>| Reverse(entry.and_then(|entry| model.get_disk_usage(entry, kind)));
+				get_disk_usage(a).cmp(&get_disk_usage(b)).then_with(by_name)
+			}
+		}
+	}
+}
+
+const fn get_size(entry: &FileEntry) -> Option<u64> {
+	if let FileKind::File { size } = entry.kind() {
+		Some(*size)
+	} else {
+		None
 	}
 }
 
 pub struct FsTreeViewNode {
 	model_node_id: NodeId,
 	is_expanded: bool,
+	is_flattened: bool,
 }
 
 impl FsTreeViewNode {
 	pub const fn from_model_node_id(model_node_id: NodeId) -> Self {
-		Self { model_node_id, is_expanded: false }
+		Self { model_node_id, is_expanded: false, is_flattened: false }
 	}
 	
 	pub const fn model_node_id(&self) -> NodeId {
@@ -68,4 +165,9 @@ impl FsTreeViewNode {
 	pub const fn is_expanded(&self) -> bool {
 		self.is_expanded
 	}
+	
+	/// Returns whether the children of this directory are all its descendants that are not directories, instead of its direct children.
+	pub const fn is_flattened(&self) -> bool {
+		self.is_flattened
+	}
 }
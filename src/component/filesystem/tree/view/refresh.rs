@@ -2,16 +2,22 @@ use std::collections::{HashMap, HashSet};
 
 use slab_tree::{NodeId, NodeRef};
 
-use crate::component::filesystem::tree::{FsTreeModel, FsTreeView, FsTreeViewNode};
+use crate::component::filesystem::tree::{FsTreeModel, FsTreeView, FsTreeViewFilter, FsTreeViewNode, FsTreeViewSort};
 
 impl FsTreeView {
-	pub fn refresh_children(&mut self, parent_node_id: NodeId, model: &FsTreeModel) -> bool {
+	pub fn refresh_children(&mut self, parent_node_id: NodeId, model: &mut FsTreeModel) -> bool {
 		if let Some(parent_node) = self.get(parent_node_id) {
 			let parent_data = parent_node.data();
 			
 			if parent_data.is_expanded {
 				let old_children = collect_old_model_to_view_node_id_map(&parent_node);
-				let new_model_ids = collect_new_model_ids(model, parent_data);
+				
+				let new_model_ids = if parent_data.is_flattened {
+					let model_node_id = parent_data.model_node_id;
+					Self::collect_flattened_children(model, model_node_id, self.filter, self.sort)
+				} else {
+					collect_new_model_ids(model, parent_data, self.filter, self.sort)
+				};
 				
 				for new_model_id in &new_model_ids {
 					if let Some(mut child_node) = old_children.get(new_model_id).and_then(|id| self.get_mut(*id)) {
@@ -50,10 +56,10 @@ fn collect_old_model_to_view_node_id_map(parent_node: &NodeRef<FsTreeViewNode>)
 	old_children
 }
 
-fn collect_new_model_ids(model: &FsTreeModel, parent_data: &FsTreeViewNode) -> Vec<NodeId> {
+fn collect_new_model_ids(model: &FsTreeModel, parent_data: &FsTreeViewNode, filter: FsTreeViewFilter, sort: FsTreeViewSort) -> Vec<NodeId> {
 	let mut new_model_children = model.get_children(parent_data.model_node_id).unwrap_or_default();
 	
-	FsTreeView::sort_children(&mut new_model_children, model);
+	FsTreeView::filter_and_sort_children(&mut new_model_children, model, filter, sort);
 	
 	new_model_children
 }
@@ -1,6 +1,6 @@
 use slab_tree::{NodeId, NodeMut};
 
-use crate::component::filesystem::tree::{FsTreeModel, FsTreeView, FsTreeViewNode};
+use crate::component::filesystem::tree::{FsTreeModel, FsTreeView, FsTreeViewFilter, FsTreeViewNode, FsTreeViewSort};
 
 impl FsTreeView {
 	pub fn traverse_up_root(&mut self, model: &mut FsTreeModel) -> Option<NodeId> {
@@ -9,8 +9,10 @@ impl FsTreeView {
 		if let Some(new_model_root_id) = model.traverse_up_root() {
 			self.set_root(new_model_root_id);
 			
+			let (filter, sort) = (self.filter, self.sort);
+			
 			if let Some(mut new_view_root) = self.get_mut(self.root_id) {
-				Self::resolve_new_root_children(&mut new_view_root, model, old_model_root_id, new_model_root_id);
+				Self::resolve_new_root_children(&mut new_view_root, model, filter, sort, old_model_root_id, new_model_root_id);
 				Some(self.root_id)
 			} else {
 				None
@@ -20,11 +22,11 @@ impl FsTreeView {
 		}
 	}
 	
-	fn resolve_new_root_children(new_view_root: &mut NodeMut<FsTreeViewNode>, model: &mut FsTreeModel, old_model_root_id: NodeId, new_model_root_id: NodeId) {
+	fn resolve_new_root_children(new_view_root: &mut NodeMut<FsTreeViewNode>, model: &mut FsTreeModel, filter: FsTreeViewFilter, sort: FsTreeViewSort, old_model_root_id: NodeId, new_model_root_id: NodeId) {
 		new_view_root.data().is_expanded = true;
 		
 		if let Some(mut new_model_children) = model.resolve_children(new_model_root_id) {
-			Self::sort_children(&mut new_model_children, model);
+			Self::filter_and_sort_children(&mut new_model_children, model, filter, sort);
 			
 			for model_child_id in new_model_children {
 				if model_child_id != old_model_root_id {
@@ -0,0 +1,68 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::component::filesystem::tree::FsTreeViewFilter;
+use crate::file::{FileKind, FileSystem, IgnoreRules};
+
+pub struct WalkEntry {
+	pub path: PathBuf,
+	/// Path relative to the root of the walk, with components separated by `/`.
+	pub relative_path: String,
+	pub is_directory: bool,
+}
+
+struct PendingDirectory {
+	path: PathBuf,
+	relative_path: String,
+	ignore_rules: Rc<IgnoreRules>,
+	is_ignored: bool,
+}
+
+/// Visits all entries in the root directory and its subdirectories that are visible with the filter, in breadth-first order and sorted by name within each directory. Symbolic links to directories are not followed. The walk stops when the visitor returns `false`.
+pub fn walk<F>(file_system: &dyn FileSystem, root_path: &Path, filter: FsTreeViewFilter, mut visit: F) where F: FnMut(WalkEntry) -> bool {
+	let mut pending_directories = VecDeque::from([PendingDirectory {
+		path: root_path.to_path_buf(),
+		relative_path: String::new(),
+		ignore_rules: IgnoreRules::for_root(file_system, root_path),
+		is_ignored: false,
+	}]);
+	
+	while let Some(directory) = pending_directories.pop_front() {
+		let Ok(mut entries) = file_system.list(&directory.path) else {
+			continue;
+		};
+		
+		entries.sort_by(|a, b| a.path().cmp(&b.path()));
+		
+		for entry in entries {
+			// Entries that could not be read have no path.
+			let Some(path) = entry.path().map(Path::to_path_buf) else {
+				continue;
+			};
+			
+			let name = entry.name().str().to_owned();
+			let is_directory = matches!(entry.kind(), FileKind::Directory);
+			let is_ignored = directory.is_ignored || directory.ignore_rules.is_ignored(&path, is_directory);
+			
+			if (!filter.show_hidden && name.starts_with('.')) || (!filter.show_ignored && is_ignored) {
+				continue;
+			}
+			
+			let relative_path = if directory.relative_path.is_empty() { name } else { format!("{}/{}", directory.relative_path, name) };
+			
+			if is_directory {
+				pending_directories.push_back(PendingDirectory {
+					ignore_rules: IgnoreRules::for_directory(file_system, Some(Rc::clone(&directory.ignore_rules)), &path),
+					path: path.clone(),
+					relative_path: relative_path.clone(),
+					is_ignored,
+				});
+			}
+			
+			if !visit(WalkEntry { path, relative_path, is_directory }) {
+				return;
+			}
+		}
+	}
+}
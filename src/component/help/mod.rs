@@ -0,0 +1,221 @@
+use std::cmp::{max, min};
+
+use crossterm::event::{KeyCode, KeyModifiers, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Clear, Paragraph};
+
+use crate::component::input::InputField;
+use crate::input::keymap::KeyBinding;
+use crate::state::action::ActionResult;
+use crate::state::Environment;
+use crate::state::event::EventResult;
+use crate::state::layer::Layer;
+use crate::state::view::Frame;
+use crate::theme::theme;
+
+const WHEEL_SCROLL_LINES: usize = 3;
+
+/// Describes an action, and all key sequences bound to it.
+pub struct HelpEntry {
+	pub keys: String,
+	pub name: &'static str,
+	pub description: &'static str,
+}
+
+impl HelpEntry {
+	fn matches(&self, query: &str) -> bool {
+		[self.keys.as_str(), self.name, self.description].iter().any(|text| text.to_lowercase().contains(query))
+	}
+}
+
+/// Full screen list of key bindings, which can be scrolled and filtered by a search query.
+pub struct HelpLayer {
+	entries: Vec<HelpEntry>,
+	visible_entries: Vec<usize>,
+	scroll: usize,
+	page_height: usize,
+	query: String,
+	search_field: Option<InputField>,
+}
+
+impl HelpLayer {
+	pub fn new(entries: Vec<HelpEntry>) -> Self {
+		let visible_entries = (0..entries.len()).collect();
+		Self { entries, visible_entries, scroll: 0, page_height: 0, query: String::new(), search_field: None }
+	}
+	
+	fn set_query(&mut self, query: &str) {
+		self.query = query.to_lowercase();
+		self.visible_entries = self.entries.iter()
+			.enumerate()
+			.filter(|(_, entry)| entry.matches(&self.query))
+			.map(|(index, _)| index)
+			.collect();
+		self.scroll = 0;
+	}
+	
+	fn max_scroll(&self) -> usize {
+		self.visible_entries.len().saturating_sub(self.page_height)
+	}
+	
+	fn scroll_to(&mut self, scroll: usize) -> ActionResult {
+		let scroll = min(scroll, self.max_scroll());
+		let changed = self.scroll != scroll;
+		self.scroll = scroll;
+		ActionResult::draw_if(changed)
+	}
+	
+	fn scroll_down(&mut self, lines: usize) -> ActionResult {
+		self.scroll_to(self.scroll.saturating_add(lines))
+	}
+	
+	fn scroll_up(&mut self, lines: usize) -> ActionResult {
+		self.scroll_to(self.scroll.saturating_sub(lines))
+	}
+	
+	#[allow(clippy::wildcard_enum_match_arm)]
+	fn handle_search_input(&mut self, key_binding: KeyBinding) -> ActionResult {
+		let Some(field) = &mut self.search_field else {
+			return ActionResult::Nothing;
+		};
+		
+		match (key_binding.code(), key_binding.modifiers()) {
+			(KeyCode::Esc, KeyModifiers::NONE) |
+			(KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+				self.search_field = None;
+				self.set_query("");
+				ActionResult::Draw
+			}
+			
+			(KeyCode::Enter, KeyModifiers::NONE) => {
+				self.search_field = None;
+				ActionResult::Draw
+			}
+			
+			_ => {
+				if field.handle_input(key_binding) {
+					let query = field.text().to_owned();
+					self.set_query(&query);
+					ActionResult::Draw
+				} else {
+					ActionResult::Nothing
+				}
+			}
+		}
+	}
+	
+	fn render_entries(&self, frame: &mut Frame, area: Rect) {
+		let theme = theme();
+		let keys_width = self.entries.iter().fold(0, |width, entry| max(width, Span::from(entry.keys.as_str()).width()));
+		let name_width = self.entries.iter().fold(0, |width, entry| max(width, Span::from(entry.name).width()));
+		
+		let lines = self.visible_entries.iter()
+			.skip(self.scroll)
+			.take(usize::from(area.height))
+			.filter_map(|index| self.entries.get(*index))
+			.map(|entry| Line::from(vec![
+				Span::raw(" "),
+				Span::styled(format!("{:keys_width$}", entry.keys), theme.dialog_key),
+				Span::raw("  "),
+				Span::styled(format!("{:name_width$}", entry.name), theme.secondary),
+				Span::raw("  "),
+				Span::raw(entry.description),
+			]))
+			.collect::<Vec<_>>();
+		
+		frame.render_widget(Paragraph::new(lines), area);
+	}
+	
+	fn render_footer(&mut self, frame: &mut Frame, area: Rect) {
+		let theme = theme();
+		
+		if let Some(field) = &mut self.search_field {
+			frame.render_widget(Paragraph::new("/").style(theme.command_line.input), Rect { width: 1, ..area });
+			field.render(frame, area.x.saturating_add(1), area.y, area.width.saturating_sub(1), theme.command_line);
+			return;
+		}
+		
+		let text = if self.query.is_empty() {
+			String::from(" j/k: scroll  /: search  q: close")
+		} else {
+			format!(" /{}: {} of {} bindings  Esc: clear search", self.query, self.visible_entries.len(), self.entries.len())
+		};
+		
+		frame.render_widget(Paragraph::new(text).style(theme.status_bar), area);
+	}
+}
+
+impl Layer for HelpLayer {
+	#[allow(clippy::wildcard_enum_match_arm)]
+	fn handle_input(&mut self, _environment: &Environment, key_binding: KeyBinding) -> ActionResult {
+		if self.search_field.is_some() {
+			return self.handle_search_input(key_binding);
+		}
+		
+		match (key_binding.code(), key_binding.modifiers()) {
+			(KeyCode::Esc, KeyModifiers::NONE) => {
+				if self.query.is_empty() {
+					ActionResult::PopLayer
+				} else {
+					self.set_query("");
+					ActionResult::Draw
+				}
+			}
+			
+			(KeyCode::Char('q' | '?'), KeyModifiers::NONE) |
+			(KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+				ActionResult::PopLayer
+			}
+			
+			(KeyCode::Char('/'), KeyModifiers::NONE) => {
+				self.search_field = Some(InputField::with_text(self.query.as_str()));
+				ActionResult::Draw
+			}
+			
+			(KeyCode::Char('j') | KeyCode::Down, KeyModifiers::NONE) => self.scroll_down(1),
+			(KeyCode::Char('k') | KeyCode::Up, KeyModifiers::NONE) => self.scroll_up(1),
+			(KeyCode::Char('d'), KeyModifiers::CONTROL) => self.scroll_down(self.page_height / 2),
+			(KeyCode::Char('u'), KeyModifiers::CONTROL) => self.scroll_up(self.page_height / 2),
+			(KeyCode::Char('f'), KeyModifiers::CONTROL) | (KeyCode::PageDown, KeyModifiers::NONE) => self.scroll_down(self.page_height),
+			(KeyCode::Char('b'), KeyModifiers::CONTROL) | (KeyCode::PageUp, KeyModifiers::NONE) => self.scroll_up(self.page_height),
+			(KeyCode::Char('g') | KeyCode::Home, KeyModifiers::NONE) => self.scroll_to(0),
+			(KeyCode::Char('G') | KeyCode::End, KeyModifiers::NONE) => self.scroll_to(usize::MAX),
+			
+			_ => ActionResult::Nothing
+		}
+	}
+	
+	#[allow(clippy::wildcard_enum_match_arm)]
+	fn handle_mouse(&mut self, _environment: &Environment, event: MouseEvent) -> ActionResult {
+		match event.kind {
+			MouseEventKind::ScrollDown => self.scroll_down(WHEEL_SCROLL_LINES),
+			MouseEventKind::ScrollUp => self.scroll_up(WHEEL_SCROLL_LINES),
+			_ => ActionResult::Nothing
+		}
+	}
+	
+	fn handle_events(&mut self, _environment: &Environment) -> EventResult {
+		EventResult::Nothing
+	}
+	
+	fn render(&mut self, frame: &mut Frame) {
+		let size = frame.size();
+		if size.height < 3 {
+			return;
+		}
+		
+		let title_area = Rect { height: 1, ..size };
+		let entries_area = Rect { y: size.y.saturating_add(1), height: size.height.saturating_sub(2), ..size };
+		let footer_area = Rect { y: size.bottom().saturating_sub(1), height: 1, ..size };
+		
+		self.page_height = usize::from(entries_area.height);
+		self.scroll = min(self.scroll, self.max_scroll());
+		
+		frame.render_widget(Clear, size);
+		frame.render_widget(Paragraph::new(" Key Bindings").style(theme().status_bar.add_modifier(Modifier::BOLD)), title_area);
+		self.render_entries(frame, entries_area);
+		self.render_footer(frame, footer_area);
+	}
+}
@@ -4,12 +4,13 @@ use std::cmp::min;
 use crossterm::event::{KeyCode, KeyModifiers};
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
-use ratatui::style::{Color, Style};
+use ratatui::style::Style;
 use ratatui::text::Span;
 use ratatui::widgets::{Clear, Paragraph, StatefulWidget, Widget};
 
 use crate::input::keymap::KeyBinding;
 use crate::state::view::Frame;
+use crate::theme::DialogStyle;
 
 pub use self::overlay::InputFieldOverlayLayer;
 
@@ -38,6 +39,17 @@ impl InputField {
 		&self.text
 	}
 	
+	pub fn set_text(&mut self, text: impl Into<String>) -> bool {
+		let text = text.into();
+		if self.text == text {
+			false
+		} else {
+			self.text = text;
+			self.caret = self.caret_end();
+			true
+		}
+	}
+	
 	fn caret_end(&self) -> usize {
 		self.text.chars().count()
 	}
@@ -125,14 +137,14 @@ impl InputField {
 		}
 	}
 	
-	pub fn render(&mut self, frame: &mut Frame, x: u16, y: u16, width: u16, default_background: Color, trimmed_background: Color) {
+	pub fn render(&self, frame: &mut Frame, x: u16, y: u16, width: u16, style: DialogStyle) {
 		let area = Rect::new(x, y, width, 1);
 		
 		let widget = InputFieldWidget {
 			text: self.text(),
 			caret: self.caret,
-			default_background,
-			trimmed_background,
+			style: style.input,
+			truncated_style: style.input_truncated,
 		};
 		
 		let mut caret_x = 0;
@@ -144,11 +156,11 @@ impl InputField {
 struct InputFieldWidget<'a> {
 	text: &'a str,
 	caret: usize,
-	default_background: Color,
-	trimmed_background: Color,
+	style: Style,
+	truncated_style: Style,
 }
 
-impl<'a> StatefulWidget for InputFieldWidget<'a> {
+impl StatefulWidget for InputFieldWidget<'_> {
 	type State = u16;
 	
 	fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
@@ -189,7 +201,6 @@ impl<'a> StatefulWidget for InputFieldWidget<'a> {
 			if let Some(new_remaining_width) = remaining_width.checked_sub(char_width) {
 				remaining_width = new_remaining_width;
 				end_char_index = next_char_index;
-				continue;
 			} else {
 				has_truncated_end = true;
 				break;
@@ -208,22 +219,17 @@ impl<'a> StatefulWidget for InputFieldWidget<'a> {
 			start_char_index = char_index;
 		}
 		
-		let style = Style::default()
-			.fg(Color::Black)
-			.bg(self.default_background);
-		
 		Clear.render(area, buf);
 		
 		#[allow(clippy::indexing_slicing, clippy::string_slice)] // Indices are obtained from char_indices.
 		Paragraph::new(&self.text[start_char_index..end_char_index])
-			.style(style)
+			.style(self.style)
 			.render(area, buf);
 		
 		if has_truncated_end {
 			buf.get_mut(area.right().saturating_sub(1), area.y)
 			   .set_char('~')
-			   .set_fg(Color::Black)
-			   .set_bg(self.trimmed_background);
+			   .set_style(self.truncated_style);
 		}
 		
 		*state = u16::try_from(caret_x).unwrap_or(u16::MAX);
@@ -1,8 +1,7 @@
 use std::cmp::min;
 
-use crossterm::event::{KeyCode, KeyModifiers};
+use crossterm::event::{KeyCode, KeyModifiers, MouseEvent};
 use ratatui::layout::Rect;
-use ratatui::style::{Color, Style};
 use ratatui::text::Span;
 use ratatui::widgets::Paragraph;
 
@@ -13,6 +12,7 @@ use crate::state::Environment;
 use crate::state::event::EventResult;
 use crate::state::layer::Layer;
 use crate::state::view::Frame;
+use crate::theme::theme;
 
 pub struct InputFieldOverlayLayer<'a> {
 	field: InputField,
@@ -28,7 +28,7 @@ impl<'a> InputFieldOverlayLayer<'a> {
 	}
 }
 
-impl<'a> Layer for InputFieldOverlayLayer<'a> {
+impl Layer for InputFieldOverlayLayer<'_> {
 	#[allow(clippy::wildcard_enum_match_arm)]
 	fn handle_input(&mut self, _environment: &Environment, key_binding: KeyBinding) -> ActionResult {
 		match (key_binding.code(), key_binding.modifiers()) {
@@ -41,12 +41,8 @@ impl<'a> Layer for InputFieldOverlayLayer<'a> {
 				(self.confirm_action)(self.field.text().to_owned())
 			}
 			
-			(KeyCode::Backspace, KeyModifiers::NONE) => {
-				if self.field.text().is_empty() {
-					ActionResult::PopLayer
-				} else {
-					ActionResult::draw_if(self.field.handle_input(key_binding))
-				}
+			(KeyCode::Backspace, KeyModifiers::NONE) if self.field.text().is_empty() => {
+				ActionResult::PopLayer
 			}
 			
 			_ => {
@@ -55,6 +51,10 @@ impl<'a> Layer for InputFieldOverlayLayer<'a> {
 		}
 	}
 	
+	fn handle_mouse(&mut self, _environment: &Environment, _event: MouseEvent) -> ActionResult {
+		ActionResult::Nothing
+	}
+	
 	fn handle_events(&mut self, _environment: &Environment) -> EventResult {
 		EventResult::Nothing
 	}
@@ -72,18 +72,14 @@ impl<'a> Layer for InputFieldOverlayLayer<'a> {
 		let prefix_width = min(u16::try_from(prefix_text.width()).unwrap_or(u16::MAX), size.width.saturating_sub(2));
 		
 		if prefix_width > 0 {
-			let prefix_style = Style::new()
-				.fg(Color::Black)
-				.bg(Color::LightYellow);
-			
 			let prefix_paragraph = Paragraph::new(self.read_only_prefix)
-				.style(prefix_style);
+				.style(theme().command_line.input);
 			
 			frame.render_widget(prefix_paragraph, Rect { x, y, width: prefix_width, height: 1 });
 		}
 		
 		if size.width > prefix_width {
-			self.field.render(frame, x.saturating_add(prefix_width), y, size.width.saturating_sub(prefix_width), Color::LightYellow, Color::Yellow);
+			self.field.render(frame, x.saturating_add(prefix_width), y, size.width.saturating_sub(prefix_width), theme().command_line);
 		}
 	}
 }
@@ -1,3 +1,4 @@
 pub mod dialog;
 pub mod filesystem;
+pub mod help;
 pub mod input;
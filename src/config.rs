@@ -0,0 +1,25 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Returns the directory with configuration files, which is `$XDG_CONFIG_HOME/bark` or `~/.config/bark` on Unix, and `%APPDATA%\bark` on Windows.
+#[cfg(unix)]
+pub fn get_config_dir() -> Option<PathBuf> {
+	let base = env::var_os("XDG_CONFIG_HOME")
+		.filter(|path| !path.is_empty())
+		.map(PathBuf::from)
+		.or_else(|| env::var_os("HOME").filter(|path| !path.is_empty()).map(|home| PathBuf::from(home).join(".config")))?;
+	
+	Some(base.join("bark"))
+}
+
+/// Returns the directory with configuration files, which is `$XDG_CONFIG_HOME/bark` or `~/.config/bark` on Unix, and `%APPDATA%\bark` on Windows.
+#[cfg(not(unix))]
+pub fn get_config_dir() -> Option<PathBuf> {
+	env::var_os("APPDATA").filter(|path| !path.is_empty()).map(|app_data| PathBuf::from(app_data).join("bark"))
+}
+
+/// Reads a file from the configuration directory, or returns `None` if the file does not exist or cannot be read.
+pub fn read_config_file(name: &str) -> Option<String> {
+	fs::read_to_string(get_config_dir()?.join(name)).ok()
+}
@@ -0,0 +1,177 @@
+use std::fs::{self, File, Metadata};
+use std::io::{self, BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{Datelike, Local, Timelike};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use tar::{Builder, Header, HeaderMode};
+use xz2::write::XzEncoder;
+use zip::{CompressionMethod, DateTime, ZipWriter};
+use zip::write::FileOptions;
+use zstd::stream::write::Encoder as ZstdEncoder;
+
+use crate::file::archive::{ArchiveKind, ArchiveProgress, cancelled_error, ProgressReader, TarCompression};
+
+const XZ_PRESET: u32 = 6;
+
+/// Entry that is added to an archive.
+struct ArchiveSource {
+	path: PathBuf,
+	inner_path: PathBuf,
+	metadata: Metadata,
+}
+
+/// Creates an archive that contains the entries, including everything inside directories. Entries are stored under their file names. Fails if the archive already exists. Reports progress as bytes read from added files, and stops once `on_progress` returns false. The archive is removed if creating it fails or stops.
+pub fn create_archive<F>(archive_path: &Path, kind: ArchiveKind, entry_paths: &[PathBuf], mut on_progress: F) -> io::Result<()> where F: FnMut(ArchiveProgress) -> bool {
+	let mut sources = Vec::new();
+	
+	for path in entry_paths {
+		if let Some(name) = path.file_name() {
+			collect_sources(path, Path::new(name), &mut sources)?;
+		}
+	}
+	
+	let total_bytes = sources.iter().filter(|source| source.metadata.is_file()).map(|source| source.metadata.len()).fold(0, u64::saturating_add);
+	let mut on_progress = |done_bytes| on_progress(ArchiveProgress { done_bytes, total_bytes });
+	
+	let file = BufWriter::new(File::options().write(true).create_new(true).open(archive_path)?);
+	
+	let result = match kind {
+		ArchiveKind::Zip => write_zip(file, &sources, &mut on_progress),
+		ArchiveKind::Tar(TarCompression::None) => write_tar(file, &sources, &mut on_progress).and_then(finish_file),
+		ArchiveKind::Tar(TarCompression::Gzip) => write_tar(GzEncoder::new(file, Compression::default()), &sources, &mut on_progress).and_then(GzEncoder::finish).and_then(finish_file),
+		ArchiveKind::Tar(TarCompression::Xz) => write_tar(XzEncoder::new(file, XZ_PRESET), &sources, &mut on_progress).and_then(XzEncoder::finish).and_then(finish_file),
+		ArchiveKind::Tar(TarCompression::Zstd) => ZstdEncoder::new(file, 0).and_then(|encoder| write_tar(encoder, &sources, &mut on_progress)).and_then(ZstdEncoder::finish).and_then(finish_file),
+	};
+	
+	if result.is_err() {
+		let _ = fs::remove_file(archive_path);
+	}
+	
+	result
+}
+
+/// Collects the entry, and everything inside it if it is a directory. Symbolic links are stored as links.
+fn collect_sources(path: &Path, inner_path: &Path, sources: &mut Vec<ArchiveSource>) -> io::Result<()> {
+	let metadata = fs::symlink_metadata(path)?;
+	let is_dir = metadata.is_dir();
+	
+	sources.push(ArchiveSource { path: path.to_path_buf(), inner_path: inner_path.to_path_buf(), metadata });
+	
+	if is_dir {
+		let mut children = fs::read_dir(path)?.map(|entry| entry.map(|entry| entry.file_name())).collect::<io::Result<Vec<_>>>()?;
+		children.sort();
+		
+		for name in children {
+			collect_sources(&path.join(&name), &inner_path.join(&name), sources)?;
+		}
+	}
+	
+	Ok(())
+}
+
+fn finish_file(file: BufWriter<File>) -> io::Result<()> {
+	file.into_inner().map_err(io::IntoInnerError::into_error)?.sync_all()
+}
+
+fn write_tar<W: Write, F>(writer: W, sources: &[ArchiveSource], on_progress: &mut F) -> io::Result<W> where F: FnMut(u64) -> bool {
+	let mut builder = Builder::new(writer);
+	builder.follow_symlinks(false);
+	
+	let mut done_bytes = 0_u64;
+	
+	for source in sources {
+		if source.metadata.is_file() {
+			let mut header = Header::new_gnu();
+			header.set_metadata_in_mode(&source.metadata, HeaderMode::Complete);
+			
+			let file = ProgressReader::new(File::open(&source.path)?, |read_bytes| on_progress(done_bytes.saturating_add(read_bytes)));
+			builder.append_data(&mut header, &source.inner_path, file)?;
+			done_bytes = done_bytes.saturating_add(source.metadata.len());
+		} else {
+			builder.append_path_with_name(&source.path, &source.inner_path)?;
+		}
+		
+		if !on_progress(done_bytes) {
+			return Err(cancelled_error());
+		}
+	}
+	
+	builder.into_inner()
+}
+
+fn write_zip<F>(file: BufWriter<File>, sources: &[ArchiveSource], on_progress: &mut F) -> io::Result<()> where F: FnMut(u64) -> bool {
+	let mut zip = ZipWriter::new(file);
+	let mut done_bytes = 0_u64;
+	
+	for source in sources {
+		let name = get_zip_name(&source.inner_path);
+		let options = get_zip_file_options(&source.metadata);
+		let file_type = source.metadata.file_type();
+		
+		if file_type.is_dir() {
+			zip.add_directory(name, options)?;
+		} else if file_type.is_symlink() {
+			zip.add_symlink(name, fs::read_link(&source.path)?.to_string_lossy(), options)?;
+		} else if file_type.is_file() {
+			zip.start_file(name, options)?;
+			
+			let mut file = ProgressReader::new(File::open(&source.path)?, |read_bytes| on_progress(done_bytes.saturating_add(read_bytes)));
+			io::copy(&mut file, &mut zip)?;
+			done_bytes = done_bytes.saturating_add(source.metadata.len());
+		}
+		
+		if !on_progress(done_bytes) {
+			return Err(cancelled_error());
+		}
+	}
+	
+	zip.finish().map_err(io::Error::from).and_then(finish_file)
+}
+
+/// Zip archives always separate path components with `/`.
+fn get_zip_name(inner_path: &Path) -> String {
+	inner_path.components()
+	          .filter_map(|component| if let Component::Normal(name) = component { Some(name.to_string_lossy()) } else { None })
+	          .collect::<Vec<_>>()
+	          .join("/")
+}
+
+fn get_zip_file_options(metadata: &Metadata) -> FileOptions {
+	let mut options = set_unix_permissions(FileOptions::default(), metadata)
+		.compression_method(CompressionMethod::Deflated)
+		.large_file(metadata.len() > u64::from(u32::MAX));
+	
+	if let Some(time) = metadata.modified().ok().and_then(get_zip_modified_time) {
+		options = options.last_modified_time(time);
+	}
+	
+	options
+}
+
+/// Converts a modification time into the local timezone, which is what zip archives store. Times before 1980 cannot be stored.
+fn get_zip_modified_time(time: std::time::SystemTime) -> Option<DateTime> {
+	let time = chrono::DateTime::<Local>::from(time);
+	
+	DateTime::from_date_and_time(
+		u16::try_from(time.year()).ok()?,
+		u8::try_from(time.month()).ok()?,
+		u8::try_from(time.day()).ok()?,
+		u8::try_from(time.hour()).ok()?,
+		u8::try_from(time.minute()).ok()?,
+		u8::try_from(time.second()).ok()?,
+	).ok()
+}
+
+#[cfg(unix)]
+fn set_unix_permissions(options: FileOptions, metadata: &Metadata) -> FileOptions {
+	use std::os::unix::fs::MetadataExt;
+	options.unix_permissions(metadata.mode() & 0o7777)
+}
+
+/// Other platforms have no Unix permissions.
+#[cfg(not(unix))]
+const fn set_unix_permissions(options: FileOptions, _metadata: &Metadata) -> FileOptions {
+	options
+}
@@ -0,0 +1,732 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::{self, File};
+use std::io::{self, BufReader, ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{Local, NaiveDate};
+use flate2::read::GzDecoder;
+use lazy_static::lazy_static;
+use tar::EntryType;
+use xz2::read::XzDecoder;
+use zip::{DateTime, ZipArchive};
+use zstd::stream::read::Decoder as ZstdDecoder;
+
+use crate::file::{FileEntry, FileKind, FileMode, FileName, FileOwner, remove_recursively};
+use crate::file::copy::set_modified_time;
+
+pub use self::create::create_archive;
+
+mod create;
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ArchiveKind {
+	Zip,
+	Tar(TarCompression),
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+pub enum TarCompression {
+	None,
+	Gzip,
+	Xz,
+	Zstd,
+}
+
+impl ArchiveKind {
+	/// Detects the kind of archive from the extension of a file name.
+	pub fn from_name(name: &str) -> Option<Self> {
+		let name = name.to_lowercase();
+		EXTENSIONS.iter().find(|(extension, _)| name.ends_with(extension)).map(|(_, kind)| *kind)
+	}
+}
+
+/// Returns the file name without its archive extension, or `None` if it has no archive extension.
+pub fn strip_archive_extension(name: &str) -> Option<&str> {
+	EXTENSIONS.iter().find_map(|(extension, _)| {
+		let stem_length = name.len().checked_sub(extension.len())?;
+		name.get(stem_length..).filter(|suffix| suffix.eq_ignore_ascii_case(extension)).and_then(|_| name.get(..stem_length))
+	})
+}
+
+const EXTENSIONS: [(&str, ArchiveKind); 8] = [
+	(".zip", ArchiveKind::Zip),
+	(".tar", ArchiveKind::Tar(TarCompression::None)),
+	(".tar.gz", ArchiveKind::Tar(TarCompression::Gzip)),
+	(".tgz", ArchiveKind::Tar(TarCompression::Gzip)),
+	(".tar.xz", ArchiveKind::Tar(TarCompression::Xz)),
+	(".txz", ArchiveKind::Tar(TarCompression::Xz)),
+	(".tar.zst", ArchiveKind::Tar(TarCompression::Zstd)),
+	(".tzst", ArchiveKind::Tar(TarCompression::Zstd)),
+];
+
+/// Location of an entry inside an archive file. The root of the archive has an empty inner path.
+#[derive(Clone, Eq, PartialEq, Hash)]
+pub struct ArchiveLocation {
+	archive_path: PathBuf,
+	kind: ArchiveKind,
+	inner_path: PathBuf,
+}
+
+impl ArchiveLocation {
+	pub fn root(archive_path: &Path, kind: ArchiveKind) -> Self {
+		Self { archive_path: archive_path.to_path_buf(), kind, inner_path: PathBuf::new() }
+	}
+	
+	pub fn archive_path(&self) -> &Path {
+		&self.archive_path
+	}
+	
+	pub fn inner_path(&self) -> &Path {
+		&self.inner_path
+	}
+	
+	pub fn is_root(&self) -> bool {
+		self.inner_path.as_os_str().is_empty()
+	}
+	
+	/// Returns the path that extracting into the target directory creates, which is the target directory itself for the root of the archive.
+	pub fn extraction_target(&self, target_directory: &Path) -> PathBuf {
+		self.inner_path.file_name().map_or_else(|| target_directory.to_path_buf(), |name| target_directory.join(name))
+	}
+	
+	/// Returns the path of the archive file joined with the path inside it, which is only meant to be shown to users.
+	pub fn display_path(&self) -> PathBuf {
+		if self.is_root() {
+			self.archive_path.clone()
+		} else {
+			self.archive_path.join(&self.inner_path)
+		}
+	}
+	
+	fn child(&self, name: &str) -> Self {
+		Self { inner_path: self.inner_path.join(name), ..self.clone() }
+	}
+}
+
+/// Header of an entry inside an archive.
+#[derive(Clone)]
+struct ArchiveHeader {
+	inner_path: PathBuf,
+	kind: FileKind,
+	mode: FileMode,
+	owner: Option<FileOwner>,
+	mtime: Option<SystemTime>,
+}
+
+impl ArchiveHeader {
+	/// Creates a header for a directory that has no entry of its own, but contains other entries.
+	const fn implicit_directory(inner_path: PathBuf) -> Self {
+		Self { inner_path, kind: FileKind::Directory, mode: FileMode::Unknown, owner: None, mtime: None }
+	}
+}
+
+impl FileEntry {
+	fn from_archive_header(location: ArchiveLocation, name: &str, header: &ArchiveHeader) -> Self {
+		Self {
+			path: None,
+			archive_location: Some(location),
+			name: FileName::from(name),
+			kind: header.kind,
+			mode: header.mode,
+			owner: header.owner,
+			stats: None,
+			mtime: header.mtime,
+			atime: None,
+			btime: None,
+		}
+	}
+}
+
+/// Reads entries directly inside an archive file, or inside a directory in an archive file. Archives do not need to have entries for all directories, so missing directories are added for entries nested inside them.
+pub fn read_archive_children(location: &ArchiveLocation) -> io::Result<Vec<FileEntry>> {
+	let mut children = BTreeMap::new();
+	
+	for header in read_cached_archive_headers(&location.archive_path, location.kind)?.iter() {
+		let Ok(relative_path) = header.inner_path.strip_prefix(&location.inner_path) else {
+			continue;
+		};
+		
+		let mut components = relative_path.components();
+		let Some(name) = components.next().map(|component| component.as_os_str().to_string_lossy().into_owned()) else {
+			continue;
+		};
+		
+		if components.next().is_some() {
+			children.entry(name).or_insert_with_key(|name| ArchiveHeader::implicit_directory(location.inner_path.join(name)));
+		} else {
+			children.insert(name, header.clone());
+		}
+	}
+	
+	Ok(children.into_iter().map(|(name, header)| FileEntry::from_archive_header(location.child(&name), &name, &header)).collect())
+}
+
+/// Number of archives whose headers are kept in memory, so that expanding directories inside recently opened archives does not read them again.
+const MAX_CACHED_ARCHIVES: usize = 8;
+
+struct CachedArchive {
+	archive_path: PathBuf,
+	kind: ArchiveKind,
+	mtime: SystemTime,
+	headers: Arc<[ArchiveHeader]>,
+}
+
+lazy_static! {
+	static ref ARCHIVE_CACHE: Mutex<VecDeque<CachedArchive>> = Mutex::new(VecDeque::new());
+}
+
+/// Reads headers of all entries in an archive, or returns them from the cache if the archive was not modified since it was last read. Archives without a modification time are never cached.
+fn read_cached_archive_headers(archive_path: &Path, kind: ArchiveKind) -> io::Result<Arc<[ArchiveHeader]>> {
+	let Ok(mtime) = fs::metadata(archive_path).and_then(|metadata| metadata.modified()) else {
+		return read_archive_headers(archive_path, kind).map(Arc::from);
+	};
+	
+	if let Some(headers) = get_cached_archive_headers(archive_path, kind, mtime) {
+		return Ok(headers);
+	}
+	
+	let headers = Arc::<[ArchiveHeader]>::from(read_archive_headers(archive_path, kind)?);
+	
+	if let Ok(mut cache) = ARCHIVE_CACHE.lock() {
+		cache.retain(|cached| cached.archive_path != archive_path || cached.kind != kind);
+		cache.push_front(CachedArchive { archive_path: archive_path.to_path_buf(), kind, mtime, headers: Arc::clone(&headers) });
+		cache.truncate(MAX_CACHED_ARCHIVES);
+	}
+	
+	Ok(headers)
+}
+
+/// Returns cached headers of the archive if they were read at the modification time, and moves them to the front of the cache.
+fn get_cached_archive_headers(archive_path: &Path, kind: ArchiveKind, mtime: SystemTime) -> Option<Arc<[ArchiveHeader]>> {
+	let mut cache = ARCHIVE_CACHE.lock().ok()?;
+	let index = cache.iter().position(|cached| cached.archive_path == archive_path && cached.kind == kind && cached.mtime == mtime)?;
+	let cached = cache.remove(index)?;
+	let headers = Arc::clone(&cached.headers);
+	
+	cache.push_front(cached);
+	drop(cache);
+	
+	Some(headers)
+}
+
+fn read_archive_headers(archive_path: &Path, kind: ArchiveKind) -> io::Result<Vec<ArchiveHeader>> {
+	match kind {
+		ArchiveKind::Zip => read_zip_headers(archive_path),
+		ArchiveKind::Tar(compression) => read_tar_headers(archive_path, compression),
+	}
+}
+
+fn read_zip_headers(archive_path: &Path) -> io::Result<Vec<ArchiveHeader>> {
+	let mut archive = ZipArchive::new(BufReader::new(File::open(archive_path)?))?;
+	let mut headers = Vec::with_capacity(archive.len());
+	
+	for index in 0..archive.len() {
+		let file = archive.by_index(index)?;
+		
+		let Some(inner_path) = sanitize_inner_path(Path::new(file.name())) else {
+			continue;
+		};
+		
+		let mode = file.unix_mode();
+		let kind = if file.is_dir() {
+			FileKind::Directory
+		} else if mode.is_some_and(is_symlink_mode) {
+			FileKind::Symlink
+		} else {
+			FileKind::File { size: file.size() }
+		};
+		
+		let mtime = get_zip_modified_time(file.last_modified());
+		headers.push(ArchiveHeader { inner_path, kind, mode: mode.map_or(FileMode::Unknown, FileMode::Known), owner: None, mtime });
+	}
+	
+	Ok(headers)
+}
+
+/// Converts a modification time stored in a zip archive, which is in the local timezone of whoever created the archive, assuming it is the local timezone here.
+fn get_zip_modified_time(time: DateTime) -> Option<SystemTime> {
+	NaiveDate::from_ymd_opt(i32::from(time.year()), u32::from(time.month()), u32::from(time.day()))
+		.and_then(|date| date.and_hms_opt(u32::from(time.hour()), u32::from(time.minute()), u32::from(time.second())))
+		.and_then(|date_time| date_time.and_local_timezone(Local).earliest())
+		.map(SystemTime::from)
+}
+
+fn read_tar_headers(archive_path: &Path, compression: TarCompression) -> io::Result<Vec<ArchiveHeader>> {
+	let mut archive = open_tar(BufReader::new(File::open(archive_path)?), compression)?;
+	let mut headers = Vec::new();
+	
+	for entry in archive.entries()? {
+		let entry = entry?;
+		let header = entry.header();
+		
+		let Some(inner_path) = sanitize_inner_path(&entry.path()?) else {
+			continue;
+		};
+		
+		let Some(kind) = get_tar_entry_kind(header.entry_type(), entry.size()) else {
+			continue;
+		};
+		
+		let mode = header.mode().map_or(FileMode::Unknown, FileMode::Known);
+		let owner = header.uid().ok().zip(header.gid().ok()).and_then(|(uid, gid)| Some(FileOwner::new(u32::try_from(uid).ok()?, u32::try_from(gid).ok()?)));
+		let mtime = header.mtime().ok().and_then(|seconds| UNIX_EPOCH.checked_add(Duration::from_secs(seconds)));
+		
+		headers.push(ArchiveHeader { inner_path, kind, mode, owner, mtime });
+	}
+	
+	Ok(headers)
+}
+
+fn open_tar<'a>(file: impl Read + 'a, compression: TarCompression) -> io::Result<tar::Archive<Box<dyn Read + 'a>>> {
+	let reader: Box<dyn Read + 'a> = match compression {
+		TarCompression::None => Box::new(file),
+		TarCompression::Gzip => Box::new(GzDecoder::new(file)),
+		TarCompression::Xz => Box::new(XzDecoder::new(file)),
+		TarCompression::Zstd => Box::new(ZstdDecoder::new(file)?),
+	};
+	
+	Ok(tar::Archive::new(reader))
+}
+
+/// Returns the kind of entry, or `None` for entries that only hold metadata of other entries. Hard links have no contents of their own, so they are links like symbolic links.
+#[allow(clippy::wildcard_enum_match_arm)]
+const fn get_tar_entry_kind(entry_type: EntryType, size: u64) -> Option<FileKind> {
+	match entry_type {
+		EntryType::Regular | EntryType::Continuous | EntryType::GNUSparse => Some(FileKind::File { size }),
+		EntryType::Directory => Some(FileKind::Directory),
+		EntryType::Symlink | EntryType::Link => Some(FileKind::Symlink),
+		EntryType::Char => Some(FileKind::CharDevice),
+		EntryType::Block => Some(FileKind::BlockDevice),
+		EntryType::Fifo => Some(FileKind::Pipe),
+		_ => None,
+	}
+}
+
+const fn is_symlink_mode(mode: u32) -> bool {
+	mode & 0o170_000 == 0o120_000
+}
+
+/// Returns the path without leading `/` and `.` components, or `None` if it is empty or escapes the archive with `..` components.
+fn sanitize_inner_path(path: &Path) -> Option<PathBuf> {
+	let mut result = PathBuf::new();
+	
+	for component in path.components() {
+		match component {
+			Component::Normal(name) => result.push(name),
+			Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+			Component::ParentDir => return None,
+		}
+	}
+	
+	(!result.as_os_str().is_empty()).then_some(result)
+}
+
+/// Progress of creating or extracting an archive, measured in bytes read from the files that are added, or from the archive that is extracted.
+#[derive(Copy, Clone, Default)]
+pub struct ArchiveProgress {
+	pub done_bytes: u64,
+	pub total_bytes: u64,
+}
+
+/// Reader that reports how many bytes were read from it so far, and fails once the report is rejected.
+struct ProgressReader<R, F> where F: FnMut(u64) -> bool {
+	inner: R,
+	read_bytes: u64,
+	on_progress: F,
+}
+
+impl<R, F> ProgressReader<R, F> where F: FnMut(u64) -> bool {
+	const fn new(inner: R, on_progress: F) -> Self {
+		Self { inner, read_bytes: 0, on_progress }
+	}
+}
+
+impl<R: Read, F> Read for ProgressReader<R, F> where F: FnMut(u64) -> bool {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let count = self.inner.read(buf)?;
+		self.read_bytes = self.read_bytes.saturating_add(u64::try_from(count).unwrap_or(u64::MAX));
+		
+		if (self.on_progress)(self.read_bytes) {
+			Ok(count)
+		} else {
+			Err(cancelled_error())
+		}
+	}
+}
+
+impl<R: Seek, F> Seek for ProgressReader<R, F> where F: FnMut(u64) -> bool {
+	fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+		self.inner.seek(pos)
+	}
+}
+
+pub(super) fn cancelled_error() -> io::Error {
+	io::Error::new(ErrorKind::Other, "Cancelled")
+}
+
+/// Extracts an entry from an archive into the target directory, including all entries inside it if it is a directory, or all entries if the location is the root of the archive. Returns the path of the extracted entry, which must not exist yet, or the target directory when extracting the whole archive. Existing files are never replaced, and existing directories are merged. Reports progress as bytes read from the archive file, and stops once `on_progress` returns false. Everything that was extracted is removed if extracting fails or stops, unless the target already existed.
+pub fn extract_archive_entry<F>(location: &ArchiveLocation, target_directory: &Path, mut on_progress: F) -> io::Result<PathBuf> where F: FnMut(ArchiveProgress) -> bool {
+	let target_path = location.extraction_target(target_directory);
+	let target_existed = fs::symlink_metadata(&target_path).is_ok();
+	
+	if target_existed && !location.is_root() {
+		return Err(io::Error::new(ErrorKind::AlreadyExists, "Something with this name already exists"));
+	}
+	
+	let parent_inner_path = location.inner_path.parent().unwrap_or_else(|| Path::new(""));
+	let get_target = |inner_path: &Path| {
+		inner_path.starts_with(&location.inner_path).then(|| inner_path.strip_prefix(parent_inner_path).ok().map(|relative_path| target_directory.join(relative_path))).flatten()
+	};
+	
+	let total_bytes = fs::metadata(&location.archive_path)?.len();
+	let file = ProgressReader::new(BufReader::new(File::open(&location.archive_path)?), |done_bytes| on_progress(ArchiveProgress { done_bytes, total_bytes }));
+	
+	fs::create_dir_all(target_directory)?;
+	let canonical_target_directory = target_directory.canonicalize()?;
+	
+	let result = match location.kind {
+		ArchiveKind::Zip => extract_from_zip(file, &canonical_target_directory, get_target),
+		ArchiveKind::Tar(compression) => extract_from_tar(file, compression, target_directory, &canonical_target_directory, get_target),
+	};
+	
+	if let Err(e) = result {
+		if !target_existed && fs::symlink_metadata(&target_path).is_ok() {
+			let _ = remove_recursively(&target_path);
+		}
+		
+		return Err(e);
+	}
+	
+	if fs::symlink_metadata(&target_path).is_err() {
+		return Err(io::Error::new(ErrorKind::NotFound, "Entry was not found in the archive"));
+	}
+	
+	Ok(target_path)
+}
+
+/// Symbolic links are created after all files, so that files cannot be written through them.
+fn extract_from_zip<R, F>(file: R, canonical_target_directory: &Path, get_target: F) -> io::Result<()> where R: Read + Seek, F: Fn(&Path) -> Option<PathBuf> {
+	let mut archive = ZipArchive::new(file)?;
+	let mut symlinks = Vec::new();
+	let mut modified_times = Vec::new();
+	
+	for index in 0..archive.len() {
+		let mut file = archive.by_index(index)?;
+		
+		let Some(target) = sanitize_inner_path(Path::new(file.name())).and_then(|inner_path| get_target(&inner_path)) else {
+			continue;
+		};
+		
+		if file.is_dir() {
+			create_directory_inside(canonical_target_directory, &target)?;
+			continue;
+		}
+		
+		if let Some(parent) = target.parent() {
+			create_directory_inside(canonical_target_directory, parent)?;
+		}
+		
+		ensure_does_not_exist(&target)?;
+		
+		if file.unix_mode().is_some_and(is_symlink_mode) {
+			let mut link_target = String::new();
+			file.read_to_string(&mut link_target)?;
+			symlinks.push((PathBuf::from(link_target), target));
+			continue;
+		}
+		
+		io::copy(&mut file, &mut File::create(&target)?)?;
+		set_unix_mode(&target, file.unix_mode())?;
+		
+		if let Some(mtime) = get_zip_modified_time(file.last_modified()) {
+			modified_times.push((target, mtime));
+		}
+	}
+	
+	for (link_target, target) in symlinks {
+		ensure_does_not_exist(&target)?;
+		create_symlink(&link_target, &target)?;
+	}
+	
+	// Times are set at the end, because writing files would change times of directories again.
+	for (target, mtime) in modified_times {
+		set_modified_time(&target, mtime)?;
+	}
+	
+	Ok(())
+}
+
+fn extract_from_tar<F>(file: impl Read, compression: TarCompression, target_directory: &Path, canonical_target_directory: &Path, get_target: F) -> io::Result<()> where F: Fn(&Path) -> Option<PathBuf> {
+	let mut archive = open_tar(file, compression)?;
+	
+	for entry in archive.entries()? {
+		let mut entry = entry?;
+		
+		let Some(inner_path) = sanitize_inner_path(&entry.path()?) else {
+			continue;
+		};
+		
+		let Some(target) = get_target(&inner_path) else {
+			continue;
+		};
+		
+		if let Some(parent) = target.parent() {
+			create_directory_inside(canonical_target_directory, parent)?;
+		}
+		
+		// Unpacking would replace existing files.
+		if entry.header().entry_type() != EntryType::Directory {
+			ensure_does_not_exist(&target)?;
+		}
+		
+		// Entries that keep their path from the archive are unpacked by tar itself, which also checks that hard links point inside the target directory. Entries extracted from a subdirectory of the archive lose the path of the subdirectory, so their hard links are resolved here.
+		if target == target_directory.join(&inner_path) {
+			entry.unpack_in(target_directory)?;
+		} else if entry.header().entry_type() == EntryType::Link {
+			let source = entry.link_name()?.as_deref().and_then(sanitize_inner_path).and_then(|link_inner_path| get_target(&link_inner_path));
+			let source = source.filter(|source| source.canonicalize().is_ok_and(|source| source.starts_with(canonical_target_directory)));
+			let source = source.ok_or_else(|| io::Error::new(ErrorKind::InvalidData, format!("{} links to an entry that is not extracted", inner_path.to_string_lossy())))?;
+			fs::hard_link(source, &target)?;
+		} else {
+			entry.unpack(&target)?;
+		}
+	}
+	
+	Ok(())
+}
+
+/// Creates a directory and all missing directories that lead to it, after checking that the closest one that already exists is inside the target directory once symbolic links are resolved. This stops symbolic links from the archive, or ones that were already in a merged directory, from redirecting writes outside of the target directory.
+fn create_directory_inside(canonical_target_directory: &Path, directory: &Path) -> io::Result<()> {
+	if let Some(existing_ancestor) = directory.ancestors().find(|ancestor| fs::symlink_metadata(ancestor).is_ok()) {
+		if !existing_ancestor.canonicalize()?.starts_with(canonical_target_directory) {
+			return Err(io::Error::new(ErrorKind::InvalidData, format!("{} is outside of the target directory", directory.to_string_lossy())));
+		}
+	}
+	
+	fs::create_dir_all(directory)
+}
+
+fn ensure_does_not_exist(path: &Path) -> io::Result<()> {
+	if fs::symlink_metadata(path).is_ok() {
+		Err(io::Error::new(ErrorKind::AlreadyExists, format!("{} already exists", path.to_string_lossy())))
+	} else {
+		Ok(())
+	}
+}
+
+#[cfg(unix)]
+fn create_symlink(link_target: &Path, path: &Path) -> io::Result<()> {
+	std::os::unix::fs::symlink(link_target, path)
+}
+
+#[cfg(not(unix))]
+fn create_symlink(link_target: &Path, path: &Path) -> io::Result<()> {
+	std::os::windows::fs::symlink_file(link_target, path)
+}
+
+#[cfg(unix)]
+fn set_unix_mode(path: &Path, mode: Option<u32>) -> io::Result<()> {
+	use std::os::unix::fs::PermissionsExt;
+	
+	if let Some(mode) = mode {
+		fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o7777))?;
+	}
+	
+	Ok(())
+}
+
+/// Other platforms have no Unix permissions.
+#[cfg(not(unix))]
+fn set_unix_mode(_path: &Path, _mode: Option<u32>) -> io::Result<()> {
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs::{self, File};
+	use std::io::{self, Write};
+	use std::path::{Path, PathBuf};
+	use std::{env, process};
+	
+	use tar::{EntryType, Header};
+	use zip::write::FileOptions;
+	use zip::ZipWriter;
+	
+	use crate::file::{ArchiveKind, ArchiveLocation, extract_archive_entry, FileKind, read_archive_children, remove_recursively};
+	use crate::file::archive::{get_tar_entry_kind, TarCompression};
+	
+	/// Directory with an archive, a directory to extract it into, and a directory outside of it that extraction must not touch. It is deleted when dropped.
+	struct TestDirectory {
+		root: PathBuf,
+	}
+	
+	impl TestDirectory {
+		fn new(name: &str) -> Self {
+			let root = env::temp_dir().join(format!("bark-test-{}-{name}", process::id()));
+			let _ = remove_recursively(&root);
+			assert!(fs::create_dir_all(root.join("target")).is_ok(), "Could not create target directory");
+			assert!(fs::create_dir_all(root.join("outside")).is_ok(), "Could not create outside directory");
+			Self { root }
+		}
+		
+		fn path(&self, relative_path: &str) -> PathBuf {
+			self.root.join(relative_path)
+		}
+		
+		fn outside_is_empty(&self) -> bool {
+			fs::read_dir(self.path("outside")).is_ok_and(|mut entries| entries.next().is_none())
+		}
+		
+		fn extract(&self, archive_name: &str, kind: ArchiveKind) -> io::Result<PathBuf> {
+			extract_archive_entry(&ArchiveLocation::root(&self.path(archive_name), kind), &self.path("target"), |_| true)
+		}
+	}
+	
+	impl Drop for TestDirectory {
+		fn drop(&mut self) {
+			let _ = remove_recursively(&self.root);
+		}
+	}
+	
+	enum TarEntry<'a> {
+		File(&'a str, &'a [u8]),
+		Symlink(&'a str, &'a Path),
+		HardLink(&'a str, &'a Path),
+	}
+	
+	fn write_tar(path: &Path, entries: &[TarEntry]) -> io::Result<()> {
+		let mut builder = tar::Builder::new(File::create(path)?);
+		
+		for entry in entries {
+			let mut header = Header::new_gnu();
+			header.set_mode(0o644);
+			
+			match entry {
+				TarEntry::File(name, contents) => {
+					header.set_entry_type(EntryType::Regular);
+					header.set_size(u64::try_from(contents.len()).unwrap_or_default());
+					builder.append_data(&mut header, name, *contents)?;
+				}
+				TarEntry::Symlink(name, target) | TarEntry::HardLink(name, target) => {
+					header.set_entry_type(if matches!(entry, TarEntry::Symlink(..)) { EntryType::Symlink } else { EntryType::Link });
+					header.set_size(0);
+					builder.append_link(&mut header, name, target)?;
+				}
+			}
+		}
+		
+		builder.into_inner()?.flush()
+	}
+	
+	#[test]
+	fn extracts_tar_archives() {
+		let directory = TestDirectory::new("extract-tar");
+		let archive = directory.path("a.tar");
+		assert!(write_tar(&archive, &[TarEntry::File("dir/file", b"abc"), TarEntry::Symlink("dir/link", Path::new("file")), TarEntry::HardLink("dir/hard", Path::new("dir/file"))]).is_ok(), "Could not write archive");
+		
+		assert_eq!(directory.extract("a.tar", ArchiveKind::Tar(TarCompression::None)).ok(), Some(directory.path("target")));
+		assert_eq!(fs::read(directory.path("target/dir/file")).ok(), Some(b"abc".to_vec()));
+		assert_eq!(fs::read_link(directory.path("target/dir/link")).ok(), Some(PathBuf::from("file")));
+		assert_eq!(fs::read(directory.path("target/dir/hard")).ok(), Some(b"abc".to_vec()));
+	}
+	
+	#[test]
+	fn extracts_hard_links_from_tar_subdirectories() {
+		let directory = TestDirectory::new("extract-tar-subdirectory");
+		let archive = directory.path("a.tar");
+		assert!(write_tar(&archive, &[TarEntry::File("dir/sub/file", b"abc"), TarEntry::HardLink("dir/sub/hard", Path::new("dir/sub/file"))]).is_ok(), "Could not write archive");
+		
+		let location = ArchiveLocation::root(&archive, ArchiveKind::Tar(TarCompression::None)).child("dir").child("sub");
+		assert_eq!(extract_archive_entry(&location, &directory.path("target"), |_| true).ok(), Some(directory.path("target/sub")));
+		assert_eq!(fs::read(directory.path("target/sub/hard")).ok(), Some(b"abc".to_vec()));
+	}
+	
+	#[test]
+	fn does_not_write_tar_entries_through_symlinks() {
+		let directory = TestDirectory::new("tar-symlink");
+		let archive = directory.path("a.tar");
+		let outside = directory.path("outside");
+		assert!(write_tar(&archive, &[TarEntry::Symlink("link", &outside), TarEntry::File("link/file", b"abc")]).is_ok(), "Could not write archive");
+		
+		assert!(directory.extract("a.tar", ArchiveKind::Tar(TarCompression::None)).is_err(), "Extraction wrote through a symbolic link");
+		assert!(directory.outside_is_empty(), "Something was written outside of the target directory");
+	}
+	
+	#[test]
+	fn does_not_extract_tar_hard_links_to_outside_files() {
+		let directory = TestDirectory::new("tar-hard-link");
+		let archive = directory.path("a.tar");
+		let secret = directory.path("outside/secret");
+		assert!(fs::write(&secret, "secret").is_ok(), "Could not write outside file");
+		assert!(write_tar(&archive, &[TarEntry::HardLink("hard", &secret)]).is_ok(), "Could not write archive");
+		
+		assert!(directory.extract("a.tar", ArchiveKind::Tar(TarCompression::None)).is_err(), "Extraction linked a file outside of the target directory");
+		assert!(fs::symlink_metadata(directory.path("target/hard")).is_err(), "Hard link was created");
+	}
+	
+	#[test]
+	#[cfg(unix)]
+	fn does_not_write_through_symlinks_in_merged_directories() {
+		let directory = TestDirectory::new("merged-symlink");
+		let archive = directory.path("a.tar");
+		assert!(write_tar(&archive, &[TarEntry::File("link/file", b"abc")]).is_ok(), "Could not write archive");
+		assert!(std::os::unix::fs::symlink(directory.path("outside"), directory.path("target/link")).is_ok(), "Could not create symbolic link");
+		
+		assert!(directory.extract("a.tar", ArchiveKind::Tar(TarCompression::None)).is_err(), "Extraction wrote through a symbolic link");
+		assert!(directory.outside_is_empty(), "Something was written outside of the target directory");
+	}
+	
+	#[test]
+	fn does_not_write_zip_entries_through_symlinks() {
+		let directory = TestDirectory::new("zip-symlink");
+		let archive = directory.path("a.zip");
+		let outside = directory.path("outside");
+		
+		let result = File::create(&archive).map_err(zip::result::ZipError::from).and_then(|file| {
+			let mut zip = ZipWriter::new(file);
+			zip.add_symlink("link", outside.to_string_lossy(), FileOptions::default())?;
+			zip.start_file("link/file", FileOptions::default())?;
+			zip.write_all(b"abc")?;
+			zip.finish().map(|_| ())
+		});
+		
+		assert!(result.is_ok(), "Could not write archive");
+		assert!(directory.extract("a.zip", ArchiveKind::Zip).is_err(), "Extraction wrote through a symbolic link");
+		assert!(directory.outside_is_empty(), "Something was written outside of the target directory");
+	}
+	
+	#[test]
+	fn reports_tar_hard_links_as_links() {
+		assert!(matches!(get_tar_entry_kind(EntryType::Link, 0), Some(FileKind::Symlink)), "Hard link is not a link");
+		assert!(matches!(get_tar_entry_kind(EntryType::Regular, 3), Some(FileKind::File { size: 3 })), "Regular file is not a file");
+	}
+	
+	#[test]
+	#[cfg(unix)]
+	fn reads_archive_again_only_once_it_is_modified() {
+		use std::time::Duration;
+		
+		use crate::file::copy::set_modified_time;
+		
+		let directory = TestDirectory::new("cached-headers");
+		let archive = directory.path("a.tar");
+		let location = ArchiveLocation::root(&archive, ArchiveKind::Tar(TarCompression::None));
+		let read_names = || read_archive_children(&location).unwrap_or_default().iter().map(|entry| entry.name().str().to_owned()).collect::<Vec<_>>();
+		
+		assert!(write_tar(&archive, &[TarEntry::File("a", b"a")]).is_ok(), "Could not write archive");
+		let Ok(mtime) = fs::metadata(&archive).and_then(|metadata| metadata.modified()) else {
+			panic!("Could not read modification time");
+		};
+		
+		assert_eq!(read_names(), vec!["a"]);
+		
+		assert!(write_tar(&archive, &[TarEntry::File("a", b"a"), TarEntry::File("b", b"b")]).is_ok(), "Could not write archive");
+		assert!(set_modified_time(&archive, mtime).is_ok(), "Could not restore modification time");
+		assert_eq!(read_names(), vec!["a"]);
+		
+		assert!(set_modified_time(&archive, mtime + Duration::from_secs(10)).is_ok(), "Could not change modification time");
+		assert_eq!(read_names(), vec!["a", "b"]);
+	}
+}
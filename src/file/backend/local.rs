@@ -0,0 +1,81 @@
+use std::fs::{self, DirEntry, File, OpenOptions};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::file::{change_owner, copy_attributes, copy_file_with_progress, copy_recursively, FileEntry, remove_recursively};
+use crate::file::backend::{already_exists_error, FileSystem};
+
+/// File system of the computer, which is used unless something else is needed.
+pub struct LocalFileSystem;
+
+impl FileSystem for LocalFileSystem {
+	fn list(&self, path: &Path) -> io::Result<Vec<FileEntry>> {
+		Ok(fs::read_dir(path)?.map(read_entry).collect())
+	}
+	
+	fn stat(&self, path: &Path) -> FileEntry {
+		FileEntry::from(path)
+	}
+	
+	fn exists(&self, path: &Path) -> bool {
+		fs::symlink_metadata(path).is_ok()
+	}
+	
+	fn open_file(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+		Ok(Box::new(File::open(path)?))
+	}
+	
+	fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+		fs::read_link(path)
+	}
+	
+	fn rename(&self, old_path: &Path, new_path: &Path) -> io::Result<()> {
+		// The check is not atomic, but renaming would silently replace existing files on most platforms.
+		if self.exists(new_path) {
+			Err(already_exists_error())
+		} else {
+			fs::rename(old_path, new_path)
+		}
+	}
+	
+	fn create_file(&self, path: &Path) -> io::Result<()> {
+		OpenOptions::new().write(true).create_new(true).open(path).map(|_| ())
+	}
+	
+	fn create_directory(&self, path: &Path) -> io::Result<()> {
+		fs::create_dir(path)
+	}
+	
+	fn hard_link(&self, original: &Path, link: &Path) -> io::Result<()> {
+		fs::hard_link(original, link)
+	}
+	
+	fn remove(&self, path: &Path) -> io::Result<()> {
+		remove_recursively(path)
+	}
+	
+	fn copy(&self, source: &Path, target: &Path) -> io::Result<()> {
+		copy_recursively(source, target)
+	}
+	
+	fn copy_file(&self, source: &Path, target: &Path, on_progress: &mut dyn FnMut(u64) -> bool) -> io::Result<()> {
+		if self.exists(target) {
+			Err(already_exists_error())
+		} else {
+			copy_file_with_progress(source, target, on_progress)
+		}
+	}
+	
+	fn copy_attributes(&self, source: &Path, target: &Path) -> io::Result<()> {
+		copy_attributes(source, target)
+	}
+	
+	fn change_owner(&self, path: &Path, uid: Option<u32>, gid: Option<u32>) -> io::Result<()> {
+		change_owner(path, uid, gid)
+	}
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn read_entry(entry: io::Result<DirEntry>) -> FileEntry {
+	entry.as_ref().ok().map_or_else(FileEntry::dummy, FileEntry::from)
+}
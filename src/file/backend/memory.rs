@@ -0,0 +1,522 @@
+use std::collections::BTreeMap;
+use std::io::{self, Cursor, ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::SystemTime;
+
+use crate::file::{FileEntry, FileKind, FileMode, FileName, FileOwner};
+use crate::file::backend::{already_exists_error, FileSystem};
+
+const DIRECTORY_MODE: u32 = 0o040_755;
+const FILE_MODE: u32 = 0o100_644;
+const SYMLINK_MODE: u32 = 0o120_777;
+
+/// Same limit as Linux, which stops symbolic link loops.
+const MAX_SYMLINK_HOPS: usize = 40;
+
+/// File system that only exists in memory, so that reading and changing entries is deterministic and does not touch the disk. It starts with an empty root directory.
+pub struct MemoryFileSystem {
+	entries: Mutex<Entries>,
+}
+
+type Entries = BTreeMap<PathBuf, MemoryEntry>;
+
+#[derive(Clone)]
+struct MemoryEntry {
+	contents: MemoryContents,
+	owner: FileOwner,
+	mtime: SystemTime,
+}
+
+#[derive(Clone)]
+enum MemoryContents {
+	File(Vec<u8>),
+	Directory,
+	Symlink(PathBuf),
+}
+
+impl MemoryEntry {
+	fn new(contents: MemoryContents) -> Self {
+		Self { contents, owner: FileOwner::new(0, 0), mtime: SystemTime::now() }
+	}
+	
+	fn to_file_entry(&self, path: &Path) -> FileEntry {
+		let (kind, mode) = match &self.contents {
+			MemoryContents::File(bytes) => (FileKind::File { size: u64::try_from(bytes.len()).unwrap_or(u64::MAX) }, FILE_MODE),
+			MemoryContents::Directory => (FileKind::Directory, DIRECTORY_MODE),
+			MemoryContents::Symlink(_) => (FileKind::Symlink, SYMLINK_MODE),
+		};
+		
+		FileEntry {
+			path: Some(path.to_path_buf()),
+			archive_location: None,
+			name: get_name(path),
+			kind,
+			mode: FileMode::Known(mode),
+			owner: Some(self.owner),
+			stats: None,
+			mtime: Some(self.mtime),
+			atime: None,
+			btime: None,
+		}
+	}
+}
+
+impl MemoryFileSystem {
+	pub fn new() -> Self {
+		let mut entries = BTreeMap::new();
+		entries.insert(PathBuf::from("/"), MemoryEntry::new(MemoryContents::Directory));
+		Self { entries: Mutex::new(entries) }
+	}
+	
+	/// Adds a file with the contents, and all directories that lead to it.
+	pub fn with_file(self, path: impl AsRef<Path>, contents: impl Into<Vec<u8>>) -> Self {
+		self.insert_with_parents(path.as_ref(), MemoryContents::File(contents.into()))
+	}
+	
+	/// Adds a directory, and all directories that lead to it.
+	pub fn with_directory(self, path: impl AsRef<Path>) -> Self {
+		self.insert_with_parents(path.as_ref(), MemoryContents::Directory)
+	}
+	
+	/// Adds a symbolic link, and all directories that lead to it. The target does not need to exist.
+	pub fn with_symlink(self, path: impl AsRef<Path>, target: impl AsRef<Path>) -> Self {
+		self.insert_with_parents(path.as_ref(), MemoryContents::Symlink(target.as_ref().to_path_buf()))
+	}
+	
+	fn insert_with_parents(self, path: &Path, contents: MemoryContents) -> Self {
+		{
+			let mut entries = self.lock();
+			
+			for ancestor in path.ancestors().skip(1) {
+				entries.entry(ancestor.to_path_buf()).or_insert_with(|| MemoryEntry::new(MemoryContents::Directory));
+			}
+			
+			entries.insert(path.to_path_buf(), MemoryEntry::new(contents));
+		}
+		
+		self
+	}
+	
+	/// Returns contents of a file, following symbolic links.
+	pub fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+		let entries = self.lock();
+		
+		match Self::resolve_symlinks(&entries, path)?.map(|entry| &entry.contents) {
+			Some(MemoryContents::File(bytes)) => Ok(bytes.clone()),
+			Some(_) => Err(io::Error::new(ErrorKind::InvalidInput, "Not a file")),
+			None => Err(not_found_error()),
+		}
+	}
+	
+	fn lock(&self) -> MutexGuard<'_, Entries> {
+		// Entries are never left half-changed, so they can be used even if another thread panicked.
+		self.entries.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+	}
+	
+	/// Returns the entry at the path, or the entry that a chain of symbolic links starting at the path points to. Relative targets are relative to the directory that contains the link.
+	fn resolve_symlinks<'a>(entries: &'a Entries, path: &Path) -> io::Result<Option<&'a MemoryEntry>> {
+		let mut path = path.to_path_buf();
+		
+		for _ in 0..MAX_SYMLINK_HOPS {
+			match entries.get(&path) {
+				Some(MemoryEntry { contents: MemoryContents::Symlink(target), .. }) => {
+					path = path.parent().map_or_else(|| target.clone(), |parent| parent.join(target));
+				}
+				entry => return Ok(entry),
+			}
+		}
+		
+		Err(io::Error::new(ErrorKind::InvalidInput, "Too many levels of symbolic links"))
+	}
+	
+	/// Inserts a new entry, after checking that it does not exist yet, and that its parent is a directory.
+	fn insert_new(entries: &mut Entries, path: &Path, entry: MemoryEntry) -> io::Result<()> {
+		if entries.contains_key(path) {
+			return Err(already_exists_error());
+		}
+		
+		if !path.parent().and_then(|parent| entries.get(parent)).is_some_and(|parent| matches!(parent.contents, MemoryContents::Directory)) {
+			return Err(not_found_error());
+		}
+		
+		entries.insert(path.to_path_buf(), entry);
+		Ok(())
+	}
+	
+	/// Returns paths of the entry and everything inside it.
+	fn collect_subtree(entries: &Entries, path: &Path) -> Vec<PathBuf> {
+		entries.range(path.to_path_buf()..)
+		       .map(|(entry_path, _)| entry_path)
+		       .take_while(|entry_path| entry_path.starts_with(path))
+		       .cloned()
+		       .collect()
+	}
+	
+	fn rename_entry(entries: &mut Entries, old_path: &Path, new_path: &Path) -> io::Result<()> {
+		if !entries.contains_key(old_path) {
+			return Err(not_found_error());
+		}
+		
+		if new_path.starts_with(old_path) {
+			return Err(io::Error::new(ErrorKind::InvalidInput, "Cannot move a directory into itself"));
+		}
+		
+		let moved_paths = Self::collect_subtree(entries, old_path);
+		let moved_entries = moved_paths.iter().filter_map(|path| entries.remove(path).map(|entry| (path, entry))).collect::<Vec<_>>();
+		let mut moved_entries = moved_entries.into_iter();
+		
+		if let Some((_, root_entry)) = moved_entries.next() {
+			if let Err(e) = Self::insert_new(entries, new_path, root_entry.clone()) {
+				entries.insert(old_path.to_path_buf(), root_entry);
+				entries.extend(moved_entries.map(|(path, entry)| (path.clone(), entry)));
+				return Err(e);
+			}
+		}
+		
+		for (path, entry) in moved_entries {
+			if let Ok(relative_path) = path.strip_prefix(old_path) {
+				entries.insert(new_path.join(relative_path), entry);
+			}
+		}
+		
+		Ok(())
+	}
+	
+	fn remove_entry(entries: &mut Entries, path: &Path) -> io::Result<()> {
+		if !entries.contains_key(path) {
+			return Err(not_found_error());
+		}
+		
+		for removed_path in Self::collect_subtree(entries, path) {
+			entries.remove(&removed_path);
+		}
+		
+		Ok(())
+	}
+	
+	fn copy_entry(entries: &mut Entries, source: &Path, target: &Path) -> io::Result<()> {
+		if target.starts_with(source) {
+			return Err(io::Error::new(ErrorKind::InvalidInput, "Cannot copy a directory into itself"));
+		}
+		
+		let copied_entries = Self::collect_subtree(entries, source).into_iter()
+			.filter_map(|path| Some((path.strip_prefix(source).ok()?.to_path_buf(), entries.get(&path)?.clone())))
+			.collect::<Vec<_>>();
+		
+		let Some((_, root_entry)) = copied_entries.first() else {
+			return Err(not_found_error());
+		};
+		
+		Self::insert_new(entries, target, root_entry.clone())?;
+		
+		for (relative_path, entry) in copied_entries.into_iter().skip(1) {
+			entries.insert(target.join(relative_path), entry);
+		}
+		
+		Ok(())
+	}
+	
+	/// Copies a file or a symbolic link, and returns the number of copied bytes.
+	fn copy_file_entry(entries: &mut Entries, source: &Path, target: &Path) -> io::Result<u64> {
+		let entry = match entries.get(source) {
+			Some(entry) if !matches!(entry.contents, MemoryContents::Directory) => entry.clone(),
+			Some(_) => return Err(io::Error::new(ErrorKind::InvalidInput, "Not a file")),
+			None => return Err(not_found_error()),
+		};
+		
+		let copied_bytes = if let MemoryContents::File(bytes) = &entry.contents { u64::try_from(bytes.len()).unwrap_or(u64::MAX) } else { 0 };
+		Self::insert_new(entries, target, entry)?;
+		Ok(copied_bytes)
+	}
+	
+	/// Entries do not share their contents, so the link is a copy of the file.
+	fn hard_link_entry(entries: &mut Entries, original: &Path, link: &Path) -> io::Result<()> {
+		let entry = match entries.get(original) {
+			Some(entry) if matches!(entry.contents, MemoryContents::File(_)) => entry.clone(),
+			Some(_) => return Err(io::Error::new(ErrorKind::InvalidInput, "Not a file")),
+			None => return Err(not_found_error()),
+		};
+		
+		Self::insert_new(entries, link, entry)
+	}
+	
+	fn copy_entry_mtime(entries: &mut Entries, source: &Path, target: &Path) -> io::Result<()> {
+		let mtime = entries.get(source).ok_or_else(not_found_error)?.mtime;
+		entries.get_mut(target).ok_or_else(not_found_error)?.mtime = mtime;
+		Ok(())
+	}
+	
+	fn change_entry_owner(entries: &mut Entries, path: &Path, uid: Option<u32>, gid: Option<u32>) -> io::Result<()> {
+		let entry = entries.get_mut(path).ok_or_else(not_found_error)?;
+		let owner = entry.owner;
+		entry.owner = FileOwner::new(uid.unwrap_or_else(|| owner.uid()), gid.unwrap_or_else(|| owner.gid()));
+		Ok(())
+	}
+}
+
+impl Default for MemoryFileSystem {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl FileSystem for MemoryFileSystem {
+	fn list(&self, path: &Path) -> io::Result<Vec<FileEntry>> {
+		let entries = self.lock();
+		
+		match entries.get(path).map(|entry| &entry.contents) {
+			Some(MemoryContents::Directory) => {}
+			Some(_) => return Err(io::Error::new(ErrorKind::InvalidInput, "Not a directory")),
+			None => return Err(not_found_error()),
+		}
+		
+		Ok(entries.iter()
+		          .filter(|(entry_path, _)| entry_path.parent() == Some(path))
+		          .map(|(entry_path, entry)| entry.to_file_entry(entry_path))
+		          .collect())
+	}
+	
+	fn stat(&self, path: &Path) -> FileEntry {
+		self.lock().get(path).map_or_else(|| FileEntry { path: Some(path.to_path_buf()), name: get_name(path), ..FileEntry::dummy() }, |entry| entry.to_file_entry(path))
+	}
+	
+	fn exists(&self, path: &Path) -> bool {
+		self.lock().contains_key(path)
+	}
+	
+	fn open_file(&self, path: &Path) -> io::Result<Box<dyn Read + Send>> {
+		Ok(Box::new(Cursor::new(self.read(path)?)))
+	}
+	
+	fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+		match self.lock().get(path).map(|entry| &entry.contents) {
+			Some(MemoryContents::Symlink(target)) => Ok(target.clone()),
+			Some(_) => Err(io::Error::new(ErrorKind::InvalidInput, "Not a symbolic link")),
+			None => Err(not_found_error()),
+		}
+	}
+	
+	fn rename(&self, old_path: &Path, new_path: &Path) -> io::Result<()> {
+		Self::rename_entry(&mut self.lock(), old_path, new_path)
+	}
+	
+	fn create_file(&self, path: &Path) -> io::Result<()> {
+		Self::insert_new(&mut self.lock(), path, MemoryEntry::new(MemoryContents::File(Vec::new())))
+	}
+	
+	fn create_directory(&self, path: &Path) -> io::Result<()> {
+		Self::insert_new(&mut self.lock(), path, MemoryEntry::new(MemoryContents::Directory))
+	}
+	
+	fn hard_link(&self, original: &Path, link: &Path) -> io::Result<()> {
+		Self::hard_link_entry(&mut self.lock(), original, link)
+	}
+	
+	fn remove(&self, path: &Path) -> io::Result<()> {
+		Self::remove_entry(&mut self.lock(), path)
+	}
+	
+	fn copy(&self, source: &Path, target: &Path) -> io::Result<()> {
+		Self::copy_entry(&mut self.lock(), source, target)
+	}
+	
+	fn copy_file(&self, source: &Path, target: &Path, on_progress: &mut dyn FnMut(u64) -> bool) -> io::Result<()> {
+		let copied_bytes = Self::copy_file_entry(&mut self.lock(), source, target)?;
+		on_progress(copied_bytes);
+		Ok(())
+	}
+	
+	fn copy_attributes(&self, source: &Path, target: &Path) -> io::Result<()> {
+		Self::copy_entry_mtime(&mut self.lock(), source, target)
+	}
+	
+	fn change_owner(&self, path: &Path, uid: Option<u32>, gid: Option<u32>) -> io::Result<()> {
+		Self::change_entry_owner(&mut self.lock(), path, uid, gid)
+	}
+}
+
+fn get_name(path: &Path) -> FileName {
+	path.file_name().map_or_else(|| FileName::from("/"), |name| FileName::from(name.to_os_string()))
+}
+
+fn not_found_error() -> io::Error {
+	io::Error::from(ErrorKind::NotFound)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::{ErrorKind, Read};
+	use std::path::{Path, PathBuf};
+	
+	use crate::file::{FileEntry, FileKind, FileSystem, MemoryFileSystem};
+	
+	fn list_names(file_system: &MemoryFileSystem, path: &str) -> Vec<String> {
+		file_system.list(Path::new(path)).unwrap_or_default().iter().map(|entry| entry.name().str().to_owned()).collect()
+	}
+	
+	fn error_kind(result: std::io::Result<()>) -> Option<ErrorKind> {
+		result.err().map(|e| e.kind())
+	}
+	
+	#[test]
+	fn lists_children_of_directories() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/a/file.txt", "abc")
+			.with_directory("/a/dir")
+			.with_symlink("/a/link", "file.txt")
+			.with_file("/a/dir/nested", "");
+		
+		assert_eq!(list_names(&file_system, "/"), vec!["a"]);
+		assert_eq!(list_names(&file_system, "/a"), vec!["dir", "file.txt", "link"]);
+		assert_eq!(list_names(&file_system, "/a/dir"), vec!["nested"]);
+		
+		let entries = file_system.list(Path::new("/a")).unwrap_or_default();
+		let kinds = entries.iter().map(FileEntry::kind).collect::<Vec<_>>();
+		assert!(matches!(kinds.as_slice(), [FileKind::Directory, FileKind::File { size: 3 }, FileKind::Symlink]), "Kinds do not match the entries");
+		
+		assert_eq!(file_system.list(Path::new("/a/file.txt")).err().map(|e| e.kind()), Some(ErrorKind::InvalidInput));
+		assert_eq!(file_system.list(Path::new("/missing")).err().map(|e| e.kind()), Some(ErrorKind::NotFound));
+	}
+	
+	#[test]
+	fn stats_missing_entries_as_unknown() {
+		let file_system = MemoryFileSystem::new();
+		let entry = file_system.stat(Path::new("/missing"));
+		
+		assert_eq!(entry.path(), Some(Path::new("/missing")));
+		assert_eq!(entry.name().str(), "missing");
+		assert!(matches!(entry.kind(), FileKind::Unknown), "Missing entry has a known kind");
+		assert!(!file_system.exists(Path::new("/missing")), "Missing entry exists");
+	}
+	
+	#[test]
+	fn creates_files_and_directories() {
+		let file_system = MemoryFileSystem::new().with_directory("/a");
+		
+		assert_eq!(error_kind(file_system.create_directory(Path::new("/a/dir"))), None);
+		assert_eq!(error_kind(file_system.create_file(Path::new("/a/dir/file"))), None);
+		assert_eq!(file_system.read(Path::new("/a/dir/file")).ok(), Some(Vec::new()));
+		
+		assert_eq!(error_kind(file_system.create_file(Path::new("/a/dir/file"))), Some(ErrorKind::AlreadyExists));
+		assert_eq!(error_kind(file_system.create_directory(Path::new("/a/dir"))), Some(ErrorKind::AlreadyExists));
+		assert_eq!(error_kind(file_system.create_file(Path::new("/missing/file"))), Some(ErrorKind::NotFound));
+		assert_eq!(error_kind(file_system.create_file(Path::new("/a/dir/file/inner"))), Some(ErrorKind::NotFound));
+	}
+	
+	#[test]
+	fn renames_directories_with_their_contents() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/a/dir/file", "x")
+			.with_file("/a/dir/sub/nested", "y")
+			.with_file("/a/dir-sibling", "z")
+			.with_directory("/b");
+		
+		assert_eq!(error_kind(file_system.rename(Path::new("/a/dir"), Path::new("/b/moved"))), None);
+		
+		assert_eq!(list_names(&file_system, "/a"), vec!["dir-sibling"]);
+		assert_eq!(list_names(&file_system, "/b/moved"), vec!["file", "sub"]);
+		assert_eq!(file_system.read(Path::new("/b/moved/sub/nested")).ok(), Some(b"y".to_vec()));
+	}
+	
+	#[test]
+	fn refuses_invalid_renames() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/a/file", "x")
+			.with_file("/a/other", "y")
+			.with_directory("/a/dir");
+		
+		assert_eq!(error_kind(file_system.rename(Path::new("/a/file"), Path::new("/a/other"))), Some(ErrorKind::AlreadyExists));
+		assert_eq!(error_kind(file_system.rename(Path::new("/a/dir"), Path::new("/a/dir/inner"))), Some(ErrorKind::InvalidInput));
+		assert_eq!(error_kind(file_system.rename(Path::new("/a/missing"), Path::new("/a/new"))), Some(ErrorKind::NotFound));
+		assert_eq!(error_kind(file_system.rename(Path::new("/a/file"), Path::new("/missing/file"))), Some(ErrorKind::NotFound));
+		
+		assert_eq!(list_names(&file_system, "/a"), vec!["dir", "file", "other"]);
+		assert_eq!(file_system.read(Path::new("/a/file")).ok(), Some(b"x".to_vec()));
+	}
+	
+	#[test]
+	fn removes_directories_with_their_contents() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/a/dir/file", "x")
+			.with_file("/a/dir/sub/nested", "y")
+			.with_file("/a/dir-sibling", "z");
+		
+		assert_eq!(error_kind(file_system.remove(Path::new("/a/dir"))), None);
+		assert_eq!(error_kind(file_system.remove(Path::new("/a/dir"))), Some(ErrorKind::NotFound));
+		
+		assert_eq!(list_names(&file_system, "/a"), vec!["dir-sibling"]);
+		assert!(!file_system.exists(Path::new("/a/dir/sub/nested")), "Nested entry was not removed");
+	}
+	
+	#[test]
+	fn removes_symlinks_without_following_them() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/a/file", "x")
+			.with_symlink("/a/link", "/a/file");
+		
+		assert_eq!(error_kind(file_system.remove(Path::new("/a/link"))), None);
+		assert_eq!(list_names(&file_system, "/a"), vec!["file"]);
+	}
+	
+	#[test]
+	fn copies_directories_with_their_contents() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/a/dir/file", "x")
+			.with_file("/a/dir/sub/nested", "y");
+		
+		assert_eq!(error_kind(file_system.copy(Path::new("/a/dir"), Path::new("/a/copy"))), None);
+		assert_eq!(error_kind(file_system.copy(Path::new("/a/dir"), Path::new("/a/copy"))), Some(ErrorKind::AlreadyExists));
+		assert_eq!(error_kind(file_system.copy(Path::new("/a/dir"), Path::new("/a/dir/sub/copy"))), Some(ErrorKind::InvalidInput));
+		
+		assert_eq!(list_names(&file_system, "/a"), vec!["copy", "dir"]);
+		assert_eq!(file_system.read(Path::new("/a/copy/sub/nested")).ok(), Some(b"y".to_vec()));
+		assert_eq!(file_system.read(Path::new("/a/dir/sub/nested")).ok(), Some(b"y".to_vec()));
+	}
+	
+	#[test]
+	fn copies_files_with_progress() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/a/file", "abc")
+			.with_directory("/a/dir");
+		
+		let mut progress = Vec::new();
+		assert_eq!(error_kind(file_system.copy_file(Path::new("/a/file"), Path::new("/a/copy"), &mut |bytes| { progress.push(bytes); true })), None);
+		assert_eq!(progress, vec![3]);
+		assert_eq!(file_system.read(Path::new("/a/copy")).ok(), Some(b"abc".to_vec()));
+		
+		assert_eq!(error_kind(file_system.copy_file(Path::new("/a/dir"), Path::new("/a/dir-copy"), &mut |_| true)), Some(ErrorKind::InvalidInput));
+	}
+	
+	#[test]
+	fn reads_files_through_symlinks() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/a/file", "abc")
+			.with_symlink("/a/relative", "file")
+			.with_symlink("/b/absolute", "/a/relative")
+			.with_symlink("/b/loop", "/b/loop");
+		
+		let mut contents = String::new();
+		let result = file_system.open_file(Path::new("/b/absolute")).and_then(|mut file| file.read_to_string(&mut contents));
+		assert_eq!(result.ok(), Some(3));
+		assert_eq!(contents, "abc");
+		
+		assert_eq!(file_system.read_link(Path::new("/b/absolute")).ok(), Some(PathBuf::from("/a/relative")));
+		assert_eq!(file_system.read_link(Path::new("/a/file")).err().map(|e| e.kind()), Some(ErrorKind::InvalidInput));
+		assert_eq!(file_system.read(Path::new("/b/loop")).err().map(|e| e.kind()), Some(ErrorKind::InvalidInput));
+	}
+	
+	#[test]
+	fn changes_owners_and_attributes() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/a/file", "x")
+			.with_file("/a/other", "y");
+		
+		assert_eq!(error_kind(file_system.change_owner(Path::new("/a/file"), Some(1000), None)), None);
+		assert_eq!(file_system.stat(Path::new("/a/file")).uid(), Some(1000));
+		assert_eq!(file_system.stat(Path::new("/a/file")).gid(), Some(0));
+		
+		assert_eq!(error_kind(file_system.copy_attributes(Path::new("/a/file"), Path::new("/a/other"))), None);
+		assert_eq!(file_system.stat(Path::new("/a/other")).modified_time(), file_system.stat(Path::new("/a/file")).modified_time());
+	}
+}
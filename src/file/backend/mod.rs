@@ -0,0 +1,61 @@
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::file::FileEntry;
+
+pub use self::local::LocalFileSystem;
+#[cfg(test)]
+pub use self::memory::MemoryFileSystem;
+
+mod local;
+#[cfg(test)]
+mod memory;
+
+/// Storage that the tree reads entries from, and that file actions change. Paths are absolute.
+pub trait FileSystem: Send + Sync {
+	/// Reads entries inside a directory. Entries that cannot be read are replaced with dummy entries.
+	fn list(&self, path: &Path) -> io::Result<Vec<FileEntry>>;
+	
+	/// Reads an entry without following symbolic links. Entries that cannot be read have an unknown kind.
+	fn stat(&self, path: &Path) -> FileEntry;
+	
+	/// Returns whether anything exists at the path, including broken symbolic links.
+	fn exists(&self, path: &Path) -> bool;
+	
+	/// Opens a file for reading, following symbolic links.
+	fn open_file(&self, path: &Path) -> io::Result<Box<dyn Read + Send>>;
+	
+	/// Returns the target of a symbolic link.
+	fn read_link(&self, path: &Path) -> io::Result<PathBuf>;
+	
+	/// Renames or moves an entry. Fails if the new path already exists.
+	fn rename(&self, old_path: &Path, new_path: &Path) -> io::Result<()>;
+	
+	/// Creates an empty file. Fails if the path already exists.
+	fn create_file(&self, path: &Path) -> io::Result<()>;
+	
+	/// Creates an empty directory. Fails if the path already exists.
+	fn create_directory(&self, path: &Path) -> io::Result<()>;
+	
+	/// Creates a hard link to a file. Fails if the link already exists.
+	fn hard_link(&self, original: &Path, link: &Path) -> io::Result<()>;
+	
+	/// Deletes a file, a symbolic link, or a directory with all its contents. Symbolic links are deleted without following them.
+	fn remove(&self, path: &Path) -> io::Result<()>;
+	
+	/// Copies a file, a symbolic link, or a directory with all its contents. Fails if the target already exists.
+	fn copy(&self, source: &Path, target: &Path) -> io::Result<()>;
+	
+	/// Copies a file or a symbolic link, but not a directory. Reports the number of bytes copied so far, and stops once `on_progress` returns false. Fails if the target already exists.
+	fn copy_file(&self, source: &Path, target: &Path, on_progress: &mut dyn FnMut(u64) -> bool) -> io::Result<()>;
+	
+	/// Gives the target the attributes of the source, such as its modification time. Used after copying the contents of a directory.
+	fn copy_attributes(&self, source: &Path, target: &Path) -> io::Result<()>;
+	
+	/// Changes the owner and/or group of an entry without following symbolic links. Leaves the owner or group unchanged if its id is `None`.
+	fn change_owner(&self, path: &Path, uid: Option<u32>, gid: Option<u32>) -> io::Result<()>;
+}
+
+fn already_exists_error() -> io::Error {
+	io::Error::new(io::ErrorKind::AlreadyExists, "Something with this name already exists")
+}
@@ -0,0 +1,150 @@
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Read, Write};
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::file::archive::cancelled_error;
+
+/// Size of chunks in which files are copied when reporting progress.
+const COPY_BUFFER_SIZE: usize = 256 * 1024;
+
+/// Copies a file, a symbolic link, or a directory with all its contents. Copies keep permissions and modification times of the originals, and symbolic links are copied as links. Fails if the target already exists.
+pub fn copy_recursively(source: &Path, target: &Path) -> io::Result<()> {
+	let metadata = fs::symlink_metadata(source)?;
+	let file_type = metadata.file_type();
+	
+	if fs::symlink_metadata(target).is_ok() {
+		return Err(io::Error::new(ErrorKind::AlreadyExists, "Something with this name already exists"));
+	}
+	
+	if file_type.is_symlink() {
+		return copy_symlink(source, target);
+	}
+	
+	if file_type.is_dir() {
+		fs::create_dir(target)?;
+		
+		for entry in fs::read_dir(source)? {
+			let entry = entry?;
+			copy_recursively(&entry.path(), &target.join(entry.file_name()))?;
+		}
+		
+		fs::set_permissions(target, metadata.permissions())?;
+	} else {
+		fs::copy(source, target)?;
+	}
+	
+	set_modified_time(target, metadata.modified()?)
+}
+
+/// Copies a file or a symbolic link, keeping its permissions and modification time. Reports the number of bytes copied so far after every chunk, and stops once `on_progress` returns false. The partial copy is removed if copying fails or stops. Fails if the target already exists.
+pub fn copy_file_with_progress(source: &Path, target: &Path, on_progress: &mut dyn FnMut(u64) -> bool) -> io::Result<()> {
+	let metadata = fs::symlink_metadata(source)?;
+	
+	if metadata.file_type().is_symlink() {
+		return copy_symlink(source, target);
+	}
+	
+	let mut source_file = File::open(source)?;
+	let mut target_file = File::options().write(true).create_new(true).open(target)?;
+	
+	let result = copy_contents(&mut source_file, &mut target_file, on_progress)
+		.and_then(|()| target_file.set_permissions(metadata.permissions()))
+		.and_then(|()| set_modified_time(target, metadata.modified()?));
+	
+	if result.is_err() {
+		drop(target_file);
+		let _ = fs::remove_file(target);
+	}
+	
+	result
+}
+
+fn copy_contents(source: &mut File, target: &mut File, on_progress: &mut dyn FnMut(u64) -> bool) -> io::Result<()> {
+	let mut buffer = vec![0; COPY_BUFFER_SIZE];
+	let mut copied_bytes = 0_u64;
+	
+	loop {
+		let count = match source.read(&mut buffer) {
+			Ok(0) => return Ok(()),
+			Ok(count) => count,
+			Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+			Err(e) => return Err(e),
+		};
+		
+		target.write_all(buffer.get(..count).unwrap_or_default())?;
+		copied_bytes = copied_bytes.saturating_add(u64::try_from(count).unwrap_or(u64::MAX));
+		
+		if !on_progress(copied_bytes) {
+			return Err(cancelled_error());
+		}
+	}
+}
+
+/// Gives the target the permissions and modification time of the source. Directories are copied empty first and get their attributes after their contents, so that read-only directories can be filled, and filling them does not change the time.
+pub fn copy_attributes(source: &Path, target: &Path) -> io::Result<()> {
+	let metadata = fs::symlink_metadata(source)?;
+	
+	if !metadata.file_type().is_symlink() {
+		fs::set_permissions(target, metadata.permissions())?;
+		set_modified_time(target, metadata.modified()?)?;
+	}
+	
+	Ok(())
+}
+
+/// Deletes a file, a symbolic link, or a directory with all its contents. Symbolic links to directories are deleted without following them.
+pub fn remove_recursively(path: &Path) -> io::Result<()> {
+	if fs::symlink_metadata(path)?.is_dir() {
+		fs::remove_dir_all(path)
+	} else {
+		fs::remove_file(path)
+	}
+}
+
+#[cfg(unix)]
+fn copy_symlink(source: &Path, target: &Path) -> io::Result<()> {
+	std::os::unix::fs::symlink(fs::read_link(source)?, target)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(source: &Path, target: &Path) -> io::Result<()> {
+	let link_target = fs::read_link(source)?;
+	
+	if fs::metadata(source).is_ok_and(|metadata| metadata.is_dir()) {
+		std::os::windows::fs::symlink_dir(link_target, target)
+	} else {
+		std::os::windows::fs::symlink_file(link_target, target)
+	}
+}
+
+#[cfg(unix)]
+pub(super) fn set_modified_time(path: &Path, time: SystemTime) -> io::Result<()> {
+	use std::ffi::CString;
+	use std::os::unix::ffi::OsStrExt;
+	use std::time::UNIX_EPOCH;
+	
+	let path = CString::new(path.as_os_str().as_bytes())?;
+	let offset = time.duration_since(UNIX_EPOCH).map_err(|_| io::Error::new(ErrorKind::InvalidInput, "Modification time is before 1970"))?;
+	
+	let times = [
+		libc::timespec { tv_sec: 0, tv_nsec: libc::UTIME_OMIT },
+		libc::timespec {
+			tv_sec: libc::time_t::try_from(offset.as_secs()).map_err(|_| io::Error::new(ErrorKind::InvalidInput, "Modification time is too far in the future"))?,
+			tv_nsec: libc::c_long::from(offset.subsec_nanos()),
+		},
+	];
+	
+	// SAFETY: The path is a valid null-terminated string, and the array has the two elements utimensat reads. Both outlive the call.
+	if unsafe { libc::utimensat(libc::AT_FDCWD, path.as_ptr(), times.as_ptr(), 0) } == 0 {
+		Ok(())
+	} else {
+		Err(io::Error::last_os_error())
+	}
+}
+
+/// Other platforms keep the time when the copy was made.
+#[cfg(not(unix))]
+pub(super) fn set_modified_time(_path: &Path, _time: SystemTime) -> io::Result<()> {
+	Ok(())
+}
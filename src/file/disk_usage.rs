@@ -0,0 +1,169 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, Metadata};
+use std::path::{Path, PathBuf};
+
+use crate::file::{FileEntry, FileKind};
+
+/// How many entries are scanned between calls of the progress callback.
+const PROGRESS_INTERVAL: usize = 1000;
+
+/// Size of an entry, or the total size of a directory and all its contents.
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
+pub struct DiskUsageTotal {
+	/// Sum of the sizes of all files.
+	pub apparent: u64,
+	/// Sum of the space allocated on disk for all entries, including directories.
+	pub allocated: u64,
+}
+
+impl DiskUsageTotal {
+	/// Returns the size of a file entry, or zero for other kinds of entries.
+	pub fn of_entry(entry: &FileEntry) -> Self {
+		let apparent = if let FileKind::File { size } = entry.kind() { *size } else { 0 };
+		let allocated = entry.stats().map_or(apparent, |stats| stats.blocks().saturating_mul(512));
+		Self { apparent, allocated }
+	}
+	
+	fn add(&mut self, other: Self) {
+		self.apparent = self.apparent.saturating_add(other.apparent);
+		self.allocated = self.allocated.saturating_add(other.allocated);
+	}
+	
+	fn subtract(&mut self, other: Self) {
+		self.apparent = self.apparent.saturating_sub(other.apparent);
+		self.allocated = self.allocated.saturating_sub(other.allocated);
+	}
+}
+
+/// Total sizes of a directory and all directories inside it, as counted by a single scan.
+pub struct DiskUsage {
+	root: PathBuf,
+	directories: HashMap<PathBuf, DiskUsageTotal>,
+}
+
+impl DiskUsage {
+	/// Scans all entries inside the root directory without following symbolic links. Files with multiple hard links are only counted the first time they are found. Unless `cross_file_systems` is set, directories on other file systems than the root are skipped, and have no total.
+	///
+	/// The `progress` callback receives the number of scanned entries periodically. If it returns `false`, the scan stops and returns `None`.
+	pub fn scan<F>(root: &Path, cross_file_systems: bool, progress: F) -> Option<Self> where F: FnMut(usize) -> bool {
+		let metadata = fs::symlink_metadata(root).ok().filter(Metadata::is_dir)?;
+		
+		let mut scanner = Scanner {
+			root_device: get_device(&metadata),
+			cross_file_systems,
+			seen_hard_links: HashSet::new(),
+			directories: HashMap::new(),
+			scanned_entries: 0,
+			progress,
+		};
+		
+		scanner.scan_directory(root, &metadata)?;
+		Some(Self { root: root.to_path_buf(), directories: scanner.directories })
+	}
+	
+	/// Returns the total size of a scanned directory.
+	pub fn get(&self, path: &Path) -> Option<DiskUsageTotal> {
+		self.directories.get(path).copied()
+	}
+	
+	/// Forgets a deleted entry, and subtracts its size from all directories that contained it. Directories use their scanned total instead of the given size.
+	pub fn remove(&mut self, path: &Path, size: DiskUsageTotal) {
+		let size = if let Some(total) = self.directories.remove(path) {
+			self.directories.retain(|directory, _| !directory.starts_with(path));
+			total
+		} else {
+			size
+		};
+		
+		for ancestor in path.ancestors().skip(1).take_while(|ancestor| ancestor.starts_with(&self.root)) {
+			if let Some(total) = self.directories.get_mut(ancestor) {
+				total.subtract(size);
+			}
+		}
+	}
+}
+
+struct Scanner<F> where F: FnMut(usize) -> bool {
+	root_device: Option<u64>,
+	cross_file_systems: bool,
+	seen_hard_links: HashSet<(u64, u64)>,
+	directories: HashMap<PathBuf, DiskUsageTotal>,
+	scanned_entries: usize,
+	progress: F,
+}
+
+impl<F> Scanner<F> where F: FnMut(usize) -> bool {
+	/// Returns the total size of the directory and its contents, or `None` if the scan was stopped.
+	fn scan_directory(&mut self, path: &Path, metadata: &Metadata) -> Option<DiskUsageTotal> {
+		let mut total = self.count(metadata);
+		
+		// Unreadable directories and entries only count with the size that is already known.
+		for entry in fs::read_dir(path).into_iter().flatten().flatten() {
+			let Ok(metadata) = entry.metadata() else {
+				continue;
+			};
+			
+			self.scanned_entries = self.scanned_entries.saturating_add(1);
+			
+			if self.scanned_entries % PROGRESS_INTERVAL == 0 && !(self.progress)(self.scanned_entries) {
+				return None;
+			}
+			
+			if !metadata.is_dir() {
+				total.add(self.count(&metadata));
+			} else if self.cross_file_systems || get_device(&metadata) == self.root_device {
+				total.add(self.scan_directory(&entry.path(), &metadata)?);
+			}
+		}
+		
+		self.directories.insert(path.to_path_buf(), total);
+		Some(total)
+	}
+	
+	/// Returns the size of a single entry, or zero for files whose other hard link was already counted.
+	fn count(&mut self, metadata: &Metadata) -> DiskUsageTotal {
+		if let Some(id) = get_hard_link_id(metadata) {
+			if !self.seen_hard_links.insert(id) {
+				return DiskUsageTotal::default();
+			}
+		}
+		
+		let apparent = if metadata.is_file() { metadata.len() } else { 0 };
+		DiskUsageTotal { apparent, allocated: get_allocated_size(metadata) }
+	}
+}
+
+#[cfg(unix)]
+#[allow(clippy::unnecessary_wraps)] // Other platforms have no device numbers.
+fn get_device(metadata: &Metadata) -> Option<u64> {
+	use std::os::unix::fs::MetadataExt;
+	Some(metadata.dev())
+}
+
+#[cfg(not(unix))]
+fn get_device(_metadata: &Metadata) -> Option<u64> {
+	None
+}
+
+/// Returns the device and inode of files with more than one hard link, which identify the file regardless of which link it was found through.
+#[cfg(unix)]
+fn get_hard_link_id(metadata: &Metadata) -> Option<(u64, u64)> {
+	use std::os::unix::fs::MetadataExt;
+	(!metadata.is_dir() && metadata.nlink() > 1).then(|| (metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn get_hard_link_id(_metadata: &Metadata) -> Option<(u64, u64)> {
+	None
+}
+
+#[cfg(unix)]
+fn get_allocated_size(metadata: &Metadata) -> u64 {
+	use std::os::unix::fs::MetadataExt;
+	metadata.blocks().saturating_mul(512)
+}
+
+#[cfg(not(unix))]
+fn get_allocated_size(metadata: &Metadata) -> u64 {
+	metadata.len()
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Status of an entry in a git repository, ordered from least to most important when aggregating statuses of directory contents.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum GitStatus {
+	Ignored,
+	Untracked,
+	Staged,
+	Modified,
+	Conflicted,
+}
+
+pub struct GitRepositoryStatus {
+	root: PathBuf,
+	entries: HashMap<PathBuf, GitStatus>,
+	directories: HashMap<PathBuf, GitStatus>,
+}
+
+impl GitRepositoryStatus {
+	/// Reads status of the git repository that contains the path, or returns `None` if the path is not inside a git repository.
+	pub fn read(path: &Path) -> io::Result<Option<Self>> {
+		let Some(root) = find_repository_root(path)? else {
+			return Ok(None);
+		};
+		
+		let output = run_git(&root, &["status", "--porcelain=v2", "-z", "--untracked-files=normal", "--ignored=traditional"])?;
+		let Some(output) = output else {
+			return Ok(None);
+		};
+		
+		let mut status = Self { root, entries: HashMap::new(), directories: HashMap::new() };
+		status.parse_entries(&output);
+		Ok(Some(status))
+	}
+	
+	fn parse_entries(&mut self, output: &[u8]) {
+		let mut records = output.split(|b| *b == 0);
+		
+		while let Some(record) = records.next() {
+			let record = String::from_utf8_lossy(record);
+			let mut fields = record.splitn(2, ' ');
+			
+			let (status, path) = match (fields.next(), fields.next()) {
+				(Some("1"), Some(rest)) => (parse_xy(rest), rest.splitn(8, ' ').nth(7)),
+				(Some("2"), Some(rest)) => {
+					// Renamed entries are followed by a record with the original path.
+					records.next();
+					(parse_xy(rest), rest.splitn(9, ' ').nth(8))
+				}
+				(Some("u"), Some(rest)) => (GitStatus::Conflicted, rest.splitn(10, ' ').nth(9)),
+				(Some("?"), Some(rest)) => (GitStatus::Untracked, Some(rest)),
+				(Some("!"), Some(rest)) => (GitStatus::Ignored, Some(rest)),
+				_ => continue,
+			};
+			
+			if let Some(path) = path {
+				self.insert(self.root.join(path.trim_end_matches('/')), status);
+			}
+		}
+	}
+	
+	fn insert(&mut self, path: PathBuf, status: GitStatus) {
+		if status != GitStatus::Ignored {
+			for ancestor in path.ancestors().skip(1) {
+				let directory_status = self.directories.entry(ancestor.to_path_buf()).or_insert(status);
+				*directory_status = (*directory_status).max(status);
+				
+				if ancestor == self.root {
+					break;
+				}
+			}
+		}
+		
+		self.entries.insert(path, status);
+	}
+	
+	/// Returns the status of an entry. Directories get the most important status of their contents. Entries inside untracked or ignored directories inherit the status of the directory.
+	pub fn get(&self, path: &Path) -> Option<GitStatus> {
+		if let Some(status) = self.entries.get(path).or_else(|| self.directories.get(path)) {
+			return Some(*status);
+		}
+		
+		for ancestor in path.ancestors().skip(1) {
+			if !ancestor.starts_with(&self.root) || ancestor == self.root {
+				break;
+			}
+			
+			if let Some(status @ (GitStatus::Untracked | GitStatus::Ignored)) = self.entries.get(ancestor) {
+				return Some(*status);
+			}
+		}
+		
+		None
+	}
+}
+
+/// Parses the `XY` field, where `X` is the status in the index and `Y` is the status in the working tree. Entries with changes in the working tree are considered modified even if they also have staged changes.
+fn parse_xy(fields: &str) -> GitStatus {
+	if fields.chars().nth(1).is_some_and(|working_tree| working_tree != '.') {
+		GitStatus::Modified
+	} else {
+		GitStatus::Staged
+	}
+}
+
+fn find_repository_root(path: &Path) -> io::Result<Option<PathBuf>> {
+	let output = run_git(path, &["rev-parse", "--show-toplevel"])?;
+	
+	Ok(output.map(|output| {
+		let root = String::from_utf8_lossy(&output);
+		PathBuf::from(root.trim_end_matches('\n'))
+	}))
+}
+
+/// Runs a git command in the directory, and returns its standard output if it succeeded.
+fn run_git<S: AsRef<OsStr>>(directory: &Path, args: &[S]) -> io::Result<Option<Vec<u8>>> {
+	let output = Command::new("git")
+		.arg("-C")
+		.arg(directory)
+		.args(args)
+		.stdin(Stdio::null())
+		.stderr(Stdio::null())
+		.output()?;
+	
+	Ok(output.status.success().then_some(output.stdout))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use std::path::{Path, PathBuf};
+	
+	use crate::file::git::{GitRepositoryStatus, GitStatus};
+	
+	fn parse(output: &str) -> GitRepositoryStatus {
+		let mut status = GitRepositoryStatus { root: PathBuf::from("/repo"), entries: HashMap::new(), directories: HashMap::new() };
+		status.parse_entries(output.replace('\n', "\0").as_bytes());
+		status
+	}
+	
+	fn get(status: &GitRepositoryStatus, path: &str) -> Option<GitStatus> {
+		status.get(Path::new(path))
+	}
+	
+	#[test]
+	fn parses_ordinary_entries() {
+		let status = parse(concat!(
+			"1 .M N... 100644 100644 100644 0123 4567 src/modified.rs\n",
+			"1 M. N... 100644 100644 100644 0123 4567 src/staged.rs\n",
+			"1 MM N... 100644 100644 100644 0123 4567 src/both.rs\n",
+			"1 A. N... 000000 100644 100644 0000 4567 name with spaces.txt\n",
+		));
+		
+		assert_eq!(get(&status, "/repo/src/modified.rs"), Some(GitStatus::Modified));
+		assert_eq!(get(&status, "/repo/src/staged.rs"), Some(GitStatus::Staged));
+		assert_eq!(get(&status, "/repo/src/both.rs"), Some(GitStatus::Modified));
+		assert_eq!(get(&status, "/repo/name with spaces.txt"), Some(GitStatus::Staged));
+		assert_eq!(get(&status, "/repo/src/unchanged.rs"), None);
+	}
+	
+	#[test]
+	fn parses_renamed_entries_and_skips_original_path() {
+		let status = parse(concat!(
+			"2 R. N... 100644 100644 100644 0123 0123 R100 new name.rs\n",
+			"old name.rs\n",
+			"? untracked.txt\n",
+		));
+		
+		assert_eq!(get(&status, "/repo/new name.rs"), Some(GitStatus::Staged));
+		assert_eq!(get(&status, "/repo/old name.rs"), None);
+		assert_eq!(get(&status, "/repo/untracked.txt"), Some(GitStatus::Untracked));
+	}
+	
+	#[test]
+	fn parses_conflicted_untracked_and_ignored_entries() {
+		let status = parse(concat!(
+			"u UU N... 100644 100644 100644 100644 0123 4567 89ab conflict.rs\n",
+			"? new/\n",
+			"! target/\n",
+			"# branch.oid 0123\n",
+		));
+		
+		assert_eq!(get(&status, "/repo/conflict.rs"), Some(GitStatus::Conflicted));
+		assert_eq!(get(&status, "/repo/new"), Some(GitStatus::Untracked));
+		assert_eq!(get(&status, "/repo/new/nested/file.rs"), Some(GitStatus::Untracked));
+		assert_eq!(get(&status, "/repo/target"), Some(GitStatus::Ignored));
+		assert_eq!(get(&status, "/repo/target/debug"), Some(GitStatus::Ignored));
+	}
+	
+	#[test]
+	fn directories_get_most_important_status_of_contents() {
+		let status = parse(concat!(
+			"? src/a/untracked.rs\n",
+			"1 M. N... 100644 100644 100644 0123 4567 src/a/staged.rs\n",
+			"1 .M N... 100644 100644 100644 0123 4567 src/b/modified.rs\n",
+			"u UU N... 100644 100644 100644 100644 0123 4567 89ab docs/conflict.md\n",
+			"! src/ignored.log\n",
+		));
+		
+		assert_eq!(get(&status, "/repo/src/a"), Some(GitStatus::Staged));
+		assert_eq!(get(&status, "/repo/src/b"), Some(GitStatus::Modified));
+		assert_eq!(get(&status, "/repo/src"), Some(GitStatus::Modified));
+		assert_eq!(get(&status, "/repo/docs"), Some(GitStatus::Conflicted));
+		assert_eq!(get(&status, "/repo"), Some(GitStatus::Conflicted));
+		assert_eq!(get(&status, "/"), None);
+	}
+}
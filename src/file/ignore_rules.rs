@@ -0,0 +1,127 @@
+use std::io::Read;
+use std::path::Path;
+use std::rc::Rc;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+
+use crate::file::{FileKind, FileSystem};
+
+/// Rules from `.gitignore` and `.ignore` files of a directory, and of all its parent directories. The root directory of a git repository also includes rules from `.git/info/exclude`.
+pub struct IgnoreRules {
+	parent: Option<Rc<Self>>,
+	/// Matchers ordered from highest to lowest precedence.
+	matchers: Vec<Gitignore>,
+}
+
+impl IgnoreRules {
+	/// Creates rules for a directory whose parent directories are not known yet. Rules of parent directories are collected up to the root of the git repository that contains the directory.
+	pub fn for_root(file_system: &dyn FileSystem, directory: &Path) -> Rc<Self> {
+		let parent_rules = directory.ancestors()
+		                            .skip(1)
+		                            .find(|ancestor| file_system.exists(&ancestor.join(".git")))
+		                            .and_then(|repository_root| Self::for_parents(file_system, directory, repository_root));
+		
+		Self::for_directory(file_system, parent_rules, directory)
+	}
+	
+	fn for_parents(file_system: &dyn FileSystem, directory: &Path, repository_root: &Path) -> Option<Rc<Self>> {
+		let parent = directory.parent().filter(|parent| parent.starts_with(repository_root))?;
+		Some(Self::for_directory(file_system, Self::for_parents(file_system, parent, repository_root), parent))
+	}
+	
+	/// Creates rules for a directory, which inherit rules of its parent directory.
+	pub fn for_directory(file_system: &dyn FileSystem, parent: Option<Rc<Self>>, directory: &Path) -> Rc<Self> {
+		let mut matchers = Vec::new();
+		
+		add_matcher(file_system, &mut matchers, directory, ".ignore");
+		add_matcher(file_system, &mut matchers, directory, ".gitignore");
+		
+		if matches!(file_system.stat(&directory.join(".git")).kind(), FileKind::Directory) {
+			add_matcher(file_system, &mut matchers, directory, ".git/info/exclude");
+		}
+		
+		Rc::new(Self { parent, matchers })
+	}
+	
+	/// Checks whether the entry is ignored. The path must be inside the directory these rules were created for.
+	pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+		let mut rules = Some(self);
+		
+		while let Some(current) = rules {
+			for matcher in &current.matchers {
+				match matcher.matched(path, is_dir) {
+					Match::None => {}
+					Match::Ignore(_) => return true,
+					Match::Whitelist(_) => return false,
+				}
+			}
+			
+			rules = current.parent.as_deref();
+		}
+		
+		false
+	}
+}
+
+fn add_matcher(file_system: &dyn FileSystem, matchers: &mut Vec<Gitignore>, directory: &Path, file_name: &str) {
+	let file_path = directory.join(file_name);
+	let mut contents = String::new();
+	if file_system.open_file(&file_path).and_then(|mut file| file.read_to_string(&mut contents)).is_err() {
+		return;
+	}
+	
+	let mut builder = GitignoreBuilder::new(directory);
+	
+	for line in contents.lines() {
+		// Invalid patterns are skipped, so that the other patterns in the file still apply.
+		let _ = builder.add_line(Some(file_path.clone()), line);
+	}
+	
+	if let Ok(matcher) = builder.build() {
+		matchers.push(matcher);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::Path;
+	
+	use crate::file::{IgnoreRules, MemoryFileSystem};
+	
+	#[test]
+	fn applies_rules_of_parent_directories_inside_repository() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/outside/.gitignore", "*.txt\n")
+			.with_directory("/outside/repo/.git")
+			.with_file("/outside/repo/.gitignore", "*.log\n")
+			.with_file("/outside/repo/src/.ignore", "!keep.log\n");
+		
+		let rules = IgnoreRules::for_root(&file_system, Path::new("/outside/repo/src"));
+		
+		assert!(rules.is_ignored(Path::new("/outside/repo/src/debug.log"), false), "rule of parent directory was not applied");
+		assert!(!rules.is_ignored(Path::new("/outside/repo/src/keep.log"), false), "rule of the directory itself does not take precedence");
+		assert!(!rules.is_ignored(Path::new("/outside/repo/src/notes.txt"), false), "rule from outside the repository was applied");
+	}
+	
+	#[test]
+	fn applies_excluded_files_of_repository_root() {
+		let file_system = MemoryFileSystem::new()
+			.with_file("/repo/.git/info/exclude", "secret\n");
+		
+		let rules = IgnoreRules::for_root(&file_system, Path::new("/repo"));
+		
+		assert!(rules.is_ignored(Path::new("/repo/secret"), false), "rule from .git/info/exclude was not applied");
+		assert!(!rules.is_ignored(Path::new("/repo/public"), false), "entry without a matching rule was ignored");
+	}
+	
+	#[test]
+	fn ignores_nothing_without_ignore_files() {
+		let file_system = MemoryFileSystem::new()
+			.with_directory("/dir");
+		
+		let rules = IgnoreRules::for_root(&file_system, Path::new("/dir"));
+		
+		assert!(!rules.is_ignored(Path::new("/dir/file"), false), "entry was ignored without any rules");
+	}
+}
@@ -6,23 +6,45 @@ use std::time::SystemTime;
 use lazy_static::lazy_static;
 use normalize_path::NormalizePath;
 
+pub use crate::file::archive::{ArchiveKind, ArchiveLocation, ArchiveProgress, create_archive, extract_archive_entry, read_archive_children, strip_archive_extension};
+pub use crate::file::backend::{FileSystem, LocalFileSystem};
+#[cfg(test)]
+pub use crate::file::backend::MemoryFileSystem;
+pub use crate::file::copy::{copy_attributes, copy_file_with_progress, copy_recursively, remove_recursively};
+pub use crate::file::disk_usage::{DiskUsage, DiskUsageTotal};
+pub use crate::file::git::{GitRepositoryStatus, GitStatus};
+pub use crate::file::ignore_rules::IgnoreRules;
 pub use crate::file::kind::FileKind;
 pub use crate::file::mode::{FileMode, Permission};
 pub use crate::file::name::FileName;
-pub use crate::file::owner::{FileOwner, FileOwnerName, FileOwnerNameCache};
+pub use crate::file::owner::{change_owner, FileOwner, FileOwnerName, FileOwnerNameCache, find_group_id_by_name, find_user_id_by_name, get_all_group_names, get_all_user_names};
+pub use crate::file::stats::FileStats;
+pub use crate::file::temporary::create_temporary_file;
 
+mod archive;
+mod backend;
+mod copy;
+mod disk_usage;
+mod git;
+mod ignore_rules;
 mod kind;
 mod mode;
 mod name;
 mod owner;
+mod stats;
+mod temporary;
 
 pub struct FileEntry {
 	path: Option<PathBuf>,
+	archive_location: Option<ArchiveLocation>,
 	name: FileName,
 	kind: FileKind,
 	mode: FileMode,
 	owner: Option<FileOwner>,
+	stats: Option<FileStats>,
 	mtime: Option<SystemTime>,
+	atime: Option<SystemTime>,
+	btime: Option<SystemTime>,
 }
 
 lazy_static! {
@@ -32,26 +54,34 @@ lazy_static! {
 impl FileEntry {
 	fn new(path: impl AsRef<Path>, name: FileName, metadata: Result<&Metadata, &std::io::Error>) -> Self {
 		let path = path.as_ref();
-		assert!(path.is_absolute(), "Path is not absolute: {path:?}");
+		assert!(path.is_absolute(), "Path is not absolute: {}", path.display());
 		
 		Self {
 			path: Some(path.normalize()),
+			archive_location: None,
 			name,
 			kind: metadata.map(FileKind::from).unwrap_or(FileKind::Unknown),
 			mode: metadata.map(FileMode::from).unwrap_or(FileMode::Unknown),
 			owner: metadata.ok().and_then(|m| FileOwner::try_from(m).ok()),
+			stats: metadata.ok().and_then(|m| FileStats::try_from(m).ok()),
 			mtime: metadata.ok().and_then(|m| m.modified().ok()),
+			atime: metadata.ok().and_then(|m| m.accessed().ok()),
+			btime: metadata.ok().and_then(|m| m.created().ok()),
 		}
 	}
 	
 	pub fn dummy() -> Self {
 		Self {
 			path: None,
+			archive_location: None,
 			name: FileName::dummy(),
 			kind: FileKind::Unknown,
 			mode: FileMode::Unknown,
 			owner: None,
+			stats: None,
 			mtime: None,
+			atime: None,
+			btime: None,
 		}
 	}
 	
@@ -63,6 +93,25 @@ impl FileEntry {
 		self.path.as_deref()
 	}
 	
+	/// Returns the location of entries inside archives, which have no path of their own.
+	pub const fn archive_location(&self) -> Option<&ArchiveLocation> {
+		self.archive_location.as_ref()
+	}
+	
+	/// Returns the kind of archive if this is a file with an archive extension, which can be browsed like a directory.
+	pub fn archive_kind(&self) -> Option<ArchiveKind> {
+		if matches!(self.kind, FileKind::File { .. }) && self.path.is_some() {
+			ArchiveKind::from_name(self.name.str())
+		} else {
+			None
+		}
+	}
+	
+	/// Returns the path of the entry, or the path of the archive joined with the path inside it for entries inside archives.
+	pub fn display_path(&self) -> Option<PathBuf> {
+		self.path.clone().or_else(|| self.archive_location.as_ref().map(ArchiveLocation::display_path))
+	}
+	
 	pub const fn name(&self) -> &FileName {
 		&self.name
 	}
@@ -83,9 +132,47 @@ impl FileEntry {
 		self.owner.map(FileOwner::gid)
 	}
 	
+	/// Returns whether the name starts with a dot, which hides the entry by convention.
+	pub fn is_hidden(&self) -> bool {
+		self.name.str().starts_with('.')
+	}
+	
+	/// Returns the extension of files, or `None` for other kinds of entries and files without an extension. Leading dots of hidden files do not start an extension.
+	pub fn extension(&self) -> Option<&str> {
+		if !matches!(self.kind, FileKind::File { .. }) {
+			return None;
+		}
+		
+		let name = self.name.str();
+		let (stem, extension) = name.trim_start_matches('.').rsplit_once('.')?;
+		(!stem.is_empty() && !extension.is_empty()).then_some(extension)
+	}
+	
+	pub const fn stats(&self) -> Option<FileStats> {
+		self.stats
+	}
+	
+	/// Returns an id that is shared by all hard links to the same file, or `None` for directories and for files with only one link.
+	pub fn hard_link_id(&self) -> Option<(u64, u64)> {
+		let stats = self.stats.filter(|stats| stats.link_count() > 1 && !matches!(self.kind, FileKind::Directory))?;
+		Some((stats.device(), stats.inode()))
+	}
+	
 	pub const fn modified_time(&self) -> Option<&SystemTime> {
 		self.mtime.as_ref()
 	}
+	
+	pub const fn accessed_time(&self) -> Option<&SystemTime> {
+		self.atime.as_ref()
+	}
+	
+	pub fn changed_time(&self) -> Option<SystemTime> {
+		self.stats.and_then(FileStats::changed_time)
+	}
+	
+	pub const fn birth_time(&self) -> Option<&SystemTime> {
+		self.btime.as_ref()
+	}
 }
 
 impl From<&DirEntry> for FileEntry {
@@ -93,7 +180,7 @@ impl From<&DirEntry> for FileEntry {
 		let path = entry.path();
 		let name = FileName::from(entry.file_name());
 		let metadata = entry.metadata();
-		return Self::new(path, name, metadata.as_ref());
+		Self::new(path, name, metadata.as_ref())
 	}
 }
 
@@ -106,6 +193,6 @@ impl From<&Path> for FileEntry {
 		};
 		
 		let metadata = path.symlink_metadata();
-		return Self::new(path, name, metadata.as_ref());
+		Self::new(path, name, metadata.as_ref())
 	}
 }
@@ -2,6 +2,7 @@ use std::ffi::OsString;
 use std::fmt::{Display, Formatter};
 
 pub struct FileName {
+	#[allow(dead_code)]
 	os: Option<OsString>,
 	str: String,
 }
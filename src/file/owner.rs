@@ -1,6 +1,8 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fs::Metadata;
+use std::io;
+use std::path::Path;
 
 use crate::util;
 
@@ -11,6 +13,10 @@ pub struct FileOwner {
 }
 
 impl FileOwner {
+	pub const fn new(uid: u32, gid: u32) -> Self {
+		Self { uid, gid }
+	}
+	
 	pub const fn uid(self) -> u32 {
 		self.uid
 	}
@@ -100,19 +106,102 @@ impl FileOwnerNameCache {
 	}
 }
 
+pub fn find_user_id_by_name(name: &str) -> Option<u32> {
+	system::get_uid_by_user_name(name)
+}
+
+pub fn find_group_id_by_name(name: &str) -> Option<u32> {
+	system::get_gid_by_group_name(name)
+}
+
+pub fn get_all_user_names() -> Vec<String> {
+	system::get_all_user_names()
+}
+
+pub fn get_all_group_names() -> Vec<String> {
+	system::get_all_group_names()
+}
+
+/// Changes the owner and/or group of a file without following symbolic links. Leaves the owner or group unchanged if its id is `None`.
+pub fn change_owner(path: &Path, uid: Option<u32>, gid: Option<u32>) -> io::Result<()> {
+	system::change_owner(path, uid, gid)
+}
+
 #[cfg(unix)]
 mod system {
+	use std::ffi::{CStr, CString};
+	use std::io;
+	use std::os::unix::ffi::OsStrExt;
+	use std::path::Path;
+	
 	pub fn get_user_name_by_uid(uid: u32) -> Option<String> {
-		users::get_user_by_uid(uid).and_then(|user| user.name().to_str().map(str::to_string))
+		users::get_user_by_uid(uid).and_then(|user| user.name().to_str().map(str::to_owned))
 	}
 	
 	pub fn get_group_name_by_gid(gid: u32) -> Option<String> {
-		users::get_group_by_gid(gid).and_then(|group| group.name().to_str().map(str::to_string))
+		users::get_group_by_gid(gid).and_then(|group| group.name().to_str().map(str::to_owned))
+	}
+	
+	pub fn get_uid_by_user_name(name: &str) -> Option<u32> {
+		users::get_user_by_name(name).map(|user| user.uid())
+	}
+	
+	pub fn get_gid_by_group_name(name: &str) -> Option<u32> {
+		users::get_group_by_name(name).map(|group| group.gid())
+	}
+	
+	pub fn get_all_user_names() -> Vec<String> {
+		// SAFETY: The passwd database is only enumerated from the UI thread.
+		let users = unsafe { users::all_users() };
+		users.filter_map(|user| user.name().to_str().map(str::to_owned)).collect()
+	}
+	
+	pub fn get_all_group_names() -> Vec<String> {
+		let mut names = Vec::new();
+		
+		// SAFETY: The group database is only enumerated from the UI thread, and every returned entry is copied before the next call to `getgrent`.
+		unsafe {
+			libc::setgrent();
+			
+			loop {
+				let group = libc::getgrent();
+				if group.is_null() {
+					break;
+				}
+				
+				if let Ok(name) = CStr::from_ptr((*group).gr_name).to_str() {
+					names.push(name.to_owned());
+				}
+			}
+			
+			libc::endgrent();
+		}
+		
+		names
+	}
+	
+	pub fn change_owner(path: &Path, uid: Option<u32>, gid: Option<u32>) -> io::Result<()> {
+		let path = CString::new(path.as_os_str().as_bytes())?;
+		
+		// Passing -1 tells lchown to leave the id unchanged.
+		let uid = uid.unwrap_or(libc::uid_t::MAX);
+		let gid = gid.unwrap_or(libc::gid_t::MAX);
+		
+		// SAFETY: The path is a valid null-terminated string that outlives the call.
+		if unsafe { libc::lchown(path.as_ptr(), uid, gid) } == 0 {
+			Ok(())
+		} else {
+			Err(io::Error::last_os_error())
+		}
 	}
 }
 
 #[cfg(not(unix))]
 mod system {
+	use std::io;
+	use std::io::ErrorKind;
+	use std::path::Path;
+	
 	pub fn get_user_name_by_uid(_uid: u32) -> Option<String> {
 		None
 	}
@@ -120,4 +209,24 @@ mod system {
 	pub fn get_group_name_by_gid(_gid: u32) -> Option<String> {
 		None
 	}
+	
+	pub fn get_uid_by_user_name(_name: &str) -> Option<u32> {
+		None
+	}
+	
+	pub fn get_gid_by_group_name(_name: &str) -> Option<u32> {
+		None
+	}
+	
+	pub fn get_all_user_names() -> Vec<String> {
+		Vec::new()
+	}
+	
+	pub fn get_all_group_names() -> Vec<String> {
+		Vec::new()
+	}
+	
+	pub fn change_owner(_path: &Path, _uid: Option<u32>, _gid: Option<u32>) -> io::Result<()> {
+		Err(io::Error::new(ErrorKind::Unsupported, "Changing file ownership is not supported on this platform"))
+	}
 }
@@ -0,0 +1,63 @@
+use std::fs::Metadata;
+use std::time::SystemTime;
+
+/// Metadata that is only available on Unix systems.
+#[derive(Copy, Clone)]
+pub struct FileStats {
+	device: u64,
+	inode: u64,
+	link_count: u64,
+	blocks: u64,
+	ctime: Option<SystemTime>,
+}
+
+impl FileStats {
+	/// Returns the id of the device that contains the entry.
+	pub const fn device(self) -> u64 {
+		self.device
+	}
+	
+	pub const fn inode(self) -> u64 {
+		self.inode
+	}
+	
+	pub const fn link_count(self) -> u64 {
+		self.link_count
+	}
+	
+	/// Returns the number of allocated 512-byte blocks.
+	pub const fn blocks(self) -> u64 {
+		self.blocks
+	}
+	
+	/// Returns the time of the last status change.
+	pub const fn changed_time(self) -> Option<SystemTime> {
+		self.ctime
+	}
+}
+
+impl TryFrom<&Metadata> for FileStats {
+	type Error = ();
+	
+	#[cfg(unix)]
+	fn try_from(metadata: &Metadata) -> Result<Self, Self::Error> {
+		use std::os::unix::fs::MetadataExt;
+		use std::time::{Duration, UNIX_EPOCH};
+		
+		let ctime_offset = u32::try_from(metadata.ctime_nsec()).ok().map(|nanos| Duration::new(metadata.ctime().unsigned_abs(), nanos));
+		let ctime = ctime_offset.and_then(|offset| if metadata.ctime() < 0 { UNIX_EPOCH.checked_sub(offset) } else { UNIX_EPOCH.checked_add(offset) });
+		
+		Ok(Self {
+			device: metadata.dev(),
+			inode: metadata.ino(),
+			link_count: metadata.nlink(),
+			blocks: metadata.blocks(),
+			ctime,
+		})
+	}
+	
+	#[cfg(not(unix))]
+	fn try_from(_metadata: &Metadata) -> Result<Self, Self::Error> {
+		Err(())
+	}
+}
@@ -0,0 +1,49 @@
+use std::collections::hash_map::RandomState;
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::SystemTime;
+
+const MAX_ATTEMPTS: u32 = 100;
+
+/// Creates a new file with a random name in the temporary directory, which only the current user can read and write. The file is never opened if something already exists at the path, so it cannot be redirected by a symbolic link planted by another user.
+pub fn create_temporary_file(prefix: &str, extension: &str) -> io::Result<(PathBuf, File)> {
+	let directory = env::temp_dir();
+	
+	for attempt in 0..MAX_ATTEMPTS {
+		let path = directory.join(format!("{prefix}-{:016x}.{extension}", random_u64(attempt)));
+		
+		match open_new_private_file(&path) {
+			Ok(file) => return Ok((path, file)),
+			Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+			Err(e) => return Err(e),
+		}
+	}
+	
+	Err(io::Error::new(ErrorKind::AlreadyExists, "Could not find a free name for a temporary file"))
+}
+
+fn open_new_private_file(path: &Path) -> io::Result<File> {
+	let mut options = OpenOptions::new();
+	options.read(true).write(true).create_new(true);
+	
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::OpenOptionsExt;
+		options.mode(0o600);
+	}
+	
+	options.open(path)
+}
+
+/// Hashes the process id, time, and attempt number with a randomly seeded hasher, so that names cannot be guessed in advance.
+fn random_u64(attempt: u32) -> u64 {
+	let mut hasher = RandomState::new().build_hasher();
+	hasher.write_u32(process::id());
+	hasher.write_u32(attempt);
+	hasher.write_u128(SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map(|duration| duration.as_nanos()).unwrap_or(0));
+	hasher.finish()
+}
@@ -1,3 +1,5 @@
+use std::fmt::{Display, Formatter};
+
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash)]
@@ -37,3 +39,49 @@ impl From<KeyEvent> for KeyBinding {
 		Self { code, modifiers }
 	}
 }
+
+/// Formats the key in the same notation that is used to define key sequences, such as `g`, `<lt>`, or `<Ctrl-Space>`.
+impl Display for KeyBinding {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let name = get_key_name(self.code);
+		
+		if self.modifiers.is_empty() && name.chars().count() == 1 {
+			return write!(f, "{name}");
+		}
+		
+		write!(f, "<")?;
+		
+		for (modifier, modifier_name) in [(KeyModifiers::CONTROL, "Ctrl"), (KeyModifiers::ALT, "Alt"), (KeyModifiers::SHIFT, "Shift"), (KeyModifiers::SUPER, "Super")] {
+			if self.modifiers.contains(modifier) {
+				write!(f, "{modifier_name}-")?;
+			}
+		}
+		
+		write!(f, "{name}>")
+	}
+}
+
+#[allow(clippy::wildcard_enum_match_arm)]
+fn get_key_name(code: KeyCode) -> String {
+	match code {
+		KeyCode::Backspace => String::from("BS"),
+		KeyCode::Char(' ') => String::from("Space"),
+		KeyCode::Char('<') => String::from("lt"),
+		KeyCode::Char(char) => char.to_string(),
+		KeyCode::Delete => String::from("Del"),
+		KeyCode::Down => String::from("Down"),
+		KeyCode::End => String::from("End"),
+		KeyCode::Enter => String::from("Enter"),
+		KeyCode::Esc => String::from("Esc"),
+		KeyCode::F(number) => format!("F{number}"),
+		KeyCode::Home => String::from("Home"),
+		KeyCode::Insert => String::from("Insert"),
+		KeyCode::Left => String::from("Left"),
+		KeyCode::PageDown => String::from("PageDown"),
+		KeyCode::PageUp => String::from("PageUp"),
+		KeyCode::Right => String::from("Right"),
+		KeyCode::Tab => String::from("Tab"),
+		KeyCode::Up => String::from("Up"),
+		_ => String::from("?"),
+	}
+}
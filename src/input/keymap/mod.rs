@@ -1,11 +1,8 @@
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
-use parser::KeySequenceParser;
-
-use crate::input::keymap::parser::ParseError;
-
 pub use self::binding::KeyBinding;
+pub use self::parser::{KeySequenceParser, ParseError};
 
 mod binding;
 mod parser;
@@ -25,6 +22,10 @@ impl<V> KeyMap<V> {
 		
 		while let Some(key) = iter.next() {
 			if iter.peek().is_none() {
+				if let Some(KeyMapTrieNode::SubTree(_)) = map.keybinds.get(key) {
+					return Err(KeyMapInsertErrorType::ConflictingKeySequence);
+				}
+				
 				map.keybinds.insert(*key, KeyMapTrieNode::Leaf(value));
 				return Ok(());
 			} else if let KeyMapTrieNode::SubTree(ref mut nested) = map.keybinds.entry(*key).or_insert_with(|| KeyMapTrieNode::SubTree(Self::new())) {
@@ -66,6 +67,41 @@ impl<V> KeyMap<V> {
 		
 		KeyMapLookupResult::None
 	}
+	
+	/// Returns all key sequences that start with the prefix, without the prefix, and their values, in no particular order.
+	pub fn continuations(&self, prefix: &[KeyBinding]) -> Vec<(Vec<KeyBinding>, &V)> {
+		let mut map = self;
+		
+		for key in prefix {
+			if let Some(KeyMapTrieNode::SubTree(nested)) = map.keybinds.get(key) {
+				map = nested;
+			} else {
+				return Vec::new();
+			}
+		}
+		
+		map.entries()
+	}
+	
+	/// Returns all key sequences and their values, in no particular order.
+	pub fn entries(&self) -> Vec<(Vec<KeyBinding>, &V)> {
+		let mut entries = Vec::new();
+		self.collect_entries(&mut Vec::new(), &mut entries);
+		entries
+	}
+	
+	fn collect_entries<'a>(&'a self, prefix: &mut Vec<KeyBinding>, entries: &mut Vec<(Vec<KeyBinding>, &'a V)>) {
+		for (key, node) in &self.keybinds {
+			prefix.push(*key);
+			
+			match node {
+				KeyMapTrieNode::Leaf(value) => entries.push((prefix.clone(), value)),
+				KeyMapTrieNode::SubTree(nested) => nested.collect_entries(prefix, entries),
+			}
+			
+			prefix.pop();
+		}
+	}
 }
 
 enum KeyMapTrieNode<V> {
@@ -86,7 +122,7 @@ pub struct KeyMapInsertError {
 }
 
 impl KeyMapInsertError {
-	const fn new(sequence: String, error: KeyMapInsertErrorType) -> Self {
+	pub const fn new(sequence: String, error: KeyMapInsertErrorType) -> Self {
 		Self { sequence, error }
 	}
 	
@@ -104,14 +140,105 @@ pub enum KeyMapInsertErrorType {
 	EmptyKeySequence,
 	ConflictingKeySequence,
 	ParseError(ParseError),
+	UnknownAction(String),
 }
 
 impl Display for KeyMapInsertErrorType {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
 		match self {
 			Self::EmptyKeySequence => write!(f, "Empty key sequence."),
-			Self::ConflictingKeySequence => write!(f, "Conflicting key sequence."),
+			Self::ConflictingKeySequence => write!(f, "Conflicts with a key sequence that starts with it, or that it starts with."),
 			Self::ParseError(err) => write!(f, "Parse error: {err}"),
+			Self::UnknownAction(name) => write!(f, "Unknown action: {name}"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::input::keymap::{KeyBinding, KeyMap, KeyMapInsertErrorType, KeyMapLookupResult, KeySequenceParser};
+	
+	fn parse(key_sequence: &str) -> Vec<KeyBinding> {
+		let mut parser = KeySequenceParser::new(key_sequence);
+		let mut sequence = Vec::new();
+		
+		while let Ok(Some(key)) = parser.next() {
+			sequence.push(key);
 		}
+		
+		sequence
+	}
+	
+	fn format(entries: Vec<(Vec<KeyBinding>, &u32)>) -> Vec<String> {
+		let mut entries = entries.into_iter()
+			.map(|(key_sequence, value)| format!("{} {value}", key_sequence.iter().map(ToString::to_string).collect::<String>()))
+			.collect::<Vec<_>>();
+		
+		entries.sort();
+		entries
+	}
+	
+	fn create_map() -> KeyMap<u32> {
+		let mut map = KeyMap::new();
+		
+		for (index, key_sequence) in ["j", "gg", "gd", "zf", "<Ctrl-R>", "<lt>"].into_iter().enumerate() {
+			assert!(map.insert(key_sequence, u32::try_from(index).unwrap_or_default()).is_ok(), "{key_sequence} could not be inserted");
+		}
+		
+		map
+	}
+	
+	#[test]
+	fn lists_all_entries() {
+		assert_eq!(format(create_map().entries()), vec!["<Ctrl-r> 4", "<lt> 5", "gd 2", "gg 1", "j 0", "zf 3"]);
+		assert!(KeyMap::<u32>::new().entries().is_empty());
+	}
+	
+	#[test]
+	fn lists_continuations_of_prefixes() {
+		let map = create_map();
+		
+		assert_eq!(format(map.continuations(&parse("g"))), vec!["d 2", "g 1"]);
+		assert_eq!(format(map.continuations(&parse("z"))), vec!["f 3"]);
+		assert_eq!(format(map.continuations(&[])), format(map.entries()));
+	}
+	
+	#[test]
+	fn lists_no_continuations_of_complete_or_unknown_sequences() {
+		let map = create_map();
+		
+		assert!(map.continuations(&parse("j")).is_empty());
+		assert!(map.continuations(&parse("gg")).is_empty());
+		assert!(map.continuations(&parse("x")).is_empty());
+		assert!(map.continuations(&parse("gx")).is_empty());
+	}
+	
+	#[test]
+	fn replaces_values_of_existing_key_sequences() {
+		let mut map = create_map();
+		assert!(map.insert("gd", 10).is_ok());
+		assert_eq!(format(map.entries()), vec!["<Ctrl-r> 4", "<lt> 5", "gd 10", "gg 1", "j 0", "zf 3"]);
+	}
+	
+	#[test]
+	fn looks_up_key_sequences() {
+		let map = create_map();
+		
+		assert!(matches!(map.lookup(&parse("j")), KeyMapLookupResult::Found(0)));
+		assert!(matches!(map.lookup(&parse("gd")), KeyMapLookupResult::Found(2)));
+		assert!(matches!(map.lookup(&parse("g")), KeyMapLookupResult::Prefix));
+		assert!(matches!(map.lookup(&parse("gx")), KeyMapLookupResult::None));
+		assert!(matches!(map.lookup(&parse("x")), KeyMapLookupResult::None));
+	}
+	
+	#[test]
+	fn rejects_conflicting_key_sequences() {
+		let mut map = create_map();
+		
+		assert!(matches!(map.insert("jx", 10).map_err(|e| e.error().clone()), Err(KeyMapInsertErrorType::ConflictingKeySequence)));
+		assert!(matches!(map.insert("g", 10).map_err(|e| e.error().clone()), Err(KeyMapInsertErrorType::ConflictingKeySequence)));
+		assert!(matches!(map.insert("", 10).map_err(|e| e.error().clone()), Err(KeyMapInsertErrorType::EmptyKeySequence)));
+		assert!(matches!(map.insert("<Foo>", 10).map_err(|e| e.error().clone()), Err(KeyMapInsertErrorType::ParseError(_))));
+		assert_eq!(format(map.entries()), vec!["<Ctrl-r> 4", "<lt> 5", "gd 2", "gg 1", "j 0", "zf 3"]);
 	}
 }
@@ -1 +1,2 @@
 pub mod keymap;
+pub mod source;
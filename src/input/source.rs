@@ -0,0 +1,88 @@
+#[cfg(test)]
+use std::collections::VecDeque;
+use std::io;
+use std::time::Duration;
+
+use crossterm::event::Event;
+#[cfg(test)]
+use crossterm::event::KeyEvent;
+
+#[cfg(test)]
+use crate::input::keymap::{KeySequenceParser, ParseError};
+
+/// Source of terminal events that drive the application.
+pub trait EventSource {
+	/// Waits up to the timeout for the next event, and returns `None` if no event arrived in time.
+	fn next_event(&mut self, timeout: Duration) -> io::Result<Option<Event>>;
+	
+	/// Returns whether the source will never produce another event, which stops the application.
+	fn is_exhausted(&self) -> bool;
+	
+	/// Returns whether the application should finish all background work before taking the next event from the source, and before stopping once the source is exhausted. This makes prepared events see the results of jobs that earlier events started.
+	fn waits_for_background_work(&self) -> bool;
+}
+
+/// Reads events from the terminal.
+pub struct TerminalEventSource;
+
+impl EventSource for TerminalEventSource {
+	fn next_event(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+		if crossterm::event::poll(timeout)? {
+			crossterm::event::read().map(Some)
+		} else {
+			Ok(None)
+		}
+	}
+	
+	fn is_exhausted(&self) -> bool {
+		false
+	}
+	
+	fn waits_for_background_work(&self) -> bool {
+		false
+	}
+}
+
+/// Produces a prepared list of events without waiting, and is exhausted once all of them were produced.
+#[cfg(test)]
+pub struct ScriptedEventSource {
+	events: VecDeque<Event>,
+}
+
+#[cfg(test)]
+impl ScriptedEventSource {
+	pub const fn new() -> Self {
+		Self { events: VecDeque::new() }
+	}
+	
+	/// Creates a source that presses the keys in a key sequence, written in the same notation as key bindings, such as `jjl<F2>foo<Enter>`.
+	pub fn from_keys(key_sequence: &str) -> Result<Self, ParseError> {
+		let mut source = Self::new();
+		let mut parser = KeySequenceParser::new(key_sequence);
+		
+		while let Some(key) = parser.next()? {
+			source.push(Event::Key(KeyEvent::new(key.code(), key.modifiers())));
+		}
+		
+		Ok(source)
+	}
+	
+	pub fn push(&mut self, event: Event) {
+		self.events.push_back(event);
+	}
+}
+
+#[cfg(test)]
+impl EventSource for ScriptedEventSource {
+	fn next_event(&mut self, _timeout: Duration) -> io::Result<Option<Event>> {
+		Ok(self.events.pop_front())
+	}
+	
+	fn is_exhausted(&self) -> bool {
+		self.events.is_empty()
+	}
+	
+	fn waits_for_background_work(&self) -> bool {
+		true
+	}
+}
@@ -29,7 +29,6 @@
 #![warn(clippy::str_to_string)]
 #![warn(clippy::string_add)]
 #![warn(clippy::string_slice)]
-#![warn(clippy::string_to_string)]
 #![warn(clippy::todo)]
 #![warn(clippy::try_err)]
 #![warn(clippy::undocumented_unsafe_blocks)]
@@ -38,7 +37,6 @@
 #![warn(clippy::unseparated_literal_suffix)]
 #![warn(clippy::unwrap_in_result)]
 #![warn(clippy::unwrap_used)]
-#![warn(clippy::unwrap_used)]
 #![warn(clippy::use_debug)]
 #![warn(clippy::verbose_file_reads)]
 #![warn(clippy::wildcard_enum_match_arm)]
@@ -49,19 +47,28 @@
 #![allow(clippy::option_if_let_else)]
 #![allow(clippy::redundant_else)]
 
+// Lints about the package manifest and the resolved dependency tree, which cannot be allowed on an item.
+#![allow(clippy::cargo_common_metadata)]
+#![allow(clippy::multiple_crate_versions)]
+
 use std::env;
 use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::sync::Arc;
 
+use crate::file::LocalFileSystem;
+use crate::input::source::TerminalEventSource;
 use crate::state::init::StateInitializer;
-use crate::state::view::View;
+use crate::state::view::TerminalView;
 
 mod app;
 mod component;
+mod config;
 mod file;
 mod input;
 mod state;
+mod theme;
 mod util;
 
 const VERSION: Option<&str> = option_env!("CARGO_PKG_VERSION");
@@ -69,13 +76,13 @@ const VERSION: Option<&str> = option_env!("CARGO_PKG_VERSION");
 #[allow(clippy::print_stdout)]
 fn main() -> ExitCode {
 	let args = env::args_os().skip(1).collect::<Vec<_>>();
-	if args.len() == 1 && args.get(0).is_some_and(|arg| arg == "-v" || arg == "--version") {
+	if args.len() == 1 && args.first().is_some_and(|arg| arg == "-v" || arg == "--version") {
 		println!("{}", VERSION.unwrap_or("unknown"));
 		return ExitCode::SUCCESS;
 	}
 	
 	#[allow(clippy::indexing_slicing)] // Guarded by condition.
-	let args = if args.len() == 2 && args.get(0).is_some_and(|arg| arg == "--") {
+	let args = if args.len() == 2 && args.first().is_some_and(|arg| arg == "--") {
 		&args[1..]
 	} else {
 		&args[..]
@@ -86,7 +93,7 @@ fn main() -> ExitCode {
 		return ExitCode::FAILURE;
 	}
 	
-	match get_start_path(args.get(0)) {
+	match get_start_path(args.first()) {
 		StartPathResult::Ok(path) => {
 			prepare_and_run_app(&path)
 		},
@@ -107,8 +114,8 @@ enum StartPathResult<'a> {
 	InvalidWorkingDirectory,
 }
 
-fn get_start_path(path_arg: Option<&OsString>) -> StartPathResult {
-	return if let Some(path) = path_arg {
+fn get_start_path(path_arg: Option<&OsString>) -> StartPathResult<'_> {
+	if let Some(path) = path_arg {
 		if let Ok(path) = Path::new(path).canonicalize() {
 			StartPathResult::Ok(path)
 		} else {
@@ -127,7 +134,9 @@ fn prepare_and_run_app(start_path: &Path) -> ExitCode {
 		Ok(action_map) => {
 			run_app(&StateInitializer {
 				filesystem_start_path: start_path,
+				filesystem_backend: Arc::new(LocalFileSystem),
 				filesystem_action_map: action_map,
+				filesystem_action_map_errors: component::filesystem::defaults::get_key_config_errors(),
 			})
 		},
 		Err(e) => {
@@ -139,16 +148,16 @@ fn prepare_and_run_app(start_path: &Path) -> ExitCode {
 
 #[allow(clippy::print_stdout)]
 fn run_app(state_initializer: &StateInitializer) -> ExitCode {
-	View::restore_terminal_on_panic();
+	TerminalView::restore_terminal_on_panic();
 	
-	match View::stdout() {
+	match TerminalView::stdout() {
 		Err(e) => {
-			View::restore_terminal();
+			TerminalView::restore_terminal();
 			println!("Failed to initialize terminal: {e}");
 			ExitCode::FAILURE
 		}
 		Ok(mut view) => {
-			let result = app::run(state_initializer, &mut view);
+			let result = app::run(state_initializer, &mut view, &mut TerminalEventSource);
 			let _ = view.close();
 			
 			if let Err(e) = result {
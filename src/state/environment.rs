@@ -1,5 +1,7 @@
 use std::io;
 
+use ratatui::backend::Backend;
+
 use crate::state::view::View;
 
 pub struct Environment {
@@ -7,10 +9,10 @@ pub struct Environment {
 	pub terminal_height: u16,
 }
 
-impl TryFrom<&View> for Environment {
+impl<B: Backend> TryFrom<&View<B>> for Environment {
 	type Error = io::Error;
 	
-	fn try_from(view: &View) -> Result<Self, Self::Error> {
+	fn try_from(view: &View<B>) -> Result<Self, Self::Error> {
 		let size = view.size()?;
 		
 		Ok(Self {
@@ -1,8 +1,14 @@
 use std::path::Path;
+use std::sync::Arc;
 
 use crate::component::filesystem::ActionKeyMap;
+use crate::file::FileSystem;
 
+#[allow(clippy::struct_field_names)] // Prefixes name the layer that the fields initialize.
 pub struct StateInitializer<'a> {
 	pub filesystem_start_path: &'a Path,
+	pub filesystem_backend: Arc<dyn FileSystem>,
 	pub filesystem_action_map: &'static ActionKeyMap,
+	/// Errors in the configuration of the action map, which are shown in a dialog after startup.
+	pub filesystem_action_map_errors: &'static [String],
 }
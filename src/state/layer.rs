@@ -1,3 +1,5 @@
+use crossterm::event::MouseEvent;
+
 use crate::input::keymap::KeyBinding;
 use crate::state::action::ActionResult;
 use crate::state::Environment;
@@ -6,6 +8,17 @@ use crate::state::view::Frame;
 
 pub trait Layer {
 	fn handle_input(&mut self, environment: &Environment, key_binding: KeyBinding) -> ActionResult;
+	fn handle_mouse(&mut self, environment: &Environment, event: MouseEvent) -> ActionResult;
 	fn handle_events(&mut self, environment: &Environment) -> EventResult;
 	fn render(&mut self, frame: &mut Frame);
+	
+	/// Returns layers that were opened while handling events, which are shown on top of all other layers.
+	fn take_opened_layers(&mut self) -> Vec<Box<dyn Layer>> {
+		Vec::new()
+	}
+	
+	/// Returns whether the layer is waiting for background work, such as running jobs, whose results it will handle in a later call to [`Layer::handle_events`].
+	fn has_background_work(&self) -> bool {
+		false
+	}
 }
@@ -1,3 +1,8 @@
+use std::sync::Arc;
+
+use crossterm::event::MouseEvent;
+
+use crate::component::filesystem::defaults;
 use crate::component::filesystem::FsLayer;
 use crate::input::keymap::KeyBinding;
 use crate::state::action::ActionResult;
@@ -22,20 +27,40 @@ pub struct State {
 
 impl State {
 	pub fn new(initializer: &StateInitializer, environment: Environment) -> Self {
-		Self {
-			layers: vec![Box::new(FsLayer::new(initializer.filesystem_start_path, initializer.filesystem_action_map))],
-			environment
+		let filesystem_layer = FsLayer::new(initializer.filesystem_start_path, Arc::clone(&initializer.filesystem_backend), initializer.filesystem_action_map);
+		let dialog_y = filesystem_layer.dialog_y();
+		
+		let mut layers: Vec<Box<dyn Layer>> = vec![Box::new(filesystem_layer)];
+		
+		if !initializer.filesystem_action_map_errors.is_empty() {
+			layers.push(Box::new(defaults::create_key_config_errors_dialog(dialog_y, initializer.filesystem_action_map_errors)));
 		}
+		
+		Self { layers, environment }
 	}
 	
 	pub fn handle_events(&mut self) -> EventResult {
-		self.layers.iter_mut().fold(EventResult::Nothing, |result, layer| result.merge(layer.handle_events(&self.environment)))
+		let result = self.layers.iter_mut().fold(EventResult::Nothing, |result, layer| result.merge(layer.handle_events(&self.environment)));
+		
+		let opened_layers = self.layers.iter_mut().flat_map(|layer| layer.take_opened_layers()).collect::<Vec<_>>();
+		let result = result.merge(EventResult::draw_if(!opened_layers.is_empty()));
+		
+		self.layers.extend(opened_layers);
+		result
+	}
+	
+	pub fn has_background_work(&self) -> bool {
+		self.layers.iter().any(|layer| layer.has_background_work())
 	}
 	
 	pub fn handle_input(&mut self, key_binding: KeyBinding) -> ActionResult {
 		self.layers.last_mut().map_or(ActionResult::Nothing, |layer| layer.handle_input(&self.environment, key_binding))
 	}
 	
+	pub fn handle_mouse(&mut self, event: MouseEvent) -> ActionResult {
+		self.layers.last_mut().map_or(ActionResult::Nothing, |layer| layer.handle_mouse(&self.environment, event))
+	}
+	
 	pub fn handle_resize(&mut self, width: u16, height: u16) {
 		self.environment.terminal_width = width;
 		self.environment.terminal_height = height;
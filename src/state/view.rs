@@ -2,24 +2,36 @@ use std::{io, panic};
 use std::io::{stdout, Stdout};
 
 use crossterm::{ExecutableCommand, terminal};
-use ratatui::backend::CrosstermBackend;
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use ratatui::backend::{Backend, CrosstermBackend};
+#[cfg(test)]
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::Terminal;
 use ratatui::terminal::CompletedFrame;
 use ratatui::widgets::{StatefulWidget, Widget};
 
-pub struct View {
-	term: Terminal<CrosstermBackend<Stdout>>,
+pub struct View<B: Backend> {
+	term: Terminal<B>,
 	render_request: RenderRequest,
 }
 
-impl View {
+/// View that renders into the terminal through standard output.
+pub type TerminalView = View<CrosstermBackend<Stdout>>;
+
+/// View that renders into an in-memory buffer, so that the rendered screen can be inspected without a terminal.
+#[cfg(test)]
+pub type HeadlessView = View<TestBackend>;
+
+impl TerminalView {
 	pub fn stdout() -> io::Result<Self> {
 		terminal::enable_raw_mode()?;
 		
 		let mut term = Terminal::new(CrosstermBackend::new(stdout()))?;
 		
 		term.backend_mut().execute(terminal::EnterAlternateScreen)?;
+		term.backend_mut().execute(EnableMouseCapture)?;
 		term.hide_cursor()?;
 		term.clear()?;
 		
@@ -36,16 +48,47 @@ impl View {
 	}
 	
 	pub fn restore_terminal() {
+		let _ = stdout().execute(DisableMouseCapture);
+		let _ = terminal::disable_raw_mode();
+	}
+	
+	/// Hands the terminal over to an external program, such as an editor, and takes it back after the program exits. Programs reset terminal modes when they exit, so raw mode, the alternate screen, and mouse capture are enabled again.
+	pub fn run_external_program<T>(run: impl FnOnce() -> T) -> T {
+		let _ = stdout().execute(DisableMouseCapture);
+		let _ = stdout().execute(terminal::LeaveAlternateScreen);
 		let _ = terminal::disable_raw_mode();
+		
+		let result = run();
+		
+		let _ = terminal::enable_raw_mode();
+		let _ = stdout().execute(terminal::EnterAlternateScreen);
+		let _ = stdout().execute(EnableMouseCapture);
+		result
 	}
 	
 	pub fn close(mut self) -> io::Result<()> {
 		self.term.show_cursor()?;
+		self.term.backend_mut().execute(DisableMouseCapture)?;
 		self.term.backend_mut().execute(terminal::LeaveAlternateScreen)?;
 		
 		terminal::disable_raw_mode()
 	}
+}
+
+#[cfg(test)]
+impl HeadlessView {
+	pub fn headless(width: u16, height: u16) -> io::Result<Self> {
+		let term = Terminal::new(TestBackend::new(width, height))?;
+		Ok(Self { term, render_request: RenderRequest::Draw })
+	}
 	
+	/// Returns the screen contents after the last render.
+	pub fn buffer(&self) -> &Buffer {
+		self.term.backend().buffer()
+	}
+}
+
+impl<B: Backend> View<B> {
 	pub fn size(&self) -> io::Result<Rect> {
 		self.term.size()
 	}
@@ -77,11 +120,19 @@ impl View {
 		Ok(())
 	}
 	
-	fn draw<R>(&mut self, renderer: R) -> io::Result<CompletedFrame> where R: FnOnce(&mut Frame) {
+	fn draw<R>(&mut self, renderer: R) -> io::Result<CompletedFrame<'_>> where R: FnOnce(&mut Frame) {
 		self.term.draw(|frame| {
-			let mut frame = Frame::new(frame);
-			renderer(&mut frame);
-			frame.apply_cursor();
+			let mut cursor = None;
+			
+			frame.render_widget(FrameRenderer(|area, buffer| {
+				let mut frame = Frame::new(buffer, area);
+				renderer(&mut frame);
+				cursor = frame.cursor;
+			}), frame.size());
+			
+			if let Some((x, y)) = cursor {
+				frame.set_cursor(x, y);
+			}
 		})
 	}
 }
@@ -109,26 +160,36 @@ impl RenderRequest {
 	}
 }
 
-pub struct Frame<'a, 'b> {
-	inner: &'a mut ratatui::Frame<'b, CrosstermBackend<Stdout>>,
+/// Adapts a rendering function to a widget, so that [`Frame`] can draw directly into the buffer of any backend.
+struct FrameRenderer<F>(F) where F: FnOnce(Rect, &mut Buffer);
+
+impl<F> Widget for FrameRenderer<F> where F: FnOnce(Rect, &mut Buffer) {
+	fn render(self, area: Rect, buffer: &mut Buffer) {
+		(self.0)(area, buffer);
+	}
+}
+
+pub struct Frame<'a> {
+	buffer: &'a mut Buffer,
+	area: Rect,
 	cursor: Option<(u16, u16)>,
 }
 
-impl<'a, 'b> Frame<'a, 'b> {
-	pub fn new(inner: &'a mut ratatui::Frame<'b, CrosstermBackend<Stdout>>) -> Self {
-		Self { inner, cursor: None }
+impl<'a> Frame<'a> {
+	pub fn new(buffer: &'a mut Buffer, area: Rect) -> Self {
+		Self { buffer, area, cursor: None }
 	}
 	
-	pub fn size(&self) -> Rect {
-		self.inner.size()
+	pub const fn size(&self) -> Rect {
+		self.area
 	}
 	
 	pub fn render_widget<W: Widget>(&mut self, widget: W, area: Rect) {
-		self.inner.render_widget(widget, area);
+		widget.render(area, self.buffer);
 	}
 	
 	pub fn render_stateful_widget<W: StatefulWidget>(&mut self, widget: W, area: Rect, state: &mut W::State) {
-		self.inner.render_stateful_widget(widget, area, state);
+		widget.render(area, self.buffer, state);
 	}
 	
 	pub fn set_cursor(&mut self, x: u16, y: u16) {
@@ -138,10 +199,4 @@ impl<'a, 'b> Frame<'a, 'b> {
 	pub fn hide_cursor(&mut self) {
 		self.cursor = None;
 	}
-	
-	fn apply_cursor(&mut self) {
-		if let Some((x, y)) = self.cursor {
-			self.inner.set_cursor(x, y);
-		}
-	}
 }
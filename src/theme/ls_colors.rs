@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+
+use ratatui::style::{Color, Style};
+
+use crate::file::{FileEntry, FileKind, Permission};
+use crate::theme::sgr::parse_sgr;
+
+/// Long keywords accepted by `dircolors` configuration files, and the `LS_COLORS` indicators they map to.
+const DIRCOLORS_KEYWORDS: &[(&str, &str)] = &[
+	("NORMAL", "no"), ("NORM", "no"),
+	("FILE", "fi"),
+	("RESET", "rs"),
+	("DIR", "di"),
+	("LNK", "ln"), ("LINK", "ln"), ("SYMLINK", "ln"),
+	("ORPHAN", "or"),
+	("MISSING", "mi"),
+	("FIFO", "pi"), ("PIPE", "pi"),
+	("SOCK", "so"),
+	("BLK", "bd"), ("BLOCK", "bd"),
+	("CHR", "cd"), ("CHAR", "cd"),
+	("DOOR", "do"),
+	("EXEC", "ex"),
+	("SUID", "su"), ("SETUID", "su"),
+	("SGID", "sg"), ("SETGID", "sg"),
+	("STICKY", "st"),
+	("OTHER_WRITABLE", "ow"), ("OWR", "ow"),
+	("STICKY_OTHER_WRITABLE", "tw"), ("OWT", "tw"),
+	("CAPABILITY", "ca"),
+	("MULTIHARDLINK", "mh"),
+];
+
+/// Keywords of `dircolors` configuration files that do not affect colors.
+const IGNORED_DIRCOLORS_KEYWORDS: &[&str] = &[
+	"TERM", "COLORTERM", "COLOR", "OPTIONS", "EIGHTBIT", "LEFT", "LEFTCODE", "RIGHT", "RIGHTCODE", "END", "ENDCODE", "CLRTOEOL",
+];
+
+/// Styles of file names, using the same rules as `ls`. Entries are styled by their kind and permissions, and regular files can also be styled by glob patterns matched against their names.
+pub struct LsColors {
+	indicators: HashMap<String, Style>,
+	/// Lowercase glob patterns in the order they were defined. Later patterns take precedence.
+	patterns: Vec<(Vec<char>, Style)>,
+}
+
+impl LsColors {
+	pub fn new() -> Self {
+		let mut indicators = HashMap::new();
+		indicators.insert(String::from("no"), Style::default().fg(Color::White));
+		indicators.insert(String::from("fi"), Style::default().fg(Color::White));
+		indicators.insert(String::from("di"), Style::default().fg(Color::LightBlue));
+		indicators.insert(String::from("ln"), Style::default().fg(Color::LightCyan));
+		indicators.insert(String::from("so"), Style::default().fg(Color::LightMagenta));
+		indicators.insert(String::from("bd"), Style::default().fg(Color::LightYellow));
+		indicators.insert(String::from("cd"), Style::default().fg(Color::LightYellow));
+		indicators.insert(String::from("pi"), Style::default().fg(Color::LightYellow));
+		indicators.insert(String::from("ex"), Style::default().fg(Color::LightGreen));
+		
+		Self { indicators, patterns: Vec::new() }
+	}
+	
+	/// Applies entries from the `LS_COLORS` environment variable, such as `di=01;34:*.tar=01;31`. Invalid entries are ignored.
+	pub fn apply_ls_colors(&mut self, ls_colors: &str) {
+		for entry in ls_colors.split(':') {
+			if let Some((key, sequence)) = entry.split_once('=') {
+				if key.starts_with('*') {
+					self.add_pattern(key, sequence);
+				} else {
+					self.set_indicator(key, sequence);
+				}
+			}
+		}
+	}
+	
+	/// Applies a line from a `dircolors` configuration file, such as `DIR 01;34`, `.tar 01;31`, or `*README 04`. Returns `false` if the keyword is not recognized.
+	pub fn apply_dircolors_line(&mut self, keyword: &str, sequence: &str) -> bool {
+		if keyword.starts_with('.') {
+			self.add_pattern(&format!("*{keyword}"), sequence);
+			return true;
+		}
+		
+		if keyword.starts_with('*') {
+			self.add_pattern(keyword, sequence);
+			return true;
+		}
+		
+		if let Some((_, indicator)) = DIRCOLORS_KEYWORDS.iter().find(|(name, _)| name.eq_ignore_ascii_case(keyword)) {
+			self.set_indicator(indicator, sequence);
+			return true;
+		}
+		
+		IGNORED_DIRCOLORS_KEYWORDS.iter().any(|name| name.eq_ignore_ascii_case(keyword))
+	}
+	
+	fn set_indicator(&mut self, indicator: &str, sequence: &str) {
+		if let Some(style) = parse_sgr(sequence) {
+			self.indicators.insert(indicator.to_owned(), style);
+		}
+	}
+	
+	fn add_pattern(&mut self, pattern: &str, sequence: &str) {
+		if let Some(style) = parse_sgr(sequence) {
+			self.patterns.push((pattern.to_lowercase().chars().collect(), style));
+		}
+	}
+	
+	pub fn map_styles<F>(&mut self, f: F) where F: Fn(Style) -> Style {
+		for style in self.indicators.values_mut().chain(self.patterns.iter_mut().map(|(_, style)| style)) {
+			*style = f(*style);
+		}
+	}
+	
+	pub fn get_style(&self, entry: &FileEntry) -> Style {
+		let style = match entry.kind() {
+			FileKind::File { .. } => return self.get_file_style(entry),
+			FileKind::Directory => self.get_directory_style(entry),
+			FileKind::Symlink => self.indicators.get("ln"),
+			FileKind::BlockDevice => self.indicators.get("bd"),
+			FileKind::CharDevice => self.indicators.get("cd"),
+			FileKind::Pipe => self.indicators.get("pi"),
+			FileKind::Socket => self.indicators.get("so"),
+			FileKind::Unknown => None,
+		};
+		
+		style.or_else(|| self.indicators.get("no")).copied().unwrap_or_default()
+	}
+	
+	fn get_directory_style(&self, entry: &FileEntry) -> Option<&Style> {
+		let mode = entry.mode();
+		let is_sticky = mode.is_sticky() == Some(true);
+		let is_other_writable = mode.others().write() == Permission::Yes;
+		
+		let special = match (is_sticky, is_other_writable) {
+			(true, true) => self.indicators.get("tw"),
+			(false, true) => self.indicators.get("ow"),
+			(true, false) => self.indicators.get("st"),
+			(false, false) => None,
+		};
+		
+		special.or_else(|| self.indicators.get("di"))
+	}
+	
+	/// Special permissions take precedence over patterns, like in `ls`.
+	fn get_file_style(&self, entry: &FileEntry) -> Style {
+		let mode = entry.mode();
+		
+		let special = if mode.is_setuid() == Some(true) && self.indicators.contains_key("su") {
+			self.indicators.get("su")
+		} else if mode.is_setgid() == Some(true) && self.indicators.contains_key("sg") {
+			self.indicators.get("sg")
+		} else if mode.is_executable_by_any() == Some(true) && self.indicators.contains_key("ex") {
+			self.indicators.get("ex")
+		} else if entry.stats().is_some_and(|stats| stats.link_count() > 1) && self.indicators.contains_key("mh") {
+			self.indicators.get("mh")
+		} else {
+			None
+		};
+		
+		special.or_else(|| self.get_pattern_style(entry.name().str()))
+		       .or_else(|| self.indicators.get("fi"))
+		       .or_else(|| self.indicators.get("no"))
+		       .copied()
+		       .unwrap_or_default()
+	}
+	
+	fn get_pattern_style(&self, name: &str) -> Option<&Style> {
+		if self.patterns.is_empty() {
+			return None;
+		}
+		
+		let name = name.to_lowercase().chars().collect::<Vec<_>>();
+		self.patterns.iter().rev().find(|(pattern, _)| matches_glob(pattern, &name)).map(|(_, style)| style)
+	}
+}
+
+/// Matches a name against a glob pattern, where `*` matches any sequence of characters, and `?` matches any single character.
+fn matches_glob(pattern: &[char], name: &[char]) -> bool {
+	let mut pattern_index = 0;
+	let mut name_index = 0;
+	let mut backtrack = None;
+	
+	while name_index < name.len() {
+		match pattern.get(pattern_index) {
+			Some('*') => {
+				backtrack = Some((pattern_index, name_index));
+				pattern_index = pattern_index.saturating_add(1);
+				continue;
+			}
+			Some(c) if *c == '?' || name.get(name_index) == Some(c) => {
+				pattern_index = pattern_index.saturating_add(1);
+				name_index = name_index.saturating_add(1);
+				continue;
+			}
+			_ => {}
+		}
+		
+		let Some((star_pattern_index, star_name_index)) = backtrack else {
+			return false;
+		};
+		
+		// Let the last star match one more character, and try again.
+		pattern_index = star_pattern_index.saturating_add(1);
+		name_index = star_name_index.saturating_add(1);
+		backtrack = Some((star_pattern_index, name_index));
+	}
+	
+	pattern.get(pattern_index..).is_some_and(|rest| rest.iter().all(|c| *c == '*'))
+}
+
+#[cfg(test)]
+mod tests {
+	use ratatui::style::{Color, Modifier, Style};
+	
+	use crate::theme::ls_colors::{LsColors, matches_glob};
+	
+	fn glob(pattern: &str, name: &str) -> bool {
+		matches_glob(&pattern.chars().collect::<Vec<_>>(), &name.chars().collect::<Vec<_>>())
+	}
+	
+	fn indicator(colors: &LsColors, indicator: &str) -> Option<Style> {
+		colors.indicators.get(indicator).copied()
+	}
+	
+	fn pattern_style(colors: &LsColors, name: &str) -> Option<Style> {
+		colors.get_pattern_style(name).copied()
+	}
+	
+	#[test]
+	fn matches_globs() {
+		assert!(glob("*", ""));
+		assert!(glob("*", "name"));
+		assert!(glob("*.tar", "archive.tar"));
+		assert!(glob("*.tar", ".tar"));
+		assert!(!glob("*.tar", "archive.tar.gz"));
+		assert!(glob("*.tar*", "archive.tar.gz"));
+		assert!(glob("readme*", "readme.md"));
+		assert!(glob("*a*b*c", "xaxxbxxc"));
+		assert!(!glob("*a*b*c", "xaxxcxxb"));
+		assert!(glob("?.rs", "a.rs"));
+		assert!(!glob("?.rs", "ab.rs"));
+		assert!(!glob("", "a"));
+	}
+	
+	#[test]
+	fn applies_ls_colors_indicators() {
+		let mut colors = LsColors::new();
+		colors.apply_ls_colors("di=01;34:ln=38;5;208:ex=38;2;1;2;3:so=:su=37;41");
+		
+		assert_eq!(indicator(&colors, "di"), Some(Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD)));
+		assert_eq!(indicator(&colors, "ln"), Some(Style::default().fg(Color::Indexed(208))));
+		assert_eq!(indicator(&colors, "ex"), Some(Style::default().fg(Color::Rgb(1, 2, 3))));
+		assert_eq!(indicator(&colors, "so"), Some(Style::default()));
+		assert_eq!(indicator(&colors, "su"), Some(Style::default().fg(Color::Gray).bg(Color::Red)));
+	}
+	
+	#[test]
+	fn ignores_invalid_ls_colors_entries() {
+		let mut colors = LsColors::new();
+		colors.apply_ls_colors("di=01;x:ln:ex=38;5:fi=32::=31:*.rs=999");
+		
+		assert_eq!(indicator(&colors, "di"), Some(Style::default().fg(Color::LightBlue)));
+		assert_eq!(indicator(&colors, "ln"), Some(Style::default().fg(Color::LightCyan)));
+		assert_eq!(indicator(&colors, "ex"), Some(Style::default().fg(Color::LightGreen)));
+		assert_eq!(indicator(&colors, "fi"), Some(Style::default().fg(Color::Green)));
+		assert_eq!(pattern_style(&colors, "main.rs"), None);
+	}
+	
+	#[test]
+	fn later_patterns_take_precedence_and_ignore_case() {
+		let mut colors = LsColors::new();
+		colors.apply_ls_colors("*.gz=31:*.tar.gz=32:*README=04");
+		
+		assert_eq!(pattern_style(&colors, "file.gz"), Some(Style::default().fg(Color::Red)));
+		assert_eq!(pattern_style(&colors, "file.TAR.GZ"), Some(Style::default().fg(Color::Green)));
+		assert_eq!(pattern_style(&colors, "readme"), Some(Style::default().add_modifier(Modifier::UNDERLINED)));
+		assert_eq!(pattern_style(&colors, "readme.md"), None);
+	}
+	
+	#[test]
+	fn applies_dircolors_lines() {
+		let mut colors = LsColors::new();
+		
+		assert!(colors.apply_dircolors_line("DIR", "01;34"));
+		assert!(colors.apply_dircolors_line("symlink", "36"));
+		assert!(colors.apply_dircolors_line(".tar", "01;31"));
+		assert!(colors.apply_dircolors_line("*Makefile", "33"));
+		assert!(colors.apply_dircolors_line("TERM", "xterm*"));
+		assert!(!colors.apply_dircolors_line("UNKNOWN", "31"));
+		
+		assert_eq!(indicator(&colors, "di"), Some(Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD)));
+		assert_eq!(indicator(&colors, "ln"), Some(Style::default().fg(Color::Cyan)));
+		assert_eq!(pattern_style(&colors, "a.tar"), Some(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)));
+		assert_eq!(pattern_style(&colors, "makefile"), Some(Style::default().fg(Color::Yellow)));
+	}
+}
@@ -0,0 +1,243 @@
+use std::env;
+
+use lazy_static::lazy_static;
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::config;
+use crate::file::GitStatus;
+use crate::theme::sgr::parse_sgr;
+
+pub use self::ls_colors::LsColors;
+
+mod ls_colors;
+mod sgr;
+
+lazy_static! {
+	static ref THEME: Theme = Theme::load();
+}
+
+/// Returns the theme loaded on first use.
+pub fn theme() -> &'static Theme {
+	&THEME
+}
+
+pub struct Theme {
+	pub file_names: LsColors,
+	pub selected: Style,
+	pub marked: Style,
+	pub marker: Style,
+	/// Unknown values, and markers of truncated text.
+	pub dimmed: Style,
+	/// Less important text, such as file kinds, extensions, and hints.
+	pub secondary: Style,
+	pub numeric_owner: Style,
+	pub permission_read: Style,
+	pub permission_write: Style,
+	pub permission_execute: Style,
+	pub permission_none: Style,
+	pub git_ignored: Style,
+	pub git_untracked: Style,
+	pub git_staged: Style,
+	pub git_modified: Style,
+	pub git_conflicted: Style,
+	pub disk_usage_bar: Style,
+	pub diff_only_left: Style,
+	pub diff_only_right: Style,
+	pub diff_changed: Style,
+	pub error_text: Style,
+	pub status_bar: Style,
+	pub status_info: Style,
+	pub status_error: Style,
+	pub dialog_key: Style,
+	pub dialog_info: DialogStyle,
+	pub dialog_warning: DialogStyle,
+	pub dialog_error: DialogStyle,
+	pub command_line: DialogStyle,
+}
+
+/// Styles of a dialog, which are derived from the color of its border.
+#[derive(Copy, Clone)]
+pub struct DialogStyle {
+	pub border: Style,
+	pub input: Style,
+	pub input_truncated: Style,
+}
+
+impl DialogStyle {
+	fn from_border(border: Style) -> Self {
+		let Some(color) = border.fg.filter(|color| *color != Color::Reset) else {
+			let input = Style::default().add_modifier(Modifier::REVERSED);
+			return Self { border, input, input_truncated: input };
+		};
+		
+		Self {
+			border,
+			input: Style::default().fg(Color::Black).bg(color),
+			input_truncated: Style::default().fg(Color::Black).bg(get_darker_color(color)),
+		}
+	}
+}
+
+const fn get_darker_color(color: Color) -> Color {
+	#[allow(clippy::wildcard_enum_match_arm)]
+	match color {
+		Color::LightRed => Color::Red,
+		Color::LightGreen => Color::Green,
+		Color::LightYellow => Color::Yellow,
+		Color::LightBlue => Color::Blue,
+		Color::LightMagenta => Color::Magenta,
+		Color::LightCyan => Color::Cyan,
+		Color::White => Color::Gray,
+		Color::Gray => Color::DarkGray,
+		other => other,
+	}
+}
+
+impl Theme {
+	fn new() -> Self {
+		Self {
+			file_names: LsColors::new(),
+			selected: Style::default().add_modifier(Modifier::REVERSED),
+			marked: Style::default().add_modifier(Modifier::BOLD),
+			marker: Style::default().fg(Color::LightYellow),
+			dimmed: Style::default().fg(Color::DarkGray),
+			secondary: Style::default().fg(Color::Gray),
+			numeric_owner: Style::default().fg(Color::Indexed(248 /* Grey66 */)),
+			permission_read: Style::default().fg(Color::LightBlue),
+			permission_write: Style::default().fg(Color::LightRed),
+			permission_execute: Style::default().fg(Color::LightGreen),
+			permission_none: Style::default().fg(Color::Gray),
+			git_ignored: Style::default().fg(Color::DarkGray),
+			git_untracked: Style::default().fg(Color::LightRed),
+			git_staged: Style::default().fg(Color::LightGreen),
+			git_modified: Style::default().fg(Color::LightYellow),
+			git_conflicted: Style::default().fg(Color::LightMagenta),
+			disk_usage_bar: Style::default().fg(Color::LightBlue),
+			diff_only_left: Style::default().fg(Color::LightRed),
+			diff_only_right: Style::default().fg(Color::LightGreen),
+			diff_changed: Style::default().fg(Color::LightYellow),
+			error_text: Style::default().fg(Color::LightRed),
+			status_bar: Style::default().fg(Color::White).bg(Color::Indexed(236 /* Grey19 */)),
+			status_info: Style::default().fg(Color::LightCyan),
+			status_error: Style::default().fg(Color::LightRed).add_modifier(Modifier::BOLD),
+			dialog_key: Style::default().fg(Color::LightCyan),
+			dialog_info: DialogStyle::from_border(Style::default().fg(Color::LightCyan)),
+			dialog_warning: DialogStyle::from_border(Style::default().fg(Color::LightYellow)),
+			dialog_error: DialogStyle::from_border(Style::default().fg(Color::LightRed)),
+			command_line: DialogStyle::from_border(Style::default().fg(Color::LightYellow)),
+		}
+	}
+	
+	/// Loads the default theme, applies file name colors from the `LS_COLORS` environment variable, and then applies the `theme` configuration file. If the `NO_COLOR` environment variable is set, all colors are removed.
+	fn load() -> Self {
+		let mut theme = Self::new();
+		
+		if let Ok(ls_colors) = env::var("LS_COLORS") {
+			theme.file_names.apply_ls_colors(&ls_colors);
+		}
+		
+		if let Some(contents) = config::read_config_file("theme") {
+			theme.apply_config(&contents);
+		}
+		
+		if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+			theme.remove_colors();
+		}
+		
+		theme
+	}
+	
+	/// Applies a configuration file in the `dircolors` format. Besides `dircolors` keywords, it accepts keywords for styles of the user interface. Unrecognized lines are ignored.
+	fn apply_config(&mut self, contents: &str) {
+		for line in contents.lines() {
+			let line = line.split_once('#').map_or(line, |(before_comment, _)| before_comment);
+			let mut tokens = line.split_whitespace();
+			
+			let (Some(keyword), Some(sequence)) = (tokens.next(), tokens.next()) else {
+				continue;
+			};
+			
+			if self.file_names.apply_dircolors_line(keyword, sequence) {
+				continue;
+			}
+			
+			if let Some(style) = parse_sgr(sequence) {
+				self.set_style(keyword, style);
+			}
+		}
+	}
+	
+	fn set_style(&mut self, keyword: &str, style: Style) {
+		match keyword.to_ascii_uppercase().as_str() {
+			"DIALOG_INFO" => self.dialog_info = DialogStyle::from_border(style),
+			"DIALOG_WARNING" => self.dialog_warning = DialogStyle::from_border(style),
+			"DIALOG_ERROR" => self.dialog_error = DialogStyle::from_border(style),
+			"COMMAND_LINE" => self.command_line = DialogStyle::from_border(style),
+			keyword => {
+				if let Some(target) = self.get_style_mut(keyword) {
+					*target = style;
+				}
+			}
+		}
+	}
+	
+	fn get_style_mut(&mut self, keyword: &str) -> Option<&mut Style> {
+		match keyword {
+			"SELECTED" => Some(&mut self.selected),
+			"MARKED" => Some(&mut self.marked),
+			"MARKER" => Some(&mut self.marker),
+			"DIMMED" => Some(&mut self.dimmed),
+			"SECONDARY" => Some(&mut self.secondary),
+			"NUMERIC_OWNER" => Some(&mut self.numeric_owner),
+			"PERMISSION_READ" => Some(&mut self.permission_read),
+			"PERMISSION_WRITE" => Some(&mut self.permission_write),
+			"PERMISSION_EXECUTE" => Some(&mut self.permission_execute),
+			"PERMISSION_NONE" => Some(&mut self.permission_none),
+			"GIT_IGNORED" => Some(&mut self.git_ignored),
+			"GIT_UNTRACKED" => Some(&mut self.git_untracked),
+			"GIT_STAGED" => Some(&mut self.git_staged),
+			"GIT_MODIFIED" => Some(&mut self.git_modified),
+			"GIT_CONFLICTED" => Some(&mut self.git_conflicted),
+			"DISK_USAGE_BAR" => Some(&mut self.disk_usage_bar),
+			"DIFF_ONLY_LEFT" => Some(&mut self.diff_only_left),
+			"DIFF_ONLY_RIGHT" => Some(&mut self.diff_only_right),
+			"DIFF_CHANGED" => Some(&mut self.diff_changed),
+			"ERROR_TEXT" => Some(&mut self.error_text),
+			"STATUS_BAR" => Some(&mut self.status_bar),
+			"STATUS_INFO" => Some(&mut self.status_info),
+			"STATUS_ERROR" => Some(&mut self.status_error),
+			"DIALOG_KEY" => Some(&mut self.dialog_key),
+			_ => None,
+		}
+	}
+	
+	fn remove_colors(&mut self) {
+		let remove_color = |style: Style| Style { fg: None, bg: None, ..style };
+		
+		self.file_names.map_styles(remove_color);
+		
+		for style in [
+			&mut self.selected, &mut self.marked, &mut self.marker, &mut self.dimmed, &mut self.secondary, &mut self.numeric_owner,
+			&mut self.permission_read, &mut self.permission_write, &mut self.permission_execute, &mut self.permission_none,
+			&mut self.git_ignored, &mut self.git_untracked, &mut self.git_staged, &mut self.git_modified, &mut self.git_conflicted,
+			&mut self.disk_usage_bar, &mut self.diff_only_left, &mut self.diff_only_right, &mut self.diff_changed,
+			&mut self.error_text, &mut self.status_bar, &mut self.status_info, &mut self.status_error, &mut self.dialog_key,
+		] {
+			*style = remove_color(*style);
+		}
+		
+		for dialog_style in [&mut self.dialog_info, &mut self.dialog_warning, &mut self.dialog_error, &mut self.command_line] {
+			*dialog_style = DialogStyle::from_border(remove_color(dialog_style.border));
+		}
+	}
+	
+	pub const fn git_status(&self, status: GitStatus) -> Style {
+		match status {
+			GitStatus::Ignored    => self.git_ignored,
+			GitStatus::Untracked  => self.git_untracked,
+			GitStatus::Staged     => self.git_staged,
+			GitStatus::Modified   => self.git_modified,
+			GitStatus::Conflicted => self.git_conflicted,
+		}
+	}
+}
@@ -0,0 +1,63 @@
+use ratatui::style::{Color, Modifier, Style};
+
+const BASIC_COLORS: [Color; 8] = [
+	Color::Black, Color::Red, Color::Green, Color::Yellow,
+	Color::Blue, Color::Magenta, Color::Cyan, Color::Gray,
+];
+
+const BRIGHT_COLORS: [Color; 8] = [
+	Color::DarkGray, Color::LightRed, Color::LightGreen, Color::LightYellow,
+	Color::LightBlue, Color::LightMagenta, Color::LightCyan, Color::White,
+];
+
+/// Parses a sequence of SGR (Select Graphic Rendition) parameters separated by semicolons, such as `01;34` or `38;5;208`. Unsupported parameters are ignored, but malformed ones make the whole sequence invalid.
+pub fn parse_sgr(sequence: &str) -> Option<Style> {
+	let mut style = Style::default();
+	let mut codes = sequence.split(';').map(|code| if code.is_empty() { Some(0) } else { code.parse::<u8>().ok() });
+	
+	while let Some(code) = codes.next() {
+		style = match code? {
+			0 => Style::default(),
+			1 => style.add_modifier(Modifier::BOLD),
+			2 => style.add_modifier(Modifier::DIM),
+			3 => style.add_modifier(Modifier::ITALIC),
+			4 => style.add_modifier(Modifier::UNDERLINED),
+			5 => style.add_modifier(Modifier::SLOW_BLINK),
+			6 => style.add_modifier(Modifier::RAPID_BLINK),
+			7 => style.add_modifier(Modifier::REVERSED),
+			8 => style.add_modifier(Modifier::HIDDEN),
+			9 => style.add_modifier(Modifier::CROSSED_OUT),
+			22 => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+			23 => style.remove_modifier(Modifier::ITALIC),
+			24 => style.remove_modifier(Modifier::UNDERLINED),
+			25 => style.remove_modifier(Modifier::SLOW_BLINK | Modifier::RAPID_BLINK),
+			27 => style.remove_modifier(Modifier::REVERSED),
+			28 => style.remove_modifier(Modifier::HIDDEN),
+			29 => style.remove_modifier(Modifier::CROSSED_OUT),
+			code @ 30..=37 => style.fg(get_color(&BASIC_COLORS, code.saturating_sub(30))),
+			38 => style.fg(parse_extended_color(&mut codes)?),
+			39 => style.fg(Color::Reset),
+			code @ 40..=47 => style.bg(get_color(&BASIC_COLORS, code.saturating_sub(40))),
+			48 => style.bg(parse_extended_color(&mut codes)?),
+			49 => style.bg(Color::Reset),
+			code @ 90..=97 => style.fg(get_color(&BRIGHT_COLORS, code.saturating_sub(90))),
+			code @ 100..=107 => style.bg(get_color(&BRIGHT_COLORS, code.saturating_sub(100))),
+			_ => style,
+		};
+	}
+	
+	Some(style)
+}
+
+fn get_color(colors: &[Color; 8], index: u8) -> Color {
+	colors.get(usize::from(index)).copied().unwrap_or(Color::Reset)
+}
+
+/// Parses the parameters following `38` or `48`, which are either `5;<index>` for 256-color palettes, or `2;<r>;<g>;<b>` for true colors.
+fn parse_extended_color(codes: &mut impl Iterator<Item = Option<u8>>) -> Option<Color> {
+	match codes.next()?? {
+		5 => Some(Color::Indexed(codes.next()??)),
+		2 => Some(Color::Rgb(codes.next()??, codes.next()??, codes.next()??)),
+		_ => None,
+	}
+}
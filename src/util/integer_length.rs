@@ -16,7 +16,7 @@ impl IntegerLength for u32 {
 
 impl IntegerLength for i32 {
 	fn int_len(self) -> usize {
-		let sign_len = if self < 0 { 1 } else { 0 };
+		let sign_len = if self.is_negative() { 1 } else { 0 };
 		let digit_count = self.abs().checked_ilog10().unwrap_or(1).saturating_add(1);
 		digit_count.saturating_add(sign_len) as usize
 	}